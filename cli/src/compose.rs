@@ -0,0 +1,62 @@
+use tract_core::framework::Framework;
+use tract_hir::internal::*;
+
+use crate::{Parameters, TractResult};
+
+pub fn handle(
+    params: &Parameters,
+    matches: &clap::ArgMatches,
+    sub_matches: &clap::ArgMatches,
+) -> TractResult<()> {
+    let mut model = params
+        .tract_model
+        .downcast_ref::<TypedModel>()
+        .context("Can only compose a typed model. (using --pass ?)")?
+        .clone();
+
+    let links = sub_matches
+        .values_of("link")
+        .into_iter()
+        .flatten()
+        .map(|link| {
+            if let Some((from, to)) = link.split_once(':') {
+                Ok((from, to))
+            } else {
+                bail!("Links are expected as output:input, got {}", link)
+            }
+        })
+        .collect::<TractResult<Vec<_>>>()?;
+
+    let mut used = vec![false; links.len()];
+    let nnef = super::nnef(matches)?;
+    for path in sub_matches.values_of("append").into_iter().flatten() {
+        let proto = nnef.proto_model_for_path(path)?;
+        // load on the same symbol table so symbols are unified by name
+        let other = nnef
+            .translate(&proto, &model.symbol_table)
+            .map_err(|(_, e)| e)
+            .with_context(|| format!("Loading {}", path))?;
+        let mut step_links = vec![];
+        for (ix, (from, to)) in links.iter().enumerate() {
+            if model.output_outlets()?.iter().any(|o| model.outlet_name(*o) == *from)
+                && other.input_outlets()?.iter().any(|i| other.node(i.node).name == *to)
+            {
+                step_links.push((*from, *to));
+                used[ix] = true;
+            }
+        }
+        // once links are given, they are not mixed with name matching
+        if !links.is_empty() && step_links.is_empty() {
+            bail!("None of the --link options applies to {}", path)
+        }
+        model =
+            model.compose(&other, &step_links).with_context(|| format!("Appending {}", path))?;
+        info!("Appended {}", path);
+    }
+    if let Some(ix) = used.iter().position(|u| !u) {
+        bail!("Link {}:{} does not match any appended model", links[ix].0, links[ix].1)
+    }
+    model.declutter()?;
+
+    super::write_nnef(matches, sub_matches.value_of("output").unwrap(), &model)
+}
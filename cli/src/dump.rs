@@ -164,7 +164,7 @@ pub fn handle(
     }
 
     if let Some(path) = sub_matches.value_of("nnef") {
-        let nnef = super::nnef(matches);
+        let nnef = super::nnef(matches)?;
         if let Some(mut typed) = model.downcast_ref::<TypedModel>().cloned() {
             rename_outputs(&mut typed, sub_matches)?;
             let file = std::fs::File::create(path)?;
@@ -176,7 +176,7 @@ pub fn handle(
     }
 
     if let Some(path) = sub_matches.value_of("nnef-tar") {
-        let nnef = super::nnef(matches);
+        let nnef = super::nnef(matches)?;
         if let Some(mut typed) = model.downcast_ref::<TypedModel>().cloned() {
             rename_outputs(&mut typed, sub_matches)?;
             let file = std::fs::File::create(path)?;
@@ -187,7 +187,7 @@ pub fn handle(
     }
 
     if let Some(path) = sub_matches.value_of("nnef-dir") {
-        let nnef = super::nnef(matches);
+        let nnef = super::nnef(matches)?;
         if let Some(mut typed) = model.downcast_ref::<TypedModel>().cloned() {
             rename_outputs(&mut typed, sub_matches)?;
             if let Some(renamed) = sub_matches.values_of("nnef-override-output-name") {
@@ -207,7 +207,7 @@ pub fn handle(
     }
 
     if let Some(path) = sub_matches.value_of("nnef-graph") {
-        let nnef = super::nnef(matches);
+        let nnef = super::nnef(matches)?;
         if let Some(mut typed) = model.downcast_ref::<TypedModel>().cloned() {
             rename_outputs(&mut typed, sub_matches)?;
             let proto = tract_nnef::ser::to_proto_model(&nnef, &typed)?;
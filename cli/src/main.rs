@@ -22,12 +22,15 @@ use readings_probe::*;
 
 mod bench;
 mod compare;
+mod compose;
 mod cost;
 mod dump;
 mod errors {}
 mod export;
 mod params;
+mod quantize;
 mod run;
+mod split;
 #[cfg(feature = "pulse")]
 mod stream_check;
 mod tensor;
@@ -78,7 +81,7 @@ fn main() -> tract_core::anyhow::Result<()> {
         .arg(arg!(verbose: -v ... "Sets the level of verbosity."))
         .arg(arg!([model] "Sets the model to use"))
         .arg(arg!(-f --format [format]
-                  "Hint the model format ('kaldi', 'onnx', 'nnef' or 'tf') instead of guess from extension."))
+                  "Hint the model format ('kaldi', 'onnx', 'nnef', 'tf' or 'tflite') instead of guess from extension."))
         .arg(Arg::new("input").long("input").short('i').multiple_occurrences(true).takes_value(true).long_help(
                   "Set input shape and type (@file.pb or @file.npz:thing.npy or 3x4xi32)."))
         .arg(Arg::new("constantize").long("constantize").multiple_occurrences(true).takes_value(true).long_help(
@@ -117,9 +120,11 @@ fn main() -> tract_core::anyhow::Result<()> {
         .arg(arg!(--pass [STAGE] "Pass to stop preprocessing after.").possible_values(STAGES))
         .arg(arg!(--"declutter-step" [STEP] "Stop decluttering process after application of patch number N"))
         .arg(arg!(--"optimize-step" [STEP] "Stop optimizing process after application of patch number N"))
+        .arg(arg!(--"pass-disable" [PASS] ... "Disable an optimizer pass (PropConst, ChangeAxes, fuse, ...) in declutter and optimize"))
         .arg(arg!(--"extract-decluttered-sub" [SUB] "Zoom on a subgraph after decluttering by parent node name"))
 
         .arg(arg!(--"half-floats" "Convert the decluttered network from f32 to f16"))
+        .arg(arg!(--"half-floats-f32-accumulation" "With --half-floats, keep matrix products and convolutions in f32"))
         .arg(arg!(--set [set] ... "Set a symbol to a concrete value after decluttering"))
 
         // deprecated
@@ -130,6 +135,7 @@ fn main() -> tract_core::anyhow::Result<()> {
         .arg(arg!(--"nnef-tract-core" "Allow usage of tract-core extension in NNEF dump and load"))
         .arg(arg!(--"nnef-tract-onnx" "Allow usage of tract-onnx extension in NNEF dump and load"))
         .arg(arg!(--"nnef-tract-pulse" "Allow usage of tract-pulse extension in NNEF dump and load"))
+        .arg(arg!(--plugin [lib] ... "Load operators from a plugin library (ONNX and NNEF)"))
 
         .arg(arg!(-O --optimize "Optimize before running"))
         .arg(arg!(--pulse [PULSE] "Translate to pulse network"))
@@ -225,6 +231,109 @@ fn main() -> tract_core::anyhow::Result<()> {
     let run = assertions_options(run);
     app = app.subcommand(run);
 
+    let quantize = clap::Command::new("quantize")
+        .long_about("Post-training int8 or weight-only block quantization, written as NNEF")
+        .arg(
+            Arg::new("calibration")
+                .long("calibration")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .help("Calibration inputs (.npz), one sample per file. Defaults to the run inputs"),
+        )
+        .arg(
+            Arg::new("method")
+                .long("method")
+                .takes_value(true)
+                .possible_values(["minmax", "histogram"])
+                .help("Calibration method [default: minmax]"),
+        )
+        .arg(
+            Arg::new("bins")
+                .long("bins")
+                .takes_value(true)
+                .help("Number of histogram bins [default: 2048]"),
+        )
+        .arg(
+            Arg::new("percentile")
+                .long("percentile")
+                .takes_value(true)
+                .help("Fraction of values kept by histogram calibration [default: 0.9999]"),
+        )
+        .arg(
+            Arg::new("block-quant")
+                .long("block-quant")
+                .takes_value(true)
+                .possible_values(["q4_0", "q8_0"])
+                .help("Weight-only block quantization of matmul weights, without calibration"),
+        )
+        .arg(
+            Arg::new("output")
+                .takes_value(true)
+                .required(true)
+                .help("Output NNEF model (directory, .tar or .tgz)"),
+        );
+    let quantize = run_options(quantize);
+    let quantize = assertions_options(quantize);
+    app = app.subcommand(quantize);
+
+    let compose = clap::Command::new("compose")
+        .long_about("Append NNEF models to the model, wiring outputs to inputs by name, written as NNEF")
+        .arg(
+            Arg::new("append")
+                .long("append")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .required(true)
+                .help("NNEF model to append, in order"),
+        )
+        .arg(
+            Arg::new("link")
+                .long("link")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .help("Feed an output to an input of the appended model (output:input). Each appended model needs at least one link once any is given. Defaults to matching names"),
+        )
+        .arg(
+            Arg::new("output")
+                .takes_value(true)
+                .required(true)
+                .help("Output NNEF model (directory, .tar or .tgz)"),
+        );
+    app = app.subcommand(compose);
+
+    let split = clap::Command::new("split")
+        .long_about("Extract a subgraph, or partition the model in stages of comparable cost, written as NNEF")
+        .arg(
+            Arg::new("stages")
+                .long("stages")
+                .takes_value(true)
+                .help("Number of stages to partition the model into [default: 2]"),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .help("Extract a single subgraph from these outlets (labels or node names, with an optional :slot, comma separated). Defaults to the model inputs"),
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .help("Extract a single subgraph up to these outlets (labels or node names, with an optional :slot, comma separated). Defaults to the model outputs"),
+        )
+        .arg(
+            Arg::new("output")
+                .takes_value(true)
+                .required(true)
+                .help("Output directory for the stage-N.nnef.tgz files"),
+        );
+    app = app.subcommand(split);
+
     let optimize = clap::Command::new("optimize").about("Optimize the graph");
     app = app.subcommand(output_options(optimize));
 
@@ -521,6 +630,12 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> TractResult<()> {
 
         Some(("run", m)) => run::handle(&params, &matches, m),
 
+        Some(("quantize", m)) => quantize::handle(&params, &matches, m),
+
+        Some(("split", m)) => split::handle(&params, &matches, m),
+
+        Some(("compose", m)) => compose::handle(&params, &matches, m),
+
         #[cfg(feature = "pulse")]
         Some(("stream-check", m)) => {
             stream_check::handle(&params, &display_params_from_clap(&matches, m)?)
@@ -566,8 +681,12 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> TractResult<()> {
     Ok(())
 }
 
-fn nnef(matches: &clap::ArgMatches) -> tract_nnef::internal::Nnef {
+fn nnef(matches: &clap::ArgMatches) -> TractResult<tract_nnef::internal::Nnef> {
     let mut fw = tract_nnef::nnef();
+    #[cfg(feature = "plugin")]
+    for plugin in matches.values_of("plugin").into_iter().flatten() {
+        fw = fw.with_plugin(plugin)?;
+    }
     if matches.is_present("nnef-tract-onnx") {
         #[cfg(feature = "onnx")]
         {
@@ -593,5 +712,26 @@ fn nnef(matches: &clap::ArgMatches) -> tract_nnef::internal::Nnef {
     if matches.is_present("nnef-tract-core") {
         fw = fw.with_tract_core();
     }
-    fw
+    Ok(fw)
+}
+
+/// Write a model as NNEF, in a directory, a .tar or a .tgz depending on the path.
+fn write_nnef(matches: &clap::ArgMatches, path: &str, model: &TypedModel) -> TractResult<()> {
+    // quantization casts, block-quantized matmuls and most decluttered ops are tract_core
+    // extensions
+    let mut nnef = nnef(matches)?;
+    if !matches.is_present("nnef-tract-core") {
+        nnef = nnef.with_tract_core();
+    }
+    if path.ends_with(".tgz") || path.ends_with(".tar.gz") {
+        let file = std::fs::File::create(path)?;
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        nnef.write_to_tar(model, encoder).context("Writting model to tar")?;
+    } else if path.ends_with(".tar") {
+        let file = std::fs::File::create(path)?;
+        nnef.write_to_tar(model, file).context("Writting model to tar")?;
+    } else {
+        nnef.write_to_dir(model, path)?;
+    }
+    Ok(())
 }
@@ -4,6 +4,7 @@ use std::io::Read;
 use std::path::PathBuf;
 use std::str::FromStr;
 use tract_core::ops::konst::Const;
+use tract_core::optim::Optimizer;
 #[allow(unused_imports)]
 use tract_itertools::Itertools;
 use tract_libcli::profile::BenchLimits;
@@ -157,6 +158,8 @@ impl Parameters {
         let format = matches.value_of("format").unwrap_or(
             if location.path().extension().map(|s| s == "onnx").unwrap_or(false) {
                 "onnx"
+            } else if location.path().extension().map(|s| s == "tflite").unwrap_or(false) {
+                "tflite"
             } else if location.path().extension().map(|s| s == "raw" || s == "txt").unwrap_or(false)
             {
                 "kaldi"
@@ -187,8 +190,17 @@ impl Parameters {
                     (SomeGraphDef::NoGraphDef, Box::new(parsed), Option::<TfExt>::None)
                 }
             }
+            #[cfg(feature = "tflite")]
+            "tflite" => {
+                let tflite = tract_tflite::tflite();
+                info_usage("loaded framework (tflite)", probe);
+                let proto = tflite.proto_model_for_read(&mut *location.read()?)?;
+                info_usage("proto model loaded", probe);
+                let parsed = tflite.model_for_proto_model_with_symbols(&proto, symbol_table)?;
+                (SomeGraphDef::NoGraphDef, Box::new(parsed), Option::<TfExt>::None)
+            }
             "nnef" => {
-                let nnef = super::nnef(matches);
+                let nnef = super::nnef(matches)?;
                 let mut proto_model = if location.is_dir() {
                     if let ModelLocation::Fs(dir) = location {
                         nnef.proto_model_for_path(dir)?
@@ -275,6 +287,10 @@ impl Parameters {
                 if matches.is_present("onnx-ignore-output-types") {
                     onnx = onnx.with_ignore_output_types(true);
                 }
+                #[cfg(feature = "plugin")]
+                for plugin in matches.values_of("plugin").into_iter().flatten() {
+                    onnx = onnx.with_plugin(plugin)?;
+                }
                 info_usage("loaded framework (onnx)", probe);
                 let graph = onnx.proto_model_for_read(&mut *location.read()?)?;
                 info_usage("proto model loaded", probe);
@@ -584,6 +600,32 @@ impl Parameters {
 
         let nnef_cycle = matches.is_present("nnef-cycle");
 
+        let disabled_passes: Vec<&str> =
+            matches.values_of("pass-disable").map(|v| v.collect()).unwrap_or_default();
+        let known_passes = Optimizer::declutter()
+            .pass_names()
+            .into_iter()
+            .chain(Optimizer::codegen().pass_names())
+            .unique()
+            .collect_vec();
+        for pass in &disabled_passes {
+            if !known_passes.iter().any(|p| p == pass) {
+                bail!("Unknown optimizer pass {}, known passes: {}", pass, known_passes.join(", "));
+            }
+        }
+        let without_disabled = |mut optimizer: Optimizer| -> TractResult<Optimizer> {
+            for pass in &disabled_passes {
+                if optimizer.pass_names().iter().any(|p| p == pass) {
+                    optimizer = optimizer.without_pass(pass)?;
+                }
+            }
+            Ok(optimizer)
+        };
+        let declutter = |mut m: TypedModel| -> TractResult<TypedModel> {
+            without_disabled(Optimizer::declutter())?.optimize(&mut m)?;
+            Ok(m)
+        };
+
         info!("Will stop at {}", stop_at);
 
         if stop_at == "load" {
@@ -664,7 +706,7 @@ impl Parameters {
                     }
                 }
             }
-            let mut dec = tract_core::optim::Optimizer::declutter();
+            let mut dec = without_disabled(Optimizer::declutter())?;
             if let Some(steps) = matches.value_of("declutter-step") {
                 dec = dec.stopping_at(steps.parse()?);
             }
@@ -685,14 +727,18 @@ impl Parameters {
                     let sym = m.symbol_table.sym(&sym);
                     PulsedModel::new(&m, sym, &pulse)
                 });
+                // keep the pulsed model around for stream-check
+                let pulsed = pulsed_model.clone();
                 stage!("pulse-to-type", pulsed_model -> typed_model, |m:PulsedModel| m.into_typed());
-                stage!("pulse-declutter", typed_model -> typed_model, |m:TypedModel| m.into_decluttered());
+                pulsed_model = pulsed;
+                stage!("pulse-declutter", typed_model -> typed_model, declutter);
             }
         }
         if matches.is_present("half-floats") {
+            let f32_accumulation = matches.is_present("half-floats-f32-accumulation");
             stage!("half-float", typed_model -> typed_model, |m:TypedModel| {
                 use tract_core::model::translator::Translate;
-                tract_core::half::HalfTranslator.translate_model(&m)
+                tract_core::half::HalfTranslator::with_f32_accumulation(f32_accumulation).translate_model(&m)
             });
         }
         if let Some(set) = matches.values_of("set") {
@@ -710,17 +756,17 @@ impl Parameters {
             stage!("set", typed_model -> typed_model, |m: TypedModel| {
                 m.concretize_dims(&values)
             });
-            stage!("set-declutter", typed_model -> typed_model, TypedModel::into_decluttered);
+            stage!("set-declutter", typed_model -> typed_model, declutter);
         }
         if nnef_cycle {
             stage!("nnef-cycle", typed_model -> typed_model, |m:TypedModel| {
-                let nnef = super::nnef(matches);
+                let nnef = super::nnef(matches)?;
                 let mut vec = vec!();
                 nnef.write(&m, &mut vec).context("Serializing")?;
                 info!("Dumped, now reloading...");
                 nnef.model_for_read(&mut &*vec).context("Deserializing")
             });
-            stage!("nnef-declutter", typed_model -> typed_model, |m:TypedModel| m.into_decluttered());
+            stage!("nnef-declutter", typed_model -> typed_model, declutter);
         }
         if let Some(sub) = matches.value_of("extract-decluttered-sub") {
             stage!("extract", typed_model -> typed_model, |m:TypedModel| {
@@ -730,7 +776,7 @@ impl Parameters {
         }
         stage!("before-optimize", typed_model -> typed_model, Ok);
         stage!("optimize", typed_model -> typed_model, |mut m:TypedModel| {
-            let mut opt = tract_core::optim::Optimizer::codegen();
+            let mut opt = without_disabled(Optimizer::codegen())?;
             if let Some(steps) = matches.value_of("optimize-step") {
                 opt = opt.stopping_at(steps.parse()?);
             }
@@ -761,6 +807,7 @@ impl Parameters {
                     (true, None)
                 }
             }
+            Some(("stream-check", _)) => (false, Some("declutter")),
             _ => (false, None),
         };
 
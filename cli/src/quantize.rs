@@ -0,0 +1,95 @@
+use tract_core::ops::cnn::ConvUnary;
+use tract_core::ops::matmul::mir_quant_unary::QMatMulUnary;
+use tract_core::ops::matmul::BlockQuantMatMul;
+use tract_core::quantization::{
+    quantize, quantize_weights_blockwise, Calibration, CalibrationMethod,
+};
+use tract_hir::internal::*;
+
+use crate::{Parameters, TractResult};
+
+pub fn handle(
+    params: &Parameters,
+    matches: &clap::ArgMatches,
+    sub_matches: &clap::ArgMatches,
+) -> TractResult<()> {
+    let model = params
+        .tract_model
+        .downcast_ref::<TypedModel>()
+        .context("Can only quantize a typed model. (using --pass ?)")?;
+
+    let quantized = if let Some(format) = sub_matches.value_of("block-quant") {
+        block_quantize(model, format.parse()?)?
+    } else {
+        calibrate_and_quantize(params, model, sub_matches)?
+    };
+
+    super::write_nnef(matches, sub_matches.value_of("output").unwrap(), &quantized)
+}
+
+fn calibrate_and_quantize(
+    params: &Parameters,
+    model: &TypedModel,
+    sub_matches: &clap::ArgMatches,
+) -> TractResult<TypedModel> {
+    let samples = if let Some(files) = sub_matches.values_of("calibration") {
+        files.map(|file| calibration_sample(model, file)).collect::<TractResult<Vec<_>>>()?
+    } else {
+        let run_params = crate::tensor::run_params_from_subcommand(params, sub_matches)?;
+        tract_libcli::tensor::retrieve_or_make_inputs(model, &run_params)?
+    };
+
+    let method = match sub_matches.value_of("method").unwrap_or("minmax") {
+        "minmax" => CalibrationMethod::MinMax,
+        "histogram" => CalibrationMethod::Histogram {
+            bins: sub_matches.value_of("bins").unwrap_or("2048").parse()?,
+            percentile: sub_matches.value_of("percentile").unwrap_or("0.9999").parse()?,
+        },
+        other => bail!("Unknown calibration method {} (expected minmax or histogram)", other),
+    };
+
+    let calibration = Calibration::from_model(model, &samples, method)?;
+    let quantized = quantize(model, &calibration)?;
+    let count = quantized
+        .nodes()
+        .iter()
+        .filter(|n| {
+            n.op_as::<ConvUnary>().map(|c| c.q_params.is_some()).unwrap_or(false)
+                || n.op_is::<QMatMulUnary>()
+        })
+        .count();
+    if count == 0 {
+        bail!(
+            "No operator was quantized. Quantization applies to decluttered models: do not use -O."
+        )
+    }
+    info!("Quantized {} operators, calibrated on {} samples", count, samples.len());
+    Ok(quantized)
+}
+
+fn block_quantize(model: &TypedModel, format: BlockQuant) -> TractResult<TypedModel> {
+    let quantized = quantize_weights_blockwise(model, format)?;
+    let count = quantized.nodes().iter().filter(|n| n.op_is::<BlockQuantMatMul>()).count();
+    if count == 0 {
+        bail!("No matmul weights could be block-quantized. Do not use -O.")
+    }
+    info!("Block-quantized {} matmul weights as {}", count, format);
+    Ok(quantized)
+}
+
+/// Read one set of model inputs from a npz file, by input name.
+fn calibration_sample(model: &TypedModel, file: &str) -> TractResult<TVec<TValue>> {
+    let mut npz = ndarray_npy::NpzReader::new(
+        std::fs::File::open(file).with_context(|| format!("Opening {}", file))?,
+    )?;
+    model
+        .input_outlets()?
+        .iter()
+        .map(|input| {
+            let name = model.node(input.node).name.as_str();
+            let tensor = tract_libcli::tensor::for_npz(&mut npz, name)
+                .with_context(|| format!("Reading input {} from {}", name, file))?;
+            Ok(tensor.into_tvalue())
+        })
+        .collect()
+}
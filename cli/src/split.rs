@@ -0,0 +1,93 @@
+use tract_core::model::partition::Stage;
+use tract_hir::internal::*;
+use tract_itertools::Itertools;
+
+use crate::{Parameters, TractResult};
+
+pub fn handle(
+    params: &Parameters,
+    matches: &clap::ArgMatches,
+    sub_matches: &clap::ArgMatches,
+) -> TractResult<()> {
+    let model = params
+        .tract_model
+        .downcast_ref::<TypedModel>()
+        .context("Can only split a typed model. (using --pass ?)")?;
+
+    let stages = if sub_matches.is_present("from") || sub_matches.is_present("to") {
+        let inputs = outlets(model, sub_matches, "from", model.input_outlets()?)?;
+        let outputs = outlets(model, sub_matches, "to", model.output_outlets()?)?;
+        let subgraph = model.extract_subgraph(&inputs, &outputs)?;
+        tvec!(Stage { model: subgraph, inputs, outputs })
+    } else {
+        let count = sub_matches.value_of("stages").unwrap_or("2").parse()?;
+        model.partition(count)?
+    };
+
+    let mut nnef = super::nnef(matches)?;
+    if !matches.is_present("nnef-tract-core") {
+        nnef = nnef.with_tract_core();
+    }
+    let dir = std::path::Path::new(sub_matches.value_of("output").unwrap());
+    std::fs::create_dir_all(dir).with_context(|| format!("Creating {:?}", dir))?;
+    for (ix, stage) in stages.iter().enumerate() {
+        let path = dir.join(format!("stage-{}.nnef.tgz", ix));
+        let file = std::fs::File::create(&path)?;
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        nnef.write_to_tar(&stage.model, encoder)
+            .with_context(|| format!("Writting stage {} to {:?}", ix, path))?;
+        println!(
+            "{:?}: {} nodes, inputs: {}, outputs: {}",
+            path,
+            stage.model.nodes().len(),
+            names(&stage.model, stage.model.input_outlets()?),
+            names(&stage.model, stage.model.output_outlets()?),
+        );
+    }
+    Ok(())
+}
+
+/// Outlets named in a subcommand argument, by outlet label, node name (for its first output)
+/// or node name and output slot as in `name:1`.
+fn outlets(
+    model: &TypedModel,
+    sub_matches: &clap::ArgMatches,
+    arg: &str,
+    default: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    if let Some(names) = sub_matches.values_of(arg) {
+        names
+            .flat_map(|s| s.split(','))
+            .map(|name| {
+                if let Some(outlet) = model.find_outlet_label(name) {
+                    Ok(outlet)
+                } else if let Ok(node) = model.node_id_by_name(name) {
+                    Ok(OutletId::new(node, 0))
+                } else if let Some((node, slot)) =
+                    name.rsplit_once(':').and_then(|(n, s)| Some((n, s.parse::<usize>().ok()?)))
+                {
+                    let node = model.node_by_name(node)?;
+                    ensure!(
+                        slot < node.outputs.len(),
+                        "Node {} has no output {} (it has {})",
+                        node.name,
+                        slot,
+                        node.outputs.len()
+                    );
+                    Ok(OutletId::new(node.id, slot))
+                } else {
+                    bail!("No outlet or node named {}", name)
+                }
+            })
+            .collect()
+    } else {
+        Ok(default.into())
+    }
+}
+
+fn names(model: &TypedModel, outlets: &[OutletId]) -> String {
+    outlets
+        .iter()
+        .map(|o| model.outlet_label(*o).unwrap_or(&model.node(o.node).name).to_string())
+        .join(", ")
+}
@@ -28,13 +28,19 @@ pub fn check_outputs(got: &[Vec<TValue>], params: &Parameters) -> TractResult<()
             let axis = props
                 .get("pulse.output_axes")
                 .context("multiple turn without pulse.output_axes property")?
-                .as_slice::<i64>()?[ix] as usize;
+                .as_slice::<i64>()?[ix];
             let delay = props
                 .get("pulse.delay")
                 .context("multiple turn without pulse.delay properties")?
                 .as_slice::<i64>()?[ix] as usize;
-            let stacked = Tensor::stack_tensors(axis, &got[ix])?;
-            stacked.slice(axis, delay, delay + exp.shape()[axis])?.into()
+            if axis < 0 {
+                // non streaming output, the last turn holds the final value
+                got[ix].last().unwrap().clone()
+            } else {
+                let axis = axis as usize;
+                let stacked = Tensor::stack_tensors(axis, &got[ix])?;
+                stacked.slice(axis, delay, delay + exp.shape()[axis])?.into()
+            }
         } else {
             got[ix][0].clone()
         };
@@ -1,12 +1,76 @@
 use crate::internal::translator::Translate;
 use crate::internal::*;
-use crate::ops::cnn::ConvUnary;
-use crate::ops::matmul::MatMulUnary;
+use crate::ops::array::{Pad, PadMode};
+use crate::ops::cast::{cast, Cast};
+use crate::ops::cnn::{ConvUnary, DeconvUnary};
+use crate::ops::konst::Const;
+use crate::ops::matmul::{MatMul, MatMulUnary};
+use crate::ops::nn::{Reduce, Softmax};
 use crate::ops::scan::{InputMapping, Scan, StateInitializer};
 use crate::ops::source::TypedSource;
 
-#[derive(Debug)]
-pub struct HalfTranslator;
+/// Converts a f32 model to f16.
+///
+/// Inputs, weights, constants and activations are switched to f16. Softmax and reductions are
+/// numerically sensitive: they are kept in f32 and the translator wires casts around them.
+///
+/// With `f32_accumulation`, matrix products and convolutions are kept in f32 too, so they
+/// accumulate in single precision while the rest of the network runs in f16.
+#[derive(Debug, Default, Clone)]
+pub struct HalfTranslator {
+    pub f32_accumulation: bool,
+}
+
+impl HalfTranslator {
+    pub fn with_f32_accumulation(f32_accumulation: bool) -> HalfTranslator {
+        HalfTranslator { f32_accumulation }
+    }
+
+    fn keep_in_f32(&self, op: &dyn TypedOp) -> bool {
+        op.downcast_ref::<Softmax>().is_some()
+            || op.downcast_ref::<Reduce>().is_some()
+            || (self.f32_accumulation
+                && (op.downcast_ref::<ConvUnary>().is_some()
+                    || op.downcast_ref::<DeconvUnary>().is_some()
+                    || op.downcast_ref::<MatMulUnary>().is_some()
+                    || op.downcast_ref::<MatMul>().is_some()))
+    }
+
+    fn wire_in_f32(
+        &self,
+        node: &Node<TypedFact, Box<dyn TypedOp>>,
+        target: &mut Graph<TypedFact, Box<dyn TypedOp>>,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let mut inputs = tvec!();
+        for (ix, input) in node.inputs.iter().enumerate() {
+            let mut wire = mapping[input];
+            if target.outlet_fact(wire)?.datum_type == f16::datum_type() {
+                wire = target.wire_node(
+                    format!("{}.input_{}_as_f32", node.name, ix),
+                    cast(f32::datum_type()),
+                    &[wire],
+                )?[0];
+            }
+            inputs.push(wire);
+        }
+        let outputs = target.wire_node(&node.name, node.op.clone(), &inputs)?;
+        let mut wires = tvec!();
+        for (ix, output) in outputs.into_iter().enumerate() {
+            if target.outlet_fact(output)?.datum_type == f32::datum_type() {
+                let name = if node.outputs.len() > 1 {
+                    format!("{}.output_{}_as_f16", node.name, ix)
+                } else {
+                    format!("{}.as_f16", node.name)
+                };
+                wires.push(target.wire_node(name, cast(f16::datum_type()), &[output])?[0]);
+            } else {
+                wires.push(output);
+            }
+        }
+        Ok(wires)
+    }
+}
 
 impl Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>> for HalfTranslator {
     fn translate_node(
@@ -16,25 +80,42 @@ impl Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>> for Hal
         target: &mut Graph<TypedFact, Box<dyn TypedOp>>,
         mapping: &HashMap<OutletId, OutletId>,
     ) -> TractResult<TVec<OutletId>> {
+        if self.keep_in_f32(node.op.as_ref()) {
+            return self.wire_in_f32(node, target, mapping);
+        }
         let new_op = if let Some(source) = node.op_as::<TypedSource>() {
             Box::new(TypedSource::new(fact_f32_to_f16(&source.fact)))
+        } else if let Some(op) = node.op_as::<Const>() {
+            Box::new(Const(tensor_f32_to_f16(&op.0)))
+        } else if let Some(op) = node.op_as::<Cast>() {
+            if op.to == f32::datum_type() {
+                Box::new(cast(f16::datum_type()))
+            } else {
+                node.op.clone()
+            }
+        } else if let Some(op) = node.op_as::<Pad>() {
+            if let PadMode::Constant(value) = &op.mode {
+                Box::new(Pad { mode: PadMode::Constant(tensor_f32_to_f16(value)), ..op.clone() })
+            } else {
+                node.op.clone()
+            }
         } else if let Some(op) = node.op_as::<ConvUnary>() {
             Box::new(ConvUnary {
                 kernel: tensor_f32_to_f16(&op.kernel),
                 bias: op.bias.as_ref().map(tensor_f32_to_f16),
                 ..op.clone()
             })
+        } else if let Some(op) = node.op_as::<DeconvUnary>() {
+            Box::new(DeconvUnary {
+                kernel: tensor_f32_to_f16(&op.kernel),
+                bias: op.bias.as_ref().map(tensor_f32_to_f16),
+                ..op.clone()
+            })
         } else if let Some(op) = node.op_as::<MatMulUnary>() {
             Box::new(MatMulUnary { a: tensor_f32_to_f16(&op.a), ..op.clone() })
-                /*
-        } else if let Some(op) = node.op_as::<UnaryOp>() {
-            let mut new = op.clone();
-            new.a = tensor_f32_to_f16(&op.a);
-            Box::new(new)
-            */
         } else if let Some(op) = node.op_as::<Scan>() {
             let mut new = op.clone();
-            new.body = HalfTranslator.translate_model(&op.body)?;
+            new.body = self.translate_model(&op.body)?;
             for im in &mut new.input_mapping {
                 if let InputMapping::State { initializer: StateInitializer::Value(v) } = im {
                     *v = tensor_f32_to_f16(v)
@@ -69,3 +150,62 @@ fn tensor_f32_to_f16(t: &Arc<Tensor>) -> Arc<Tensor> {
         Arc::clone(t)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::matmul::MatMulAxes;
+    use crate::ops::nn::Reducer;
+
+    fn model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let input = model.add_source("input", f32::fact([2, 3]))?;
+        let a = tensor2(&[[0.5f32, -1.0, 2.0], [0.25, 1.5, -0.5]]);
+        let mm = model.wire_node(
+            "mm",
+            MatMulUnary::new(a.into_arc_tensor(), MatMulAxes::default_for_rank(2).transposing_b()),
+            &[input],
+        )?;
+        let offset = model.add_const("offset", rctensor0(0.5f32))?;
+        let offset = model.wire_node("offset.add_axes", AxisOp::Add(0), &[offset])?;
+        let offset = model.wire_node("offset.add_axes_2", AxisOp::Add(0), &offset)?;
+        let sum = model.wire_node("add", crate::ops::math::add(), &[mm[0], offset[0]])?;
+        let softmax =
+            model.wire_node("softmax", Softmax::new(tvec!(1), f32::datum_type()), &sum)?;
+        let reduced = model.wire_node("sum", Reduce::new(tvec!(1), Reducer::Sum), &softmax)?;
+        model.set_output_outlets(&reduced)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn translate_and_run() -> TractResult<()> {
+        let model = model()?;
+        let half = HalfTranslator::default().translate_model(&model)?;
+        let input = tensor2(&[[1.0f32, 2.0, 3.0], [-1.0, 0.0, 0.5]]);
+        assert_eq!(half.input_fact(0)?.datum_type, f16::datum_type());
+        assert_eq!(half.output_fact(0)?.datum_type, f16::datum_type());
+        let softmax = half.node_by_name("softmax")?;
+        assert_eq!(half.outlet_fact(softmax.id.into())?.datum_type, f32::datum_type());
+        let mm = half.node_by_name("mm")?;
+        assert_eq!(half.outlet_fact(mm.id.into())?.datum_type, f16::datum_type());
+
+        let reference = model.into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let half_input = input.cast_to::<f16>()?.into_owned();
+        let found = half.into_optimized()?.into_runnable()?.run(tvec!(half_input.into_tvalue()))?;
+        found[0].cast_to::<f32>()?.close_enough(&reference[0], Approximation::Approximate)
+    }
+
+    #[test]
+    fn f32_accumulation() -> TractResult<()> {
+        let half = HalfTranslator::with_f32_accumulation(true).translate_model(&model()?)?;
+        let mm = half.node_by_name("mm")?;
+        assert_eq!(half.outlet_fact(mm.id.into())?.datum_type, f32::datum_type());
+        assert_eq!(
+            half.node_by_name("mm.input_0_as_f32")?.op_as::<Cast>().unwrap().to,
+            DatumType::F32
+        );
+        let add = half.node_by_name("add")?;
+        assert_eq!(half.outlet_fact(add.id.into())?.datum_type, f16::datum_type());
+        Ok(())
+    }
+}
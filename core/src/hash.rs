@@ -56,9 +56,97 @@ pub fn hash_opt_f32<H: Hasher>(s: &Option<f32>, state: &mut H) {
     }
 }
 
+// TypeId is not stable across compilations, so ops are told apart by name to keep
+// model signatures stable.
 impl Hash for Box<dyn TypedOp> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        std::hash::Hash::hash(&self.type_id(), state);
+        std::hash::Hash::hash(&self.name(), state);
         self.dyn_hash(state)
     }
 }
+
+/// FNV-1a hasher with a fixed seed and platform independent integer encoding.
+///
+/// Unlike `DefaultHasher`, its output does not change from one release or platform to
+/// another, so it can be used for values that are persisted, like model signatures.
+#[derive(Clone, Debug)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> StableHasher {
+        StableHasher(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stable_hasher_is_fnv1a() {
+        let mut hasher = StableHasher::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn stable_hasher_usize_is_u64() {
+        let mut a = StableHasher::default();
+        a.write_usize(12);
+        let mut b = StableHasher::default();
+        b.write_u64(12);
+        assert_eq!(a.finish(), b.finish());
+    }
+}
@@ -81,6 +81,9 @@ mod late_bind;
 pub mod model;
 pub mod optim;
 pub mod plan;
+pub mod plan_cache;
+pub mod quantization;
+pub mod snapshot;
 pub mod value;
 
 pub use dyn_clone;
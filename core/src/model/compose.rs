@@ -0,0 +1,217 @@
+//! Composition of models, feeding the outputs of a model to the inputs of another one.
+use crate::internal::*;
+use std::collections::HashSet;
+
+impl TypedModel {
+    /// Append `other` after `self`, wiring some outputs of `self` to inputs of `other`.
+    ///
+    /// `links` pairs an output of `self` (named after its label or node) with an input of
+    /// `other` (named after its source node). If `links` is empty, outputs and inputs with the
+    /// same name are linked. Output facts must be compatible with the input facts they feed.
+    ///
+    /// Symbols are unified by name: the symbols of `other` are rebound to the symbols of the
+    /// same name in the symbol table of `self`. This covers the facts of `other`, but not the
+    /// dimensions its operators may embed: composing fails if they refer to a symbol.
+    ///
+    /// The composed model inputs are the inputs of `self`, followed by the unlinked inputs of
+    /// `other`. Its outputs are the outputs of `other`, followed by the unlinked outputs of
+    /// `self`. Nodes of `other` are renamed when their name is already in use.
+    pub fn compose(&self, other: &TypedModel, links: &[(&str, &str)]) -> TractResult<TypedModel> {
+        let links: Vec<(&str, &str)> = if links.is_empty() {
+            other
+                .input_outlets()?
+                .iter()
+                .map(|i| other.node(i.node).name.as_str())
+                .filter(|name| self.outputs.iter().any(|o| self.outlet_name(*o) == *name))
+                .map(|name| (name, name))
+                .collect()
+        } else {
+            links.to_vec()
+        };
+        if links.is_empty() {
+            bail!("No input of the appended model matches an output of the model");
+        }
+
+        let mut model = self.clone();
+        let mut mapping: HashMap<OutletId, OutletId> = HashMap::new();
+        let mut linked: TVec<OutletId> = tvec!();
+        for (from, to) in links {
+            let outlet =
+                if let Some(o) = self.outputs.iter().find(|o| self.outlet_name(**o) == from) {
+                    *o
+                } else {
+                    bail!("Model has no output named {}", from)
+                };
+            let input = if let Some(i) = other.inputs.iter().find(|i| other.node(i.node).name == to)
+            {
+                *i
+            } else {
+                bail!("Appended model has no input named {}", to)
+            };
+            if mapping.contains_key(&input) {
+                bail!("Input {} is linked twice", to);
+            }
+            let fact = self.outlet_fact(outlet)?;
+            let expected = rebind_symbols(other.outlet_fact(input)?, &self.symbol_table);
+            if !fact.compatible_with(&expected) {
+                bail!("Can not feed {} ({:?}) to {} ({:?})", from, fact, to, expected);
+            }
+            mapping.insert(input, outlet);
+            linked.push(outlet);
+        }
+
+        let mut names: HashSet<String> = model.nodes().iter().map(|n| n.name.clone()).collect();
+        let mut unique = |name: &str| {
+            let mut candidate = name.to_string();
+            let mut i = 0;
+            while names.contains(&candidate) {
+                candidate = format!("{}_{}", name, i);
+                i += 1;
+            }
+            names.insert(candidate.clone());
+            candidate
+        };
+        for input in other.input_outlets()? {
+            if !mapping.contains_key(input) {
+                let name = unique(&other.node(input.node).name);
+                let fact = rebind_symbols(other.outlet_fact(*input)?, &model.symbol_table);
+                let source = model.add_source(name, fact)?;
+                mapping.insert(*input, source);
+            }
+        }
+        for id in other.eval_order()? {
+            let node = other.node(id);
+            if other.inputs.contains(&OutletId::new(id, 0)) {
+                continue;
+            }
+            let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+            let wires = model.wire_node(unique(&node.name), node.op.clone(), &inputs)?;
+            for wire in &wires {
+                for symbol in model.outlet_fact(*wire)?.shape.iter().flat_map(|d| d.symbols()) {
+                    if model.symbol_table.get(&symbol.to_string()) != Some(symbol.clone()) {
+                        bail!(
+                            "Node {} of the appended model refers to its own symbol {}",
+                            node.name,
+                            symbol
+                        );
+                    }
+                }
+            }
+            for (ix, wire) in wires.into_iter().enumerate() {
+                mapping.insert(OutletId::new(id, ix), wire);
+            }
+        }
+
+        let mut outputs = vec![];
+        for output in other.output_outlets()? {
+            model.set_outlet_label(mapping[output], other.outlet_name(*output))?;
+            outputs.push(mapping[output]);
+        }
+        for output in self.output_outlets()? {
+            if !linked.contains(output) {
+                model.set_outlet_label(*output, self.outlet_name(*output))?;
+                outputs.push(*output);
+            }
+        }
+        model.set_output_outlets(&outputs)?;
+        for (k, v) in &other.properties {
+            model.properties.entry(k.clone()).or_insert_with(|| v.clone());
+        }
+        Ok(model)
+    }
+}
+
+/// Rebind the symbols of a fact to the symbols of the same name in `table`.
+fn rebind_symbols(fact: &TypedFact, table: &SymbolTable) -> TypedFact {
+    let mut fact = fact.clone();
+    for ix in 0..fact.shape.len() {
+        let mut dim = fact.shape[ix].clone();
+        for symbol in dim.symbols() {
+            dim = dim.substitute(&symbol, &table.sym(&symbol.to_string()).into());
+        }
+        fact.shape.set(ix, dim);
+    }
+    fact
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn affine(input: &str, output: &str, a: f32, b: f32) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source(input, f32::fact([2]))?;
+        let a = model.add_const(format!("{}.a", output), tensor1(&[a, a]))?;
+        let b = model.add_const(format!("{}.b", output), tensor1(&[b, b]))?;
+        let ax = model.wire_node(format!("{}.ax", output), math::mul(), &[x, a])?;
+        let y = model.wire_node(output, math::add(), &[ax[0], b])?;
+        model.set_output_outlets(&y)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn compose_by_name() -> TractResult<()> {
+        let pre = affine("x", "y", 2.0, 1.0)?;
+        let post = affine("y", "z", 3.0, 0.0)?;
+        let model = pre.compose(&post, &[])?;
+        assert_eq!(model.input_outlets()?.len(), 1);
+        assert_eq!(model.output_outlets()?.len(), 1);
+        assert_eq!(model.outlet_label(model.output_outlets()?[0]), Some("z"));
+        let out = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.]).into_tvalue()))?;
+        assert_eq!(*out[0], tensor1(&[9f32, 15.]));
+        Ok(())
+    }
+
+    #[test]
+    fn compose_with_links() -> TractResult<()> {
+        let pre = affine("x", "y", 2.0, 1.0)?;
+        let post = affine("x", "y", 3.0, 0.0)?;
+        assert!(pre.compose(&post, &[("y", "nope")]).is_err());
+        let model = pre.compose(&post, &[("y", "x")])?;
+        // the post model nodes are renamed
+        assert!(model.node_id_by_name("y_0").is_ok());
+        let model = model.into_decluttered()?;
+        let out = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.]).into_tvalue()))?;
+        assert_eq!(*out[0], tensor1(&[9f32, 15.]));
+        Ok(())
+    }
+
+    #[test]
+    fn incompatible_facts() -> TractResult<()> {
+        let pre = affine("x", "y", 2.0, 1.0)?;
+        let mut post = TypedModel::default();
+        let y = post.add_source("y", f32::fact([3]))?;
+        post.set_output_outlets(&[y])?;
+        assert!(pre.compose(&post, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn shared_symbols() -> TractResult<()> {
+        let symbols = SymbolTable::default();
+        let n = symbols.sym("N");
+        let mut pre = TypedModel { symbol_table: symbols.clone(), ..TypedModel::default() };
+        let x = pre.add_source("x", f32::fact(&[n.to_dim()]))?;
+        pre.set_output_outlets(&[x])?;
+        let mut post = TypedModel { symbol_table: symbols, ..TypedModel::default() };
+        let n = post.symbol_table.sym("N");
+        let x = post.add_source("x", f32::fact(&[n.to_dim()]))?;
+        post.set_output_outlets(&[x])?;
+        pre.compose(&post, &[])?;
+
+        // separate tables: symbols are matched by name
+        let mut other = TypedModel::default();
+        let n = other.symbol_table.sym("N");
+        let m = other.symbol_table.sym("M");
+        let x = other.add_source("x", f32::fact(&[n.to_dim()]))?;
+        let y = other.add_source("y", f32::fact(&[m.to_dim()]))?;
+        other.set_output_outlets(&[x, y])?;
+        let model = pre.compose(&other, &[])?;
+        let n = pre.symbol_table.get("N").unwrap();
+        let m = pre.symbol_table.get("M").unwrap();
+        assert_eq!(model.output_fact(0)?.shape, f32::fact(&[n.to_dim()]).shape);
+        assert_eq!(model.output_fact(1)?.shape, f32::fact(&[m.to_dim()]).shape);
+        Ok(())
+    }
+}
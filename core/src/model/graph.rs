@@ -93,6 +93,100 @@ where
         self.inputs.push(id);
         Ok(id)
     }
+
+    /// Extract the minimal subgraph computing `outputs` from `inputs`.
+    ///
+    /// Each input outlet becomes a new source with the same fact, named after the outlet
+    /// label, or after its node. Only the nodes needed to compute `outputs` are kept, and the
+    /// output outlets are labelled the same way, so a subgraph outputs can be matched by name to
+    /// the inputs of a subgraph extracted downstream. Fails if the outputs depend on a model
+    /// source which is not part of `inputs`.
+    pub fn extract_subgraph(&self, inputs: &[OutletId], outputs: &[OutletId]) -> TractResult<Self> {
+        for outlet in inputs.iter().chain(outputs.iter()) {
+            if outlet.node >= self.nodes.len()
+                || outlet.slot >= self.nodes[outlet.node].outputs.len()
+            {
+                bail!("Invalid outlet {:?} for subgraph extraction", outlet);
+            }
+        }
+        let mut needed = bit_set::BitSet::with_capacity(self.nodes.len());
+        let mut todo: Vec<OutletId> = outputs.to_vec();
+        while let Some(outlet) = todo.pop() {
+            if inputs.contains(&outlet) || needed.contains(outlet.node) {
+                continue;
+            }
+            let node = &self.nodes[outlet.node];
+            if Self::is_source(&node.op) {
+                bail!(
+                    "Subgraph depends on model input {} which is not one of its inputs",
+                    node.name
+                );
+            }
+            needed.insert(outlet.node);
+            todo.extend(node.inputs.iter().copied());
+        }
+        let mut subgraph = Self::default();
+        let mut mapping: HashMap<OutletId, OutletId> = HashMap::new();
+        // copied nodes keep their names, sources get renamed on collision
+        let mut names: std::collections::HashSet<String> =
+            needed.iter().map(|id| self.nodes[id].name.clone()).collect();
+        for &input in inputs {
+            if mapping.contains_key(&input) {
+                bail!("Input {:?} appears twice in subgraph extraction", input);
+            }
+            let name = self.outlet_name(input);
+            let mut candidate = name.clone();
+            let mut i = 0;
+            while names.contains(&candidate) {
+                candidate = format!("{}_{}", name, i);
+                i += 1;
+            }
+            names.insert(candidate.clone());
+            let fact = self.outlet_fact(input)?.clone();
+            let source = subgraph.add_source(candidate, fact)?;
+            mapping.insert(input, source);
+        }
+        let targets = outputs.iter().map(|o| o.node).collect::<Vec<_>>();
+        for id in super::order::eval_order_for_nodes(&self.nodes, &[], &targets, &[])? {
+            if !needed.contains(id) {
+                continue;
+            }
+            let node = &self.nodes[id];
+            let facts = node.outputs.iter().map(|o| o.fact.clone()).collect();
+            let new_id = subgraph.add_node(&node.name, node.op.clone(), facts)?;
+            for (ix, input) in node.inputs.iter().enumerate() {
+                subgraph.add_edge(mapping[input], InletId::new(new_id, ix))?;
+            }
+            for slot in 0..node.outputs.len() {
+                let outlet = OutletId::new(id, slot);
+                mapping.insert(outlet, OutletId::new(new_id, slot));
+                if let Some(label) = self.outlet_label(outlet) {
+                    subgraph.set_outlet_label(OutletId::new(new_id, slot), label.to_string())?;
+                }
+            }
+        }
+        for &output in outputs {
+            if !inputs.contains(&output) {
+                subgraph.set_outlet_label(mapping[&output], self.outlet_name(output))?;
+            }
+        }
+        subgraph.outputs = outputs.iter().map(|o| mapping[o]).collect();
+        subgraph.symbol_table = self.symbol_table.clone();
+        subgraph.properties = self.properties.clone();
+        Ok(subgraph)
+    }
+
+    /// Name for an outlet: its label if any, else its node name, suffixed by the slot for
+    /// secondary outputs.
+    pub fn outlet_name(&self, outlet: OutletId) -> String {
+        if let Some(label) = self.outlet_label(outlet) {
+            label.to_string()
+        } else if outlet.slot == 0 {
+            self.nodes[outlet.node].name.clone()
+        } else {
+            format!("{}.{}", self.nodes[outlet.node].name, outlet.slot)
+        }
+    }
 }
 
 impl<F, O> Graph<F, O>
@@ -100,6 +194,14 @@ where
     F: Fact + Hash + Clone + 'static,
     O: fmt::Debug + fmt::Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
 {
+    /// Hash of the model, stable across tract releases and platforms.
+    pub fn signature(&self) -> u64 {
+        use std::hash::Hasher;
+        let mut hasher = crate::hash::StableHasher::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
     pub fn add_node(
         &mut self,
         name: impl Into<String>,
@@ -722,4 +824,23 @@ mod test {
         let mut hasher = std::collections::hash_map::DefaultHasher::default();
         model.hash(&mut hasher);
     }
+
+    #[test]
+    // signatures are persisted in state snapshots: changing this value breaks them
+    fn stable_signature() {
+        let mut model = TypedModel::default();
+        let s = model.add_source("source", f32::fact([1, 2, 3])).unwrap();
+        model.set_output_outlets(&[s]).unwrap();
+        assert_eq!(model.signature(), 6619860046420902697);
+    }
+
+    #[test]
+    fn stable_signature_with_mini_ops() {
+        let mut model = TypedModel::default();
+        let s = model.add_source("source", f32::fact([1, 2, 3])).unwrap();
+        let abs = model.wire_node("abs", crate::ops::math::abs(), &[s]).unwrap();
+        let add = model.wire_node("add", crate::ops::math::add(), &[s, abs[0]]).unwrap();
+        model.set_output_outlets(&add).unwrap();
+        assert_eq!(model.signature(), 18223618666738863358);
+    }
 }
@@ -36,10 +36,12 @@
 use std::collections::HashMap;
 use std::str;
 
+mod compose;
 mod fact;
 mod graph;
 mod node;
 pub mod order;
+pub mod partition;
 mod patch;
 pub mod translator;
 pub mod typed;
@@ -0,0 +1,212 @@
+//! Partitioning of a model in sequential stages of comparable cost.
+use crate::internal::*;
+use crate::ops::konst::Const;
+use crate::ops::source::TypedSource;
+
+/// One stage of a partitioned model.
+#[derive(Clone, Debug)]
+pub struct Stage {
+    /// The stage model, as extracted by `extract_subgraph`.
+    pub model: TypedModel,
+    /// Outlets of the original model feeding the stage inputs.
+    pub inputs: TVec<OutletId>,
+    /// Outlets of the original model computed by the stage outputs.
+    pub outputs: TVec<OutletId>,
+}
+
+impl TypedModel {
+    /// Split the model in at most `stages` sequential parts of comparable cost.
+    ///
+    /// Nodes are assigned to stages following the evaluation order, weighting them by the
+    /// compute part of `TypedOp::cost` (or counting them if no operator reports a compute
+    /// cost). A stage outputs everything the later stages and the model outputs need from it,
+    /// including values consumed several stages downstream. Empty stages are skipped.
+    ///
+    /// Constants are not assigned to stages: every stage using a constant gets its own copy of
+    /// the `Const` node. The copies share the same tensor in memory, but each stage serialized
+    /// on its own embeds the full value, so weights used by several stages are stored several
+    /// times and the stages together can be larger than the original model.
+    pub fn partition(&self, stages: usize) -> TractResult<TVec<Stage>> {
+        ensure!(stages > 0, "Can not partition a model in zero stages");
+        let order = self.eval_order()?;
+        let movable = |id: usize| {
+            let node = self.node(id);
+            !node.op_is::<TypedSource>() && !node.op_is::<Const>()
+        };
+        let mut costs = vec![0f64; self.nodes.len()];
+        for &id in &order {
+            if movable(id) {
+                let inputs = self.node_input_facts(id)?;
+                for (cost, value) in self.node(id).op.cost(&inputs)? {
+                    if cost.is_compute() {
+                        costs[id] += value.to_i64().unwrap_or(1) as f64;
+                    }
+                }
+            }
+        }
+        if costs.iter().all(|c| *c == 0.0) {
+            order.iter().filter(|id| movable(**id)).for_each(|&id| costs[id] = 1.0);
+        }
+        let total: f64 = costs.iter().sum();
+
+        let mut stage_of: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut done = 0.0;
+        for &id in &order {
+            if movable(id) {
+                let middle = done + costs[id] / 2.0;
+                done += costs[id];
+                stage_of[id] = Some(((middle / total * stages as f64) as usize).min(stages - 1));
+            }
+        }
+
+        let mut result = tvec!();
+        for stage in 0..stages {
+            let mut inputs: TVec<OutletId> = tvec!();
+            let mut outputs: TVec<OutletId> = tvec!();
+            for &id in &order {
+                let node = self.node(id);
+                if stage_of[id] == Some(stage) {
+                    for input in &node.inputs {
+                        if movable(input.node)
+                            && stage_of[input.node] != Some(stage)
+                            && !inputs.contains(input)
+                        {
+                            inputs.push(*input);
+                        }
+                    }
+                    for slot in 0..node.outputs.len() {
+                        let outlet = OutletId::new(id, slot);
+                        let used_later = self
+                            .outlet_successors(outlet)
+                            .iter()
+                            .any(|succ| stage_of[succ.node].map(|s| s > stage).unwrap_or(false));
+                        if used_later || self.outputs.contains(&outlet) {
+                            outputs.push(outlet);
+                        }
+                    }
+                } else if node.op_is::<TypedSource>()
+                    && node.outputs[0]
+                        .successors
+                        .iter()
+                        .any(|succ| stage_of[succ.node] == Some(stage))
+                {
+                    inputs.push(OutletId::new(id, 0));
+                }
+            }
+            if outputs.is_empty() {
+                continue;
+            }
+            let model = self.extract_subgraph(&inputs, &outputs)?;
+            result.push(Stage { model, inputs, outputs });
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn chain(len: usize) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source("input", f32::fact([2, 3]))?;
+        let source = wire;
+        for ix in 0..len {
+            let other = if ix % 2 == 0 { source } else { wire };
+            wire = model.wire_node(format!("add-{}", ix), math::add(), &[wire, other])?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        Ok(model)
+    }
+
+    #[test]
+    fn extract_middle() -> TractResult<()> {
+        let model = chain(4)?;
+        let from = model.node_by_name("add-0")?.id;
+        let to = model.node_by_name("add-2")?.id;
+        let sub = model.extract_subgraph(
+            &[OutletId::new(0, 0), OutletId::new(from, 0)],
+            &[OutletId::new(to, 0)],
+        )?;
+        assert_eq!(sub.nodes().len(), 4);
+        assert_eq!(sub.node(sub.input_outlets()?[1].node).name, "add-0");
+        assert_eq!(sub.outlet_label(sub.output_outlets()?[0]), Some("add-2"));
+        let x = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        let twice = tensor2(&[[2f32, 4., 6.], [8., 10., 12.]]);
+        let out = sub.into_runnable()?.run(tvec!(x.into_tvalue(), twice.into_tvalue()))?;
+        assert_eq!(*out[0], tensor2(&[[5f32, 10., 15.], [20., 25., 30.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn extract_keeps_names_unique() -> TractResult<()> {
+        use crate::ops::cnn::{MaxPool, PaddingSpec, PoolSpec};
+        use crate::ops::nn::DataFormat;
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", f32::fact([1, 1, 2, 2]))?;
+        let pool_spec =
+            PoolSpec::new(DataFormat::NCHW, tvec!(2, 2), PaddingSpec::Valid, None, None, None);
+        let pool = MaxPool::new(pool_spec, Some(i64::datum_type()));
+        let pool = model.wire_node("pool", pool, &[source])?;
+        model.set_output_outlets(&pool)?;
+        // the values of the pool become an input, its indices are still computed
+        let sub = model.extract_subgraph(&[source, pool[0]], &[pool[1]])?;
+        let names = sub.nodes().iter().map(|n| &n.name).collect::<std::collections::HashSet<_>>();
+        assert_eq!(names.len(), sub.nodes().len());
+        assert_eq!(sub.node(sub.input_outlets()?[1].node).name, "pool_0");
+        assert!(sub.node_by_name("pool")?.op_is::<MaxPool>());
+        Ok(())
+    }
+
+    #[test]
+    fn extract_needs_inputs() {
+        let model = chain(3).unwrap();
+        let from = model.node_by_name("add-0").unwrap().id;
+        assert!(model.extract_subgraph(&[OutletId::new(from, 0)], &model.outputs).is_err());
+    }
+
+    #[test]
+    fn partition_chain() -> TractResult<()> {
+        let model = chain(6)?;
+        let stages = model.partition(3)?;
+        assert_eq!(stages.len(), 3);
+        assert!(stages.iter().all(|s| s.model.nodes().len() == 2 + s.inputs.len()));
+        // the model input is used by every other add
+        assert!(stages.iter().all(|s| s.inputs.contains(&OutletId::new(0, 0))));
+        assert_eq!(stages[2].outputs, model.outputs.iter().copied().collect::<TVec<_>>());
+
+        let x = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        let mut values: HashMap<OutletId, TValue> = HashMap::new();
+        values.insert(OutletId::new(0, 0), x.clone().into_tvalue());
+        for stage in stages {
+            let inputs = stage.inputs.iter().map(|i| values[i].clone()).collect();
+            let outputs = stage.model.into_runnable()?.run(inputs)?;
+            for (outlet, value) in stage.outputs.iter().zip(outputs) {
+                values.insert(*outlet, value);
+            }
+        }
+        let output = model.outputs[0];
+        let expected = model.into_runnable()?.run(tvec!(x.into_tvalue()))?;
+        assert_eq!(values[&output], expected[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn partition_duplicates_constants() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", f32::fact([2]))?;
+        let k = model.add_const("k", tensor1(&[1f32, 2.]))?;
+        let a = model.wire_node("a", math::add(), &[source, k])?;
+        let b = model.wire_node("b", math::mul(), &[a[0], k])?;
+        model.set_output_outlets(&b)?;
+        let stages = model.partition(2)?;
+        assert_eq!(stages.len(), 2);
+        let konsts: Vec<Arc<Tensor>> = stages
+            .iter()
+            .map(|s| s.model.nodes().iter().find_map(|n| n.op_as::<Const>()).unwrap().0.clone())
+            .collect();
+        assert!(Arc::ptr_eq(&konsts[0], &konsts[1]));
+        Ok(())
+    }
+}
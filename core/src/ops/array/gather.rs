@@ -105,11 +105,21 @@ impl TypedOp for Gather {
                     },
                     &[wire],
                 )?[0];
-                wire = patch.wire_node(
-                    format!("{}.rm_axis", node.name),
-                    crate::ops::change_axes::AxisOp::Rm(self.axis),
-                    &[wire],
-                )?[0];
+                // the gathered axis is replaced by the (unit) axes of the indices
+                if indices.rank() == 0 {
+                    wire = patch.wire_node(
+                        format!("{}.rm_axis", node.name),
+                        crate::ops::change_axes::AxisOp::Rm(self.axis),
+                        &[wire],
+                    )?[0];
+                }
+                for ix in 1..indices.rank() {
+                    wire = patch.wire_node(
+                        format!("{}.add_axis_{}", node.name, ix),
+                        crate::ops::change_axes::AxisOp::Add(self.axis),
+                        &[wire],
+                    )?[0];
+                }
                 patch.shunt_outside(model, node.id.into(), wire)?;
                 return Ok(Some(patch));
             }
@@ -150,4 +160,19 @@ mod tests {
             assert_eq!(*output.to_scalar::<i64>().unwrap(), idx + 1);
         }
     }
+
+    #[test]
+    fn declutter_single_index_keeps_indices_rank() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let data = model.add_source("data", f32::fact([2, 3]))?;
+        let indices = model.add_const("indices", tensor1(&[2i64]))?;
+        let gathered = model.wire_node("gather", Gather::new(1), &[data, indices])?;
+        model.set_output_outlets(&gathered)?;
+        let model = model.into_decluttered()?;
+        assert_eq!(model.output_fact(0)?.shape, f32::fact([2, 1]).shape);
+        let output =
+            model.into_runnable()?.run(tvec!(tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]).into()))?;
+        assert_eq!(*output[0], tensor2(&[[3f32], [6.]]));
+        Ok(())
+    }
 }
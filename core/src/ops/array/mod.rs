@@ -24,7 +24,7 @@ pub use self::one_hot::OneHot;
 pub use self::pad::{Pad, PadMode};
 pub use self::reshape::FiniteReshape;
 pub use self::range::Range;
-pub use self::scatter_elements::ScatterElements;
+pub use self::scatter_elements::{ScatterElements, ScatterReduction};
 pub use self::scatter_nd::ScatterNd;
 pub use self::slice::Slice;
 pub use self::tile::Tile;
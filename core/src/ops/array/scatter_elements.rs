@@ -1,9 +1,69 @@
 use crate::internal::*;
 use ndarray::*;
+use std::ops::{Add, Mul};
+
+/// How updates are combined with the values they land on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScatterReduction {
+    None,
+    Add,
+    Mul,
+    Max,
+    Min,
+}
+
+impl ScatterReduction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScatterReduction::None => "none",
+            ScatterReduction::Add => "add",
+            ScatterReduction::Mul => "mul",
+            ScatterReduction::Max => "max",
+            ScatterReduction::Min => "min",
+        }
+    }
+
+    pub fn parse(s: &str) -> TractResult<ScatterReduction> {
+        Ok(match s {
+            "none" => ScatterReduction::None,
+            "add" => ScatterReduction::Add,
+            "mul" => ScatterReduction::Mul,
+            "max" => ScatterReduction::Max,
+            "min" => ScatterReduction::Min,
+            s => bail!("Unknown scatter reduction: {}", s),
+        })
+    }
+
+    pub(crate) fn reduce<T>(&self, current: T, update: T) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<Output = T> + PartialOrd,
+    {
+        match self {
+            ScatterReduction::None => update,
+            ScatterReduction::Add => current + update,
+            ScatterReduction::Mul => current * update,
+            ScatterReduction::Max => {
+                if update > current {
+                    update
+                } else {
+                    current
+                }
+            }
+            ScatterReduction::Min => {
+                if update < current {
+                    update
+                } else {
+                    current
+                }
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, new, Hash)]
 pub struct ScatterElements {
     pub axis: usize,
+    pub reduction: ScatterReduction,
 }
 impl_dyn_hash!(ScatterElements);
 
@@ -12,15 +72,20 @@ impl Op for ScatterElements {
         "ScatterElements".into()
     }
 
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} reduction: {}", self.axis, self.reduction.as_str())])
+    }
+
     op_as_typed_op!();
 }
 
 impl ScatterElements {
-    unsafe fn eval_t<T: Datum>(
+    unsafe fn scatter<T: Datum>(
         &self,
         data: TValue,
         indices: &ArrayViewD<i64>,
         updates: TValue,
+        combine: impl Fn(&mut T, &T),
     ) -> TractResult<TValue> {
         let mut data = data.into_tensor().into_array_unchecked::<T>();
         let updates_view = updates.to_array_view_unchecked::<T>();
@@ -28,12 +93,34 @@ impl ScatterElements {
             let index = indices[&coords];
             coords[self.axis] =
                 if index < 0 { index + data.shape()[self.axis] as i64 } else { index } as usize;
-            data[coords] = value.clone()
+            combine(&mut data[coords], value)
         }
         let mut tensor = data.into_tensor();
         tensor.set_datum_type(updates.datum_type());
         Ok(tensor.into_tvalue())
     }
+
+    unsafe fn eval_t<T: Datum>(
+        &self,
+        data: TValue,
+        indices: &ArrayViewD<i64>,
+        updates: TValue,
+    ) -> TractResult<TValue> {
+        self.scatter::<T>(data, indices, updates, |d, u| *d = u.clone())
+    }
+
+    unsafe fn eval_reduce_t<T>(
+        &self,
+        data: TValue,
+        indices: &ArrayViewD<i64>,
+        updates: TValue,
+    ) -> TractResult<TValue>
+    where
+        T: Datum + Copy + Add<Output = T> + Mul<Output = T> + PartialOrd,
+    {
+        let reduction = self.reduction;
+        self.scatter::<T>(data, indices, updates, |d, u| *d = reduction.reduce(*d, *u))
+    }
 }
 
 impl TypedOp for ScatterElements {
@@ -61,9 +148,43 @@ impl EvalOp for ScatterElements {
             );
         }
         unsafe {
-            Ok(tvec!(dispatch_datum_by_size!(Self::eval_t(data.datum_type())(
-                self, data, &indices, updates
-            ))?))
+            if self.reduction == ScatterReduction::None {
+                Ok(tvec!(dispatch_datum_by_size!(Self::eval_t(data.datum_type())(
+                    self, data, &indices, updates
+                ))?))
+            } else {
+                Ok(tvec!(dispatch_numbers!(Self::eval_reduce_t(data.datum_type())(
+                    self, data, &indices, updates
+                ))?))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scatter_add() -> TractResult<()> {
+        let op = ScatterElements::new(1, ScatterReduction::Add);
+        let output = op.eval(tvec!(
+            tensor2(&[[1f32, 2., 3., 4., 5.]]).into(),
+            tensor2(&[[1i64, 1]]).into(),
+            tensor2(&[[1.1f32, 2.1]]).into(),
+        ))?;
+        output[0].close_enough(&tensor2(&[[1f32, 5.2, 3., 4., 5.]]), true)
+    }
+
+    #[test]
+    fn scatter_max() -> TractResult<()> {
+        let op = ScatterElements::new(0, ScatterReduction::Max);
+        let output = op.eval(tvec!(
+            tensor1(&[1i32, 5, 3]).into(),
+            tensor1(&[0i64, 1, 0]).into(),
+            tensor1(&[4i32, 2, 2]).into(),
+        ))?;
+        assert_eq!(*output[0], tensor1(&[4i32, 5, 3]));
+        Ok(())
+    }
+}
@@ -1,8 +1,13 @@
 use crate::internal::*;
 use ndarray::*;
+use std::ops::{Add, Mul};
+
+use super::ScatterReduction;
 
 #[derive(Debug, Clone, new, Hash)]
-pub struct ScatterNd;
+pub struct ScatterNd {
+    pub reduction: ScatterReduction,
+}
 
 impl_dyn_hash!(ScatterNd);
 
@@ -11,15 +16,20 @@ impl Op for ScatterNd {
         "ScatterNd".into()
     }
 
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("reduction: {}", self.reduction.as_str())])
+    }
+
     op_as_typed_op!();
 }
 
 impl ScatterNd {
-    unsafe fn eval_t<T: Datum>(
+    unsafe fn scatter<T: Datum>(
         &self,
         data: TValue,
         indices: &ArrayViewD<i64>,
         updates: TValue,
+        combine: impl Fn(&mut T, &T),
     ) -> TractResult<TValue> {
         let mut data = data.into_tensor().into_array_unchecked::<T>();
         let updates_view = updates.to_array_view_unchecked::<T>();
@@ -35,12 +45,34 @@ impl ScatterNd {
                 data.index_axis_inplace(Axis(0), *x as usize);
             }
 
-            data.assign(&updates)
+            data.zip_mut_with(&updates, &combine)
         }
         let mut tensor = data.into_tensor();
         tensor.set_datum_type(updates.datum_type());
         Ok(tensor.into_tvalue())
     }
+
+    unsafe fn eval_t<T: Datum>(
+        &self,
+        data: TValue,
+        indices: &ArrayViewD<i64>,
+        updates: TValue,
+    ) -> TractResult<TValue> {
+        self.scatter::<T>(data, indices, updates, |d, u| *d = u.clone())
+    }
+
+    unsafe fn eval_reduce_t<T>(
+        &self,
+        data: TValue,
+        indices: &ArrayViewD<i64>,
+        updates: TValue,
+    ) -> TractResult<TValue>
+    where
+        T: Datum + Copy + Add<Output = T> + Mul<Output = T> + PartialOrd,
+    {
+        let reduction = self.reduction;
+        self.scatter::<T>(data, indices, updates, |d, u| *d = reduction.reduce(*d, *u))
+    }
 }
 
 impl TypedOp for ScatterNd {
@@ -68,9 +100,32 @@ impl EvalOp for ScatterNd {
             );
         }
         unsafe {
-            Ok(tvec!(dispatch_datum_by_size!(Self::eval_t(data.datum_type())(
-                self, data, &indices, updates
-            ))?))
+            if self.reduction == ScatterReduction::None {
+                Ok(tvec!(dispatch_datum_by_size!(Self::eval_t(data.datum_type())(
+                    self, data, &indices, updates
+                ))?))
+            } else {
+                Ok(tvec!(dispatch_numbers!(Self::eval_reduce_t(data.datum_type())(
+                    self, data, &indices, updates
+                ))?))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scatter_nd_mul() -> TractResult<()> {
+        let op = ScatterNd::new(ScatterReduction::Mul);
+        let output = op.eval(tvec!(
+            tensor1(&[1f32, 2., 3., 4.]).into(),
+            tensor2(&[[1i64], [3], [1]]).into(),
+            tensor1(&[10f32, 2., 3.]).into(),
+        ))?;
+        assert_eq!(*output[0], tensor1(&[1f32, 60., 3., 8.]));
+        Ok(())
+    }
+}
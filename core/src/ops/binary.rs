@@ -110,7 +110,7 @@ downcast_rs::impl_downcast!(BinMiniOp);
 
 impl Hash for Box<dyn BinMiniOp> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        std::hash::Hash::hash(&self.type_id(), state);
+        std::hash::Hash::hash(&self.name(), state);
         self.dyn_hash(state)
     }
 }
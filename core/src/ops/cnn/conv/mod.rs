@@ -7,12 +7,14 @@ pub mod proptest;
 mod proptest_q;
 mod q_sum_b;
 mod unary;
+mod winograd;
 
 use crate::internal::*;
 
 pub use self::im2col::Im2Col;
 pub(crate) use self::q_sum_b::QSumB;
 pub use self::unary::ConvUnary;
+pub use self::winograd::{Winograd, WinogradTile};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum KernelFormat {
@@ -1,4 +1,4 @@
-use super::KernelFormat;
+use super::{KernelFormat, WinogradTile};
 use crate::ops::cnn::*;
 use crate::ops::nn::*;
 use crate::setup_test_logger;
@@ -113,16 +113,12 @@ impl ConvProblem {
         out
     }
 
-    fn tract(&self) -> anyhow::Result<ArrayD<f32>> {
-        setup_test_logger();
-        assert_eq!(self.data.shape(), &*self.shape_in.shape, "inconsistent shapes in test");
-        let mut model = TypedModel::default();
-        let wire = model.add_source("input", f32::fact(&self.shape_in.shape))?;
+    fn conv(&self) -> ConvUnary {
         let co = match self.kernel_format {
             KernelFormat::OIHW => self.kernel.shape()[0],
             KernelFormat::HWIO => self.kernel.shape()[self.kernel.ndim() - 1] * self.group,
         };
-        let op = ConvUnary::new(
+        ConvUnary::new(
             PoolSpec::new(
                 self.shape_in.fmt,
                 self.geo_ker().into(),
@@ -136,13 +132,49 @@ impl ConvProblem {
             self.group,
             self.bias.clone().map(|a| a.into_arc_tensor()),
             None,
-        );
-        let wire = model.wire_node("conv", op, &[wire])?[0];
+        )
+    }
+
+    fn tract(&self) -> anyhow::Result<ArrayD<f32>> {
+        setup_test_logger();
+        assert_eq!(self.data.shape(), &*self.shape_in.shape, "inconsistent shapes in test");
+        let mut model = TypedModel::default();
+        let wire = model.add_source("input", f32::fact(&self.shape_in.shape))?;
+        let wire = model.wire_node("conv", self.conv(), &[wire])?[0];
         model.set_output_outlets(&[wire])?;
         let mut output =
             model.into_optimized()?.into_runnable()?.run(tvec![self.data.clone().into_tvalue()])?;
         output.remove(0).into_tensor().into_array::<f32>()
     }
+
+    fn tract_winograd(&self, tile: WinogradTile) -> anyhow::Result<ArrayD<f32>> {
+        setup_test_logger();
+        let mut model = TypedModel::default();
+        let wire = model.add_source("input", f32::fact(&self.shape_in.shape))?;
+        let wire = self.conv().wire_as_winograd(&mut model, "conv", wire, tile)?;
+        model.set_output_outlets(&[wire])?;
+        let mut output = model.into_runnable()?.run(tvec![self.data.clone().into_tvalue()])?;
+        output.remove(0).into_tensor().into_array::<f32>()
+    }
+
+    /// Winograd transforms lose precision on large partial sums. The tolerance is the first
+    /// order rounding error bound: epsilon times the largest possible partial sum magnitude,
+    /// times the amplification of the transforms.
+    fn check_winograd(&self, tile: WinogradTile) {
+        let found = self.tract_winograd(tile).unwrap();
+        let expected = self.reference();
+        let max = |a: &ArrayD<f32>| a.iter().fold(1f32, |m, x| m.max(x.abs()));
+        assert_eq!(found.shape(), expected.shape());
+        let fan_in = self.kernel.len() / self.kernel_format.o(self.kernel.shape());
+        let tolerance = f32::EPSILON
+            * tile.rounding_amplification()
+            * max(&self.data)
+            * max(&self.kernel)
+            * fan_in as f32;
+        for (f, e) in found.iter().zip(expected.iter()) {
+            assert!((f - e).abs() <= tolerance, "{f} != {e} (tolerance: {tolerance})");
+        }
+    }
 }
 
 impl Arbitrary for ConvProblem {
@@ -232,11 +264,86 @@ pub fn shapes(rank: usize) -> BoxedStrategy<(Vec<usize>, Vec<usize>)> {
         .boxed()
 }
 
+/// 3x3 stride 1 convolutions, as handled by Winograd.
+fn winograd_problem() -> BoxedStrategy<ConvProblem> {
+    (
+        any::<DataFormat>(),
+        any::<KernelFormat>(),
+        prop_oneof![Just(PaddingSpec::Valid), Just(PaddingSpec::SameUpper)],
+        1usize..=2,
+        1usize..=4,
+        1usize..=4,
+        3usize..10,
+        3usize..10,
+    )
+        .prop_flat_map(|(df, kf, pad, n, ci, co, h, w)| {
+            let shape_in = df.from_n_c_hw(n, ci, [h, w]).unwrap();
+            let data_in = tensor(shape_in.shape.iter().cloned().collect());
+            let ker_shape = match kf {
+                KernelFormat::HWIO => vec![3, 3, ci, co],
+                KernelFormat::OIHW => vec![co, ci, 3, 3],
+            };
+            let bias = proptest::option::of(tensor(vec![co]));
+            (Just((kf, pad, shape_in)), data_in, tensor(ker_shape), bias)
+        })
+        .prop_map(|((kernel_format, pad, shape_in), data, kernel, bias)| ConvProblem {
+            shape_in,
+            kernel_format,
+            group: 1,
+            data,
+            kernel,
+            bias,
+            pad,
+            strides: tvec!(1, 1),
+        })
+        .boxed()
+}
+
 proptest::proptest! {
     #[test]
     fn prop(pb in any::<ConvProblem>()) {
         pb.tract().unwrap().into_tensor().close_enough(&pb.reference().into_tensor(), true).unwrap();
     }
+
+    #[test]
+    fn winograd_f2x2(pb in winograd_problem()) {
+        pb.check_winograd(WinogradTile::F2x2_3x3);
+    }
+}
+
+#[test]
+fn winograd_extreme_values() {
+    let shape_in = DataFormat::NHWC.from_n_c_hw(2, 1, [6, 6]).unwrap();
+    let extreme = |i: usize| if (i * 7) % 3 == 0 { -128f32 } else { 127. };
+    let data = ArrayD::from_shape_fn(&*shape_in.shape, |ix| extreme(ix.as_array_view().sum()));
+    let kernel = ArrayD::from_shape_fn(&[3, 3, 1, 3][..], |ix| extreme(ix[0] * 3 + ix[1] + ix[3]));
+    let pb = ConvProblem {
+        shape_in,
+        kernel_format: KernelFormat::HWIO,
+        group: 1,
+        data,
+        kernel,
+        bias: None,
+        pad: PaddingSpec::SameUpper,
+        strides: tvec!(1, 1),
+    };
+    pb.check_winograd(WinogradTile::F2x2_3x3);
+}
+
+#[test]
+fn winograd_scalar_bias() {
+    let shape_in = DataFormat::NCHW.from_n_c_hw(1, 2, [4, 4]).unwrap();
+    let pb = ConvProblem {
+        shape_in,
+        kernel_format: KernelFormat::OIHW,
+        group: 1,
+        data: ArrayD::from_shape_fn(&[1, 2, 4, 4][..], |ix| ix.as_array_view().sum() as f32),
+        kernel: ArrayD::from_shape_fn(&[3, 2, 3, 3][..], |ix| ix[0] as f32 - ix[3] as f32),
+        bias: Some(arr0(0.5f32).into_dyn()),
+        pad: PaddingSpec::Valid,
+        strides: tvec!(1, 1),
+    };
+    pb.check_winograd(WinogradTile::F2x2_3x3);
 }
 
 #[test]
@@ -13,6 +13,7 @@ use crate::ops::matmul::MatMulAxes;
 
 use super::depth_wise::DepthWise;
 use super::im2col::Im2Col;
+use super::winograd::{Winograd, WinogradTile};
 use crate::ops::cnn::conv::KernelFormat;
 use crate::ops::cnn::pools::{ConcretePoolGeometry, PoolGeometry, PoolSpec};
 use crate::ops::matmul::lir_unary::{
@@ -573,6 +574,58 @@ impl ConvUnary {
         Ok(wire)
     }
 
+    /// The Winograd variant to use for this convolution, if it is a 3x3 stride 1 f32
+    /// convolution for which the cost model prefers it to im2col.
+    fn winograd_tile(&self, input_fact: &TypedFact) -> TractResult<Option<WinogradTile>> {
+        let shape = if let Some(shape) = input_fact.shape.as_concrete() {
+            shape
+        } else {
+            return Ok(None);
+        };
+        if self.q_params.is_some()
+            || self.group != 1
+            || input_fact.datum_type != f32::datum_type()
+            || self.kernel.datum_type() != f32::datum_type()
+            || *self.pool_spec.kernel_shape != [3, 3]
+            || self.pool_spec.strides().iter().any(|s| *s != 1)
+            || self.pool_spec.dilations().iter().any(|d| *d != 1)
+        {
+            return Ok(None);
+        }
+        let output_shape = self.pool_spec.output_shape(shape)?;
+        let (oh, ow) = (output_shape.hw_dims()[0], output_shape.hw_dims()[1]);
+        Ok(WinogradTile::for_conv(oh, ow, self.input_channels(), self.output_channels()))
+    }
+
+    pub fn wire_as_winograd(
+        &self,
+        model: &mut TypedModel,
+        name: &str,
+        wire: OutletId,
+        tile: WinogradTile,
+    ) -> TractResult<OutletId> {
+        let shape =
+            model.outlet_fact(wire)?.shape.as_concrete().context("Expects concrete shape")?;
+        let input_shape = self.pool_spec.data_format.shape(shape.into())?;
+        let output_shape = self.pool_spec.output_shape(shape)?;
+        let padding = self.pool_spec.computed_padding(input_shape.hw_dims());
+        let kernel = self.kernel_as_group_o_ihw()?.into_tensor().into_shape(&[
+            self.output_channels(),
+            self.input_channels(),
+            3,
+            3,
+        ])?;
+        let op = Winograd::new(
+            tile,
+            input_shape,
+            output_shape,
+            [padding[0].pad_before, padding[1].pad_before],
+            &kernel,
+            self.bias.clone(),
+        )?;
+        Ok(model.wire_node(format!("{}.winograd", name), op, &[wire])?[0])
+    }
+
     pub fn to_depth_wise<T>(&self, input: &TypedFact) -> TractResult<Box<dyn TypedOp>>
     where
         T: Datum + Clone + ::ndarray::LinalgScalar + PartialEq + Sum,
@@ -1076,6 +1129,13 @@ impl TypedOp for ConvUnary {
                 patch.shunt_outside(model, node.id.into(), wire)?;
                 patch.obliterate(node.id)?;
                 Ok(Some(patch.with_context("quantized-codegen")))
+            } else if let Some(tile) = self.winograd_tile(input_fact)? {
+                let mut patch = TypedModelPatch::new("wire_as_winograd");
+                let mut wire = patch.tap_model(model, node.inputs[0])?;
+                wire = self.wire_as_winograd(&mut patch, &node.name, wire, tile)?;
+                patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                patch.obliterate(node.id)?;
+                Ok(Some(patch))
             } else if kernel_spatial_shape.iter().product::<usize>() == 1
                 && (0..spatial_rank)
                     .all(|i| self.pool_spec.stride(i) == 1 && self.pool_spec.dilation(i) == 1)
@@ -1272,4 +1332,38 @@ mod test {
         ); // source + conv
         Ok(())
     }
+
+    #[test]
+    fn codegen_picks_winograd() -> TractResult<()> {
+        let (ci, co) = (16, 24);
+        let mut model = TypedModel::default();
+        let wire = model.add_source("source", f32::fact(dims!(1, ci, 12, 12)))?;
+        let kernel: Vec<f32> =
+            (0..co * ci * 9).map(|i| ((i * 7) % 13) as f32 / 13.0 - 0.5).collect();
+        let conv = ConvUnary {
+            pool_spec: PoolSpec {
+                data_format: NCHW,
+                dilations: None,
+                strides: None,
+                kernel_shape: tvec![3, 3],
+                padding: crate::ops::cnn::PaddingSpec::SameUpper,
+                output_channel_override: Some(co),
+            },
+            kernel_fmt: crate::ops::cnn::KernelFormat::OIHW,
+            kernel: tensor1(&kernel).into_shape(&[co, ci, 3, 3])?.into_arc_tensor(),
+            group: 1,
+            bias: Some(tensor1(&(0..co).map(|i| i as f32).collect::<Vec<_>>()).into_arc_tensor()),
+            q_params: None,
+        };
+        let wire = model.wire_node("conv", conv.clone(), &[wire])?;
+        model.set_output_outlets(&wire)?;
+        let optimized = model.into_optimized()?;
+        assert!(optimized.nodes().iter().any(|n| n.op_is::<Winograd>()));
+
+        let input: Vec<f32> = (0..ci * 144).map(|i| ((i * 5) % 11) as f32 / 11.0).collect();
+        let input = tensor1(&input).into_shape(&[1, ci, 12, 12])?;
+        let expected = conv.eval(tvec!(input.clone().into_tvalue()))?;
+        let found = optimized.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        found[0].close_enough(&expected[0], true)
+    }
 }
@@ -0,0 +1,313 @@
+use crate::internal::*;
+use crate::ops::nn::DataShape;
+use tract_linalg::mmm::{FusedSpec, MatMatMul};
+
+/// Winograd minimal filtering variant, for 3x3 stride 1 convolutions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WinogradTile {
+    /// F(2x2, 3x3): 2x2 output tiles computed from 4x4 input tiles.
+    F2x2_3x3,
+}
+
+#[rustfmt::skip]
+const F2_BT: [f32; 16] = [
+    1.0,  0.0, -1.0,  0.0,
+    0.0,  1.0,  1.0,  0.0,
+    0.0, -1.0,  1.0,  0.0,
+    0.0,  1.0,  0.0, -1.0,
+];
+
+#[rustfmt::skip]
+const F2_G: [f32; 12] = [
+    1.0,  0.0, 0.0,
+    0.5,  0.5, 0.5,
+    0.5, -0.5, 0.5,
+    0.0,  0.0, 1.0,
+];
+
+#[rustfmt::skip]
+const F2_AT: [f32; 8] = [
+    1.0, 1.0,  1.0,  0.0,
+    0.0, 1.0, -1.0, -1.0,
+];
+
+impl WinogradTile {
+    /// Output tile size.
+    pub fn m(&self) -> usize {
+        match self {
+            WinogradTile::F2x2_3x3 => 2,
+        }
+    }
+
+    /// Input tile size.
+    pub fn alpha(&self) -> usize {
+        self.m() + 2
+    }
+
+    fn bt(&self) -> &'static [f32] {
+        match self {
+            WinogradTile::F2x2_3x3 => &F2_BT,
+        }
+    }
+
+    fn g(&self) -> &'static [f32] {
+        match self {
+            WinogradTile::F2x2_3x3 => &F2_G,
+        }
+    }
+
+    fn at(&self) -> &'static [f32] {
+        match self {
+            WinogradTile::F2x2_3x3 => &F2_AT,
+        }
+    }
+
+    /// Multiply-adds for a convolution producing `oh x ow` outputs, transforms included.
+    pub fn cost(&self, oh: usize, ow: usize, ci: usize, co: usize) -> usize {
+        let (m, a) = (self.m(), self.alpha());
+        let tiles = oh.divceil(m) * ow.divceil(m);
+        let products = tiles * a * a * ci * co;
+        let input_transform = tiles * ci * 2 * a * a * a;
+        let output_transform = tiles * co * (m * a * a + m * m * a);
+        products + input_transform + output_transform
+    }
+
+    /// Bound on the amplification of rounding errors by the transforms: the product of the
+    /// infinity norms of A^T, B^T and G, squared for the two spatial axes.
+    pub fn rounding_amplification(&self) -> f32 {
+        let norm = |mat: &[f32], cols: usize| {
+            mat.chunks(cols)
+                .map(|row| row.iter().map(|x| x.abs()).sum::<f32>())
+                .fold(0f32, f32::max)
+        };
+        let a = self.alpha();
+        (norm(self.at(), a) * norm(self.bt(), a) * norm(self.g(), 3)).powi(2)
+    }
+
+    /// Pick a Winograd variant if it beats the direct 3x3 convolution.
+    pub fn for_conv(oh: usize, ow: usize, ci: usize, co: usize) -> Option<WinogradTile> {
+        let direct = oh * ow * ci * co * 9;
+        Some(WinogradTile::F2x2_3x3).filter(|tile| tile.cost(oh, ow, ci, co) < direct)
+    }
+}
+
+/// Compute `mat . x . mat^T`, mat being rows x cols, x cols x cols.
+fn sandwich(mat: &[f32], rows: usize, cols: usize, x: &[f32], out: &mut [f32]) {
+    let mut tmp = [0f32; 36];
+    for r in 0..rows {
+        for c in 0..cols {
+            tmp[r * cols + c] = (0..cols).map(|k| mat[r * cols + k] * x[k * cols + c]).sum();
+        }
+    }
+    for r in 0..rows {
+        for c in 0..rows {
+            out[r * rows + c] = (0..cols).map(|k| tmp[r * cols + k] * mat[c * cols + k]).sum();
+        }
+    }
+}
+
+/// Winograd 3x3 stride 1 convolution for f32.
+///
+/// Kernels are transformed and packed once. At run time, input tiles are transformed, then
+/// for each of the alpha x alpha transformed coordinates, a matrix product over the
+/// channels computes all tiles at once, before the output transform.
+#[derive(Debug, Clone, Hash)]
+pub struct Winograd {
+    pub tile: WinogradTile,
+    pub input_shape: DataShape,
+    pub output_shape: DataShape,
+    pub pad_before: [usize; 2],
+    /// alpha x alpha packed [co, ci] transformed kernels
+    pub packed_kernels: TVec<Arc<Tensor>>,
+    pub bias: Option<Arc<Tensor>>,
+    pub mmm: Box<dyn MatMatMul>,
+}
+
+impl_dyn_hash!(Winograd);
+
+impl Winograd {
+    /// `kernel` is in OIHW layout.
+    pub fn new(
+        tile: WinogradTile,
+        input_shape: DataShape,
+        output_shape: DataShape,
+        pad_before: [usize; 2],
+        kernel: &Tensor,
+        bias: Option<Arc<Tensor>>,
+    ) -> TractResult<Winograd> {
+        ensure!(kernel.shape()[2..] == [3, 3], "Winograd requires 3x3 kernels");
+        let (co, ci) = (kernel.shape()[0], kernel.shape()[1]);
+        let a = tile.alpha();
+        let f32_dt = f32::datum_type();
+        let mmm = tract_linalg::ops()
+            .mmm(f32_dt, f32_dt, f32_dt, Some(co), Some(ci), None)
+            .context("No f32 matrix multiplier")?;
+        let kernel = kernel.cast_to::<f32>()?;
+        let kernel = kernel.as_slice::<f32>()?;
+        let mut transformed = Tensor::zero::<f32>(&[a * a, co, ci])?;
+        let slice = transformed.as_slice_mut::<f32>()?;
+        let mut u = [0f32; 36];
+        for o in 0..co {
+            for i in 0..ci {
+                sandwich(tile.g(), a, 3, &kernel[(o * ci + i) * 9..][..9], &mut u);
+                for (e, u) in u[..a * a].iter().enumerate() {
+                    slice[(e * co + o) * ci + i] = *u;
+                }
+            }
+        }
+        let packer = mmm.a_pack();
+        let packed_kernels = (0..a * a)
+            .map(|e| unsafe {
+                let mut packed = Tensor::uninitialized_aligned::<f32>(
+                    &[packer.len(ci, co)],
+                    packer.alignment(),
+                )?;
+                packer.pack(packed.view_mut(), transformed.view_at_prefix(&[e])?, 1, 0);
+                Ok(packed.into_arc_tensor())
+            })
+            .collect::<TractResult<_>>()?;
+        Ok(Winograd { tile, input_shape, output_shape, pad_before, packed_kernels, bias, mmm })
+    }
+
+    fn eval_t(&self, input: &Tensor) -> TractResult<Tensor> {
+        let (m, a) = (self.tile.m(), self.tile.alpha());
+        let (ci, co) = (*self.input_shape.c(), *self.output_shape.c());
+        let (ih, iw) = (self.input_shape.hw_dims()[0], self.input_shape.hw_dims()[1]);
+        let (oh, ow) = (self.output_shape.hw_dims()[0], self.output_shape.hw_dims()[1]);
+        let (tiles_h, tiles_w) = (oh.divceil(m), ow.divceil(m));
+        let tiles = tiles_h * tiles_w;
+        let i_strides = (
+            *self.input_shape.n_stride().unwrap_or(&0),
+            *self.input_shape.c_stride(),
+            self.input_shape.hw_strides()[0],
+            self.input_shape.hw_strides()[1],
+        );
+        let o_strides = (
+            *self.output_shape.n_stride().unwrap_or(&0),
+            *self.output_shape.c_stride(),
+            self.output_shape.hw_strides()[0],
+            self.output_shape.hw_strides()[1],
+        );
+        let mut output = Tensor::zero::<f32>(&self.output_shape.shape)?;
+        let mut transformed_input = Tensor::zero::<f32>(&[a * a, ci, tiles])?;
+        let mut products = Tensor::zero::<f32>(&[a * a, co, tiles])?;
+        let bias = self.bias.as_ref().map(|b| b.as_slice::<f32>()).transpose()?;
+        let input = input.as_slice::<f32>()?;
+        let mut d = [0f32; 36];
+        let mut v = [0f32; 36];
+        unsafe {
+            let a_spec = self.mmm.a_packed(f32::datum_type().size_of(), ci);
+            let b_spec = self.mmm.b_late_packing();
+            let c_spec = self.mmm.c_view(0, 1);
+            let mut scratch = self.mmm.allocate_scratch_space();
+            for n in 0..*self.input_shape.n().unwrap_or(&1) {
+                let ti = transformed_input.as_slice_mut::<f32>()?;
+                for c in 0..ci {
+                    let input = &input[n * i_strides.0 + c * i_strides.1..];
+                    for ty in 0..tiles_h {
+                        for tx in 0..tiles_w {
+                            for y in 0..a {
+                                for x in 0..a {
+                                    let iy = (ty * m + y) as isize - self.pad_before[0] as isize;
+                                    let ix = (tx * m + x) as isize - self.pad_before[1] as isize;
+                                    d[y * a + x] = if iy >= 0
+                                        && ix >= 0
+                                        && (iy as usize) < ih
+                                        && (ix as usize) < iw
+                                    {
+                                        input[iy as usize * i_strides.2 + ix as usize * i_strides.3]
+                                    } else {
+                                        0.0
+                                    };
+                                }
+                            }
+                            sandwich(self.tile.bt(), a, a, &d, &mut v);
+                            let t = ty * tiles_w + tx;
+                            for (e, v) in v[..a * a].iter().enumerate() {
+                                ti[(e * ci + c) * tiles + t] = *v;
+                            }
+                        }
+                    }
+                }
+                for e in 0..a * a {
+                    self.mmm.run_with_scratch_space(
+                        co,
+                        tiles,
+                        &mut *scratch,
+                        &[
+                            FusedSpec::AddMatMul {
+                                k: ci,
+                                a: a_spec.wrap(&self.packed_kernels[e].view()),
+                                b: b_spec.wrap(&transformed_input.view_at_prefix(&[e])?)?,
+                            },
+                            FusedSpec::Store(c_spec.wrap(&products.view_at_prefix_mut(&[e])?)),
+                        ],
+                    )?;
+                }
+                let products = products.as_slice::<f32>()?;
+                let out = output.as_slice_mut::<f32>()?;
+                for c in 0..co {
+                    let out = &mut out[n * o_strides.0 + c * o_strides.1..];
+                    let bias = bias.map(|b| if b.len() == 1 { b[0] } else { b[c] }).unwrap_or(0.0);
+                    for ty in 0..tiles_h {
+                        for tx in 0..tiles_w {
+                            let t = ty * tiles_w + tx;
+                            for (e, d) in d[..a * a].iter_mut().enumerate() {
+                                *d = products[(e * co + c) * tiles + t];
+                            }
+                            sandwich(self.tile.at(), m, a, &d, &mut v);
+                            for y in 0..m.min(oh - ty * m) {
+                                for x in 0..m.min(ow - tx * m) {
+                                    out[(ty * m + y) * o_strides.2 + (tx * m + x) * o_strides.3] =
+                                        v[y * m + x] + bias;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl Op for Winograd {
+    fn name(&self) -> Cow<str> {
+        "WinogradConv".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?} pad: {:?}", self.tile, self.pad_before)])
+    }
+
+    fn validation(&self) -> Validation {
+        Validation::Rounding
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Winograd {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        Ok(tvec!(self.eval_t(&inputs[0])?.into_tvalue()))
+    }
+}
+
+impl TypedOp for Winograd {
+    fn output_facts(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(f32::fact(&self.output_shape.shape)))
+    }
+
+    fn cost(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let (oh, ow) = (self.output_shape.hw_dims()[0], self.output_shape.hw_dims()[1]);
+        let (ci, co) = (*self.input_shape.c(), *self.output_shape.c());
+        let n = *self.input_shape.n().unwrap_or(&1);
+        Ok(tvec!((Cost::FMA(f32::datum_type()), (n * self.tile.cost(oh, ow, ci, co)).to_dim())))
+    }
+
+    as_op!();
+}
@@ -47,7 +47,22 @@ pub fn adjustments(
     let pad: TVec<usize> = match &pool_spec.padding {
         PaddingSpec::Explicit(beg, end, _) => (0..rank).map(|r| beg[r] + end[r]).collect(),
         PaddingSpec::Valid => tvec!(0; rank),
-        _ => todo!("Unsupported combination of deconvolution arguments"),
+        PaddingSpec::SameUpper | PaddingSpec::SameLower => {
+            // SAME deconvolution output is input * stride, whatever the adjustment
+            let strides = pool_spec.strides();
+            ensure!(
+                input_geo
+                    .iter()
+                    .zip(strides.iter())
+                    .map(|(x, s)| x * s)
+                    .eq(output_geo.iter().cloned()),
+                "SAME deconvolution of {:?} with strides {:?} can not output {:?}",
+                input_geo,
+                strides,
+                output_geo
+            );
+            return Ok(tvec!(0; rank));
+        }
     };
     tract_itertools::izip!(
         input_geo,
@@ -40,7 +40,7 @@ impl Arbitrary for DeconvProblem {
                     Just(opt),
                     any::<DataFormat>(),
                     any::<KernelFormat>(),
-                    prop_oneof![Just(PaddingSpec::Valid), Just(PaddingSpec::SameUpper)],
+                    0usize..4,                         // padding kind
                     1usize..3,                         // n
                     1usize..4,                         // ci / group
                     1usize..4,                         // co / group
@@ -51,14 +51,6 @@ impl Arbitrary for DeconvProblem {
                     1usize..4,                         // group
                 )
             })
-            .prop_filter(
-                "dilation, strides and shapes in SAME",
-                |(_, _, _, pad, _, _, _, hwk, _, strides, dilations, _)| {
-                    pad == &PaddingSpec::Valid
-                        || tract_itertools::izip!(hwk, dilations, strides)
-                            .all(|(k, d, s)| (k - 1) * d > s - 1)
-                },
-            )
             .prop_flat_map(
                 |(
                     opt,
@@ -74,7 +66,7 @@ impl Arbitrary for DeconvProblem {
                     dilations,
                     group,
                 )| {
-                    let mut kernel_shape = hwk;
+                    let mut kernel_shape = hwk.clone();
                     match kf {
                         OIHW => {
                             kernel_shape.insert(0, co_over_group);
@@ -86,11 +78,20 @@ impl Arbitrary for DeconvProblem {
                         }
                     };
                     let data_shape = df.from_n_c_hw(n, ci_over_group * group, &hwi).unwrap();
+                    // explicit paddings crop at most half the kernel field on each side
+                    let explicit: Vec<_> = hwk
+                        .iter()
+                        .zip(dilations.iter())
+                        .map(|(k, d)| (0..=(k - 1) * d / 2, 0..=(k - 1) * d / 2))
+                        .collect();
+                    let adjustments: Vec<_> = strides.iter().map(|s| 0..*s).collect();
                     (
                         Just(opt),
                         Just(df),
                         Just(kf),
                         Just(pad),
+                        explicit,
+                        adjustments,
                         tensor(&data_shape.shape),
                         tensor(&kernel_shape),
                         proptest::option::of(tensor(&[co_over_group * group])),
@@ -105,7 +106,9 @@ impl Arbitrary for DeconvProblem {
                     optimized,
                     data_format,
                     kernel_format,
-                    padding,
+                    pad,
+                    explicit,
+                    adjustments,
                     input,
                     kernel,
                     bias,
@@ -113,7 +116,16 @@ impl Arbitrary for DeconvProblem {
                     dilations,
                     group,
                 )| {
-                    let adjustments = tvec!(0; kernel.ndim() - 2); // FIXME maybe
+                    let padding = match pad {
+                        0 => PaddingSpec::Valid,
+                        1 => PaddingSpec::SameUpper,
+                        2 => PaddingSpec::SameLower,
+                        _ => PaddingSpec::Explicit(
+                            explicit.iter().map(|p| p.0).collect(),
+                            explicit.iter().map(|p| p.1).collect(),
+                            false,
+                        ),
+                    };
                     DeconvProblem {
                         optimized,
                         data_format,
@@ -124,11 +136,21 @@ impl Arbitrary for DeconvProblem {
                         bias,
                         strides: strides.into(),
                         dilations: dilations.into(),
-                        adjustments,
+                        adjustments: adjustments.into(),
                         group,
                     }
                 },
             )
+            .prop_filter("SAME needs kernel field and adjustment to cover stride", |pb| {
+                !matches!(pb.padding, PaddingSpec::SameUpper | PaddingSpec::SameLower)
+                    || tract_itertools::izip!(
+                        pb.kernel_format.spatial_shape(pb.kernel.shape()),
+                        &pb.dilations,
+                        &pb.strides,
+                        &pb.adjustments
+                    )
+                    .all(|(k, d, s, a)| (k - 1) * d + 1 + a >= *s)
+            })
             .boxed()
     }
 }
@@ -193,21 +215,32 @@ impl DeconvProblem {
         )
         .map(|(i, k, s, d)| (i - 1) * s + (k - 1) * d + 1)
         .collect();
-        let paddings: TVec<(usize, usize)> = if self.padding == PaddingSpec::Valid {
-            tvec![(0, 0); valid_output_shape_geo.len()]
-        } else {
-            tract_itertools::izip!(input_shape.hw_dims(), &valid_output_shape_geo, &self.strides)
-                .map(|(i, o, s)| o - i * s)
-                .map(|total| (total / 2, total - total / 2))
-                .collect()
-        };
-        let output_shape_geo = if self.padding == PaddingSpec::Valid {
-            valid_output_shape_geo
-        } else {
-            tract_itertools::izip!(input_shape.hw_dims(), &self.strides)
-                .map(|(i, s)| i * s)
-                .collect()
-        };
+        // (cropped before, total crop) for each axis
+        let crops: TVec<(usize, usize)> = tract_itertools::izip!(
+            input_shape.hw_dims(),
+            &valid_output_shape_geo,
+            &self.strides,
+            &self.adjustments
+        )
+        .enumerate()
+        .map(|(ix, (i, o, s, a))| match &self.padding {
+            PaddingSpec::Valid => (0, 0),
+            PaddingSpec::Explicit(bef, aft, _) => (bef[ix], bef[ix] + aft[ix]),
+            PaddingSpec::SameUpper => {
+                let total = o + a - i * s;
+                (total / 2, total)
+            }
+            PaddingSpec::SameLower => {
+                let total = o + a - i * s;
+                (total - total / 2, total)
+            }
+        })
+        .collect();
+        let output_shape_geo: TVec<usize> =
+            tract_itertools::izip!(&valid_output_shape_geo, &self.adjustments, &crops)
+                .map(|(o, a, c)| o + a - c.1)
+                .collect();
+        let paddings: TVec<(usize, usize)> = crops.iter().map(|c| (c.0, c.1 - c.0)).collect();
         let output_shape = self.data_format.from_n_c_hw(n, co, output_shape_geo).unwrap();
         let mut output = ArrayD::zeros(&*output_shape.shape);
         if let Some(b) = &self.bias {
@@ -437,6 +470,71 @@ fn test_same_upper_strides() {
     pb.check();
 }
 
+#[test]
+fn test_same_lower_adjustment() {
+    let pb = DeconvProblem {
+        optimized: true,
+        data_format: HWC,
+        kernel_format: OIHW,
+        padding: PaddingSpec::SameLower,
+        input: arr2(&[[1.0], [2.0], [3.0]]).into_dyn(),
+        kernel: arr3(&[[[1.0, 10.0, 100.0]]]).into_dyn(),
+        bias: None,
+        strides: tvec!(2),
+        dilations: tvec!(1),
+        adjustments: tvec!(1),
+        group: 1,
+    };
+    pb.check();
+}
+
+#[test]
+fn test_explicit_adjustment() {
+    let pb = DeconvProblem {
+        optimized: false,
+        data_format: HWC,
+        kernel_format: OIHW,
+        padding: PaddingSpec::Explicit(tvec!(1), tvec!(0), false),
+        input: arr2(&[[1.0], [2.0]]).into_dyn(),
+        kernel: arr3(&[[[1.0, 10.0, 100.0]]]).into_dyn(),
+        bias: None,
+        strides: tvec!(3),
+        dilations: tvec!(1),
+        adjustments: tvec!(2),
+        group: 1,
+    };
+    pb.check();
+}
+
+#[test]
+fn test_same_lower_symbolic() -> TractResult<()> {
+    let pb = DeconvProblem {
+        optimized: true,
+        data_format: NCHW,
+        kernel_format: OIHW,
+        padding: PaddingSpec::SameLower,
+        input: Array::from_shape_fn((1, 2, 5), |(_, c, x)| (c * 5 + x) as f32).into_dyn(),
+        kernel: Array::from_shape_fn((3, 2, 3), |(o, i, x)| (o + i * 3 + x) as f32).into_dyn(),
+        bias: None,
+        strides: tvec!(2),
+        dilations: tvec!(1),
+        adjustments: tvec!(1),
+        group: 1,
+    };
+    let mut model = TypedModel::default();
+    let s = model.symbol_table.sym("S");
+    let src = model.add_source("src", f32::fact(&[1.to_dim(), 2.to_dim(), s.to_dim()]))?;
+    let output = model.wire_node("deconv", pb.as_op(), &[src])?;
+    assert_eq!(
+        model.outlet_fact(output[0])?.shape,
+        ShapeFact::from_dims(&[1.to_dim(), 3.to_dim(), s.to_dim() * 2])
+    );
+    model.set_output_outlets(&output)?;
+    let model = model.into_optimized()?;
+    let found = model.into_runnable()?.run(tvec!(pb.input.clone().into_tvalue()))?;
+    found[0].close_enough(&pb.reference().into_tensor(), true)
+}
+
 #[test]
 fn test_channel_0() {
     let pb = DeconvProblem {
@@ -0,0 +1,240 @@
+use crate::internal::*;
+use ndarray::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridSampleInterpolation {
+    Bilinear,
+    Nearest,
+    Bicubic,
+}
+
+/// Value of the samples falling out of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridSamplePadding {
+    Zeros,
+    Border,
+    Reflection,
+}
+
+/// Samples a NCHW input at the locations given by a N•H'•W'•2 grid of normalized (x, y)
+/// coordinates in [-1, 1], producing a N•C•H'•W' output (spatial transformers).
+#[derive(Debug, Clone, new, Hash)]
+pub struct GridSample {
+    pub interpolation: GridSampleInterpolation,
+    pub padding: GridSamplePadding,
+    /// -1 and 1 are the centers of the corner pixels (instead of their outer edges)
+    pub align_corners: bool,
+}
+
+impl_dyn_hash!(GridSample);
+
+// Keys cubic convolution coefficient, as most frameworks
+const CUBIC_A: f32 = -0.75;
+
+fn cubic_coefficients(t: f32) -> [f32; 4] {
+    let near = |x: f32| ((CUBIC_A + 2.0) * x - (CUBIC_A + 3.0)) * x * x + 1.0;
+    let far = |x: f32| ((CUBIC_A * x - 5.0 * CUBIC_A) * x + 8.0 * CUBIC_A) * x - 4.0 * CUBIC_A;
+    [far(t + 1.0), near(t), near(1.0 - t), far(2.0 - t)]
+}
+
+/// Reflects `x` into [min, max] until it lands inside.
+fn reflect(x: f32, min: f32, max: f32) -> f32 {
+    let range = max - min;
+    if range <= 0.0 {
+        return min;
+    }
+    let (distance, flip) = if x < min {
+        (min - x, false)
+    } else if x > max {
+        (x - max, true)
+    } else {
+        return x;
+    };
+    let flips = (distance / range).floor();
+    let rest = distance - flips * range;
+    if (flips as usize % 2 == 0) != flip {
+        min + rest
+    } else {
+        max - rest
+    }
+}
+
+impl GridSample {
+    fn denormalize(&self, g: f32, len: usize) -> f32 {
+        if self.align_corners {
+            (g + 1.0) / 2.0 * (len as f32 - 1.0)
+        } else {
+            ((g + 1.0) * len as f32 - 1.0) / 2.0
+        }
+    }
+
+    /// Applies the padding mode to an out of bound coordinate.
+    fn pad_coordinate(&self, x: f32, len: usize) -> f32 {
+        let (min, max) =
+            if self.align_corners { (0.0, len as f32 - 1.0) } else { (-0.5, len as f32 - 0.5) };
+        match self.padding {
+            GridSamplePadding::Zeros => x,
+            GridSamplePadding::Border => x.clamp(0.0, len as f32 - 1.0),
+            GridSamplePadding::Reflection if x < min || x > max => {
+                reflect(x, min, max).clamp(0.0, len as f32 - 1.0)
+            }
+            GridSamplePadding::Reflection => x,
+        }
+    }
+
+    fn pixel(&self, plane: &ArrayView2<f32>, y: isize, x: isize) -> f32 {
+        let (h, w) = plane.dim();
+        let inside = |v: isize, len: usize| (0..len as isize).contains(&v);
+        if inside(y, h) && inside(x, w) {
+            return plane[(y as usize, x as usize)];
+        }
+        let fix = |v: isize, len: usize| match self.padding {
+            GridSamplePadding::Zeros => None,
+            GridSamplePadding::Border => Some(v.clamp(0, len as isize - 1) as usize),
+            GridSamplePadding::Reflection => {
+                let (min, max) = if self.align_corners {
+                    (0.0, len as f32 - 1.0)
+                } else {
+                    (-0.5, len as f32 - 0.5)
+                };
+                Some((reflect(v as f32, min, max) as isize).clamp(0, len as isize - 1) as usize)
+            }
+        };
+        if let (Some(y), Some(x)) = (fix(y, h), fix(x, w)) {
+            plane[(y, x)]
+        } else {
+            0.0
+        }
+    }
+
+    fn sample(&self, plane: &ArrayView2<f32>, y: f32, x: f32) -> f32 {
+        let (h, w) = plane.dim();
+        let y = self.pad_coordinate(self.denormalize(y, h), h);
+        let x = self.pad_coordinate(self.denormalize(x, w), w);
+        match self.interpolation {
+            GridSampleInterpolation::Nearest => {
+                self.pixel(plane, round_ties_to_even(y) as isize, round_ties_to_even(x) as isize)
+            }
+            GridSampleInterpolation::Bilinear => {
+                let (y0, x0) = (y.floor(), x.floor());
+                let (ly, lx) = (y - y0, x - x0);
+                let (y0, x0) = (y0 as isize, x0 as isize);
+                (1.0 - ly) * (1.0 - lx) * self.pixel(plane, y0, x0)
+                    + (1.0 - ly) * lx * self.pixel(plane, y0, x0 + 1)
+                    + ly * (1.0 - lx) * self.pixel(plane, y0 + 1, x0)
+                    + ly * lx * self.pixel(plane, y0 + 1, x0 + 1)
+            }
+            GridSampleInterpolation::Bicubic => {
+                let (y0, x0) = (y.floor(), x.floor());
+                let (cy, cx) = (cubic_coefficients(y - y0), cubic_coefficients(x - x0));
+                let (y0, x0) = (y0 as isize - 1, x0 as isize - 1);
+                let mut acc = 0.0;
+                for (i, wy) in cy.iter().enumerate() {
+                    for (j, wx) in cx.iter().enumerate() {
+                        acc += wy * wx * self.pixel(plane, y0 + i as isize, x0 + j as isize);
+                    }
+                }
+                acc
+            }
+        }
+    }
+}
+
+impl Op for GridSample {
+    fn name(&self) -> Cow<str> {
+        "GridSample".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "interpolation: {:?} padding: {:?} align corners: {}",
+            self.interpolation, self.padding, self.align_corners
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for GridSample {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let dt = inputs[0].datum_type();
+        let data = inputs[0].cast_to::<f32>()?;
+        let data = data.to_array_view::<f32>()?.into_dimensionality::<Ix4>()?;
+        let grid = inputs[1].cast_to::<f32>()?;
+        let grid = grid.to_array_view::<f32>()?.into_dimensionality::<Ix4>()?;
+        let (n, c) = (data.shape()[0], data.shape()[1]);
+        let (gn, h, w, coords) = grid.dim();
+        ensure!(gn == n && coords == 2, "Expected a {}•H•W•2 grid, got {:?}", n, grid.shape());
+        let output = Array4::<f32>::from_shape_fn((n, c, h, w), |(n, c, y, x)| {
+            self.sample(&data.slice(s![n, c, .., ..]), grid[(n, y, x, 1)], grid[(n, y, x, 0)])
+        });
+        Ok(tvec!(output.into_tensor().cast_to_dt(dt)?.into_owned().into_tvalue()))
+    }
+}
+
+impl TypedOp for GridSample {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 2, "Expects an input and a grid");
+        ensure!(inputs[0].rank() == 4, "Input must be NCHW, got {:?}", inputs[0]);
+        ensure!(inputs[1].rank() == 4, "Grid must be N•H•W•2, got {:?}", inputs[1]);
+        ensure!(inputs[0].datum_type.is_float(), "Input must be float, got {:?}", inputs[0]);
+        let shape = tvec!(
+            inputs[0].shape[0].clone(),
+            inputs[0].shape[1].clone(),
+            inputs[1].shape[1].clone(),
+            inputs[1].shape[2].clone()
+        );
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use GridSampleInterpolation::*;
+    use GridSamplePadding::*;
+
+    fn run(op: GridSample, grid: &[[f32; 2]]) -> TractResult<Vec<f32>> {
+        let data = tensor4(&[[[[0f32, 1.], [2., 3.]]]]);
+        let grid = Tensor::from_shape(
+            &[1, 1, grid.len(), 2],
+            &grid.iter().flatten().copied().collect::<Vec<_>>(),
+        )?;
+        let output = op.eval(tvec!(data.into(), grid.into()))?;
+        Ok(output[0].as_slice::<f32>()?.to_vec())
+    }
+
+    #[test]
+    fn bilinear_align_corners() -> TractResult<()> {
+        let found = run(GridSample::new(Bilinear, Zeros, true), &[[-1., -1.], [0., 0.], [1., 1.]])?;
+        assert_eq!(found, vec![0., 1.5, 3.]);
+        Ok(())
+    }
+
+    #[test]
+    fn bilinear_paddings() -> TractResult<()> {
+        // x = 1 is the outer edge of the last column without align_corners
+        let zeros = run(GridSample::new(Bilinear, Zeros, false), &[[1., -0.5]])?;
+        assert_eq!(zeros, vec![0.5]);
+        let border = run(GridSample::new(Bilinear, Border, false), &[[1., -0.5]])?;
+        assert_eq!(border, vec![1.]);
+        let reflection = run(GridSample::new(Bilinear, Reflection, false), &[[2., -0.5]])?;
+        assert_eq!(reflection, vec![0.5]);
+        Ok(())
+    }
+
+    #[test]
+    fn nearest_and_bicubic() -> TractResult<()> {
+        let nearest = run(GridSample::new(Nearest, Zeros, true), &[[0.2, -1.], [-0.2, 1.]])?;
+        assert_eq!(nearest, vec![1., 2.]);
+        let bicubic = run(GridSample::new(Bicubic, Border, true), &[[-1., -1.], [1., 1.]])?;
+        assert_eq!(bicubic, vec![0., 3.]);
+        Ok(())
+    }
+}
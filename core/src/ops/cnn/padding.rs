@@ -223,16 +223,17 @@ impl PaddingSpec {
         adjustment: usize,
         upper: bool,
     ) -> TractResult<ComputedPaddedDim<D>> {
-        if (kernel - 1) * dilation < stride {
-            bail!("Invalid axis geometry for SAME padding: expect (kernel_len - 1) * dilation > stride - 1");
-        }
         let kernel_field = (kernel - 1) * dilation + 1;
+        if kernel_field + adjustment < stride {
+            bail!("Invalid axis geometry for SAME padding: expect (kernel_len - 1) * dilation + 1 + adjustment >= stride");
+        }
         let crop = kernel_field + adjustment - stride;
         let lower_crop = crop / 2;
         let higher_crop = crop - lower_crop;
         let (before, after) =
             if upper { (lower_crop, higher_crop) } else { (higher_crop, lower_crop) };
-        let deconvoluted = (convoluted.clone() - 1) * stride + kernel_field - before - after;
+        let deconvoluted =
+            (convoluted.clone() - 1) * stride + kernel_field + adjustment - before - after;
         Ok(ComputedPaddedDim::new(deconvoluted, convoluted.clone(), before.into(), after.into()))
     }
 }
@@ -306,4 +307,33 @@ mod tests {
     fn same_upper() {
         assert_eq!(PS::same(&7usize, 1usize, 1, 2, true), ComputedPaddedDim::new(7, 4, 0, 0));
     }
+
+    #[test]
+    fn same_for_deconv() {
+        assert_eq!(
+            PS::same_for_deconv(&3usize, 3usize, 1, 2, 0, true).unwrap(),
+            ComputedPaddedDim::new(6, 3, 0, 1)
+        );
+        assert_eq!(
+            PS::same_for_deconv(&3usize, 3usize, 1, 2, 0, false).unwrap(),
+            ComputedPaddedDim::new(6, 3, 1, 0)
+        );
+        assert_eq!(
+            PS::same_for_deconv(&3usize, 3usize, 1, 2, 1, true).unwrap(),
+            ComputedPaddedDim::new(6, 3, 1, 1)
+        );
+        assert_eq!(
+            PS::same_for_deconv(&3usize, 2usize, 1, 2, 0, true).unwrap(),
+            ComputedPaddedDim::new(6, 3, 0, 0)
+        );
+    }
+
+    #[test]
+    fn same_for_deconv_symbolic() {
+        let table = SymbolTable::default();
+        let s = TDim::from(table.sym("S"));
+        let dim = PS::same_for_deconv(&s, 3usize, 1, 2, 0, false).unwrap();
+        assert_eq!(dim.deconvoluted, s.clone() * 2);
+        assert_eq!((dim.pad_before, dim.pad_after), (1.into(), 0.into()));
+    }
 }
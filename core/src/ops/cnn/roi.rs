@@ -0,0 +1,333 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// How the samples falling in a region of interest bin are reduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoiPoolingMode {
+    Avg,
+    Max,
+}
+
+/// Extracts a fixed size feature map per region of interest, averaging (or maxing) bilinear
+/// samples taken on a regular grid over each output bin (Mask R-CNN RoiAlign).
+///
+/// Inputs are a NCHW feature map, R•4 regions as (x1, y1, x2, y2) and their R batch indices.
+/// Output is R•C•H•W.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct RoiAlign {
+    pub mode: RoiPoolingMode,
+    /// output (height, width)
+    pub output_shape: (usize, usize),
+    /// samples per bin along (height, width). 0 adapts to the region size.
+    pub sampling_ratio: (usize, usize),
+    #[educe(Hash(method = "hash_f32"))]
+    pub spatial_scale: f32,
+    /// shift region coordinates by half a pixel (the legacy behaviour does not, and enforces
+    /// regions of at least one pixel instead)
+    pub half_pixel: bool,
+}
+
+impl_dyn_hash!(RoiAlign);
+
+/// Max (or average) pools each region of interest, rounded to the feature map pixels, into a
+/// fixed size feature map (Fast R-CNN RoiPool).
+///
+/// Inputs and output are laid out like RoiAlign.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct RoiPool {
+    pub mode: RoiPoolingMode,
+    /// output (height, width)
+    pub output_shape: (usize, usize),
+    #[educe(Hash(method = "hash_f32"))]
+    pub spatial_scale: f32,
+}
+
+impl_dyn_hash!(RoiPool);
+
+fn roi_output_facts(
+    inputs: &[&TypedFact],
+    output_shape: (usize, usize),
+) -> TractResult<TVec<TypedFact>> {
+    ensure!(inputs.len() == 3, "Expects a feature map, regions and batch indices");
+    ensure!(inputs[0].rank() == 4, "Feature map must be NCHW, got {:?}", inputs[0]);
+    ensure!(inputs[1].rank() == 2, "Regions must be R•4, got {:?}", inputs[1]);
+    ensure!(inputs[0].datum_type.is_float(), "Feature map must be float, got {:?}", inputs[0]);
+    let shape = tvec!(
+        inputs[1].shape[0].clone(),
+        inputs[0].shape[1].clone(),
+        output_shape.0.to_dim(),
+        output_shape.1.to_dim()
+    );
+    Ok(tvec!(inputs[0].datum_type.fact(shape)))
+}
+
+/// Evaluates `pool` for each (region, channel) plane, in f32.
+fn eval_regions(
+    inputs: TVec<TValue>,
+    output_shape: (usize, usize),
+    pool: impl Fn(ArrayView2<f32>, [f32; 4], ArrayViewMut2<f32>),
+) -> TractResult<TVec<TValue>> {
+    let dt = inputs[0].datum_type();
+    let data = inputs[0].cast_to::<f32>()?;
+    let data = data.to_array_view::<f32>()?.into_dimensionality::<Ix4>()?;
+    let rois = inputs[1].cast_to::<f32>()?;
+    let rois = rois.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+    let batch = inputs[2].cast_to::<i64>()?;
+    let batch = batch.as_slice::<i64>()?;
+    ensure!(rois.ncols() == 4, "Regions must be R•4, got {:?}", rois.shape());
+    ensure!(batch.len() == rois.nrows(), "Expected {} batch indices", rois.nrows());
+    let (channels, (height, width)) = (data.shape()[1], output_shape);
+    let mut output = Array4::<f32>::zeros((rois.nrows(), channels, height, width));
+    for (ix, roi) in rois.outer_iter().enumerate() {
+        ensure!(
+            batch[ix] >= 0 && (batch[ix] as usize) < data.shape()[0],
+            "Invalid batch index {} for region {}",
+            batch[ix],
+            ix
+        );
+        let region = [roi[0], roi[1], roi[2], roi[3]];
+        for c in 0..channels {
+            pool(
+                data.slice(s![batch[ix] as usize, c, .., ..]),
+                region,
+                output.slice_mut(s![ix, c, .., ..]),
+            );
+        }
+    }
+    Ok(tvec!(output.into_tensor().cast_to_dt(dt)?.into_owned().into_tvalue()))
+}
+
+/// Pixels and weights of the bilinear interpolation at (y, x), None out of the feature map.
+fn bilinear_taps(plane: &ArrayView2<f32>, y: f32, x: f32) -> Option<[(usize, usize, f32); 4]> {
+    let (h, w) = plane.dim();
+    if h == 0 || w == 0 {
+        return None;
+    }
+    if y < -1.0 || y > h as f32 || x < -1.0 || x > w as f32 {
+        return None;
+    }
+    let axis = |v: f32, len: usize| {
+        let v = v.max(0.0);
+        if v as usize >= len - 1 {
+            (len - 1, len - 1, 0.0)
+        } else {
+            (v as usize, v as usize + 1, v - v.floor())
+        }
+    };
+    let (y0, y1, ly) = axis(y, h);
+    let (x0, x1, lx) = axis(x, w);
+    let (hy, hx) = (1.0 - ly, 1.0 - lx);
+    Some([(y0, x0, hy * hx), (y0, x1, hy * lx), (y1, x0, ly * hx), (y1, x1, ly * lx)])
+}
+
+impl RoiAlign {
+    fn pool(&self, plane: ArrayView2<f32>, roi: [f32; 4], mut output: ArrayViewMut2<f32>) {
+        let offset = if self.half_pixel { 0.5 } else { 0.0 };
+        let start_x = roi[0] * self.spatial_scale - offset;
+        let start_y = roi[1] * self.spatial_scale - offset;
+        let mut roi_w = roi[2] * self.spatial_scale - offset - start_x;
+        let mut roi_h = roi[3] * self.spatial_scale - offset - start_y;
+        if !self.half_pixel {
+            roi_w = roi_w.max(1.0);
+            roi_h = roi_h.max(1.0);
+        }
+        let bin_h = roi_h / self.output_shape.0 as f32;
+        let bin_w = roi_w / self.output_shape.1 as f32;
+        let grid = |ratio: usize, bin: f32| if ratio > 0 { ratio } else { bin.ceil() as usize };
+        let grid_h = grid(self.sampling_ratio.0, bin_h);
+        let grid_w = grid(self.sampling_ratio.1, bin_w);
+        let count = grid_h * grid_w;
+        for ((py, px), value) in output.indexed_iter_mut() {
+            let mut acc = if self.mode == RoiPoolingMode::Avg { 0.0 } else { f32::MIN };
+            for iy in 0..grid_h {
+                let y = start_y + py as f32 * bin_h + (iy as f32 + 0.5) * bin_h / grid_h as f32;
+                for ix in 0..grid_w {
+                    let x = start_x + px as f32 * bin_w + (ix as f32 + 0.5) * bin_w / grid_w as f32;
+                    let taps = bilinear_taps(&plane, y, x);
+                    let weighted = taps.iter().flatten().map(|&(y, x, w)| w * plane[(y, x)]);
+                    acc = match self.mode {
+                        RoiPoolingMode::Avg => acc + weighted.sum::<f32>(),
+                        // as the reference implementation: max of the weighted corners
+                        RoiPoolingMode::Max => acc.max(if taps.is_some() {
+                            weighted.fold(f32::MIN, f32::max)
+                        } else {
+                            0.0
+                        }),
+                    };
+                }
+            }
+            *value = if count == 0 {
+                0.0
+            } else if self.mode == RoiPoolingMode::Avg {
+                acc / count as f32
+            } else {
+                acc
+            };
+        }
+    }
+}
+
+impl Op for RoiAlign {
+    fn name(&self) -> Cow<str> {
+        "RoiAlign".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("mode: {:?} output: {:?}", self.mode, self.output_shape),
+            format!(
+                "sampling ratio: {:?} spatial scale: {} half pixel: {}",
+                self.sampling_ratio, self.spatial_scale, self.half_pixel
+            ),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoiAlign {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        eval_regions(inputs, self.output_shape, |plane, roi, output| self.pool(plane, roi, output))
+    }
+}
+
+impl TypedOp for RoiAlign {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        roi_output_facts(inputs, self.output_shape)
+    }
+
+    as_op!();
+}
+
+impl RoiPool {
+    fn pool(&self, plane: ArrayView2<f32>, roi: [f32; 4], mut output: ArrayViewMut2<f32>) {
+        let (h, w) = plane.dim();
+        let [start_x, start_y, end_x, end_y] =
+            roi.map(|c| (c * self.spatial_scale).round() as isize);
+        let roi_w = (end_x - start_x + 1).max(1) as f32;
+        let roi_h = (end_y - start_y + 1).max(1) as f32;
+        let bin_h = roi_h / self.output_shape.0 as f32;
+        let bin_w = roi_w / self.output_shape.1 as f32;
+        let range = |p: usize, bin: f32, start: isize, len: usize| {
+            let clip = |v: f32| (v as isize + start).clamp(0, len as isize) as usize;
+            let (from, to) = (clip((p as f32 * bin).floor()), clip(((p + 1) as f32 * bin).ceil()));
+            from..to.max(from)
+        };
+        for ((py, px), value) in output.indexed_iter_mut() {
+            let bin = plane.slice(s![range(py, bin_h, start_y, h), range(px, bin_w, start_x, w)]);
+            *value = if bin.len() == 0 {
+                0.0
+            } else if self.mode == RoiPoolingMode::Avg {
+                bin.sum() / bin.len() as f32
+            } else {
+                bin.fold(f32::MIN, |a, &b| a.max(b))
+            }
+        }
+    }
+}
+
+impl Op for RoiPool {
+    fn name(&self) -> Cow<str> {
+        "RoiPool".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "mode: {:?} output: {:?} spatial scale: {}",
+            self.mode, self.output_shape, self.spatial_scale
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoiPool {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        eval_regions(inputs, self.output_shape, |plane, roi, output| self.pool(plane, roi, output))
+    }
+}
+
+impl TypedOp for RoiPool {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        roi_output_facts(inputs, self.output_shape)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn feature_map() -> Tensor {
+        Tensor::from_shape(&[1, 1, 4, 4], &(0..16).map(|i| i as f32).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn align_avg_half_pixel() -> TractResult<()> {
+        // a single bin covering the whole map samples its centre
+        let op = RoiAlign::new(RoiPoolingMode::Avg, (1, 1), (1, 1), 1.0, true);
+        let output = op.eval(tvec!(
+            feature_map().into(),
+            tensor2(&[[0f32, 0., 4., 4.]]).into(),
+            tensor1(&[0i64]).into()
+        ))?;
+        assert_eq!(*output[0], tensor4(&[[[[7.5f32]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn align_f16_symbolic_regions() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let r = model.symbol_table.sym("R");
+        let data = model.add_source("data", f16::fact([1, 1, 4, 4]))?;
+        let rois = model.add_source("rois", f32::fact(&[r.to_dim(), 4.to_dim()]))?;
+        let batch = model.add_source("batch", i64::fact(&[r.to_dim()]))?;
+        let op = RoiAlign::new(RoiPoolingMode::Avg, (2, 2), (2, 2), 0.5, false);
+        let output = model.wire_node("align", op, &[data, rois, batch])?;
+        model.set_output_outlets(&output)?;
+        assert_eq!(model.outlet_fact(output[0])?.shape[0], r.to_dim());
+        let output = model.into_runnable()?.run(tvec!(
+            feature_map().cast_to::<f16>()?.into_owned().into(),
+            tensor2(&[[0f32, 0., 4., 4.], [2., 2., 8., 8.]]).into(),
+            tensor1(&[0i64, 0]).into()
+        ))?;
+        assert_eq!(output[0].shape(), &[2, 1, 2, 2]);
+        assert_eq!(output[0].datum_type(), f16::datum_type());
+        Ok(())
+    }
+
+    #[test]
+    fn align_empty_feature_map() -> TractResult<()> {
+        let op = RoiAlign::new(RoiPoolingMode::Avg, (1, 1), (1, 1), 1.0, true);
+        let output = op.eval(tvec!(
+            Tensor::zero::<f32>(&[1, 1, 0, 4])?.into(),
+            tensor2(&[[0f32, 0., 1., 1.]]).into(),
+            tensor1(&[0i64]).into()
+        ))?;
+        assert_eq!(*output[0], tensor4(&[[[[0f32]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn pool_max() -> TractResult<()> {
+        let op = RoiPool::new(RoiPoolingMode::Max, (2, 2), 1.0);
+        let output = op.eval(tvec!(
+            feature_map().into(),
+            tensor2(&[[0f32, 0., 2., 3.]]).into(),
+            tensor1(&[0i64]).into()
+        ))?;
+        assert_eq!(*output[0], tensor4(&[[[[5f32, 6.], [13., 14.]]]]));
+        Ok(())
+    }
+}
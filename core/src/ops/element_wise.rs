@@ -58,7 +58,7 @@ pub trait ElementWiseMiniOp:
 
 impl Hash for Box<dyn ElementWiseMiniOp> {
     fn hash<H: std::hash::Hasher>(&self, mut state: &mut H) {
-        std::hash::Hash::hash(&self.type_id(), state);
+        std::hash::Hash::hash(&self.name(), state);
         self.dyn_hash(&mut state)
     }
 }
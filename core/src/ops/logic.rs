@@ -13,6 +13,12 @@ bin_to_super_type!(and, And,
 bin_to_super_type!(or, Or,
                    [bool, u8, u16, u32, u64, i8, i16, i32, i64] => |c, &a, &b| *c = (a as i64 != 0 || b as i64 != 0) as _);
 bin_to_super_type!(xor, Xor, /*flip: commute, */ [bool] => |c, &a, &b| *c = a ^ b);
+bin_to_super_type!(bitand, BitAnd,
+                   [bool, u8, u16, u32, u64, i8, i16, i32, i64] => |c, &a, &b| *c = a & b);
+bin_to_super_type!(bitor, BitOr,
+                   [bool, u8, u16, u32, u64, i8, i16, i32, i64] => |c, &a, &b| *c = a | b);
+bin_to_super_type!(bitxor, BitXor,
+                   [bool, u8, u16, u32, u64, i8, i16, i32, i64] => |c, &a, &b| *c = a ^ b);
 bin_to_bool!(equals, Equals,
              [bool, u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, TDim] => |c, a, b | *c = a == b
             );
@@ -108,6 +114,11 @@ element_wise!(not, Not, [bool] => |_, vs| {
     Ok(())
 });
 
+element_wise!(bitnot, BitNot, [bool, u8, u16, u32, u64, i8, i16, i32, i64] => |_, xs| {
+    xs.iter_mut().for_each(|x| *x = !*x);
+    Ok(())
+});
+
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct Iff;
 
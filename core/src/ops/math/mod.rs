@@ -11,12 +11,15 @@ pub use tract_data::prelude::round_ties_to_even;
 use tract_linalg::{ScaleShiftAndRound, Scaler};
 use tract_num_traits::AsPrimitive;
 
+type Complex32 = Complex<f32>;
+type Complex64 = Complex<f64>;
+
 bin_to_super_type!(add, Add,
                    declutter: declutter_add,
                    linalg: Add,
                    validation: Validation::Rounding,
                    q: [i8, u8, i32, i32] => add_quant;
-                   [f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64, TDim] => |c, a, b| *c = a.clone() + b);
+                   [f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64, TDim, Complex32, Complex64] => |c, a, b| *c = a.clone() + b);
 
 fn add_quant<T>(c: &mut T, a: &T, b: &T, zp: i32, _: f32)
 where
@@ -30,7 +33,7 @@ bin_to_super_type!(sub, Sub,
                    declutter: declutter_sub,
                    linalg:Sub,
                    q: [i8, u8, i32, i32] => sub_quant;
-                   [f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64, TDim] => |c, a, b| *c = a.clone() - b);
+                   [f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64, TDim, Complex32, Complex64] => |c, a, b| *c = a.clone() - b);
 
 fn sub_quant<T>(c: &mut T, a: &T, b: &T, zp: i32, _: f32)
 where
@@ -83,7 +86,7 @@ bin_to_super_type!(mul, Mul,
                        }
                    },
                    q: [i8, u8, i32] => |c, a, b, _, _| *c = a.clone() * b;
-[f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64, TDim] => |c, a, b| *c = a.clone() * b
+[f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64, TDim, Complex32, Complex64] => |c, a, b| *c = a.clone() * b
 );
 
 bin_to_super_type!(div, Div,
@@ -127,7 +130,7 @@ out_of_place: |c:&mut Tensor, a:&Tensor, b: &Tensor| -> TractResult<bool> {
             Ok(false)
         }
 },
-[f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64] => |c, a, b| *c = a.clone() / b
+[f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64, Complex32, Complex64] => |c, a, b| *c = a.clone() / b
 );
 
 bin_to_super_type!(rem, Rem,
@@ -191,6 +194,9 @@ fn declutter_neutral(
     also_left: bool,
 ) -> TractResult<Option<TypedModelPatch>> {
     if let Some(uniform) = crate::ops::binary::one_input_is_uniform(model, node)? {
+        if uniform.uni.datum_type().is_complex() {
+            return Ok(None);
+        }
         let integer = uniform.uni.cast_to_scalar::<i64>()?;
         if tensor0(integer)
             .cast_to_dt(uniform.uni.datum_type())?
@@ -235,6 +241,9 @@ fn declutter_mul(
         return Ok(Some(p));
     }
     if let Some(uniform) = crate::ops::binary::one_input_is_uniform(model, node)? {
+        if uniform.uni.datum_type().is_complex() {
+            return Ok(None);
+        }
         let var_fact = model.outlet_fact(uniform.var)?;
         if uniform.uni.cast_to_scalar::<f64>()? == 0.0 {
             let shapes =
@@ -380,6 +389,57 @@ q: [i8, u8, i32, i32] => f32::abs;
 operating_datum_type: |dt| if dt == TDim::datum_type() { i64::datum_type() } else { dt }
 );
 
+element_wise!(conj, Conj, [Complex32, Complex64] => |_, xs| {
+    xs.iter_mut().for_each(|x| *x = x.conj());
+    Ok(())
+});
+
+element_wise_oop!(complex_abs, ComplexAbs,
+    [Complex32] => f32 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.norm());
+        Ok(())
+    },
+    [Complex64] => f64 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.norm());
+        Ok(())
+    };
+    validation: Validation::Rounding
+);
+
+element_wise_oop!(real, Real,
+    [Complex32] => f32 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.re);
+        Ok(())
+    },
+    [Complex64] => f64 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.re);
+        Ok(())
+    }
+);
+
+element_wise_oop!(imag, Imag,
+    [Complex32] => f32 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.im);
+        Ok(())
+    },
+    [Complex64] => f64 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.im);
+        Ok(())
+    }
+);
+
+element_wise_oop!(angle, Angle,
+    [Complex32] => f32 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.arg());
+        Ok(())
+    },
+    [Complex64] => f64 |_, xs, ys| {
+        xs.iter().zip(ys.iter_mut()).for_each(|(x, y)| *y = x.arg());
+        Ok(())
+    };
+    validation: Validation::Rounding
+);
+
 element_wise!(exp, Exp, [f16, f32, f64] => |_, xs| {
     xs.iter_mut().for_each(|x| *x = x.exp());
     Ok(())
@@ -617,6 +677,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn in_place_op_leaves_borrowed_input_alone() -> TractResult<()> {
+        let data = vec![1f32, -2.0, 3.0];
+        let input = unsafe {
+            Tensor::from_raw_dt_borrowed(DatumType::F32, &[3], data.as_ptr() as *const u8)?
+        };
+        let result = neg().eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*result[0], tensor1(&[-1f32, 2.0, -3.0]));
+        assert_eq!(data, [1f32, -2.0, 3.0]);
+        Ok(())
+    }
+
     #[test]
     fn div_as_shift() -> TractResult<()> {
         let mut model = TypedModel::default();
@@ -638,4 +710,32 @@ mod tests {
         assert!(op.0.downcast_ref::<ShiftRight>().is_some());
         Ok(())
     }
+
+    #[test]
+    fn complex_arithmetic() -> TractResult<()> {
+        let a = tensor1(&[Complex32::new(1.0, 2.0), Complex32::new(3.0, -1.0)]);
+        let b = tensor1(&[Complex32::new(0.0, 1.0), Complex32::new(2.0, 2.0)]);
+        let prod = mul().eval(tvec!(a.clone().into(), b.clone().into()))?;
+        assert_eq!(*prod[0], tensor1(&[Complex32::new(-2.0, 1.0), Complex32::new(8.0, 4.0)]));
+        let sum = add().eval(tvec!(a.clone().into(), b.clone().into()))?;
+        assert_eq!(*sum[0], tensor1(&[Complex32::new(1.0, 3.0), Complex32::new(5.0, 1.0)]));
+        let quotient = div().eval(tvec!(prod[0].clone(), b.into()))?;
+        quotient[0].close_enough(&a, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn complex_extraction() -> TractResult<()> {
+        let a: TValue = tensor1(&[Complex32::new(3.0, 4.0), Complex32::new(0.0, -1.0)]).into();
+        assert_eq!(*real().eval(tvec!(a.clone()))?[0], tensor1(&[3f32, 0.0]));
+        assert_eq!(*imag().eval(tvec!(a.clone()))?[0], tensor1(&[4f32, -1.0]));
+        assert_eq!(*complex_abs().eval(tvec!(a.clone()))?[0], tensor1(&[5f32, 1.0]));
+        assert_eq!(
+            *conj().eval(tvec!(a.clone()))?[0],
+            tensor1(&[Complex32::new(3.0, -4.0), Complex32::new(0.0, 1.0)])
+        );
+        angle().eval(tvec!(a))?[0]
+            .close_enough(&tensor1(&[0.9272952f32, -std::f32::consts::FRAC_PI_2]), true)?;
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+pub mod block_quant;
 pub mod lir_unary;
 pub mod mir;
 pub mod mir_quant;
@@ -13,6 +14,7 @@ use tract_itertools::Itertools;
 use tract_linalg::mmm::FusedSpec;
 use tract_ndarray::prelude::*;
 
+pub use self::block_quant::BlockQuantMatMul;
 pub use self::mir::MatMul;
 pub use self::mir_quant::{MatMulQParams, QMatMul};
 pub use self::mir_unary::MatMulUnary;
@@ -356,7 +358,7 @@ pub fn compute_shape<D: DimLike>(
 }
 
 pub fn output_type(input: DatumType) -> DatumType {
-    if input.is_float() {
+    if input.is_float() || input.is_complex_float() {
         input
     } else {
         i32::datum_type()
@@ -364,6 +366,9 @@ pub fn output_type(input: DatumType) -> DatumType {
 }
 
 pub(super) fn eval(a: &Tensor, b: &Tensor, axes: MatMulAxes) -> TractResult<Tensor> {
+    if a.datum_type().is_complex() || b.datum_type().is_complex() {
+        return eval_complex(a, b, axes);
+    }
     unsafe {
         let (m, k, n, c_shape) = compute_shape(a.shape(), b.shape(), axes)?;
         let c_dt = output_type(a.datum_type());
@@ -467,6 +472,45 @@ pub(super) fn eval(a: &Tensor, b: &Tensor, axes: MatMulAxes) -> TractResult<Tens
     }
 }
 
+/// Complex products are computed as four real products, on the real and imaginary parts:
+/// (ar + i.ai)(br + i.bi) = (ar.br - ai.bi) + i.(ar.bi + ai.br)
+fn eval_complex(a: &Tensor, b: &Tensor, axes: MatMulAxes) -> TractResult<Tensor> {
+    ensure!(
+        a.datum_type() == b.datum_type() && a.datum_type().is_complex_float(),
+        "Complex matmul requires both operands of the same complex float type, got {:?} and {:?}",
+        a.datum_type(),
+        b.datum_type()
+    );
+    fn parts(t: &Tensor) -> TractResult<(Tensor, Tensor)> {
+        let parts = reinterpret_complex_as_inner_dim(t)?;
+        let re = parts.slice(t.rank(), 0, 1)?.into_shape(t.shape())?;
+        let im = parts.slice(t.rank(), 1, 2)?.into_shape(t.shape())?;
+        Ok((re, im))
+    }
+    fn combine<T: Datum + num_traits::Float>(
+        rr: &Tensor,
+        ii: &Tensor,
+        ri: &Tensor,
+        ir: &Tensor,
+    ) -> TractResult<Tensor> {
+        let re = &rr.to_array_view::<T>()? - &ii.to_array_view::<T>()?;
+        let im = &ri.to_array_view::<T>()? + &ir.to_array_view::<T>()?;
+        let axis = Axis(re.ndim());
+        let parts = tract_ndarray::concatenate(
+            axis,
+            &[re.insert_axis(axis).view(), im.insert_axis(axis).view()],
+        )?;
+        Ok(reinterpret_inner_dim_as_complex(&parts.into_tensor())?.into_owned())
+    }
+    let (ar, ai) = parts(a)?;
+    let (br, bi) = parts(b)?;
+    let rr = eval(&ar, &br, axes)?;
+    let ii = eval(&ai, &bi, axes)?;
+    let ri = eval(&ar, &bi, axes)?;
+    let ir = eval(&ai, &br, axes)?;
+    dispatch_floatlike!(combine(rr.datum_type())(&rr, &ii, &ri, &ir))
+}
+
 pub(super) fn cost<A: DimLike + Clone, B: DimLike + Clone>(
     a: &[A],
     b: &[B],
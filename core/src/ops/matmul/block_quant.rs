@@ -0,0 +1,223 @@
+use super::*;
+use crate::internal::*;
+use tract_data::internal::BlockQuant;
+
+/// Weight-only quantized matrix multiplier. A is constant, stored as block
+/// quantized rows along k, B is the input. Accumulation happens in B type.
+#[derive(Debug, Clone, Hash)]
+pub struct BlockQuantMatMul {
+    pub format: BlockQuant,
+    /// Shape of A before quantization: all axes but a_m and a_k are 1.
+    pub a_shape: TVec<usize>,
+    /// u8 tensor of shape [m, format.storage_bytes(k)]
+    pub a: Arc<Tensor>,
+    pub axes: MatMulAxes,
+}
+
+impl_dyn_hash!(BlockQuantMatMul);
+
+impl BlockQuantMatMul {
+    pub fn quantize(a: &Tensor, axes: MatMulAxes, format: BlockQuant) -> TractResult<Self> {
+        ensure!(
+            a.shape().iter().enumerate().all(|(ix, d)| ix == axes.a_m || ix == axes.a_k || *d == 1),
+            "Block quantization requires a 2D matrix, got shape {:?}",
+            a.shape()
+        );
+        let (m, k) = (a.shape()[axes.a_m], a.shape()[axes.a_k]);
+        ensure!(
+            k % format.block_len() == 0,
+            "Block quantization requires k ({}) to be a multiple of {}",
+            k,
+            format.block_len()
+        );
+        let a_f32 = a.cast_to::<f32>()?;
+        let a_f32 = a_f32.to_array_view::<f32>()?;
+        let mut quant = Vec::with_capacity(m * format.storage_bytes(k));
+        for row in 0..m {
+            let values: Vec<f32> = a_f32.index_axis(Axis(axes.a_m), row).iter().copied().collect();
+            quant.extend_from_slice(&format.quant_f32(&values)?);
+        }
+        let quant = Tensor::from_shape(&[m, format.storage_bytes(k)], &quant)?;
+        Ok(BlockQuantMatMul { format, a_shape: a.shape().into(), a: quant.into_arc_tensor(), axes })
+    }
+
+    /// The f32 weights, as seen by the matrix multiplier.
+    pub fn dequantize(&self) -> TractResult<Tensor> {
+        let (m, k) = (self.a_shape[self.axes.a_m], self.a_shape[self.axes.a_k]);
+        let values = self.format.dequant_f32(self.a.as_slice::<u8>()?)?;
+        let mut a = Tensor::from_shape(&[m, k], &values)?;
+        if self.axes.a_m > self.axes.a_k {
+            a = a.permute_axes(&[1, 0])?;
+        }
+        a.into_shape(&self.a_shape)
+    }
+
+    fn eval_t(&self, b: &Tensor) -> TractResult<Tensor> {
+        let (m, k, n, c_shape) = compute_shape(&self.a_shape, b.shape(), self.axes)?;
+        let c_dt = b.datum_type();
+        let mm = tract_linalg::ops()
+            .mmm(c_dt, c_dt, c_dt, Some(m), Some(k), Some(n))
+            .with_context(|| format!("No matrix multiplier for {:?}", c_dt))?;
+        let c = unsafe { Tensor::uninitialized_dt(c_dt, &c_shape)? };
+
+        let mut b_bc_shape: TVec<usize> = b.shape().into();
+        b_bc_shape.remove(self.axes.b_n.max(self.axes.b_k));
+        b_bc_shape.remove(self.axes.b_n.min(self.axes.b_k));
+        let mut b_strides: TVec<isize> = b.strides().into();
+        b_strides.remove(self.axes.b_n.max(self.axes.b_k));
+        b_strides.remove(self.axes.b_n.min(self.axes.b_k));
+        let mut c_bc_shape: TVec<usize> = c_shape.clone();
+        c_bc_shape.remove(self.axes.c_m.max(self.axes.c_n));
+        c_bc_shape.remove(self.axes.c_m.min(self.axes.c_n));
+        let mut c_strides: TVec<isize> = c.strides().into();
+        c_strides.remove(self.axes.c_m.max(self.axes.c_n));
+        c_strides.remove(self.axes.c_m.min(self.axes.c_n));
+
+        unsafe {
+            let a_spec = mm.a_block_quant(self.format, k);
+            let b_spec = mm.b_late_packing_with_axes(self.axes.b_k, self.axes.b_n);
+            let c_spec = mm.c_view(self.axes.c_m, self.axes.c_n);
+            let mut scratch = mm.allocate_scratch_space();
+            for prefix in tract_ndarray::indices(&*c_bc_shape).into_iter() {
+                let mut b_offset = 0;
+                let mut c_offset = 0;
+                for (axis, &dim) in prefix.slice().iter().enumerate() {
+                    if b_bc_shape[axis] > 1 {
+                        b_offset += b_strides[axis] * dim as isize * c_dt.size_of() as isize;
+                    }
+                    c_offset += c_strides[axis] * dim as isize * c_dt.size_of() as isize;
+                }
+                mm.run_with_scratch_space(
+                    m,
+                    n,
+                    &mut *scratch,
+                    &[
+                        FusedSpec::AddMatMul {
+                            a: a_spec.wrap(&self.a.view()),
+                            b: b_spec.wrap(&TensorView::from_bytes(
+                                b,
+                                b_offset,
+                                b.shape(),
+                                b.strides(),
+                            ))?,
+                            k,
+                        },
+                        FusedSpec::Store(c_spec.wrap(&TensorView::from_bytes(
+                            &c,
+                            c_offset,
+                            c.shape(),
+                            c.strides(),
+                        ))),
+                    ],
+                )?;
+            }
+        }
+        Ok(c)
+    }
+}
+
+impl Op for BlockQuantMatMul {
+    fn name(&self) -> Cow<str> {
+        "BlockQuantMatMul".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("{:?}", self.axes),
+            format!("A: {} {:?} in {} bytes", self.format, self.a_shape, self.a.len()),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for BlockQuantMatMul {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        Ok(tvec!(self.eval_t(&inputs[0])?.into_tvalue()))
+    }
+}
+
+impl TypedOp for BlockQuantMatMul {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(
+            inputs[0].datum_type == f32::datum_type() || inputs[0].datum_type == f16::datum_type(),
+            "BlockQuantMatMul expects f32 or f16 input, got {:?}",
+            inputs[0].datum_type
+        );
+        ensure!(
+            inputs[0].rank() == self.a_shape.len(),
+            "Inconsistent matmul between input {:?} and attribute {:?} (rank mismatch)",
+            inputs[0],
+            self.a_shape
+        );
+        let (_m, _k, _n, c_shape) = compute_shape(
+            &self.a_shape.iter().map(|d| d.to_dim()).collect::<TVec<_>>(),
+            &inputs[0].shape,
+            self.axes,
+        )?;
+        Ok(tvec!(inputs[0].datum_type.fact(c_shape)))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let mut cost = super::cost(
+            &self.a_shape,
+            &inputs[0].shape.to_tvec(),
+            inputs[0].datum_type,
+            self.axes,
+        )?;
+        cost.push((Cost::Params(u8::datum_type()), self.a.len().to_dim()));
+        Ok(cost)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(format: BlockQuant, a_shape: &[usize], b_shape: &[usize], axes: MatMulAxes) {
+        let a_len = a_shape.iter().product::<usize>();
+        let a = Tensor::from_shape(
+            a_shape,
+            &(0..a_len).map(|i| ((i * 7) % 19) as f32 / 4.0 - 2.0).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let b_len = b_shape.iter().product::<usize>();
+        let b = Tensor::from_shape(
+            b_shape,
+            &(0..b_len).map(|i| ((i * 5) % 11) as f32 / 8.0 - 0.5).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let op = BlockQuantMatMul::quantize(&a, axes, format).unwrap();
+        let found = op.eval(tvec!(b.clone().into_tvalue())).unwrap().remove(0);
+        let expected = super::super::eval(&op.dequantize().unwrap(), &b, axes).unwrap();
+        found.close_enough(&expected, true).unwrap();
+    }
+
+    #[test]
+    fn q4_0_mat_vec() {
+        check(BlockQuant::Q4_0, &[7, 64], &[64, 1], MatMulAxes::default());
+    }
+
+    #[test]
+    fn q8_0_mat_mul() {
+        check(BlockQuant::Q8_0, &[9, 32], &[32, 5], MatMulAxes::default());
+    }
+
+    #[test]
+    fn q4_0_transposed_a_batched_b() {
+        let axes = MatMulAxes::default_for_rank(3).transposing_a();
+        check(BlockQuant::Q4_0, &[1, 64, 5], &[3, 64, 2], axes);
+    }
+
+    #[test]
+    fn reject_odd_k() {
+        let a = Tensor::zero::<f32>(&[4, 30]).unwrap();
+        assert!(BlockQuantMatMul::quantize(&a, MatMulAxes::default(), BlockQuant::Q4_0).is_err());
+    }
+}
@@ -355,6 +355,11 @@ impl TypedOp for LirMatMulUnary {
             }
         }
 
+        // fused operands are in the internal type (f32 for bf16)
+        if self.c_fact.datum_type.is_float() && self.c_fact.datum_type != self.mmm.internal_type() {
+            return Ok(None);
+        }
+
         if let Some(op) = succ.op_as::<ops::element_wise::ElementWiseOp>().map(|ew| ew.0.as_ref()) {
             if let Some(op) = op.downcast_ref::<ops::math::QScale>() {
                 return self.fuse_op_with_broadcast(
@@ -53,6 +53,10 @@ impl TypedOp for MatMul {
     ) -> TractResult<Option<TypedModelPatch>> {
         let a_fact = model.outlet_fact(node.inputs[0])?;
         let b_fact = model.outlet_fact(node.inputs[1])?;
+        // complex products have no linalg kernels, they stay in the generic binary form
+        if a_fact.datum_type.is_complex() {
+            return Ok(None);
+        }
         let konst_ix = if a_fact.konst.is_some() {
             0
         } else if b_fact.konst.is_some() {
@@ -130,6 +134,15 @@ mod test {
         c.close_enough(&c_found, true).unwrap();
     }
 
+    #[test]
+    fn bin_complex() -> TractResult<()> {
+        let a = tensor2(&[[Complex::new(1f32, 1.0), Complex::new(0.0, 2.0)]]);
+        let b = tensor2(&[[Complex::new(2f32, 0.0)], [Complex::new(1.0, -1.0)]]);
+        let c = tensor2(&[[Complex::new(4f32, 4.0)]]);
+        let c_found = MatMul::default().eval(tvec!(a.into(), b.into()))?.pop().unwrap();
+        c.close_enough(&c_found, true)
+    }
+
     #[test]
     fn batch_input() -> TractResult<()> {
         crate::setup_test_logger();
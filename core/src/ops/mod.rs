@@ -73,6 +73,17 @@ pub trait OpState: fmt::Debug + dyn_clone::DynClone {
         op: &dyn Op,
         inputs: TVec<TValue>,
     ) -> TractResult<TVec<TValue>>;
+
+    /// Export what this state carries from one turn to the next, as named tensors.
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        Ok(tvec!())
+    }
+
+    /// Restore a freshly created state from the output of `save`.
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        ensure!(saved.is_empty(), "{:?} has no state to restore", self);
+        Ok(())
+    }
 }
 dyn_clone::clone_trait_object!(OpState);
 
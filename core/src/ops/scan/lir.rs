@@ -148,6 +148,45 @@ impl MutableState {
 }
 
 impl OpState for State {
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        let mut saved = tvec!(("position".to_string(), tensor0(self.mutable.position as i64)));
+        for (ix, h) in self.mutable.hidden_state.iter().enumerate() {
+            saved.push((format!("hidden.{}", ix), h.clone().into_tensor()));
+        }
+        // body states are prefixed by their node id
+        for (node, state) in self.mutable.model_state.states.iter().enumerate() {
+            if let Some(state) = state {
+                for (name, t) in state.save()? {
+                    saved.push((format!("body.{}.{}", node, name), t));
+                }
+            }
+        }
+        Ok(saved)
+    }
+
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        let mutable = &mut self.mutable;
+        mutable.position = crate::snapshot::saved_usize(saved, "position")?;
+        mutable.hidden_state = (0..)
+            .map_while(|ix| crate::snapshot::saved_opt_tensor(saved, &format!("hidden.{}", ix)))
+            .map(|t| t.into_tvalue())
+            .collect();
+        mutable.model_state.reset_op_states()?;
+        for (node, state) in mutable.model_state.states.iter_mut().enumerate() {
+            let prefix = format!("body.{}.", node);
+            let body_saved: Vec<(String, Tensor)> = saved
+                .iter()
+                .filter_map(|(name, t)| Some((name.strip_prefix(&prefix)?.to_string(), t.clone())))
+                .collect();
+            if let Some(state) = state {
+                state.load(&body_saved)?;
+            } else {
+                ensure!(body_saved.is_empty(), "Saved state for stateless body node #{}", node);
+            }
+        }
+        Ok(())
+    }
+
     fn eval(
         &mut self,
         session: &mut SessionState,
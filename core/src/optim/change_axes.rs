@@ -17,6 +17,10 @@ impl Debug for ChangeAxes {
 }
 
 impl TypedPass for ChangeAxes {
+    fn name(&self) -> Cow<str> {
+        "ChangeAxes".into()
+    }
+
     fn reset(&mut self) -> TractResult<()> {
         self.0.clear();
         Ok(())
@@ -7,6 +7,7 @@ pub mod change_axes;
 mod op_optim;
 mod prop_const;
 mod push_split_down;
+pub mod rewrite;
 mod slice;
 
 use self::change_axes::ChangeAxes;
@@ -16,6 +17,11 @@ use self::slice::PushSliceUp;
 use op_optim::OpOptim;
 
 pub trait TypedPass: Debug + Send + Sync + dyn_clone::DynClone {
+    /// Name of the pass, used to refer to it in an [`Optimizer`] and in logs. Defaults to the
+    /// type name.
+    fn name(&self) -> Cow<str> {
+        std::any::type_name::<Self>().into()
+    }
     fn reset(&mut self) -> TractResult<()>;
     fn next(
         &mut self,
@@ -33,7 +39,8 @@ pub struct Optimizer {
 }
 
 impl Optimizer {
-    fn passes(passes: Vec<Box<dyn TypedPass>>) -> Optimizer {
+    /// An optimizer running `passes`, in order, until none of them finds anything to patch.
+    pub fn new(passes: Vec<Box<dyn TypedPass>>) -> Optimizer {
         Optimizer { passes, steps: None }
     }
 
@@ -41,8 +48,69 @@ impl Optimizer {
         Optimizer { steps: Some(steps), ..self }
     }
 
+    pub fn passes(&self) -> &[Box<dyn TypedPass>] {
+        &self.passes
+    }
+
+    pub fn pass_names(&self) -> Vec<String> {
+        self.passes.iter().map(|p| p.name().into_owned()).collect()
+    }
+
+    fn position(&self, name: &str) -> TractResult<usize> {
+        self.passes.iter().position(|p| p.name() == name).with_context(|| {
+            format!(
+                "No optimizer pass named {} (passes are: {})",
+                name,
+                self.pass_names().join(", ")
+            )
+        })
+    }
+
+    /// Appends a pass, run after the existing ones.
+    pub fn with_pass(mut self, pass: Box<dyn TypedPass>) -> Optimizer {
+        self.passes.push(pass);
+        self
+    }
+
+    /// Inserts a pass right before the first pass named `anchor`.
+    pub fn with_pass_before(
+        mut self,
+        anchor: &str,
+        pass: Box<dyn TypedPass>,
+    ) -> TractResult<Optimizer> {
+        let ix = self.position(anchor)?;
+        self.passes.insert(ix, pass);
+        Ok(self)
+    }
+
+    /// Inserts a pass right after the first pass named `anchor`.
+    pub fn with_pass_after(
+        mut self,
+        anchor: &str,
+        pass: Box<dyn TypedPass>,
+    ) -> TractResult<Optimizer> {
+        let ix = self.position(anchor)?;
+        self.passes.insert(ix + 1, pass);
+        Ok(self)
+    }
+
+    /// Removes all the passes named `name`.
+    pub fn without_pass(mut self, name: &str) -> TractResult<Optimizer> {
+        self.position(name)?;
+        self.passes.retain(|p| p.name() != name);
+        Ok(self)
+    }
+
+    /// Moves the first pass named `name` right before the first pass named `anchor`.
+    pub fn moving_pass_before(mut self, name: &str, anchor: &str) -> TractResult<Optimizer> {
+        let pass = self.passes.remove(self.position(name)?);
+        let ix = self.position(anchor)?;
+        self.passes.insert(ix, pass);
+        Ok(self)
+    }
+
     pub fn declutter() -> Optimizer {
-        Optimizer::passes(vec![
+        Optimizer::new(vec![
             Box::new(PropConst),
             Box::new(OpOptim("declutter", TypedOp::declutter_with_session, 0)),
             Box::new(PushSliceUp),
@@ -52,7 +120,7 @@ impl Optimizer {
     }
 
     pub fn codegen() -> Optimizer {
-        Optimizer::passes(vec![
+        Optimizer::new(vec![
             Box::new(PropConst),
             Box::new(OpOptim(
                 "codegen",
@@ -74,7 +142,12 @@ impl Optimizer {
     }
 
     pub fn session(&self) -> OptimizerSession {
-        OptimizerSession { optimizer: self, counter: 0, seen: Default::default() }
+        OptimizerSession {
+            optimizer: self,
+            counter: 0,
+            seen: Default::default(),
+            patches_by_pass: Default::default(),
+        }
     }
 }
 
@@ -83,6 +156,7 @@ pub struct OptimizerSession<'o> {
     optimizer: &'o Optimizer,
     counter: usize,
     seen: HashSet<String>,
+    patches_by_pass: HashMap<String, usize>,
 }
 
 impl<'o> OptimizerSession<'o> {
@@ -92,7 +166,10 @@ impl<'o> OptimizerSession<'o> {
         for i in 0.. {
             let old = self.counter;
             self.run_all_passes(i, model)?;
-            if old == self.counter {
+            if old == self.counter || self.optimizer.steps.map_or(false, |s| self.counter >= s) {
+                for name in self.optimizer.pass_names().into_iter().unique() {
+                    info!("Optimizer pass {}: {} patches", name, self.patches(&name));
+                }
                 return Ok(());
             }
             model.compact()?;
@@ -100,6 +177,11 @@ impl<'o> OptimizerSession<'o> {
         unreachable!()
     }
 
+    /// Number of patches applied so far by the passes named `pass`.
+    pub fn patches(&self, pass: &str) -> usize {
+        self.patches_by_pass.get(pass).copied().unwrap_or(0)
+    }
+
     pub fn run_all_passes(&mut self, i: usize, model: &mut TypedModel) -> TractResult<()> {
         let mut passes = self.optimizer.passes.clone();
         for p in passes.iter_mut() {
@@ -161,6 +243,7 @@ impl<'o> OptimizerSession<'o> {
                 .check_consistency()
                 .context("Checking target model consistency after patchign")?;
             self.counter += 1;
+            *self.patches_by_pass.entry(p.name().into_owned()).or_default() += 1;
             if let Some(steps) = self.optimizer.steps {
                 if self.counter >= steps {
                     return Ok(());
@@ -171,3 +254,97 @@ impl<'o> OptimizerSession<'o> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    #[test]
+    fn builder() -> TractResult<()> {
+        let opt = Optimizer::declutter()
+            .without_pass("ChangeAxes")?
+            .with_pass_before("PropConst", Box::new(PushSplitDown))?
+            .moving_pass_before("PushSliceUp", "PushSplitDown")?
+            .with_pass_after("declutter", Box::new(ChangeAxes::default()))?;
+        assert_eq!(
+            opt.pass_names(),
+            [
+                "PushSliceUp",
+                "PushSplitDown",
+                "PropConst",
+                "declutter",
+                "ChangeAxes",
+                "PushSplitDown"
+            ]
+        );
+        assert!(opt.without_pass("fuse").is_err());
+        Ok(())
+    }
+
+    fn bin_ops(model: &TypedModel) -> usize {
+        model.nodes().iter().filter(|n| n.op_is::<crate::ops::binary::TypedBinOp>()).count()
+    }
+
+    fn const_add() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2]))?;
+        let a = model.add_const("a", tensor1(&[1f32, 2.]))?;
+        let b = model.add_const("b", tensor1(&[3f32, 4.]))?;
+        let ab = model.wire_node("ab", math::add(), &[a, b])?[0];
+        let add = model.wire_node("add", math::add(), &[x, ab])?;
+        model.set_output_outlets(&add)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn patches_by_pass() -> TractResult<()> {
+        let mut model = const_add()?;
+        let opt = Optimizer::declutter();
+        let mut session = opt.session();
+        session.optimize(&mut model)?;
+        assert_eq!(session.patches("PropConst"), 1);
+        assert_eq!(session.patches("ChangeAxes"), 0);
+        assert_eq!(bin_ops(&model), 1);
+
+        let mut model = const_add()?;
+        let opt = Optimizer::declutter().without_pass("PropConst")?;
+        let mut session = opt.session();
+        session.optimize(&mut model)?;
+        assert_eq!(bin_ops(&model), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn patches_by_pass_stopping_early() -> TractResult<()> {
+        let mut model = const_add()?;
+        let opt = Optimizer::declutter().stopping_at(1);
+        let mut session = opt.session();
+        session.optimize(&mut model)?;
+        assert_eq!(session.patches("PropConst"), 1);
+        assert_eq!(session.counter, 1);
+        Ok(())
+    }
+
+    #[derive(Clone, Debug)]
+    struct Noop;
+
+    impl TypedPass for Noop {
+        fn reset(&mut self) -> TractResult<()> {
+            Ok(())
+        }
+
+        fn next(
+            &mut self,
+            _session: &mut OptimizerSession,
+            _model: &TypedModel,
+        ) -> TractResult<Option<TypedModelPatch>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn default_pass_name() {
+        assert!(Noop.name().ends_with("Noop"));
+    }
+}
@@ -41,6 +41,10 @@ impl std::fmt::Debug for OpOptim {
 }
 
 impl super::TypedPass for OpOptim {
+    fn name(&self) -> Cow<str> {
+        self.0.into()
+    }
+
     fn reset(&mut self) -> TractResult<()> {
         self.2 = 0;
         Ok(())
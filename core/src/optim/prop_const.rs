@@ -8,6 +8,10 @@ use crate::optim::OptimizerSession;
 pub struct PropConst;
 
 impl super::TypedPass for PropConst {
+    fn name(&self) -> Cow<str> {
+        "PropConst".into()
+    }
+
     fn reset(&mut self) -> TractResult<()> {
         Ok(())
     }
@@ -7,6 +7,10 @@ use crate::optim::OptimizerSession;
 pub struct PushSplitDown;
 
 impl super::TypedPass for PushSplitDown {
+    fn name(&self) -> Cow<str> {
+        "PushSplitDown".into()
+    }
+
     fn reset(&mut self) -> TractResult<()> {
         Ok(())
     }
@@ -0,0 +1,564 @@
+//! Declarative graph rewriting.
+//!
+//! A [`RewriteRule`] describes the shape of a subgraph with a [`Pattern`] tree (rooted at the
+//! node to be replaced), binds parts of it to named captures and emits a replacement in a
+//! [`TypedModelPatch`]. Rules are grouped in a [`RewritePass`], which can be plugged in an
+//! [`Optimizer`](super::Optimizer) like any other [`TypedPass`].
+//!
+//! ```
+//! # use tract_core::internal::*;
+//! # use tract_core::ops::{math, nn};
+//! # use tract_core::optim::rewrite::*;
+//! // x * sigmoid(x), whatever the order of the multiplication operands
+//! let pattern = bin::<math::Mul>()
+//!     .inputs([any("x"), ew::<nn::Sigmoid>().inputs([any("x")]).into()])
+//!     .commutative();
+//! let rule = RewriteRule::new("silu", pattern, |patch, m| {
+//!     let x = m.tap("x")?;
+//!     // ... wire a fused operator here
+//!     Ok(tvec!(x))
+//! });
+//! let pass = RewritePass::new("activations").with_rule(rule);
+//! ```
+
+use std::fmt;
+
+use crate::internal::*;
+use crate::ops::binary::{BinMiniOp, TypedBinOp};
+use crate::ops::element_wise::ElementWiseOp;
+
+use super::{OptimizerSession, TypedPass};
+
+type OpPredicate = Arc<dyn Fn(&dyn TypedOp) -> bool + Send + Sync>;
+type ValuePredicate = Arc<dyn Fn(&Tensor) -> bool + Send + Sync>;
+type Guard = Arc<dyn Fn(&Match) -> TractResult<bool> + Send + Sync>;
+type Rewrite =
+    Arc<dyn Fn(&mut TypedModelPatch, &Match) -> TractResult<TVec<OutletId>> + Send + Sync>;
+
+#[derive(Clone)]
+enum PatternKind {
+    Any,
+    Op { predicates: Vec<OpPredicate>, inputs: Option<Vec<Pattern>>, commutative: bool },
+    Const { predicates: Vec<ValuePredicate> },
+}
+
+/// Describes the wire feeding a node input (or the rewritten outlet, for the root pattern).
+///
+/// Operator and constant patterns are built with the typed [`OpPattern`] and [`ConstPattern`]
+/// builders, and converted to a `Pattern` with `into()`.
+#[derive(Clone)]
+pub struct Pattern {
+    kind: PatternKind,
+    capture: Option<String>,
+    slot: usize,
+}
+
+/// Matches a wire produced by a node, see [`op`].
+#[derive(Clone)]
+pub struct OpPattern {
+    predicates: Vec<OpPredicate>,
+    inputs: Option<Vec<Pattern>>,
+    commutative: bool,
+    capture: Option<String>,
+    slot: usize,
+}
+
+/// Matches a constant wire, see [`konst`].
+#[derive(Clone)]
+pub struct ConstPattern {
+    predicates: Vec<ValuePredicate>,
+    capture: Option<String>,
+}
+
+/// Matches any wire, binding it to `capture`.
+///
+/// Using the same capture name more than once in a pattern requires all occurences to bind to
+/// the same wire.
+pub fn any(capture: impl Into<String>) -> Pattern {
+    Pattern { kind: PatternKind::Any, capture: Some(capture.into()), slot: 0 }
+}
+
+/// Matches a wire produced by a node whose operator is an `O`.
+pub fn op<O: TypedOp>() -> OpPattern {
+    let is_o: OpPredicate = Arc::new(|op: &dyn TypedOp| op.as_op().downcast_ref::<O>().is_some());
+    OpPattern { predicates: vec![is_o], inputs: None, commutative: false, capture: None, slot: 0 }
+}
+
+/// Matches a wire produced by a binary operator with the `M` mini-op.
+pub fn bin<M: BinMiniOp>() -> OpPattern {
+    op::<TypedBinOp>().with(|op: &TypedBinOp| op.0.is::<M>())
+}
+
+/// Matches a wire produced by an element-wise operator with the `M` mini-op.
+pub fn ew<M: ElementWiseMiniOp>() -> OpPattern {
+    op::<ElementWiseOp>().with(|op: &ElementWiseOp| op.0.is::<M>())
+}
+
+/// Matches a wire with a constant value.
+pub fn konst() -> ConstPattern {
+    ConstPattern { predicates: vec![], capture: None }
+}
+
+/// Matches a constant wire where all values are equal to `value`.
+pub fn scalar(value: f32) -> ConstPattern {
+    konst().with_value(move |t| {
+        t.as_uniform()
+            .and_then(|u| u.cast_to_scalar::<f32>().ok())
+            .map(|u| u == value)
+            .unwrap_or(false)
+    })
+}
+
+impl OpPattern {
+    /// Binds the matched wire (and its node) to `capture`.
+    pub fn capture(self, capture: impl Into<String>) -> OpPattern {
+        OpPattern { capture: Some(capture.into()), ..self }
+    }
+
+    /// Matches the `slot`-th output of the node instead of the first one.
+    pub fn slot(self, slot: usize) -> OpPattern {
+        OpPattern { slot, ..self }
+    }
+
+    /// Adds a predicate on the node operator, which must be an `O`.
+    pub fn with<O: TypedOp>(
+        mut self,
+        predicate: impl Fn(&O) -> bool + Send + Sync + 'static,
+    ) -> OpPattern {
+        self.predicates.push(Arc::new(move |op: &dyn TypedOp| {
+            op.as_op().downcast_ref::<O>().map(&predicate).unwrap_or(false)
+        }));
+        self
+    }
+
+    /// Requires the node inputs to match `inputs`.
+    pub fn inputs(self, inputs: impl IntoIterator<Item = Pattern>) -> OpPattern {
+        OpPattern { inputs: Some(inputs.into_iter().collect()), ..self }
+    }
+
+    /// Also tries the two inputs of the node in reverse order.
+    pub fn commutative(self) -> OpPattern {
+        OpPattern { commutative: true, ..self }
+    }
+}
+
+impl From<OpPattern> for Pattern {
+    fn from(op: OpPattern) -> Pattern {
+        let OpPattern { predicates, inputs, commutative, capture, slot } = op;
+        Pattern { kind: PatternKind::Op { predicates, inputs, commutative }, capture, slot }
+    }
+}
+
+impl ConstPattern {
+    /// Binds the matched wire to `capture`.
+    pub fn capture(self, capture: impl Into<String>) -> ConstPattern {
+        ConstPattern { capture: Some(capture.into()), ..self }
+    }
+
+    /// Adds a predicate on the value of the constant.
+    pub fn with_value(
+        mut self,
+        predicate: impl Fn(&Tensor) -> bool + Send + Sync + 'static,
+    ) -> ConstPattern {
+        self.predicates.push(Arc::new(predicate));
+        self
+    }
+}
+
+impl From<ConstPattern> for Pattern {
+    fn from(konst: ConstPattern) -> Pattern {
+        let ConstPattern { predicates, capture } = konst;
+        Pattern { kind: PatternKind::Const { predicates }, capture, slot: 0 }
+    }
+}
+
+impl Pattern {
+    fn bind(&self, outlet: OutletId, captures: &mut HashMap<String, OutletId>) -> bool {
+        if let Some(name) = &self.capture {
+            if let Some(bound) = captures.get(name) {
+                return *bound == outlet;
+            }
+            captures.insert(name.clone(), outlet);
+        }
+        true
+    }
+
+    fn matches(
+        &self,
+        model: &TypedModel,
+        outlet: OutletId,
+        captures: &mut HashMap<String, OutletId>,
+    ) -> TractResult<bool> {
+        match &self.kind {
+            PatternKind::Any => Ok(self.bind(outlet, captures)),
+            PatternKind::Const { predicates } => {
+                let fact = model.outlet_fact(outlet)?;
+                if let Some(k) = &fact.konst {
+                    Ok(predicates.iter().all(|p| p(k)) && self.bind(outlet, captures))
+                } else {
+                    Ok(false)
+                }
+            }
+            PatternKind::Op { predicates, inputs, commutative } => {
+                let node = model.node(outlet.node);
+                if outlet.slot != self.slot || !predicates.iter().all(|p| p(node.op.as_ref())) {
+                    return Ok(false);
+                }
+                if let Some(inputs) = inputs {
+                    if inputs.len() != node.inputs.len() {
+                        return Ok(false);
+                    }
+                    let mut orders = vec![node.inputs.to_vec()];
+                    if *commutative && node.inputs.len() == 2 {
+                        orders.push(vec![node.inputs[1], node.inputs[0]]);
+                    }
+                    for order in orders {
+                        let mut attempt = captures.clone();
+                        let mut ok = true;
+                        for (pattern, input) in inputs.iter().zip(order.iter()) {
+                            if !pattern.matches(model, *input, &mut attempt)? {
+                                ok = false;
+                                break;
+                            }
+                        }
+                        if ok && self.bind(outlet, &mut attempt) {
+                            *captures = attempt;
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                } else {
+                    Ok(self.bind(outlet, captures))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            PatternKind::Any => write!(fmt, "any")?,
+            PatternKind::Const { .. } => write!(fmt, "const")?,
+            PatternKind::Op { inputs, .. } => {
+                write!(fmt, "op")?;
+                if let Some(inputs) = inputs {
+                    write!(fmt, "{:?}", inputs)?;
+                }
+            }
+        }
+        if let Some(capture) = &self.capture {
+            write!(fmt, " as {}", capture)?;
+        }
+        Ok(())
+    }
+}
+
+/// The captures of a successful match, handed to the rule replacement function.
+pub struct Match<'m> {
+    model: &'m TypedModel,
+    root: usize,
+    captures: HashMap<String, OutletId>,
+    taps: HashMap<String, OutletId>,
+}
+
+impl<'m> Match<'m> {
+    /// The matched model.
+    pub fn model(&self) -> &TypedModel {
+        self.model
+    }
+
+    /// The node being rewritten.
+    pub fn root(&self) -> &TypedNode {
+        self.model.node(self.root)
+    }
+
+    /// The wire bound to `capture`, in the matched model.
+    pub fn outlet(&self, capture: &str) -> TractResult<OutletId> {
+        self.captures.get(capture).copied().with_context(|| format!("No capture named {}", capture))
+    }
+
+    /// The wire bound to `capture`, tapped in the replacement patch.
+    pub fn tap(&self, capture: &str) -> TractResult<OutletId> {
+        self.taps.get(capture).copied().with_context(|| format!("No capture named {}", capture))
+    }
+
+    pub fn fact(&self, capture: &str) -> TractResult<&TypedFact> {
+        self.model.outlet_fact(self.outlet(capture)?)
+    }
+
+    /// The node producing the wire bound to `capture`.
+    pub fn node(&self, capture: &str) -> TractResult<&TypedNode> {
+        Ok(self.model.node(self.outlet(capture)?.node))
+    }
+
+    /// The operator of the node bound to `capture`, which must be an `O`.
+    pub fn op<O: TypedOp>(&self, capture: &str) -> TractResult<&O> {
+        let node = self.node(capture)?;
+        node.op_as::<O>().with_context(|| format!("Capture {} is not a {}", capture, node))
+    }
+
+    /// The value of the constant bound to `capture`.
+    pub fn konst(&self, capture: &str) -> TractResult<Arc<Tensor>> {
+        self.fact(capture)?
+            .konst
+            .clone()
+            .with_context(|| format!("Capture {} is not a constant", capture))
+    }
+}
+
+/// A pattern, and the replacement for the outputs of its root node.
+#[derive(Clone)]
+pub struct RewriteRule {
+    name: String,
+    pattern: Pattern,
+    guard: Option<Guard>,
+    rewrite: Rewrite,
+}
+
+impl RewriteRule {
+    /// `rewrite` wires the replacement in the patch from the captured wires, and returns the
+    /// outlets substituting the outputs of the root node.
+    pub fn new(
+        name: impl Into<String>,
+        pattern: impl Into<Pattern>,
+        rewrite: impl Fn(&mut TypedModelPatch, &Match) -> TractResult<TVec<OutletId>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> RewriteRule {
+        RewriteRule {
+            name: name.into(),
+            pattern: pattern.into(),
+            guard: None,
+            rewrite: Arc::new(rewrite),
+        }
+    }
+
+    /// Only rewrites when `guard` accepts the match (for conditions between captures, like
+    /// shape or datum type compatibility).
+    pub fn when(
+        self,
+        guard: impl Fn(&Match) -> TractResult<bool> + Send + Sync + 'static,
+    ) -> RewriteRule {
+        RewriteRule { guard: Some(Arc::new(guard)), ..self }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tries to apply the rule at `node`.
+    pub fn try_rewrite(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let mut captures = HashMap::default();
+        let root = OutletId::new(node.id, self.pattern.slot);
+        if node.outputs.len() <= root.slot || !self.pattern.matches(model, root, &mut captures)? {
+            return Ok(None);
+        }
+        let mut m = Match { model, root: node.id, captures, taps: HashMap::default() };
+        if let Some(guard) = &self.guard {
+            if !guard(&m)? {
+                return Ok(None);
+            }
+        }
+        let mut patch = TypedModelPatch::new(format!("{} {}", self.name, node));
+        for (name, outlet) in &m.captures {
+            if outlet.node != node.id {
+                m.taps.insert(name.clone(), patch.tap_model(model, *outlet)?);
+            }
+        }
+        let wires = (self.rewrite)(&mut patch, &m)?;
+        ensure!(
+            wires.len() <= node.outputs.len(),
+            "Rule {} returned {} outlets to replace the {} outputs of {}",
+            self.name,
+            wires.len(),
+            node.outputs.len(),
+            node
+        );
+        for (ix, wire) in wires.into_iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), wire)?;
+        }
+        Ok(Some(patch))
+    }
+}
+
+impl fmt::Debug for RewriteRule {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}: {:?}", self.name, self.pattern)
+    }
+}
+
+/// A set of rewrite rules, applied to every node in evaluation order.
+#[derive(Clone)]
+pub struct RewritePass {
+    name: String,
+    rules: Vec<RewriteRule>,
+    next_node: usize,
+}
+
+impl RewritePass {
+    pub fn new(name: impl Into<String>) -> RewritePass {
+        RewritePass { name: name.into(), rules: vec![], next_node: 0 }
+    }
+
+    pub fn with_rule(mut self, rule: RewriteRule) -> RewritePass {
+        self.rules.push(rule);
+        self
+    }
+}
+
+impl fmt::Debug for RewritePass {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.name)
+    }
+}
+
+impl TypedPass for RewritePass {
+    fn name(&self) -> Cow<str> {
+        self.name.as_str().into()
+    }
+
+    fn reset(&mut self) -> TractResult<()> {
+        self.next_node = 0;
+        Ok(())
+    }
+
+    fn next(
+        &mut self,
+        _session: &mut OptimizerSession,
+        model: &TypedModel,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        for (ix, &id) in model.eval_order()?.iter().enumerate().skip(self.next_node) {
+            let node = model.node(id);
+            for rule in &self.rules {
+                if let Some(patch) = rule
+                    .try_rewrite(model, node)
+                    .with_context(|| format!("Applying rule {} to {}", rule.name, node))?
+                {
+                    self.next_node = ix;
+                    return Ok(Some(patch));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::{math, nn};
+    use crate::optim::Optimizer;
+
+    #[derive(Debug, Clone, Hash)]
+    struct Silu;
+
+    impl_dyn_hash!(Silu);
+
+    impl Op for Silu {
+        fn name(&self) -> Cow<str> {
+            "Silu".into()
+        }
+
+        op_as_typed_op!();
+    }
+
+    impl EvalOp for Silu {
+        fn is_stateless(&self) -> bool {
+            true
+        }
+
+        fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+            let mut x = inputs[0].clone().into_tensor();
+            x.as_slice_mut::<f32>()?.iter_mut().for_each(|x| *x /= 1.0 + (-*x).exp());
+            Ok(tvec!(x.into_tvalue()))
+        }
+    }
+
+    impl TypedOp for Silu {
+        fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+            Ok(tvec!(inputs[0].without_value()))
+        }
+
+        as_op!();
+    }
+
+    fn silu_pass() -> RewritePass {
+        let pattern = bin::<math::Mul>()
+            .inputs([any("x"), ew::<nn::Sigmoid>().inputs([any("x")]).into()])
+            .commutative();
+        RewritePass::new("silu").with_rule(RewriteRule::new("silu", pattern, |patch, m| {
+            patch.wire_node(&m.root().name, Silu, &[m.tap("x")?])
+        }))
+    }
+
+    fn x_sigmoid(swap: bool, same: bool) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([3]))?;
+        let y = model.add_source("y", f32::fact([3]))?;
+        let s = model.wire_node("s", nn::sigmoid(), &[if same { x } else { y }])?[0];
+        let inputs = if swap { [s, x] } else { [x, s] };
+        let mul = model.wire_node("mul", math::mul(), &inputs)?;
+        model.set_output_outlets(&mul)?;
+        Ok(model)
+    }
+
+    fn run(model: &TypedModel) -> TractResult<Tensor> {
+        let inputs = tvec!(tensor1(&[-1f32, 0., 2.]).into(), tensor1(&[1f32, 1., 1.]).into());
+        Ok(model.clone().into_runnable()?.run(inputs)?.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn silu_both_orders() -> TractResult<()> {
+        for swap in [false, true] {
+            let model = x_sigmoid(swap, true)?;
+            let mut rewritten = model.clone();
+            Optimizer::declutter().with_pass(Box::new(silu_pass())).optimize(&mut rewritten)?;
+            assert!(rewritten.nodes().iter().any(|n| n.op_is::<Silu>()));
+            assert!(!rewritten.nodes().iter().any(|n| n.op_is::<TypedBinOp>()));
+            run(&rewritten)?.close_enough(&run(&model)?, true)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn captures_must_agree() -> TractResult<()> {
+        let model = x_sigmoid(false, false)?;
+        let mul = model.node_by_name("mul")?;
+        for rule in &silu_pass().rules {
+            assert!(rule.try_rewrite(&model, mul)?.is_none());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn constant_and_guard() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2]))?;
+        let two = model.add_const("two", tensor1(&[2f32, 2.]))?;
+        let three = model.add_const("three", tensor1(&[3f32, 3.]))?;
+        let a = model.wire_node("a", math::add(), &[x, two])?[0];
+        let b = model.wire_node("b", math::add(), &[x, three])?[0];
+        model.set_output_outlets(&[a, b])?;
+        let rule = RewriteRule::new(
+            "add_two",
+            bin::<math::Add>().inputs([any("x"), scalar(2.0).capture("c").into()]),
+            |patch, m| {
+                let x = m.tap("x")?;
+                let c = patch.add_const("c", m.konst("c")?)?;
+                patch.wire_node(&m.root().name, math::mul(), &[x, c])
+            },
+        )
+        .when(|m| Ok(m.fact("x")?.datum_type == f32::datum_type()));
+        assert!(rule.try_rewrite(&model, model.node_by_name("a")?)?.is_some());
+        assert!(rule.try_rewrite(&model, model.node_by_name("b")?)?.is_none());
+        let guarded = rule.clone().when(|_| Ok(false));
+        assert!(guarded.try_rewrite(&model, model.node_by_name("a")?)?.is_none());
+        Ok(())
+    }
+}
@@ -6,6 +6,10 @@ use crate::optim::OptimizerSession;
 pub struct PushSliceUp;
 
 impl super::TypedPass for PushSliceUp {
+    fn name(&self) -> Cow<str> {
+        "PushSliceUp".into()
+    }
+
     fn reset(&mut self) -> TractResult<()> {
         Ok(())
     }
@@ -5,6 +5,7 @@ use std::marker::PhantomData;
 use crate::internal::*;
 use crate::model::order::eval_order_for_nodes;
 use crate::model::{Fact, Graph, OutletId};
+use crate::snapshot::{NodeStateSnapshot, StateSnapshot, SNAPSHOT_FORMAT_VERSION};
 
 #[derive(Default)]
 pub struct SessionState {
@@ -173,6 +174,66 @@ where
         Ok(())
     }
 
+    /// Capture op inner states and session tensors, so that a stream can be resumed later,
+    /// possibly in another process.
+    pub fn snapshot(&self) -> TractResult<StateSnapshot> {
+        let mut nodes = vec![];
+        for (node, state) in self.states.iter().enumerate() {
+            if let Some(state) = state {
+                let name = &self.model().node(node).name;
+                let tensors = state.save().with_context(|| format!("Saving state of {}", name))?;
+                if !tensors.is_empty() {
+                    nodes.push(NodeStateSnapshot { node, name: name.clone(), tensors });
+                }
+            }
+        }
+        let mut session_tensors: Vec<(String, Tensor)> =
+            self.session_state.tensors.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        session_tensors.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(StateSnapshot {
+            version: SNAPSHOT_FORMAT_VERSION,
+            signature: self.model().signature(),
+            nodes,
+            session_tensors,
+        })
+    }
+
+    /// Restore op inner states and session tensors from a snapshot taken on the same model.
+    pub fn restore(&mut self, snapshot: &StateSnapshot) -> TractResult<()> {
+        ensure!(
+            snapshot.version == SNAPSHOT_FORMAT_VERSION,
+            "Unsupported snapshot format version {}",
+            snapshot.version
+        );
+        ensure!(
+            snapshot.signature == self.model().signature(),
+            "Snapshot was taken on a different model"
+        );
+        self.reset_turn()?;
+        self.session_state.tensors = snapshot.session_tensors.iter().cloned().collect();
+        self.reset_op_states()?;
+        for saved in &snapshot.nodes {
+            let node = self
+                .model()
+                .nodes()
+                .get(saved.node)
+                .with_context(|| format!("Snapshot refers to missing node #{}", saved.node))?;
+            ensure!(
+                node.name == saved.name,
+                "Snapshot node #{} is {}, model has {}",
+                saved.node,
+                saved.name,
+                node.name
+            );
+            let name = node.name.clone();
+            let state = self.states[saved.node]
+                .as_mut()
+                .with_context(|| format!("Snapshot has state for stateless node {}", name))?;
+            state.load(&saved.tensors).with_context(|| format!("Restoring state of {}", name))?;
+        }
+        Ok(())
+    }
+
     pub fn run(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         self.run_plan_with_eval(inputs, self::eval)
     }
@@ -270,7 +331,7 @@ where
                     for (o, v) in node.outputs.iter().zip(vs.iter()) {
                         if let Ok(f) = o.fact.to_typed_fact() {
                             for (dim_abstract, dim_concrete) in f.shape.iter().zip(v.shape()) {
-                                Self::resolve(
+                                resolve_symbols(
                                     &mut session_state.resolved_symbols,
                                     &dim_abstract,
                                     *dim_concrete as i64,
@@ -324,14 +385,6 @@ where
         Ok(())
     }
 
-    fn resolve(symbols: &mut SymbolValues, expected: &TDim, provided: i64) {
-        match expected {
-            TDim::Sym(s) => symbols[s] = Some(provided),
-            TDim::MulInt(x, expr) => Self::resolve(symbols, expr, provided / *x),
-            _ => (),
-        }
-    }
-
     pub fn set_input(&mut self, input: usize, t: TValue) -> TractResult<()> {
         let outlet: OutletId = *self
             .model()
@@ -343,7 +396,7 @@ where
         let model = plan.model.borrow();
         if let Ok(fact) = model.outlet_fact(outlet)?.to_typed_fact() {
             for (expected, provided) in fact.shape.iter().zip(t.shape()) {
-                Self::resolve(&mut session_state.resolved_symbols, &expected, *provided as i64)
+                resolve_symbols(&mut session_state.resolved_symbols, &expected, *provided as i64)
             }
         }
         let fact = self.plan.borrow().model().outlet_fact(outlet)?;
@@ -496,6 +549,15 @@ where
     }
 }
 
+/// Record the symbol values implied by a concrete dimension matching `expected`.
+pub(crate) fn resolve_symbols(symbols: &mut SymbolValues, expected: &TDim, provided: i64) {
+    match expected {
+        TDim::Sym(s) => symbols[s] = Some(provided),
+        TDim::MulInt(x, expr) => resolve_symbols(symbols, expr, provided / *x),
+        _ => (),
+    }
+}
+
 pub fn eval<F, O>(
     session_state: &mut SessionState,
     mut state: Option<&mut (dyn OpState + 'static)>,
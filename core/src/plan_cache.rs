@@ -0,0 +1,352 @@
+//! Runnable wrapper specializing a model with symbolic dimensions on the symbol values
+//! it actually runs with.
+//!
+//! Each run resolves the input symbols. Known values are served by a concretized and
+//! optimized plan from a bounded LRU cache. Unknown values are compiled in the calling
+//! thread, or, if background compilation is enabled, run with the generic, symbolic plan
+//! while a bounded pool of threads compiles the specialized plan.
+#[cfg(not(target_family = "wasm"))]
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
+#[cfg(not(target_family = "wasm"))]
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::internal::*;
+use crate::model::typed::TypedRunnableModel;
+use crate::plan::resolve_symbols;
+
+type Plan = Arc<TypedRunnableModel<TypedModel>>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlanCacheMetrics {
+    /// Runs served by a cached specialized plan.
+    pub hits: usize,
+    /// Runs with symbol values no specialized plan was ready for.
+    pub misses: usize,
+    /// Specialized plans compiled (successfully or not).
+    pub compilations: usize,
+    pub failed_compilations: usize,
+    /// Specialized plans dropped to honour the cache capacity.
+    pub evictions: usize,
+    /// Total time spent compiling specialized plans.
+    pub compile_time: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    /// Most recently used last.
+    plans: Vec<(TVec<i64>, Plan)>,
+    /// Queued or being compiled.
+    pending: Vec<TVec<i64>>,
+    /// Most recent failures last, bounded by the cache capacity.
+    failed: Vec<TVec<i64>>,
+    /// Compilations waiting for a background worker.
+    #[cfg(not(target_family = "wasm"))]
+    queue: VecDeque<(TVec<i64>, SymbolValues)>,
+    /// Running background workers.
+    #[cfg(not(target_family = "wasm"))]
+    workers: usize,
+    metrics: PlanCacheMetrics,
+}
+
+#[derive(Debug)]
+pub struct PlanCache {
+    model: Arc<TypedModel>,
+    symbolic: Plan,
+    symbols: TVec<Symbol>,
+    capacity: usize,
+    #[cfg(not(target_family = "wasm"))]
+    threads: usize,
+    cache: Arc<Mutex<Cache>>,
+    #[cfg(not(target_family = "wasm"))]
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl PlanCache {
+    /// Wrap a (typically decluttered) model, keeping at most `capacity` specialized plans.
+    pub fn new(model: TypedModel, capacity: usize) -> TractResult<PlanCache> {
+        ensure!(capacity > 0, "Plan cache capacity must be at least one");
+        let mut symbols: TVec<Symbol> = tvec!();
+        for input in model.input_outlets()? {
+            for sym in model.outlet_fact(*input)?.shape.iter().flat_map(|d| d.symbols()) {
+                if !symbols.contains(&sym) {
+                    symbols.push(sym);
+                }
+            }
+        }
+        let model = Arc::new(model);
+        let symbolic = Arc::new(SimplePlan::new((*model).clone().into_optimized()?)?);
+        Ok(PlanCache {
+            model,
+            symbolic,
+            symbols,
+            capacity,
+            #[cfg(not(target_family = "wasm"))]
+            threads: 0,
+            cache: Default::default(),
+            #[cfg(not(target_family = "wasm"))]
+            workers: Default::default(),
+        })
+    }
+
+    /// Compile specialized plans on at most `threads` background threads, running the
+    /// symbolic plan until they are ready. Zero (the default) compiles them in the calling
+    /// thread.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_background_compilation(mut self, threads: usize) -> PlanCache {
+        self.threads = threads;
+        self
+    }
+
+    pub fn symbolic_plan(&self) -> &Plan {
+        &self.symbolic
+    }
+
+    pub fn metrics(&self) -> PlanCacheMetrics {
+        self.cache.lock().unwrap().metrics
+    }
+
+    /// Number of specialized plans currently cached.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().plans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Wait for background compilations to be over.
+    pub fn wait(&self) {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+            for worker in workers {
+                let _ = worker.join();
+            }
+        }
+    }
+
+    pub fn run(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        if let Some(key) = self.key(&inputs)?.filter(|k| !k.is_empty()) {
+            if let Some(plan) = self.lookup(&key) {
+                return plan.run(inputs);
+            }
+        }
+        self.symbolic.run(inputs)
+    }
+
+    /// Symbol values implied by the input shapes, if they are all resolved.
+    fn key(&self, inputs: &[TValue]) -> TractResult<Option<TVec<i64>>> {
+        let mut values = SymbolValues::default();
+        for (outlet, input) in self.model.input_outlets()?.iter().zip(inputs.iter()) {
+            let fact = self.model.outlet_fact(*outlet)?;
+            for (expected, provided) in fact.shape.iter().zip(input.shape()) {
+                resolve_symbols(&mut values, &expected, *provided as i64);
+            }
+        }
+        Ok(self.symbols.iter().map(|s| values[s]).collect())
+    }
+
+    /// Find the specialized plan for these values, scheduling its compilation if needed.
+    fn lookup(&self, key: &TVec<i64>) -> Option<Plan> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(pos) = cache.plans.iter().position(|(k, _)| k == key) {
+            let entry = cache.plans.remove(pos);
+            let plan = entry.1.clone();
+            cache.plans.push(entry);
+            cache.metrics.hits += 1;
+            return Some(plan);
+        }
+        cache.metrics.misses += 1;
+        if cache.pending.contains(key) || cache.failed.contains(key) {
+            return None;
+        }
+        cache.pending.push(key.clone());
+        let values = self
+            .symbols
+            .iter()
+            .zip(key.iter())
+            .fold(SymbolValues::default(), |values, (s, v)| values.with(s, *v));
+        #[cfg(not(target_family = "wasm"))]
+        if self.threads > 0 {
+            cache.queue.push_back((key.clone(), values));
+            if cache.workers < self.threads {
+                cache.workers += 1;
+                drop(cache);
+                self.spawn_worker();
+            }
+            return None;
+        }
+        drop(cache);
+        Self::compile(&self.model, &self.cache, self.capacity, key.clone(), &values);
+        let cache = self.cache.lock().unwrap();
+        cache.plans.iter().find(|(k, _)| k == key).map(|(_, plan)| plan.clone())
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn spawn_worker(&self) {
+        let model = self.model.clone();
+        let cache = self.cache.clone();
+        let capacity = self.capacity;
+        let spawned = std::thread::Builder::new()
+            .name("tract-plan-cache".to_string())
+            .spawn(move || Self::work(&model, &cache, capacity));
+        match spawned {
+            Ok(handle) => {
+                let mut workers = self.workers.lock().unwrap();
+                workers.retain(|w| !w.is_finished());
+                workers.push(handle);
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to spawn plan cache worker, compiling in the calling thread: {:?}",
+                    e
+                );
+                Self::work(&self.model, &self.cache, self.capacity)
+            }
+        }
+    }
+
+    /// Compile queued plans until the queue is empty.
+    #[cfg(not(target_family = "wasm"))]
+    fn work(model: &TypedModel, cache: &Mutex<Cache>, capacity: usize) {
+        loop {
+            let job = {
+                let mut cache = cache.lock().unwrap();
+                if let Some(job) = cache.queue.pop_front() {
+                    job
+                } else {
+                    cache.workers -= 1;
+                    return;
+                }
+            };
+            Self::compile(model, cache, capacity, job.0, &job.1);
+        }
+    }
+
+    fn compile(
+        model: &TypedModel,
+        cache: &Mutex<Cache>,
+        capacity: usize,
+        key: TVec<i64>,
+        values: &SymbolValues,
+    ) {
+        let start = Instant::now();
+        let plan = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            model
+                .concretize_dims(values)
+                .and_then(|m| m.into_optimized())
+                .and_then(|m| m.into_runnable())
+        }))
+        .unwrap_or_else(|_| bail!("Panicked while compiling"));
+        let elapsed = start.elapsed();
+        let mut cache = cache.lock().unwrap();
+        cache.pending.retain(|k| k != &key);
+        cache.metrics.compilations += 1;
+        cache.metrics.compile_time += elapsed;
+        match plan {
+            Ok(plan) => {
+                debug!("Specialized plan for {:?} compiled in {:?}", key, elapsed);
+                cache.plans.push((key, Arc::new(plan)));
+                if cache.plans.len() > capacity {
+                    cache.plans.remove(0);
+                    cache.metrics.evictions += 1;
+                }
+            }
+            Err(e) => {
+                warn!("Failed to specialize plan for {:?}: {:?}", key, e);
+                cache.metrics.failed_compilations += 1;
+                cache.failed.push(key);
+                if cache.failed.len() > capacity {
+                    cache.failed.remove(0);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for PlanCache {
+    fn drop(&mut self) {
+        self.wait()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let n = model.symbol_table.sym("N");
+        let source = model.add_source("source", f32::fact(dims!(n, 2)))?;
+        let one = model.add_const("one", rctensor2(&[[1f32, 2.]]))?;
+        let sum = model.wire_node("sum", crate::ops::math::add(), &[source, one])?;
+        model.set_output_outlets(&sum)?;
+        Ok(model)
+    }
+
+    fn input(n: usize) -> TVec<TValue> {
+        tvec!(Tensor::zero::<f32>(&[n, 2]).unwrap().into_tvalue())
+    }
+
+    #[test]
+    fn specialize_in_calling_thread() -> TractResult<()> {
+        let cache = PlanCache::new(model()?, 4)?;
+        assert_eq!(*cache.run(input(3))?[0], tensor2(&[[1f32, 2.], [1., 2.], [1., 2.]]));
+        assert_eq!(cache.len(), 1);
+        cache.run(input(3))?;
+        let metrics = cache.metrics();
+        assert_eq!((metrics.hits, metrics.misses, metrics.compilations), (1, 1, 1));
+        Ok(())
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn specialize_in_background() -> TractResult<()> {
+        let cache = PlanCache::new(model()?, 4)?.with_background_compilation(1);
+        let symbolic = cache.run(input(3))?;
+        assert_eq!(cache.metrics().misses, 1);
+        cache.wait();
+        assert_eq!(cache.len(), 1);
+        let specialized = cache.run(input(3))?;
+        assert_eq!(symbolic, specialized);
+        assert_eq!(*specialized[0], tensor2(&[[1f32, 2.], [1., 2.], [1., 2.]]));
+        let metrics = cache.metrics();
+        assert_eq!((metrics.hits, metrics.misses, metrics.compilations), (1, 1, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn evict_least_recently_used() -> TractResult<()> {
+        let cache = PlanCache::new(model()?, 2)?;
+        for n in [1, 2, 1, 3, 1, 2] {
+            assert_eq!(cache.run(input(n))?[0].shape(), &[n, 2]);
+        }
+        let metrics = cache.metrics();
+        assert_eq!(metrics.compilations, 4);
+        assert_eq!(metrics.evictions, 2);
+        assert_eq!(metrics.hits, 2);
+        assert_eq!(cache.len(), 2);
+        Ok(())
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn bounded_background_workers() -> TractResult<()> {
+        let cache = PlanCache::new(model()?, 3)?.with_background_compilation(2);
+        for n in 1..=8 {
+            cache.run(input(n))?;
+            assert!(cache.cache.lock().unwrap().workers <= 2);
+            assert!(cache.workers.lock().unwrap().len() <= 2);
+        }
+        cache.wait();
+        let state = cache.cache.lock().unwrap();
+        assert_eq!(state.metrics.compilations, 8);
+        assert_eq!(state.plans.len(), 3);
+        assert!(state.pending.is_empty() && state.queue.is_empty());
+        assert_eq!(state.workers, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,445 @@
+//! Post-training int8 quantization.
+//!
+//! A float model is first run on a set of calibration inputs to collect the range of values
+//! going through each f32 outlet. Convolutions and matrix multiplications with constant weights
+//! are then rewritten to their integer forms: inputs are quantized to QI8 with the calibrated
+//! range, weights are quantized symmetrically, and the QI8 output is dequantized back to f32 for
+//! the rest of the network. Dequantize/quantize pairs between consecutive quantized operators
+//! are folded so the values stay in int8.
+//!
+//! Weight-only block quantization needs no calibration: constant float matrices of matrix
+//! multiplications are stored in a block-quantized format, and dequantized on the fly by the
+//! matrix multiplier.
+
+use crate::internal::*;
+use crate::ops::cast::{cast, Cast};
+use crate::ops::cnn::ConvUnary;
+use crate::ops::matmul::mir_quant::MatMulQParams;
+use crate::ops::matmul::mir_quant_unary::QMatMulUnary;
+use crate::ops::matmul::{BlockQuantMatMul, MatMulUnary};
+use crate::plan::{eval, SimplePlan, SimpleState};
+
+/// How the quantization range of an outlet is derived from calibration values.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CalibrationMethod {
+    /// Use the observed minimum and maximum.
+    #[default]
+    MinMax,
+    /// Build a histogram of values over the observed range, and keep the central `percentile`
+    /// of them, discarding outliers on both sides.
+    Histogram { bins: usize, percentile: f32 },
+}
+
+/// Calibrated value ranges for the f32 outlets of a model.
+#[derive(Debug, Clone, Default)]
+pub struct Calibration {
+    pub ranges: HashMap<OutletId, (f32, f32)>,
+}
+
+impl Calibration {
+    /// Run the model on each of the `samples` (one set of inputs each) and collect the ranges of
+    /// its f32 outlets.
+    pub fn from_model(
+        model: &TypedModel,
+        samples: &[TVec<TValue>],
+        method: CalibrationMethod,
+    ) -> TractResult<Calibration> {
+        ensure!(samples.len() > 0, "Calibration requires at least one input sample");
+        let mut ranges: HashMap<OutletId, (f32, f32)> = HashMap::default();
+        observe(model, samples, |outlet, values| {
+            let range = ranges.entry(outlet).or_insert((f32::INFINITY, f32::NEG_INFINITY));
+            for &v in values.iter().filter(|v| v.is_finite()) {
+                range.0 = range.0.min(v);
+                range.1 = range.1.max(v);
+            }
+        })?;
+        ranges.retain(|_, (min, max)| min <= max);
+        if let CalibrationMethod::Histogram { bins, percentile } = method {
+            ensure!(bins > 0, "Histogram calibration requires at least one bin");
+            ensure!(
+                0.0 < percentile && percentile <= 1.0,
+                "Histogram percentile must be in (0, 1], got {}",
+                percentile
+            );
+            let mut histograms: HashMap<OutletId, Vec<usize>> = HashMap::default();
+            observe(model, samples, |outlet, values| {
+                let (min, max) =
+                    if let Some(range) = ranges.get(&outlet) { *range } else { return };
+                let histogram = histograms.entry(outlet).or_insert_with(|| vec![0; bins]);
+                let width = (max - min) / bins as f32;
+                for &v in values.iter().filter(|v| v.is_finite()) {
+                    let bin = if width > 0.0 { ((v - min) / width) as usize } else { 0 };
+                    histogram[bin.min(bins - 1)] += 1;
+                }
+            })?;
+            for (outlet, histogram) in histograms {
+                let range = ranges.get_mut(&outlet).unwrap();
+                *range = trim_histogram(&histogram, *range, percentile);
+            }
+        }
+        Ok(Calibration { ranges })
+    }
+
+    /// Asymmetric QI8 type covering the calibrated range of an outlet, zero included.
+    pub fn qi8_for(&self, outlet: OutletId) -> Option<DatumType> {
+        let (min, max) = *self.ranges.get(&outlet)?;
+        Some(qi8_for_range(min, max))
+    }
+}
+
+/// Run the model on the samples, calling `observer` with every f32 value computed.
+fn observe(
+    model: &TypedModel,
+    samples: &[TVec<TValue>],
+    mut observer: impl FnMut(OutletId, &[f32]),
+) -> TractResult<()> {
+    let plan = SimplePlan::new(model)?;
+    let mut state = SimpleState::new(&plan)?;
+    for sample in samples {
+        state.run_plan_with_eval(sample.clone(), |session, op_state, node, inputs| {
+            let outputs = eval(session, op_state, node, inputs)?;
+            for (slot, output) in outputs.iter().enumerate() {
+                if output.datum_type() == f32::datum_type() {
+                    observer(OutletId::new(node.id, slot), output.as_slice::<f32>()?);
+                }
+            }
+            TractResult::Ok(outputs)
+        })?;
+    }
+    Ok(())
+}
+
+fn trim_histogram(histogram: &[usize], (min, max): (f32, f32), percentile: f32) -> (f32, f32) {
+    let total: usize = histogram.iter().sum();
+    let discard = ((1.0 - percentile) * 0.5 * total as f32) as usize;
+    let width = (max - min) / histogram.len() as f32;
+    let mut low = 0;
+    let mut seen = 0;
+    while low < histogram.len() - 1 && seen + histogram[low] <= discard {
+        seen += histogram[low];
+        low += 1;
+    }
+    let mut high = histogram.len() - 1;
+    seen = 0;
+    while high > low && seen + histogram[high] <= discard {
+        seen += histogram[high];
+        high -= 1;
+    }
+    (min + low as f32 * width, min + (high + 1) as f32 * width)
+}
+
+fn qi8_for_range(min: f32, max: f32) -> DatumType {
+    let (min, max) = (min.min(0.0), max.max(0.0));
+    let scale = if max > min { (max - min) / 255.0 } else { 1.0 };
+    let zero_point = (-128.0 - min / scale).round().clamp(-128.0, 127.0) as i32;
+    DatumType::QI8(QParams::ZpScale { zero_point, scale })
+}
+
+/// Symmetric per-tensor int8 quantization of a weight tensor.
+fn quantize_weights(weights: &Tensor) -> TractResult<Tensor> {
+    let weights = weights.cast_to::<f32>()?;
+    let weights = weights.as_slice::<f32>()?;
+    let max = weights.iter().fold(0f32, |acc, w| acc.max(w.abs()));
+    let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+    let values: Vec<i8> =
+        weights.iter().map(|w| (w / scale).round().clamp(-127.0, 127.0) as i8).collect();
+    let mut tensor = tensor1(&values);
+    unsafe { tensor.set_datum_type(DatumType::QI8(QParams::ZpScale { zero_point: 0, scale })) };
+    Ok(tensor)
+}
+
+/// Quantize the float convolutions and matrix multiplications of a model, using the ranges from
+/// a calibration of the same model.
+pub fn quantize(model: &TypedModel, calibration: &Calibration) -> TractResult<TypedModel> {
+    let mut model = model.clone();
+    let mut calibration = calibration.clone();
+    for id in model.eval_order()? {
+        let node = model.node(id);
+        let patch = if let Some(conv) = node.op_as::<ConvUnary>() {
+            quantize_conv(&model, node, conv, &calibration)?
+        } else if let Some(mm) = node.op_as::<MatMulUnary>() {
+            quantize_matmul(&model, node, mm, &calibration)?
+        } else {
+            None
+        };
+        if let Some(patch) = patch {
+            let dequantize = format!("{}.dequantize", node.name);
+            patch.apply(&mut model)?;
+            // successors now consume the dequantized output: it inherits the calibrated range
+            if let Some(range) = calibration.ranges.get(&id.into()).cloned() {
+                let replacement = model.node_by_name(&dequantize)?.id;
+                calibration.ranges.insert(replacement.into(), range);
+            }
+        }
+    }
+    fold_dequantize_quantize(&mut model)?;
+    model.into_compact()
+}
+
+/// Replace the float matrix multiplications with constant weights by their block-quantized
+/// weight-only form. Weights that can not be block-quantized (k not a multiple of the block
+/// length, or non-trivial broadcasting axes) are left untouched.
+pub fn quantize_weights_blockwise(
+    model: &TypedModel,
+    format: BlockQuant,
+) -> TractResult<TypedModel> {
+    let mut model = model.clone();
+    for id in model.eval_order()? {
+        let node = model.node(id);
+        let mm = if let Some(mm) = node.op_as::<MatMulUnary>() { mm } else { continue };
+        let input_dt = model.outlet_fact(node.inputs[0])?.datum_type;
+        if !mm.a.datum_type().is_float()
+            || (input_dt != f32::datum_type() && input_dt != f16::datum_type())
+        {
+            continue;
+        }
+        let op = if let Ok(op) = BlockQuantMatMul::quantize(&mm.a, mm.axes, format) {
+            op
+        } else {
+            continue;
+        };
+        let patch = TypedModelPatch::replace_single_op(&model, node, &node.inputs, op)?;
+        patch.apply(&mut model)?;
+    }
+    model.into_compact()
+}
+
+/// Input and output quantized types for a float node, if it has been calibrated.
+fn node_qtypes(
+    model: &TypedModel,
+    node: &TypedNode,
+    calibration: &Calibration,
+) -> TractResult<Option<(DatumType, DatumType)>> {
+    if model.outlet_fact(node.inputs[0])?.datum_type != f32::datum_type()
+        || node.outputs[0].fact.datum_type != f32::datum_type()
+    {
+        return Ok(None);
+    }
+    match (calibration.qi8_for(node.inputs[0]), calibration.qi8_for(node.id.into())) {
+        (Some(input_dt), Some(output_dt)) => Ok(Some((input_dt, output_dt))),
+        _ => Ok(None),
+    }
+}
+
+/// Wire `op` between a quantization of the node input and a dequantization of its output.
+fn quantized_patch(
+    model: &TypedModel,
+    node: &TypedNode,
+    input_dt: DatumType,
+    op: impl Into<Box<dyn TypedOp>>,
+) -> TractResult<TypedModelPatch> {
+    let mut patch = TypedModelPatch::new(format!("Quantize {}", node));
+    let input = patch.tap_model(model, node.inputs[0])?;
+    let input = patch.wire_node(format!("{}.quantize", node.name), cast(input_dt), &[input])?;
+    let output = patch.wire_node(format!("{}.q", node.name), op, &input)?;
+    let output =
+        patch.wire_node(format!("{}.dequantize", node.name), cast(f32::datum_type()), &output)?;
+    patch.shunt_outside(model, node.id.into(), output[0])?;
+    Ok(patch)
+}
+
+fn quantize_conv(
+    model: &TypedModel,
+    node: &TypedNode,
+    conv: &ConvUnary,
+    calibration: &Calibration,
+) -> TractResult<Option<TypedModelPatch>> {
+    if conv.q_params.is_some() || conv.kernel.datum_type() != f32::datum_type() {
+        return Ok(None);
+    }
+    let (input_dt, output_dt) =
+        if let Some(dts) = node_qtypes(model, node, calibration)? { dts } else { return Ok(None) };
+    let kernel = quantize_weights(&conv.kernel)?.into_shape(conv.kernel.shape())?;
+    let bias = if let Some(bias) = &conv.bias {
+        let scale = input_dt.zp_scale().1 * kernel.datum_type().zp_scale().1;
+        let bias = bias.cast_to::<f32>()?;
+        let values: Vec<i32> =
+            bias.as_slice::<f32>()?.iter().map(|b| (b / scale).round() as i32).collect();
+        Some(tensor1(&values).into_shape(bias.shape())?.into_arc_tensor())
+    } else {
+        None
+    };
+    let op = ConvUnary {
+        kernel: kernel.into_arc_tensor(),
+        bias,
+        q_params: Some((output_dt, MatMulQParams::all_from_qtype())),
+        ..conv.clone()
+    };
+    Ok(Some(quantized_patch(model, node, input_dt, op)?))
+}
+
+fn quantize_matmul(
+    model: &TypedModel,
+    node: &TypedNode,
+    mm: &MatMulUnary,
+    calibration: &Calibration,
+) -> TractResult<Option<TypedModelPatch>> {
+    if mm.a.datum_type() != f32::datum_type() {
+        return Ok(None);
+    }
+    let (input_dt, output_dt) =
+        if let Some(dts) = node_qtypes(model, node, calibration)? { dts } else { return Ok(None) };
+    let a = quantize_weights(&mm.a)?.into_shape(mm.a.shape())?;
+    let op = QMatMulUnary {
+        a: a.into_arc_tensor(),
+        bias: None,
+        axes: mm.axes,
+        output_type: output_dt,
+        params: MatMulQParams::all_from_qtype(),
+    };
+    Ok(Some(quantized_patch(model, node, input_dt, op)?))
+}
+
+/// Replace quantize(dequantize(x)) by x or a requantization of x.
+fn fold_dequantize_quantize(model: &mut TypedModel) -> TractResult<()> {
+    for id in model.eval_order()? {
+        let node = model.node(id);
+        let quant = if let Some(quant) = node.op_as::<Cast>() { quant } else { continue };
+        if !quant.to.is_quantized() {
+            continue;
+        }
+        let prec = model.node(node.inputs[0].node);
+        if !prec.op_as::<Cast>().map(|c| c.to == f32::datum_type()).unwrap_or(false) {
+            continue;
+        }
+        let source = prec.inputs[0];
+        let source_dt = model.outlet_fact(source)?.datum_type;
+        if !source_dt.is_quantized() {
+            continue;
+        }
+        let mut patch = TypedModelPatch::new(format!("Fold dequantize/quantize in {}", node));
+        let mut wire = patch.tap_model(model, source)?;
+        if source_dt != quant.to {
+            wire = patch.wire_node(&node.name, cast(quant.to), &[wire])?[0];
+        }
+        patch.shunt_outside(model, id.into(), wire)?;
+        patch.apply(model)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+    use crate::ops::matmul::MatMulAxes;
+    use crate::ops::nn::DataFormat;
+
+    fn conv(model: &mut TypedModel, name: &str, input: OutletId, seed: usize) -> OutletId {
+        let kernel: Vec<f32> =
+            (0..2 * 2 * 3).map(|i| ((i * 7 + seed) % 11) as f32 / 10. - 0.5).collect();
+        let op = ConvUnary {
+            pool_spec: PoolSpec {
+                data_format: DataFormat::NHWC,
+                kernel_shape: tvec!(3),
+                padding: PaddingSpec::SameUpper,
+                dilations: None,
+                strides: None,
+                output_channel_override: Some(2),
+            },
+            kernel_fmt: KernelFormat::OIHW,
+            kernel: tensor1(&kernel).into_shape(&[2, 2, 3]).unwrap().into_arc_tensor(),
+            group: 1,
+            bias: Some(rctensor1(&[0.1f32, -0.2])),
+            q_params: None,
+        };
+        model.wire_node(name, op, &[input]).unwrap()[0]
+    }
+
+    fn samples() -> Vec<TVec<TValue>> {
+        (0..4)
+            .map(|s| {
+                let data: Vec<f32> =
+                    (0..16).map(|i| (((i * 5 + s * 3) % 17) as f32 - 8.) / 8.).collect();
+                tvec!(tensor1(&data).into_shape(&[1, 8, 2]).unwrap().into_tvalue())
+            })
+            .collect()
+    }
+
+    fn max_error(model: &TypedModel, quantized: &TypedModel) -> TractResult<f32> {
+        let mut error = 0f32;
+        for sample in samples() {
+            let expected = model.clone().into_runnable()?.run(sample.clone())?;
+            let found = quantized.clone().into_runnable()?.run(sample)?;
+            let expected = expected[0].as_slice::<f32>()?;
+            let found = found[0].as_slice::<f32>()?;
+            for (e, f) in expected.iter().zip(found.iter()) {
+                error = error.max((e - f).abs());
+            }
+        }
+        Ok(error)
+    }
+
+    #[test]
+    fn quantize_chained_convs() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", f32::fact([1, 8, 2]))?;
+        let c1 = conv(&mut model, "c1", source, 0);
+        let c2 = conv(&mut model, "c2", c1, 3);
+        model.set_output_outlets(&[c2])?;
+        let calibration = Calibration::from_model(&model, &samples(), CalibrationMethod::MinMax)?;
+        let quantized = quantize(&model, &calibration)?;
+        let qconvs = quantized
+            .nodes()
+            .iter()
+            .filter(|n| n.op_as::<ConvUnary>().map(|c| c.q_params.is_some()).unwrap_or(false))
+            .count();
+        assert_eq!(qconvs, 2);
+        // c1 output feeds c2 in int8 directly
+        let c2 = quantized.node_by_name("c2.q")?;
+        assert!(quantized.outlet_fact(c2.inputs[0])?.datum_type.is_quantized());
+        assert!(max_error(&model, &quantized)? < 0.1);
+        Ok(())
+    }
+
+    #[test]
+    fn quantize_matmul() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", f32::fact([1, 8, 2]))?;
+        let a: Vec<f32> = (0..24).map(|i| (i % 7) as f32 / 4. - 0.6).collect();
+        let mm = MatMulUnary {
+            a: tensor1(&a).into_shape(&[1, 3, 8])?.into_arc_tensor(),
+            axes: MatMulAxes::default_for_rank(3),
+        };
+        let mm = model.wire_node("mm", mm, &[source])?;
+        model.set_output_outlets(&mm)?;
+        let calibration = Calibration::from_model(&model, &samples(), CalibrationMethod::MinMax)?;
+        let quantized = quantize(&model, &calibration)?;
+        assert!(quantized.nodes().iter().any(|n| n.op_is::<QMatMulUnary>()));
+        assert!(max_error(&model, &quantized)? < 0.1);
+        Ok(())
+    }
+
+    #[test]
+    fn block_quantize_matmul() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", f32::fact([1, 32, 2]))?;
+        let a: Vec<f32> = (0..96).map(|i| (i % 7) as f32 / 4. - 0.6).collect();
+        let mm = MatMulUnary {
+            a: tensor1(&a).into_shape(&[1, 3, 32])?.into_arc_tensor(),
+            axes: MatMulAxes::default_for_rank(3),
+        };
+        let mm = model.wire_node("mm", mm, &[source])?;
+        model.set_output_outlets(&mm)?;
+        let quantized = quantize_weights_blockwise(&model, BlockQuant::Q8_0)?;
+        assert!(quantized.nodes().iter().any(|n| n.op_is::<BlockQuantMatMul>()));
+        let input: Vec<f32> = (0..64).map(|i| ((i * 5) % 17) as f32 / 8. - 1.).collect();
+        let input = tvec!(tensor1(&input).into_shape(&[1, 32, 2])?.into_tvalue());
+        let expected = model.into_runnable()?.run(input.clone())?;
+        let found = quantized.into_runnable()?.run(input)?;
+        for (e, f) in expected[0].as_slice::<f32>()?.iter().zip(found[0].as_slice::<f32>()?) {
+            assert!((e - f).abs() < 0.05, "{e} {f}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn histogram_discards_outliers() {
+        let mut histogram = vec![0; 100];
+        histogram[0] = 1;
+        histogram[40..60].iter_mut().for_each(|h| *h = 100);
+        histogram[99] = 1;
+        let (min, max) = trim_histogram(&histogram, (-100., 100.), 0.99);
+        assert_eq!((min, max), (-20., 20.));
+    }
+}
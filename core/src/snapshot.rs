@@ -0,0 +1,240 @@
+//! Snapshots of a `SimpleState`, for saving and restoring long lived sessions.
+//!
+//! A snapshot holds the tensors each stateful op exports through `OpState::save`,
+//! and the session tensors. It is bound to the signature of the model it was taken
+//! from and can only be restored in a state running the same model.
+//!
+//! Binary layout, little endian: magic `TRSS`, format version (u32), model signature
+//! (u64), then node states and session tensors as counted lists. Tensors are stored as
+//! datum type name, shape and raw data.
+use std::io::{Read, Write};
+
+use crate::internal::*;
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"TRSS";
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeStateSnapshot {
+    pub node: usize,
+    pub name: String,
+    pub tensors: TVec<(String, Tensor)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateSnapshot {
+    pub version: u32,
+    pub signature: u64,
+    pub nodes: Vec<NodeStateSnapshot>,
+    pub session_tensors: Vec<(String, Tensor)>,
+}
+
+impl StateSnapshot {
+    pub fn write(&self, w: &mut impl Write) -> TractResult<()> {
+        w.write_all(SNAPSHOT_MAGIC)?;
+        w.write_all(&self.version.to_le_bytes())?;
+        w.write_all(&self.signature.to_le_bytes())?;
+        write_u64(w, self.nodes.len())?;
+        for node in &self.nodes {
+            write_u64(w, node.node)?;
+            write_str(w, &node.name)?;
+            write_tensors(w, &node.tensors)?;
+        }
+        write_tensors(w, &self.session_tensors)
+    }
+
+    pub fn read(r: &mut impl Read) -> TractResult<StateSnapshot> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        ensure!(&magic == SNAPSHOT_MAGIC, "Not a tract state snapshot");
+        let mut version = [0u8; 4];
+        r.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        ensure!(
+            version == SNAPSHOT_FORMAT_VERSION,
+            "Unsupported snapshot format version {} (expected {})",
+            version,
+            SNAPSHOT_FORMAT_VERSION
+        );
+        let mut signature = [0u8; 8];
+        r.read_exact(&mut signature)?;
+        let signature = u64::from_le_bytes(signature);
+        let nodes = (0..read_u64(r)?)
+            .map(|_| {
+                Ok(NodeStateSnapshot {
+                    node: read_u64(r)?,
+                    name: read_str(r)?,
+                    tensors: read_tensors(r)?.into_iter().collect(),
+                })
+            })
+            .collect::<TractResult<_>>()?;
+        let session_tensors = read_tensors(r)?;
+        Ok(StateSnapshot { version, signature, nodes, session_tensors })
+    }
+}
+
+/// Find a tensor exported by `OpState::save`.
+pub fn saved_tensor<'a>(saved: &'a [(String, Tensor)], name: &str) -> TractResult<&'a Tensor> {
+    saved
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, t)| t)
+        .with_context(|| format!("No {} in saved state", name))
+}
+
+/// Find an optional tensor exported by `OpState::save`.
+pub fn saved_opt_tensor(saved: &[(String, Tensor)], name: &str) -> Option<Tensor> {
+    saved.iter().find(|(n, _)| n == name).map(|(_, t)| t.clone())
+}
+
+pub fn saved_usize(saved: &[(String, Tensor)], name: &str) -> TractResult<usize> {
+    Ok(*saved_tensor(saved, name)?.to_scalar::<i64>()? as usize)
+}
+
+pub fn saved_isize(saved: &[(String, Tensor)], name: &str) -> TractResult<isize> {
+    Ok(*saved_tensor(saved, name)?.to_scalar::<i64>()? as isize)
+}
+
+fn write_u64(w: &mut impl Write, v: usize) -> TractResult<()> {
+    w.write_all(&(v as u64).to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u64(r: &mut impl Read) -> TractResult<usize> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes) as usize)
+}
+
+fn write_str(w: &mut impl Write, s: &str) -> TractResult<()> {
+    write_u64(w, s.len())?;
+    w.write_all(s.as_bytes())?;
+    Ok(())
+}
+
+// reads through take() so a corrupted length can not trigger a huge allocation
+fn read_bytes(r: &mut impl Read, len: usize) -> TractResult<Vec<u8>> {
+    let mut bytes = vec![];
+    r.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+    ensure!(bytes.len() == len, "Truncated snapshot: expected {} bytes, got {}", len, bytes.len());
+    Ok(bytes)
+}
+
+fn read_str(r: &mut impl Read) -> TractResult<String> {
+    let len = read_u64(r)?;
+    Ok(String::from_utf8(read_bytes(r, len)?)?)
+}
+
+fn write_tensors(w: &mut impl Write, tensors: &[(String, Tensor)]) -> TractResult<()> {
+    write_u64(w, tensors.len())?;
+    for (name, tensor) in tensors {
+        let dt = tensor.datum_type();
+        ensure!(dt.is_copy() && !dt.is_quantized(), "Can not snapshot {:?} tensor {}", dt, name);
+        write_str(w, name)?;
+        write_str(w, &format!("{:?}", dt))?;
+        write_u64(w, tensor.rank())?;
+        for d in tensor.shape() {
+            write_u64(w, *d)?;
+        }
+        w.write_all(unsafe { tensor.as_bytes() })?;
+    }
+    Ok(())
+}
+
+fn read_tensors(r: &mut impl Read) -> TractResult<Vec<(String, Tensor)>> {
+    (0..read_u64(r)?)
+        .map(|_| {
+            let name = read_str(r)?;
+            let dt: DatumType = read_str(r)?.parse()?;
+            ensure!(dt.is_copy() && !dt.is_quantized(), "Unexpected {:?} tensor in snapshot", dt);
+            let shape = (0..read_u64(r)?).map(|_| read_u64(r)).collect::<TractResult<TVec<_>>>()?;
+            let len = shape
+                .iter()
+                .try_fold(dt.size_of(), |acc, d| acc.checked_mul(*d))
+                .with_context(|| format!("Invalid shape {:?} for tensor {}", shape, name))?;
+            let data = read_bytes(r, len)?;
+            let tensor = unsafe { Tensor::from_raw_dt(dt, &shape, &data)? };
+            Ok((name, tensor))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::scan::*;
+
+    // running sum over the input frames, carrying the accumulator from turn to turn
+    fn running_sum() -> TractResult<TypedModel> {
+        let mut body = TypedModel::default();
+        let acc = body.add_source("acc", f32::fact([1]))?;
+        let x = body.add_source("x", f32::fact([1]))?;
+        let sum = body.wire_node("sum", crate::ops::math::add(), &[acc, x])?;
+        body.set_output_outlets(&sum)?;
+        let scan = Scan::new(
+            body,
+            vec![
+                InputMapping::State { initializer: StateInitializer::Value(rctensor1(&[0f32])) },
+                InputMapping::Scan(ScanInfo { slot: 0, axis: 0, chunk: 1 }),
+            ],
+            vec![OutputMapping {
+                scan: Some(ScanInfo { slot: 0, axis: 0, chunk: 1 }),
+                full_dim_hint: None,
+                last_value_slot: None,
+                state: true,
+            }],
+            None,
+            0,
+        )?;
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2]))?;
+        let scan = model.wire_node("scan", scan, &[x])?;
+        model.set_output_outlets(&scan)?;
+        model.into_optimized()
+    }
+
+    #[test]
+    fn restore_scan_state() -> TractResult<()> {
+        let model = running_sum()?.into_runnable()?;
+        let mut state = SimpleState::new(&model)?;
+        state.run(tvec!(tensor1(&[1f32, 2.]).into()))?;
+        let mut buffer = vec![];
+        state.snapshot()?.write(&mut buffer)?;
+        let expected = state.run(tvec!(tensor1(&[3f32, 4.]).into()))?;
+        assert_eq!(*expected[0], tensor1(&[6f32, 10.]));
+
+        let snapshot = StateSnapshot::read(&mut &*buffer)?;
+        let mut restored = SimpleState::new(&model)?;
+        restored.restore(&snapshot)?;
+        let found = restored.run(tvec!(tensor1(&[3f32, 4.]).into()))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_other_model() -> TractResult<()> {
+        let model = running_sum()?.into_runnable()?;
+        let mut state = SimpleState::new(&model)?;
+        state.run(tvec!(tensor1(&[1f32, 2.]).into()))?;
+        let snapshot = state.snapshot()?;
+
+        let mut other = running_sum()?;
+        other.node_mut(0).name = "input".to_string();
+        let other = other.into_runnable()?;
+        assert!(SimpleState::new(&other)?.restore(&snapshot).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reject_corrupted_length() -> TractResult<()> {
+        let model = running_sum()?.into_runnable()?;
+        let mut state = SimpleState::new(&model)?;
+        state.run(tvec!(tensor1(&[1f32, 2.]).into()))?;
+        let mut buffer = vec![];
+        state.snapshot()?.write(&mut buffer)?;
+        // first node name length: after magic, version, signature, node count and node id
+        buffer[32..40].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(StateSnapshot::read(&mut &*buffer).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+//! Block-wise quantized storage, for weight-only quantization.
+//!
+//! Values are quantized in blocks of 32 consecutive items, each block
+//! carrying its own f16 scale. A quantized matrix is stored row by row, each
+//! row being a sequence of blocks along the reduction axis.
+use crate::datum::Blob;
+use crate::TractResult;
+use half::f16;
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlockQuant {
+    /// 4-bit values with an implicit -8 offset. Block is f16 scale + 16 bytes.
+    Q4_0,
+    /// 8-bit signed values. Block is f16 scale + 32 bytes.
+    Q8_0,
+}
+
+impl BlockQuant {
+    pub fn block_len(&self) -> usize {
+        32
+    }
+
+    pub fn block_bytes(&self) -> usize {
+        match self {
+            BlockQuant::Q4_0 => 2 + 16,
+            BlockQuant::Q8_0 => 2 + 32,
+        }
+    }
+
+    /// Bytes needed to store `len` quantized values, `len` being a multiple of the block length.
+    pub fn storage_bytes(&self, len: usize) -> usize {
+        len / self.block_len() * self.block_bytes()
+    }
+
+    pub fn quant_block_f32(&self, input: &[f32], quant: &mut [u8]) {
+        debug_assert_eq!(input.len(), self.block_len());
+        debug_assert_eq!(quant.len(), self.block_bytes());
+        match self {
+            BlockQuant::Q4_0 => {
+                let max = input.iter().fold(0f32, |m, &x| if x.abs() > m.abs() { x } else { m });
+                let d = max / -8.0;
+                let id = if d == 0.0 { 0.0 } else { 1.0 / d };
+                quant[0..2].copy_from_slice(&f16::from_f32(d).to_le_bytes());
+                for j in 0..16 {
+                    let q0 = ((input[j] * id + 8.5) as u8).min(15);
+                    let q1 = ((input[j + 16] * id + 8.5) as u8).min(15);
+                    quant[2 + j] = q0 | (q1 << 4);
+                }
+            }
+            BlockQuant::Q8_0 => {
+                let amax = input.iter().fold(0f32, |m, &x| m.max(x.abs()));
+                let d = amax / 127.0;
+                let id = if d == 0.0 { 0.0 } else { 1.0 / d };
+                quant[0..2].copy_from_slice(&f16::from_f32(d).to_le_bytes());
+                for j in 0..32 {
+                    quant[2 + j] = (input[j] * id).round() as i8 as u8;
+                }
+            }
+        }
+    }
+
+    pub fn dequant_block_f32(&self, quant: &[u8], block: &mut [f32]) {
+        debug_assert_eq!(quant.len(), self.block_bytes());
+        debug_assert_eq!(block.len(), self.block_len());
+        let d = f16::from_le_bytes([quant[0], quant[1]]).to_f32();
+        match self {
+            BlockQuant::Q4_0 => {
+                for j in 0..16 {
+                    block[j] = ((quant[2 + j] & 0x0F) as i8 - 8) as f32 * d;
+                    block[j + 16] = ((quant[2 + j] >> 4) as i8 - 8) as f32 * d;
+                }
+            }
+            BlockQuant::Q8_0 => {
+                for j in 0..32 {
+                    block[j] = quant[2 + j] as i8 as f32 * d;
+                }
+            }
+        }
+    }
+
+    pub fn quant_f32(&self, input: &[f32]) -> TractResult<Blob> {
+        anyhow::ensure!(
+            input.len() % self.block_len() == 0,
+            "{} quantization expects a multiple of {} values, got {}",
+            self,
+            self.block_len(),
+            input.len()
+        );
+        let mut quant = vec![0u8; self.storage_bytes(input.len())];
+        for (block, q) in input.chunks(self.block_len()).zip(quant.chunks_mut(self.block_bytes())) {
+            self.quant_block_f32(block, q);
+        }
+        Ok(Blob(quant))
+    }
+
+    pub fn dequant_f32(&self, input: &[u8]) -> TractResult<Vec<f32>> {
+        anyhow::ensure!(
+            input.len() % self.block_bytes() == 0,
+            "{} storage is made of blocks of {} bytes, got {} bytes",
+            self,
+            self.block_bytes(),
+            input.len()
+        );
+        let mut values = vec![0f32; input.len() / self.block_bytes() * self.block_len()];
+        for (q, block) in input.chunks(self.block_bytes()).zip(values.chunks_mut(self.block_len()))
+        {
+            self.dequant_block_f32(q, block);
+        }
+        Ok(values)
+    }
+}
+
+impl fmt::Display for BlockQuant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockQuant::Q4_0 => write!(f, "Q4_0"),
+            BlockQuant::Q8_0 => write!(f, "Q8_0"),
+        }
+    }
+}
+
+impl std::str::FromStr for BlockQuant {
+    type Err = crate::TractError;
+    fn from_str(s: &str) -> TractResult<BlockQuant> {
+        match s {
+            "Q4_0" | "q4_0" => Ok(BlockQuant::Q4_0),
+            "Q8_0" | "q8_0" => Ok(BlockQuant::Q8_0),
+            _ => anyhow::bail!("Unknown block quantization format {}", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp() -> Vec<f32> {
+        (0..64).map(|i| (i as f32 - 20.0) / 10.0).collect()
+    }
+
+    #[test]
+    fn q4_0_roundtrip() {
+        let input = ramp();
+        let quant = BlockQuant::Q4_0.quant_f32(&input).unwrap();
+        assert_eq!(quant.len(), 2 * 18);
+        let output = BlockQuant::Q4_0.dequant_f32(&quant).unwrap();
+        // half a step of 16 levels over the widest block range
+        for (a, b) in input.iter().zip(output.iter()) {
+            assert!((a - b).abs() < 0.3, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn q8_0_roundtrip() {
+        let input = ramp();
+        let quant = BlockQuant::Q8_0.quant_f32(&input).unwrap();
+        assert_eq!(quant.len(), 2 * 34);
+        let output = BlockQuant::Q8_0.dequant_f32(&quant).unwrap();
+        for (a, b) in input.iter().zip(output.iter()) {
+            assert!((a - b).abs() < 0.02, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn zero_block() {
+        let quant = BlockQuant::Q4_0.quant_f32(&[0f32; 32]).unwrap();
+        assert_eq!(BlockQuant::Q4_0.dequant_f32(&quant).unwrap(), vec![0f32; 32]);
+    }
+}
@@ -3,7 +3,7 @@ use crate::dim::TDim;
 use crate::tensor::litteral::*;
 use crate::tensor::Tensor;
 use crate::TVec;
-use half::f16;
+use half::{bf16, f16};
 use num_complex::Complex;
 use scan_fmt::scan_fmt;
 use std::hash::Hash;
@@ -127,6 +127,8 @@ pub enum DatumType {
     ComplexF16,
     ComplexF32,
     ComplexF64,
+    // appended last, to keep the discriminants of the other types stable
+    BF16,
 }
 
 impl DatumType {
@@ -148,7 +150,16 @@ impl DatumType {
                 .copied()
                 .collect()
         } else if self.is_float() {
-            [F16, F32, F64].iter().filter(|s| s.size_of() >= self.size_of()).copied().collect()
+            // F16 and BF16 have the same size, but neither can represent the other
+            let floats: TVec<DatumType> = [F16, BF16, F32, F64]
+                .iter()
+                .filter(|s| **s == *self || s.size_of() > self.size_of())
+                .copied()
+                .collect();
+            let complexes = [ComplexF16, ComplexF32, ComplexF64]
+                .into_iter()
+                .filter(|c| floats.contains(&c.complex_component().unwrap()));
+            floats.iter().copied().chain(complexes).collect()
         } else if self.is_signed() {
             [I8, I16, I32, I64, TDim]
                 .iter()
@@ -203,7 +214,7 @@ impl DatumType {
     }
 
     pub fn is_float(&self) -> bool {
-        matches!(self, DatumType::F16 | DatumType::F32 | DatumType::F64)
+        matches!(self, DatumType::F16 | DatumType::BF16 | DatumType::F32 | DatumType::F64)
     }
 
     pub fn is_complex(&self) -> bool {
@@ -218,6 +229,20 @@ impl DatumType {
         matches!(self, DatumType::ComplexI16 | DatumType::ComplexI32 | DatumType::ComplexI64)
     }
 
+    /// Datum type of the real and imaginary parts of a complex datum type.
+    pub fn complex_component(&self) -> Option<DatumType> {
+        use DatumType::*;
+        match self {
+            ComplexI16 => Some(I16),
+            ComplexI32 => Some(I32),
+            ComplexI64 => Some(I64),
+            ComplexF16 => Some(F16),
+            ComplexF32 => Some(F32),
+            ComplexF64 => Some(F64),
+            _ => None,
+        }
+    }
+
     pub fn is_copy(&self) -> bool {
         *self == DatumType::Bool
             || self.is_unsigned()
@@ -307,6 +332,7 @@ impl DatumType {
             DatumType::I32 => tensor0(i32::MIN),
             DatumType::I64 => tensor0(i64::MIN),
             DatumType::F16 => tensor0(f16::MIN),
+            DatumType::BF16 => tensor0(bf16::MIN),
             DatumType::F32 => tensor0(f32::MIN),
             DatumType::F64 => tensor0(f64::MIN),
             _ => panic!("No min value for datum type {:?}", self),
@@ -324,6 +350,7 @@ impl DatumType {
             DatumType::I64 => tensor0(i64::MAX),
             DatumType::QI32(_) => tensor0(i32::MAX),
             DatumType::F16 => tensor0(f16::MAX),
+            DatumType::BF16 => tensor0(bf16::MAX),
             DatumType::F32 => tensor0(f32::MAX),
             DatumType::F64 => tensor0(f64::MAX),
             _ => panic!("No max value for datum type {:?}", self),
@@ -352,6 +379,7 @@ impl std::str::FromStr for DatumType {
                 "U32" | "u32" => Ok(DatumType::U32),
                 "U64" | "u64" => Ok(DatumType::U64),
                 "F16" | "f16" => Ok(DatumType::F16),
+                "BF16" | "bf16" => Ok(DatumType::BF16),
                 "F32" | "f32" => Ok(DatumType::F32),
                 "F64" | "f64" => Ok(DatumType::F64),
                 "Bool" | "bool" => Ok(DatumType::Bool),
@@ -446,6 +474,7 @@ macro_rules! datum {
 
 datum!(bool, Bool);
 datum!(f16, F16);
+datum!(bf16, BF16);
 datum!(f32, F32);
 datum!(f64, F64);
 datum!(i8, I8);
@@ -499,6 +528,22 @@ mod tests {
         t_i64.cast_to::<bool>().unwrap();
     }
 
+    #[test]
+    fn test_cast_bf16() {
+        let t_f32: Tensor = tensor1(&[1f32, -2.5, 3.0e5]);
+        let t_bf16 = t_f32.cast_to::<bf16>().unwrap();
+        assert_eq!(t_bf16.as_slice::<bf16>().unwrap()[1], bf16::from_f32(-2.5));
+        let back = t_bf16.cast_to::<f32>().unwrap();
+        assert_eq!(back.as_slice::<f32>().unwrap(), &[1f32, -2.5, 299008.0]);
+    }
+
+    #[test]
+    fn test_bf16_super_types() {
+        assert!(DatumType::BF16.super_types().contains(&DatumType::F32));
+        assert!(!DatumType::BF16.super_types().contains(&DatumType::F16));
+        assert_eq!("bf16".parse::<DatumType>().unwrap(), DatumType::BF16);
+    }
+
     #[test]
     fn test_parse_qu8() {
         assert_eq!(
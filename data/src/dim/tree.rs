@@ -67,6 +67,22 @@ impl TDim {
         }
     }
 
+    /// Replace a symbol by an expression.
+    pub fn substitute(&self, from: &Symbol, to: &TDim) -> TDim {
+        match self {
+            Sym(sym) if sym == from => to.clone(),
+            Sym(_) | Val(_) => self.clone(),
+            Add(terms) => {
+                terms.iter().fold(Val(0), |acc, it| -> TDim { acc + it.substitute(from, to) })
+            }
+            Mul(terms) => {
+                terms.iter().fold(Val(1), |acc, it| -> TDim { acc * it.substitute(from, to) })
+            }
+            Div(a, q) => a.substitute(from, to) / *q as i64,
+            MulInt(p, a) => a.substitute(from, to) * *p,
+        }
+    }
+
     pub fn reduce(self) -> TDim {
         self.simplify()
             .wiggle()
@@ -739,6 +755,8 @@ mod tests {
         assert_eq!(e.eval(&SymbolValues::default().with(&x, 2)).to_i64().unwrap(), 2);
         let e = e + 3;
         assert_eq!(e.eval(&SymbolValues::default().with(&x, 2)).to_i64().unwrap(), 5);
+        let y = S.0.sym("y");
+        assert_eq!(e.substitute(&x, &(TDim::from(y.clone()) * 2)), TDim::from(y) * 2 + 3);
     }
 
     #[test]
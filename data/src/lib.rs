@@ -21,7 +21,8 @@ pub mod prelude {
     pub use crate::dim::{Symbol, SymbolTable, SymbolValues, TDim, ToDim};
     pub use crate::tensor::litteral::*;
     pub use crate::tensor::{
-        natural_strides, reinterpret_inner_dim_as_complex, IntoArcTensor, IntoTensor, Tensor,
+        natural_strides, reinterpret_complex_as_inner_dim, reinterpret_inner_dim_as_complex,
+        IntoArcTensor, IntoTensor, Tensor,
     };
     pub use crate::tvec;
     pub use crate::TVec;
@@ -30,12 +31,13 @@ pub mod prelude {
         dispatch_floatlike, dispatch_hash, dispatch_numbers, dispatch_signed,
     };
     pub use crate::{TractError, TractResult};
-    pub use half::f16;
+    pub use half::{bf16, f16};
     pub use itertools as tract_itertools;
     pub use num_complex::Complex;
 }
 
 pub mod internal {
+    pub use crate::block_quant::BlockQuant;
     pub use crate::datum::ClampCast;
     pub use crate::dim::{parse_tdim, DimLike};
     pub use crate::hash::{dyn_hash, DynHash};
@@ -54,6 +56,7 @@ pub use anyhow;
 pub use dim::UndeterminedSymbol;
 pub use half;
 
+pub mod block_quant;
 mod datum;
 mod dim;
 pub mod hash;
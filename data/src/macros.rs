@@ -33,6 +33,7 @@ macro_rules! dispatch_datum {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::Blob => $($path)::*::<Blob>($($args),*),
@@ -66,6 +67,7 @@ macro_rules! dispatch_datum_by_size {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<i16>($($args),*),
+            DatumType::BF16 => $($path)::*::<i16>($($args),*),
             DatumType::F32  => $($path)::*::<i32>($($args),*),
             DatumType::F64  => $($path)::*::<i64>($($args),*),
             DatumType::Blob => $($path)::*::<Blob>($($args),*),
@@ -99,6 +101,7 @@ macro_rules! dispatch_copy {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::QI8(_)  => $($path)::*::<i8>($($args),*),
@@ -130,6 +133,7 @@ macro_rules! dispatch_copy_by_size {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<i16>($($args),*),
+            DatumType::BF16 => $($path)::*::<i16>($($args),*),
             DatumType::F32  => $($path)::*::<i32>($($args),*),
             DatumType::F64  => $($path)::*::<i64>($($args),*),
             DatumType::QI8(_)  => $($path)::*::<i8>($($args),*),
@@ -159,6 +163,7 @@ macro_rules! dispatch_numbers {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::QI8(_)  => $($path)::*::<i8>($($args),*),
@@ -184,6 +189,7 @@ macro_rules! dispatch_zerolike {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::QI8(_)  => $($path)::*::<i8>($($args),*),
@@ -205,6 +211,7 @@ macro_rules! dispatch_floatlike {
         use $crate::prelude::DatumType;
         match $dt {
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             _ => $crate::anyhow::bail!("{:?} is not float-like", $dt)
@@ -218,6 +225,7 @@ macro_rules! dispatch_signed {
         use $crate::prelude::DatumType;
         match $dt {
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::I8   => $($path)::*::<i8>($($args),*),
@@ -2,7 +2,7 @@
 use crate::datum::{round_ties_to_even, scale_by, Blob, ClampCast, Datum, DatumType, QParams};
 use crate::dim::TDim;
 use crate::TVec;
-use half::f16;
+use half::{bf16, f16};
 use itertools::Itertools;
 use ndarray::prelude::*;
 use num_complex::Complex;
@@ -42,6 +42,8 @@ impl Approximation {
         match (self, dt) {
             (Close, DatumType::F16) => (1e-3, 1e-3),
             (Approximate, DatumType::F16) => (1e-3, 5e-3),
+            (Close, DatumType::BF16) => (1e-2, 1e-2),
+            (Approximate, DatumType::BF16) => (1e-2, 2e-2),
             (Exact, _) => (0.0, 0.0),
             (Close, _) => (1e-7, 1e-7),
             (Approximate, _) => (1e-4, 5e-4),
@@ -58,6 +60,7 @@ pub struct Tensor {
     len: usize,
     layout: alloc::Layout,
     data: *mut u8,
+    owned: bool,
 }
 
 unsafe impl Send for Tensor {}
@@ -81,6 +84,7 @@ impl Hash for Tensor {
                 U32 => self.as_slice_unchecked::<u32>().hash(state),
                 U64 => self.as_slice_unchecked::<u64>().hash(state),
                 F16 => self.as_slice_unchecked::<i16>().hash(state),
+                BF16 => self.as_slice_unchecked::<i16>().hash(state),
                 F32 => self.as_slice_unchecked::<i32>().hash(state),
                 F64 => self.as_slice_unchecked::<i64>().hash(state),
                 TDim => self.as_slice_unchecked::<crate::dim::TDim>().hash(state),
@@ -138,7 +142,7 @@ impl Drop for Tensor {
                     .for_each(|s| std::ptr::drop_in_place(s as *mut TDim));
             }
         }
-        if !self.data.is_null() && self.layout.size() > 0 {
+        if self.owned && !self.data.is_null() && self.layout.size() > 0 {
             unsafe { alloc::dealloc(self.data, self.layout) }
         }
     }
@@ -186,7 +190,8 @@ impl Tensor {
             assert!(!ptr.is_null());
             ptr
         } as *mut u8;
-        let mut tensor = Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data, len: 0 };
+        let mut tensor =
+            Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data, len: 0, owned: true };
         tensor.update_strides_and_len();
         #[cfg(debug_assertions)]
         if !data.is_null() {
@@ -371,6 +376,39 @@ impl Tensor {
         Ok(tensor)
     }
 
+    /// Wrap caller-owned memory in a tensor, without copying it.
+    ///
+    /// The tensor does not free the buffer on drop. The buffer must be suitably aligned for `dt`,
+    /// and stay valid, and unmodified, for the whole lifetime of the tensor. Mutable accesses
+    /// and `into_tensor` copy the data first, so the buffer itself is never written to.
+    pub unsafe fn from_raw_dt_borrowed(
+        dt: DatumType,
+        shape: &[usize],
+        data: *const u8,
+    ) -> anyhow::Result<Tensor> {
+        anyhow::ensure!(dt.is_copy(), "Can not borrow memory for {:?} tensors", dt);
+        let bytes = shape.iter().cloned().product::<usize>() * dt.size_of();
+        anyhow::ensure!(
+            bytes == 0 || (!data.is_null() && data as usize % dt.alignment() == 0),
+            "Borrowed data for {:?} tensor must be non-null and aligned to {} bytes",
+            dt,
+            dt.alignment()
+        );
+        let layout = alloc::Layout::from_size_align(bytes, dt.alignment())?;
+        let data = if bytes == 0 { std::ptr::null_mut() } else { data as *mut u8 };
+        let mut tensor = Tensor {
+            strides: tvec!(),
+            layout,
+            dt,
+            shape: shape.into(),
+            data,
+            len: 0,
+            owned: false,
+        };
+        tensor.update_strides_and_len();
+        Ok(tensor)
+    }
+
     pub unsafe fn from_slice_align<T: Datum>(
         content: &[T],
         align: usize,
@@ -421,6 +459,13 @@ impl Tensor {
         }
     }
 
+    /// Replace borrowed data by an owned copy before it gets written to.
+    fn ensure_owned(&mut self) {
+        if !self.owned {
+            *self = self.deep_clone();
+        }
+    }
+
     /// Force the tensor shape, no consistency check.
     pub unsafe fn set_shape_unchecked(&mut self, shape: &[usize]) {
         if shape != &*self.shape {
@@ -619,6 +664,7 @@ impl Tensor {
         axis: usize,
     ) {
         use ndarray::Slice;
+        self.ensure_owned();
         unsafe fn assign_slice_t<T: Datum>(
             to: &mut Tensor,
             to_range: Range<usize>,
@@ -708,6 +754,10 @@ impl Tensor {
         if self.shape() != other.shape() {
             anyhow::bail!("Shape mismatch {:?} != {:?}", self.shape(), other.shape())
         }
+        if self.datum_type().is_complex() && other.datum_type().is_complex() {
+            return reinterpret_complex_as_inner_dim(self)?
+                .close_enough(&*reinterpret_complex_as_inner_dim(other)?, approx);
+        }
         let (atol, rtol) = approx.atol_and_rtol(&self.datum_type());
         let ma = self.cast_to::<f32>()?;
         let ma = ma.to_array_view::<f32>()?;
@@ -777,6 +827,7 @@ impl Tensor {
 
     /// Transform the data as a mutable `ndarray::Array`.
     pub unsafe fn to_array_view_mut_unchecked<D: Datum>(&mut self) -> ArrayViewMutD<D> {
+        self.ensure_owned();
         if self.len() != 0 {
             ArrayViewMutD::from_shape_ptr(&*self.shape, self.data as *mut D)
         } else {
@@ -797,12 +848,15 @@ impl Tensor {
 
     /// Access the data as a pointer.
     pub unsafe fn as_ptr_mut_unchecked<D: Datum>(&mut self) -> *mut D {
+        self.ensure_owned();
         self.data as *mut D
     }
 
     /// Access the data as a mutable pointer.
     pub fn as_ptr_mut<D: Datum>(&mut self) -> anyhow::Result<*mut D> {
-        self.as_ptr::<D>().map(|p| p as *mut D)
+        self.check_for_access::<D>()?;
+        self.ensure_owned();
+        Ok(self.data as *mut D)
     }
 
     /// Access the data as a slice.
@@ -836,6 +890,7 @@ impl Tensor {
 
     /// Access the data as a mutable slice.
     pub unsafe fn as_slice_mut_unchecked<D: Datum>(&mut self) -> &mut [D] {
+        self.ensure_owned();
         if self.data.is_null() {
             &mut []
         } else {
@@ -868,6 +923,7 @@ impl Tensor {
 
     /// Mutable access the data as a scalar.
     pub unsafe fn to_scalar_mut_unchecked<D: Datum>(&mut self) -> &mut D {
+        self.ensure_owned();
         &mut *(self.data as *mut D)
     }
 
@@ -880,6 +936,7 @@ impl Tensor {
     }
 
     pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.ensure_owned();
         if self.data.is_null() {
             &mut []
         } else {
@@ -929,6 +986,48 @@ impl Tensor {
             .for_each(|(s, d)| *d = s.as_());
     }
 
+    unsafe fn cast_complex(&self, dst_dt: DatumType) -> anyhow::Result<Tensor> {
+        let component = if let Some(component) = dst_dt.complex_component() {
+            component
+        } else {
+            anyhow::bail!(
+                "Can not cast {:?} to {:?}: extract real or imaginary part explicitly",
+                self.dt,
+                dst_dt
+            )
+        };
+        // complex integers have no arithmetic support, only complex floats can be cast to or from
+        if !dst_dt.is_complex_float() || (self.dt.is_complex() && !self.dt.is_complex_float()) {
+            anyhow::bail!(
+                "Can not cast {:?} to {:?}: only complex floats are supported",
+                self.dt,
+                dst_dt
+            )
+        }
+        if self.dt.is_complex() {
+            let parts = reinterpret_complex_as_inner_dim(self)?;
+            let parts = parts.cast_to_dt(component)?;
+            return Ok(reinterpret_inner_dim_as_complex(&parts)?.into_owned());
+        }
+        let re = self.cast_to_dt(component)?;
+        let mut result = Self::uninitialized_dt(dst_dt, &self.shape)?;
+        macro_rules! n {
+            ($c:ty) => {
+                if <$c>::datum_type() == component {
+                    re.as_slice_unchecked::<$c>()
+                        .iter()
+                        .zip(result.as_slice_mut_unchecked::<Complex<$c>>())
+                        .for_each(|(s, d)| *d = Complex::new(*s, <$c>::default()));
+                    return Ok(result);
+                }
+            };
+        }
+        n!(f16);
+        n!(f32);
+        n!(f64);
+        anyhow::bail!("Can not cast {:?} to {:?}", self.dt, dst_dt)
+    }
+
     unsafe fn cast_number_to_bool<Source: Datum + num_traits::Zero>(&self, other: &mut Tensor) {
         self.as_slice_unchecked::<Source>()
             .iter()
@@ -993,6 +1092,12 @@ impl Tensor {
                 }
                 return Ok(Cow::Owned(ints.cast_to_dt(dst_dt)?.into_owned()));
             }
+            if (self.dt.is_complex() || dst_dt.is_complex())
+                && self.dt != DatumType::String
+                && dst_dt != DatumType::String
+            {
+                return Ok(Cow::Owned(self.cast_complex(dst_dt)?));
+            }
             let mut result = Self::uninitialized_dt(dst_dt, &self.shape)?;
             if self.dt == DatumType::String {
                 dispatch_numbers!(Self::cast_from_string(dst_dt)(self, &mut result))?;
@@ -1015,6 +1120,7 @@ impl Tensor {
                             DatumType::U32 => self.natural_cast::<$source, u32>(&mut result),
                             DatumType::U64 => self.natural_cast::<$source, u64>(&mut result),
                             DatumType::F16 => self.natural_cast::<$source, f16>(&mut result),
+                            DatumType::BF16 => self.natural_cast::<$source, bf16>(&mut result),
                             DatumType::F32 => self.natural_cast::<$source, f32>(&mut result),
                             DatumType::F64 => self.natural_cast::<$source, f64>(&mut result),
                             DatumType::TDim => {
@@ -1043,6 +1149,7 @@ impl Tensor {
                 n!(i32);
                 n!(i64);
                 n!(f16);
+                n!(bf16);
                 n!(f32);
                 n!(f64);
             } else {
@@ -1201,8 +1308,15 @@ impl Tensor {
             let shape = it.shape().into();
             let vec = it.into_raw_vec().into_boxed_slice();
             let data = Box::into_raw(vec) as *mut u8;
-            let mut t =
-                Tensor { dt: T::datum_type(), shape, layout, data, strides: tvec!(), len: 0 };
+            let mut t = Tensor {
+                dt: T::datum_type(),
+                shape,
+                layout,
+                data,
+                strides: tvec!(),
+                len: 0,
+                owned: true,
+            };
             t.update_strides_and_len();
             return t;
         }
@@ -1244,6 +1358,7 @@ impl Tensor {
                 data: data.as_ptr() as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
+                owned: true,
                 ..*self
             };
             std::mem::forget(data);
@@ -1254,6 +1369,7 @@ impl Tensor {
                 data: data.as_ptr() as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
+                owned: true,
                 ..*self
             };
             std::mem::forget(data);
@@ -1303,14 +1419,17 @@ impl Tensor {
     }
 
     pub fn view_mut(&mut self) -> view::TensorView {
+        self.ensure_owned();
         unsafe { view::TensorView::at_prefix_unchecked(self, &[]) }
     }
 
     pub fn view_at_prefix_mut(&mut self, prefix: &[usize]) -> anyhow::Result<view::TensorView> {
+        self.ensure_owned();
         view::TensorView::at_prefix(self, prefix)
     }
 
     pub fn view_offsetting_mut(&mut self, coords: &[usize]) -> anyhow::Result<view::TensorView> {
+        self.ensure_owned();
         view::TensorView::offsetting(self, coords)
     }
 
@@ -1378,6 +1497,36 @@ pub fn reinterpret_inner_dim_as_complex(t: &Tensor) -> anyhow::Result<Cow<Tensor
     }
 }
 
+pub fn reinterpret_complex_as_inner_dim(t: &Tensor) -> anyhow::Result<Cow<Tensor>> {
+    let mut new_shape = t.shape().to_vec();
+    new_shape.push(2);
+    macro_rules! n {
+        ($source:ty, $dest:ty) => {
+            unsafe {
+                let mut dst_tensor = Tensor::uninitialized::<$dest>(&new_shape)?;
+                t.as_slice_unchecked::<$source>()
+                    .iter()
+                    .zip(dst_tensor.as_slice_mut_unchecked::<$dest>().chunks_mut(2))
+                    .for_each(|(s, d)| {
+                        d[0] = s.re;
+                        d[1] = s.im;
+                    });
+                Ok(Cow::Owned(dst_tensor))
+            }
+        };
+    }
+
+    match t.datum_type() {
+        DatumType::ComplexI16 => n!(Complex<i16>, i16),
+        DatumType::ComplexI32 => n!(Complex<i32>, i32),
+        DatumType::ComplexI64 => n!(Complex<i64>, i64),
+        DatumType::ComplexF16 => n!(Complex<f16>, f16),
+        DatumType::ComplexF32 => n!(Complex<f32>, f32),
+        DatumType::ComplexF64 => n!(Complex<f64>, f64),
+        _ => anyhow::bail!("{:?} is not a complex type", t.datum_type()),
+    }
+}
+
 pub fn natural_strides(shape: &[usize]) -> TVec<isize> {
     let mut strides = tvec!();
     compute_natural_stride_to(&mut strides, shape);
@@ -1474,7 +1623,8 @@ impl<D: ::ndarray::Dimension, T: Datum> IntoArcTensor for Array<T, D> {
 }
 
 impl IntoTensor for Tensor {
-    fn into_tensor(self) -> Tensor {
+    fn into_tensor(mut self) -> Tensor {
+        self.ensure_owned();
         self
     }
 }
@@ -1599,4 +1749,71 @@ mod tests {
         assert_eq!(&expected, cplx_input.as_ref());
         Ok(())
     }
+
+    #[test]
+    fn borrowed_data_is_not_copied_nor_freed() -> anyhow::Result<()> {
+        let data = vec![1f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let t = unsafe {
+            Tensor::from_raw_dt_borrowed(DatumType::F32, &[2, 3], data.as_ptr() as *const u8)?
+        };
+        assert_eq!(unsafe { t.as_ptr_unchecked::<f32>() }, data.as_ptr());
+        assert_eq!(t, crate::internal::tensor2(&[[1f32, 2.0, 3.0], [4.0, 5.0, 6.0]]));
+        let cloned = t.clone();
+        assert_ne!(unsafe { cloned.as_ptr_unchecked::<f32>() }, data.as_ptr());
+        std::mem::drop(t);
+        assert_eq!(data[5], 6.0);
+        Ok(())
+    }
+
+    #[test]
+    fn borrowed_data_is_copied_on_write() -> anyhow::Result<()> {
+        let data = vec![1f32, 2.0, 3.0];
+        let mut t = unsafe {
+            Tensor::from_raw_dt_borrowed(DatumType::F32, &[3], data.as_ptr() as *const u8)?
+        };
+        t.as_slice_mut::<f32>()?[0] = 10.0;
+        assert_ne!(unsafe { t.as_ptr_unchecked::<f32>() }, data.as_ptr());
+        assert_eq!(t, crate::internal::tensor1(&[10f32, 2.0, 3.0]));
+        let t = unsafe {
+            Tensor::from_raw_dt_borrowed(DatumType::F32, &[3], data.as_ptr() as *const u8)?
+        }
+        .into_tensor();
+        assert_ne!(unsafe { t.as_ptr_unchecked::<f32>() }, data.as_ptr());
+        assert_eq!(data, [1f32, 2.0, 3.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn borrowed_data_must_be_aligned() {
+        let data = vec![0f32; 4];
+        let misaligned = unsafe { (data.as_ptr() as *const u8).add(1) };
+        assert!(unsafe { Tensor::from_raw_dt_borrowed(DatumType::F32, &[2], misaligned) }.is_err());
+    }
+
+    #[test]
+    fn cast_real_to_complex_and_back() -> anyhow::Result<()> {
+        let real = crate::internal::tensor1(&[1i32, -2]);
+        let cplx = real.cast_to_dt(DatumType::ComplexF32)?;
+        assert_eq!(
+            cplx.as_ref(),
+            &crate::internal::tensor1(&[Complex::new(1f32, 0.0), Complex::new(-2.0, 0.0)])
+        );
+        let wide = cplx.cast_to_dt(DatumType::ComplexF64)?;
+        assert_eq!(
+            wide.as_slice::<Complex<f64>>()?,
+            &[Complex::new(1.0, 0.0), Complex::new(-2.0, 0.0)]
+        );
+        assert!(cplx.cast_to_dt(DatumType::F32).is_err());
+        assert!(real.cast_to_dt(DatumType::ComplexI32).is_err());
+        assert!(cplx.cast_to_dt(DatumType::ComplexI64).is_err());
+        assert_eq!(
+            DatumType::F32.common_super_type(DatumType::ComplexF32),
+            Some(DatumType::ComplexF32)
+        );
+        assert_eq!(
+            DatumType::F64.common_super_type(DatumType::ComplexF32),
+            Some(DatumType::ComplexF64)
+        );
+        Ok(())
+    }
 }
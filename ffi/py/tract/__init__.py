@@ -40,14 +40,25 @@ def dt_numpy_to_tract(dt):
     if dt.kind == 'b':
         return TRACT_DATUM_TYPE_BOOL
     if dt.kind == 'u':
-        return 0x1 + dt.item_size
+        return 0x10 + dt.itemsize
     if dt.kind == 'i':
-        return 0x2 + dt.item_size
+        return 0x20 + dt.itemsize
     if dt.kind == 'f':
-        return 0x3 + dt.item_size
+        return 0x30 + dt.itemsize
     if dt.kind == 'c':
-        return 0x5 + dt.item_size / 2
-    raise TractError("Unsupported Numpy dtype: " + dt)
+        return 0x50 + dt.itemsize // 2
+    raise TractError("Unsupported Numpy dtype: " + str(dt))
+
+def dt_tract_to_numpy(dt):
+    if dt == TRACT_DATUM_TYPE_BOOL:
+        return numpy.dtype(numpy.bool_)
+    kind = { 0x10: 'u', 0x20: 'i', 0x30: 'f', 0x50: 'c' }.get(dt & 0xf0)
+    if kind is None:
+        raise TractError("Unsupported tract datum type for Numpy: " + hex(dt))
+    size = dt & 0x0f
+    if kind == 'c':
+        size = size * 2
+    return numpy.dtype(kind + str(size))
 
 def version():
     return str(lib.tract_version(), "utf-8")
@@ -158,7 +169,12 @@ class Value:
         if self.ptr:
             check(lib.tract_value_destroy(byref(self.ptr)))
 
-    def from_numpy(array):
+    def from_numpy(array, copy=True):
+        """Build a Value from a numpy array.
+
+        With copy=False, the array memory is borrowed by tract instead of being copied. A
+        reference to the array is kept by the Value, and the array must not be modified
+        while the value is in use. Non-contiguous arrays are still copied once."""
         array = numpy.ascontiguousarray(array)
 
         data = array.__array_interface__['data'][0]
@@ -169,21 +185,38 @@ class Value:
         for ix in range(0, array.ndim):
             shape[ix] = array.shape[ix]
         dt = dt_numpy_to_tract(array.dtype)
-        check(lib.tract_value_create(dt, c_size_t(array.ndim), shape, data, byref(ptr)))
-        return Value(ptr)
-
-    def to_numpy(self):
+        if copy:
+            check(lib.tract_value_create(dt, c_size_t(array.ndim), shape, data, byref(ptr)))
+            return Value(ptr)
+        check(lib.tract_value_from_borrowed_data(dt, c_size_t(array.ndim), shape, data, byref(ptr)))
+        value = Value(ptr)
+        value.borrowed = array
+        return value
+
+    @property
+    def __array_interface__(self):
+        """Exposes the value memory to numpy without copy. Arrays built from it (with
+        numpy.asarray) keep the Value, and hence the memory, alive."""
         if not self.ptr:
             raise TractError("invalid value (already consumed)")
-        rank = c_size_t();
+        dt = c_int()
+        rank = c_size_t()
         shape = POINTER(c_size_t)()
-        dt = c_float
-        data = POINTER(dt)()
-        check(lib.tract_value_inspect(self.ptr, None, byref(rank), byref(shape), byref(data)))
-        rank = rank.value
-        shape = [ int(shape[ix]) for ix in range(0, rank) ]
-        array = numpy.ctypeslib.as_array(data, shape).copy()
-        return array
+        data = c_void_p()
+        check(lib.tract_value_inspect(self.ptr, byref(dt), byref(rank), byref(shape), byref(data)))
+        return {
+            'version': 3,
+            'shape': tuple(int(shape[ix]) for ix in range(0, rank.value)),
+            'typestr': dt_tract_to_numpy(dt.value).str,
+            'data': (data.value or 0, True),
+        }
+
+    def view_numpy(self):
+        """A read-only numpy view on the value memory, valid as long as the view lives."""
+        return numpy.asarray(self)
+
+    def to_numpy(self):
+        return numpy.array(self, copy=True)
 
     def into_numpy(self):
         result = self.to_numpy()
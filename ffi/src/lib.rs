@@ -170,6 +170,21 @@ pub extern "C" fn tract_nnef_destroy(nnef: *mut *mut TractNnef) -> TRACT_RESULT
     })
 }
 
+/// Load operators from a plugin library (see tract_plugin.h).
+#[no_mangle]
+pub extern "C" fn tract_nnef_with_plugin(
+    nnef: &mut TractNnef,
+    path: *const c_char,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        let path = CStr::from_ptr(path).to_str()?;
+        let plugin = tract_nnef::tract_plugin::Plugin::load(path)?;
+        plugin.register()?;
+        nnef.0 = std::mem::take(&mut nnef.0).with_loaded_plugin(&plugin)?;
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn tract_nnef_model_for_path(
     nnef: &TractNnef,
@@ -210,6 +225,21 @@ pub extern "C" fn tract_onnx_destroy(ptr: *mut *mut TractOnnx) -> TRACT_RESULT {
     })
 }
 
+/// Load operators from a plugin library (see tract_plugin.h).
+#[no_mangle]
+pub extern "C" fn tract_onnx_with_plugin(
+    onnx: &mut TractOnnx,
+    path: *const c_char,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        let path = CStr::from_ptr(path).to_str()?;
+        let plugin = tract_nnef::tract_plugin::Plugin::load(path)?;
+        plugin.register()?;
+        onnx.0 = std::mem::take(&mut onnx.0).with_loaded_plugin(&plugin)?;
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn tract_onnx_model_for_path(
     onnx: &TractOnnx,
@@ -403,8 +433,20 @@ pub extern "C" fn tract_runnable_release(runnable: *mut *mut TractRunnable) -> T
 // VALUE
 pub struct TractValue(TValue);
 
+/// Shape from a C pointer: null is only valid for scalars (rank 0).
+unsafe fn shape_from_raw<'a>(rank: usize, shape: *const usize) -> anyhow::Result<&'a [usize]> {
+    if shape.is_null() {
+        if rank != 0 {
+            anyhow::bail!("Null pointer for shape of rank {}", rank);
+        }
+        Ok(&[])
+    } else {
+        Ok(std::slice::from_raw_parts(shape, rank))
+    }
+}
+
 /// This call copies the data into tract space. All the pointers only need to be alive for the
-/// duration of the call.
+/// duration of the call. `shape` can be null for a scalar (rank 0).
 #[no_mangle]
 pub extern "C" fn tract_value_create(
     datum_type: TractDatumType,
@@ -414,16 +456,51 @@ pub extern "C" fn tract_value_create(
     value: *mut *mut TractValue,
 ) -> TRACT_RESULT {
     wrap(|| unsafe {
+        if value.is_null() {
+            anyhow::bail!("Null pointer for value output");
+        }
         let dt: DatumType = datum_type.into();
-        let shape = std::slice::from_raw_parts(shape, rank);
+        let shape = shape_from_raw(rank, shape)?;
         let len = shape.iter().product::<usize>();
-        let content = std::slice::from_raw_parts(data as *const u8, len * dt.size_of());
+        let content = if len == 0 {
+            &[]
+        } else if data.is_null() {
+            anyhow::bail!("Null pointer for data");
+        } else {
+            std::slice::from_raw_parts(data as *const u8, len * dt.size_of())
+        };
         let it = Tensor::from_raw_dt(dt, shape, content)?;
         *value = Box::into_raw(Box::new(TractValue(it.into_tvalue())));
         Ok(())
     })
 }
 
+/// Wrap caller-owned data in a Value without copying it.
+///
+/// `data` must be aligned for `datum_type`. `shape` only needs to be alive for the duration of
+/// the call, and can be null for a scalar (rank 0).
+///
+/// The buffer behind `data` is borrowed, not copied: it must outlive the value. It must stay
+/// valid and unmodified until the value and every state it has been fed to are destroyed.
+#[no_mangle]
+pub extern "C" fn tract_value_from_borrowed_data(
+    datum_type: TractDatumType,
+    rank: usize,
+    shape: *const usize,
+    data: *const c_void,
+    value: *mut *mut TractValue,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        if value.is_null() {
+            anyhow::bail!("Null pointer for value output");
+        }
+        let shape = shape_from_raw(rank, shape)?;
+        let it = Tensor::from_raw_dt_borrowed(datum_type.into(), shape, data as *const u8)?;
+        *value = Box::into_raw(Box::new(TractValue(it.into_tvalue())));
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn tract_value_destroy(value: *mut *mut TractValue) -> TRACT_RESULT {
     wrap(|| unsafe {
@@ -438,6 +515,9 @@ pub extern "C" fn tract_value_destroy(value: *mut *mut TractValue) -> TRACT_RESU
 
 /// Inspect part of a value. Except `value`, all argument pointers can be null if only some specific bits
 /// are required.
+///
+/// `shape` and `data` point to memory owned by the value: they are borrowed, not copied, and stay
+/// valid until the value is destroyed. Data is always contiguous, in row-major order.
 #[no_mangle]
 pub extern "C" fn tract_value_inspect(
     value: *mut TractValue,
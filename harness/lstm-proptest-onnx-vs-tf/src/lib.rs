@@ -2,8 +2,12 @@
 
 use proptest::prelude::*;
 
+pub mod reference;
+
 use tract_hir::internal::*;
 use tract_ndarray::prelude::*;
+use tract_onnx::ops::rec::common::CommonRec;
+use tract_onnx::ops::rec::lstm::LSTM;
 use tract_onnx::prelude::*;
 use tract_onnx::tract_hir;
 
@@ -17,6 +21,8 @@ pub struct LstmProblem {
     pub b_icfo: Array1<f32>,
     pub h0: Array2<f32>,
     pub c0: Array2<f32>,
+    /// wci, wcf and wco rows, [3, cell_size]
+    pub peepholes: Option<Array2<f32>>,
 }
 
 impl LstmProblem {
@@ -44,19 +50,31 @@ impl LstmProblem {
         let b_iofc = b_iofc.into_shape((1, 8 * s))?;
 
         let x = model.add_source("x", self.x.datum_type().fact(self.x.shape()).into())?;
-        let op = tract_onnx::ops::rec::lstm::LSTM {
+        let op = CommonRec {
             optional_y_output: Some(0),
             optional_bias_input: Some(3),
             optional_initial_h_input: Some(4),
             optional_initial_c_input: Some(5),
-            ..Default::default()
+            optional_p_input: self.peepholes.as_ref().map(|_| 6),
+            ..CommonRec::new(Box::new(LSTM::default()))
         };
         let w = model.add_const("w", w_iofc)?;
         let r = model.add_const("r", r_iofc)?;
         let b = model.add_const("b", b_iofc)?;
         let h0 = model.add_const("h0", self.h0.clone().insert_axis(Axis(0)))?;
         let c0 = model.add_const("c0", self.c0.clone().insert_axis(Axis(0)))?;
-        let lstm = model.wire_node("lstm", expand(op), &[x, w, r, b, h0, c0]).unwrap();
+        let mut inputs = vec![x, w, r, b, h0, c0];
+        if let Some(peepholes) = &self.peepholes {
+            // onnx wants them in i, o, f order
+            let mut p_iof = Array1::zeros(3 * s);
+            for (iof, ifo) in [0, 2, 1].iter().enumerate() {
+                p_iof
+                    .slice_axis_mut(Axis(0), (s * iof..s * (iof + 1)).into())
+                    .assign(&peepholes.row(*ifo));
+            }
+            inputs.push(model.add_const("p", p_iof.insert_axis(Axis(0)))?);
+        }
+        let lstm = model.wire_node("lstm", expand(op), &inputs).unwrap();
         model.set_output_outlets(&lstm).unwrap();
         model.analyse(false)?;
         model.into_typed()
@@ -95,9 +113,11 @@ impl LstmProblem {
         )?[0];
         let seq_length = model.add_const("seq_length", tensor0(self.length as i64))?;
         let w = model.add_const("w", self.w_xh_icfo.clone())?;
-        let wc1 = model.add_const("wc1", tensor1(&[0f32]))?;
-        let wc2 = model.add_const("wc2", tensor1(&[0f32]))?;
-        let wc3 = model.add_const("wc3", tensor1(&[0f32]))?;
+        let peepholes =
+            self.peepholes.clone().unwrap_or_else(|| Array2::zeros((3, self.cell_size)));
+        let wc1 = model.add_const("wc1", peepholes.row(0).to_owned())?;
+        let wc2 = model.add_const("wc2", peepholes.row(1).to_owned())?;
+        let wc3 = model.add_const("wc3", peepholes.row(2).to_owned())?;
         let b = model.add_const("b", self.b_icfo.clone())?;
 
         let lstm = model
@@ -105,9 +125,9 @@ impl LstmProblem {
                 "lstm",
                 expand(tract_tensorflow::ops::rec::block_lstm::BlockLSTM::new(
                     0.0,
-                    -1.0,
+                    None,
                     f32::datum_type(),
-                    false,
+                    self.peepholes.is_some(),
                 )),
                 &[seq_length, x, cs, h, w, wc1, wc2, wc3, b],
             )
@@ -180,11 +200,7 @@ impl LstmProblem {
         let model = self.onnx_model()?;
         let plan = SimplePlan::new(model)?;
         let mut state = SimpleState::new(plan)?;
-        let y = state
-            .run(tvec!(self.x.clone()))?
-            .remove(0)
-            .into_tensor()
-            .into_array::<f32>()?;
+        let y = state.run(tvec!(self.x.clone()))?.remove(0).into_tensor().into_array::<f32>()?;
         let y = y.into_shape((self.length, self.batch_size, self.cell_size)).unwrap();
         Ok(y.into_tvalue())
     }
@@ -219,9 +235,13 @@ fn strat() -> BoxedStrategy<LstmProblem> {
                 proptest::collection::vec((-3..3).prop_map(|a| a as f32), 4 * cell_size),
                 proptest::collection::vec((-3..3).prop_map(|a| a as f32), cell_size * batch_size),
                 proptest::collection::vec((-3..3).prop_map(|a| a as f32), cell_size * batch_size),
+                proptest::option::of(proptest::collection::vec(
+                    (-3..3).prop_map(|a| a as f32),
+                    3 * cell_size,
+                )),
             )
         })
-        .prop_map(|((length, batch_size, cell_size), x, w_xh_icfo, b_icfo, h0, c0)| {
+        .prop_map(|((length, batch_size, cell_size), x, w_xh_icfo, b_icfo, h0, c0, peepholes)| {
             let x =
                 Array3::from_shape_vec((length, batch_size, cell_size), x).unwrap().into_tvalue();
             let w_xh_icfo =
@@ -229,7 +249,8 @@ fn strat() -> BoxedStrategy<LstmProblem> {
             let b_icfo = Array1::from_shape_vec(cell_size * 4, b_icfo).unwrap();
             let h0 = Array2::from_shape_vec((batch_size, cell_size), h0).unwrap();
             let c0 = Array2::from_shape_vec((batch_size, cell_size), c0).unwrap();
-            LstmProblem { length, batch_size, cell_size, x, w_xh_icfo, b_icfo, h0, c0 }
+            let peepholes = peepholes.map(|p| Array2::from_shape_vec((3, cell_size), p).unwrap());
+            LstmProblem { length, batch_size, cell_size, x, w_xh_icfo, b_icfo, h0, c0, peepholes }
         })
         .boxed()
 }
@@ -254,6 +275,7 @@ fn test_x() {
         b_icfo: arr1(&[0.0f32, 0.0, 0.0, 0.0]),
         h0: arr2(&[[0.0f32]]),
         c0: arr2(&[[0.0f32]]),
+        peepholes: None,
     };
     let o = pb.onnx_run().unwrap();
     let t = pb.tf_run().unwrap();
@@ -271,6 +293,7 @@ fn test_seq() {
         b_icfo: arr1(&[0.0f32, 0.0, 0.0, 0.0]),
         h0: arr2(&[[0.0f32]]),
         c0: arr2(&[[0.0f32]]),
+        peepholes: None,
     };
     let o = pb.onnx_run().unwrap();
     let t = pb.tf_run().unwrap();
@@ -288,6 +311,7 @@ fn test_c0() {
         b_icfo: arr1(&[0.0f32, 0.0, 0.0, 0.0]),
         h0: arr2(&[[0.0f32]]),
         c0: arr2(&[[1.0f32]]),
+        peepholes: None,
     };
     let o = pb.onnx_run().unwrap();
     let t = pb.tf_run().unwrap();
@@ -305,6 +329,7 @@ fn test_b() {
         b_icfo: arr1(&[0.0f32, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]),
         h0: arr2(&[[0.0f32, 0.0]]),
         c0: arr2(&[[0.0f32, 0.0]]),
+        peepholes: None,
     };
     let o = pb.onnx_run().unwrap();
     let t = pb.tf_run().unwrap();
@@ -327,8 +352,27 @@ fn test_w() {
         b_icfo: arr1(&[0.0f32, 0.0, -3.0, -1.0, -1.0, 0.0, 2.0, -2.0]),
         h0: arr2(&[[1.0f32, 0.0]]),
         c0: arr2(&[[-1.0f32, -2.0]]),
+        peepholes: None,
     };
     let o = pb.onnx_run().unwrap();
     let t = pb.tf_run().unwrap();
     assert_eq!(o, t)
 }
+
+#[test]
+fn test_peepholes() {
+    let pb = LstmProblem {
+        length: 2,
+        batch_size: 1,
+        cell_size: 2,
+        x: tensor3(&[[[1.0f32, -1.0]], [[2.0, 0.0]]]).into(),
+        w_xh_icfo: Array2::<f32>::ones((4, 8)),
+        b_icfo: arr1(&[0.0f32, 1.0, 0.0, -1.0, 0.0, 0.0, 1.0, 0.0]),
+        h0: arr2(&[[0.0f32, 1.0]]),
+        c0: arr2(&[[1.0f32, -2.0]]),
+        peepholes: Some(arr2(&[[1.0f32, -1.0], [2.0, 0.0], [-3.0, 1.0]])),
+    };
+    let o = pb.onnx_run().unwrap();
+    let t = pb.tf_run().unwrap();
+    assert!(o.close_enough(&t, true).is_ok(), "\nonnx:{:?}\n tf :{:?}\n", o, t);
+}
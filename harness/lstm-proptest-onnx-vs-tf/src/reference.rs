@@ -0,0 +1,448 @@
+//! Naive ndarray implementations of the ONNX LSTM, GRU and RNN operators, to check their Scan
+//! based translations against.
+
+use proptest::prelude::*;
+
+use tract_hir::internal::*;
+use tract_ndarray::prelude::*;
+use tract_onnx::ops::rec::common::*;
+use tract_onnx::ops::rec::gru::GRU;
+use tract_onnx::ops::rec::lstm::LSTM;
+use tract_onnx::ops::rec::rnn::RNN;
+use tract_onnx::prelude::*;
+use tract_onnx::tract_hir;
+
+#[derive(Clone, Debug)]
+pub enum Cell {
+    Lstm { input_forget: bool },
+    Gru { linear_before_reset: bool },
+    Rnn,
+}
+
+impl Cell {
+    fn gates(&self) -> usize {
+        match self {
+            Cell::Lstm { .. } => 4,
+            Cell::Gru { .. } => 3,
+            Cell::Rnn => 1,
+        }
+    }
+
+    fn activation_count(&self) -> usize {
+        match self {
+            Cell::Lstm { .. } => 3,
+            Cell::Gru { .. } => 2,
+            Cell::Rnn => 1,
+        }
+    }
+
+    fn body(&self, activations: Vec<Activation>) -> Box<dyn WireBody> {
+        match *self {
+            Cell::Lstm { input_forget } => Box::new(LSTM { activations, input_forget }),
+            Cell::Gru { linear_before_reset } => Box::new(GRU { activations, linear_before_reset }),
+            Cell::Rnn => Box::new(RNN { activations }),
+        }
+    }
+}
+
+fn activate(activation: &Activation, x: f32) -> f32 {
+    match *activation {
+        Activation::Relu => x.max(0.0),
+        Activation::Tanh => x.tanh(),
+        Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+        Activation::Affine(alpha, beta) => alpha * x + beta,
+        Activation::LeakyRelu(alpha) => {
+            if x < 0.0 {
+                alpha * x
+            } else {
+                x
+            }
+        }
+        Activation::ThresholdedRelu(alpha) => {
+            if x > alpha {
+                x
+            } else {
+                0.0
+            }
+        }
+        Activation::ScaledTanh(alpha, beta) => alpha * (beta * x).tanh(),
+        Activation::HardSigmoid(alpha, beta) => (alpha * x + beta).clamp(0.0, 1.0),
+        Activation::Elu(alpha) => {
+            if x < 0.0 {
+                alpha * (x.exp() - 1.0)
+            } else {
+                x
+            }
+        }
+        Activation::Softsign => x / (1.0 + x.abs()),
+        Activation::Softplus => (x.exp() + 1.0).ln(),
+    }
+}
+
+/// A recurrent operator problem. Tensors are given in the layout=0 interface whatever
+/// `batch_first` says: it only changes the way they are fed to and read from the model.
+#[derive(Clone, Debug)]
+pub struct RecProblem {
+    pub cell: Cell,
+    pub direction: Direction,
+    pub batch_first: bool,
+    pub clip: Option<f32>,
+    /// activations for all directions
+    pub activations: Vec<Activation>,
+    /// [seq_length, batch_size, input_size]
+    pub x: Array3<f32>,
+    /// [num_directions, gates*hidden_size, input_size]
+    pub w: Array3<f32>,
+    /// [num_directions, gates*hidden_size, hidden_size]
+    pub r: Array3<f32>,
+    /// [num_directions, 2*gates*hidden_size]
+    pub b: Option<Array2<f32>>,
+    /// [num_directions, 3*hidden_size]
+    pub p: Option<Array2<f32>>,
+    /// [num_directions, batch_size, hidden_size]
+    pub initial_h: Option<Array3<f32>>,
+    /// [num_directions, batch_size, hidden_size]
+    pub initial_c: Option<Array3<f32>>,
+}
+
+/// Y, Y_h and Y_c (LSTM only) in layout=0 interface.
+pub type RecOutputs = (Array4<f32>, Array3<f32>, Option<Array3<f32>>);
+
+impl RecProblem {
+    fn with_c(&self) -> bool {
+        matches!(self.cell, Cell::Lstm { .. })
+    }
+
+    pub fn model(&self) -> TractResult<TypedModel> {
+        let mut model = InferenceModel::default();
+        let swap = |a: &Array3<f32>| -> Array3<f32> {
+            if self.batch_first {
+                a.view().permuted_axes([1, 0, 2]).to_owned()
+            } else {
+                a.clone()
+            }
+        };
+        let x = swap(&self.x);
+        let x = model.add_source("x", f32::fact(x.shape()).into())?;
+        let mut inputs = vec![
+            x,
+            model.add_const("w", self.w.clone().into_tensor())?,
+            model.add_const("r", self.r.clone().into_tensor())?,
+        ];
+        let mut op = CommonRec::new(self.cell.body(self.activations.clone()));
+        op.direction = self.direction;
+        op.batch_first = self.batch_first;
+        op.clip = self.clip;
+        let mut optional_input =
+            |name: &str, value: Option<Tensor>| -> TractResult<Option<usize>> {
+                if let Some(value) = value {
+                    inputs.push(model.add_const(name, value)?);
+                    Ok(Some(inputs.len() - 1))
+                } else {
+                    Ok(None)
+                }
+            };
+        op.optional_bias_input = optional_input("b", self.b.clone().map(|b| b.into_tensor()))?;
+        op.optional_initial_h_input =
+            optional_input("initial_h", self.initial_h.as_ref().map(|h| swap(h).into_tensor()))?;
+        op.optional_initial_c_input =
+            optional_input("initial_c", self.initial_c.as_ref().map(|c| swap(c).into_tensor()))?;
+        op.optional_p_input = optional_input("p", self.p.clone().map(|p| p.into_tensor()))?;
+        op.optional_y_output = Some(0);
+        op.optional_y_h_output = Some(1);
+        if self.with_c() {
+            op.optional_y_c_output = Some(2);
+        }
+        let outputs = model.wire_node("rec", expand(op), &inputs)?;
+        model.set_output_outlets(&outputs)?;
+        model.analyse(false)?;
+        model.into_typed()
+    }
+
+    pub fn tract_run(&self) -> TractResult<RecOutputs> {
+        let x = if self.batch_first {
+            self.x.view().permuted_axes([1, 0, 2]).to_owned()
+        } else {
+            self.x.clone()
+        };
+        let mut outputs =
+            self.model()?.into_optimized()?.into_runnable()?.run(tvec!(x.into_tvalue()))?;
+        let (y_axes, state_axes) =
+            if self.batch_first { ([1, 2, 0, 3], [1, 0, 2]) } else { ([0, 1, 2, 3], [0, 1, 2]) };
+        let state = |ix: usize| -> TractResult<Array3<f32>> {
+            let state = outputs[ix].to_array_view::<f32>()?.into_dimensionality::<Ix3>()?;
+            Ok(state.permuted_axes(state_axes).to_owned())
+        };
+        let y_h = state(1)?;
+        let y_c = if self.with_c() { Some(state(2)?) } else { None };
+        let y =
+            outputs.remove(0).into_tensor().into_array::<f32>()?.into_dimensionality::<Ix4>()?;
+        Ok((y.permuted_axes(y_axes).as_standard_layout().to_owned(), y_h, y_c))
+    }
+
+    pub fn reference(&self) -> TractResult<RecOutputs> {
+        let (seq_length, batch_size, _) = self.x.dim();
+        let hidden_size = self.r.shape()[2];
+        let gates = self.cell.gates();
+        let directions = self.w.shape()[0];
+        let gate = |a: &Array2<f32>, ix: usize| -> Array2<f32> {
+            a.slice(s![.., hidden_size * ix..hidden_size * (ix + 1)]).to_owned()
+        };
+        let act = |activation: &Activation, a: Array2<f32>| -> Array2<f32> {
+            a.mapv(|x| {
+                let x = if let Some(clip) = self.clip { x.clamp(-clip, clip) } else { x };
+                activate(activation, x)
+            })
+        };
+        let mut y = Array4::<f32>::zeros((seq_length, directions, batch_size, hidden_size));
+        let mut y_h = Array3::<f32>::zeros((directions, batch_size, hidden_size));
+        let mut y_c = Array3::<f32>::zeros((directions, batch_size, hidden_size));
+        for dir in 0..directions {
+            let acts = direction_activations(&self.activations, self.cell.activation_count(), dir)?;
+            let reverse = self.direction == Direction::Reverse || dir == 1;
+            let w = self.w.index_axis(Axis(0), dir);
+            let r = self.r.index_axis(Axis(0), dir);
+            let (wb, rb) = if let Some(b) = &self.b {
+                let b = b.index_axis(Axis(0), dir).insert_axis(Axis(0));
+                (
+                    b.slice(s![.., 0..gates * hidden_size]).to_owned(),
+                    b.slice(s![.., gates * hidden_size..]).to_owned(),
+                )
+            } else {
+                (Array2::zeros((1, gates * hidden_size)), Array2::zeros((1, gates * hidden_size)))
+            };
+            let p = if let Some(p) = &self.p {
+                p.index_axis(Axis(0), dir).insert_axis(Axis(0)).to_owned()
+            } else {
+                Array2::zeros((1, 3 * hidden_size))
+            };
+            let mut h = if let Some(h) = &self.initial_h {
+                h.index_axis(Axis(0), dir).to_owned()
+            } else {
+                Array2::zeros((batch_size, hidden_size))
+            };
+            let mut c = if let Some(c) = &self.initial_c {
+                c.index_axis(Axis(0), dir).to_owned()
+            } else {
+                Array2::zeros((batch_size, hidden_size))
+            };
+            for step in 0..seq_length {
+                let t = if reverse { seq_length - 1 - step } else { step };
+                let x = self.x.index_axis(Axis(0), t);
+                let xw = x.dot(&w.t()) + &wb;
+                let hr = h.dot(&r.t()) + &rb;
+                h = match self.cell {
+                    Cell::Lstm { input_forget } => {
+                        let g = xw + hr;
+                        let it = act(&acts[0], gate(&g, 0) + &c * &gate(&p, 0));
+                        let ft = if input_forget {
+                            it.mapv(|i| 1.0 - i)
+                        } else {
+                            act(&acts[0], gate(&g, 2) + &c * &gate(&p, 2))
+                        };
+                        let ct = act(&acts[1], gate(&g, 3));
+                        c = ft * &c + &it * &ct;
+                        let ot = act(&acts[0], gate(&g, 1) + &c * &gate(&p, 1));
+                        ot * c.mapv(|c| activate(&acts[2], c))
+                    }
+                    Cell::Gru { linear_before_reset } => {
+                        let zt = act(&acts[0], gate(&xw, 0) + gate(&hr, 0));
+                        let rt = act(&acts[0], gate(&xw, 1) + gate(&hr, 1));
+                        let ht = if linear_before_reset {
+                            act(&acts[1], gate(&xw, 2) + &rt * &gate(&hr, 2))
+                        } else {
+                            let rh = gate(&r.to_owned().reversed_axes(), 2);
+                            act(&acts[1], gate(&xw, 2) + (&rt * &h).dot(&rh) + gate(&rb, 2))
+                        };
+                        zt.mapv(|z| 1.0 - z) * ht + &zt * &h
+                    }
+                    Cell::Rnn => act(&acts[0], xw + hr),
+                };
+                y.slice_mut(s![t, dir, .., ..]).assign(&h);
+            }
+            y_h.index_axis_mut(Axis(0), dir).assign(&h);
+            y_c.index_axis_mut(Axis(0), dir).assign(&c);
+        }
+        Ok((y, y_h, if self.with_c() { Some(y_c) } else { None }))
+    }
+
+    pub fn check(&self) -> TractResult<()> {
+        let (found_y, found_y_h, found_y_c) = self.tract_run()?;
+        let (y, y_h, y_c) = self.reference()?;
+        found_y.into_tensor().close_enough(&y.into_tensor(), true).context("Checking Y")?;
+        found_y_h.into_tensor().close_enough(&y_h.into_tensor(), true).context("Checking Y_h")?;
+        if let (Some(found_y_c), Some(y_c)) = (found_y_c, y_c) {
+            found_y_c
+                .into_tensor()
+                .close_enough(&y_c.into_tensor(), true)
+                .context("Checking Y_c")?;
+        }
+        Ok(())
+    }
+}
+
+fn values(len: usize) -> BoxedStrategy<Vec<f32>> {
+    proptest::collection::vec((-4..4).prop_map(|a| a as f32 / 2.0), len).boxed()
+}
+
+fn activation() -> BoxedStrategy<Activation> {
+    prop_oneof![
+        Just(Activation::Relu),
+        Just(Activation::Tanh),
+        Just(Activation::Sigmoid),
+        Just(Activation::Affine(0.5, 0.25)),
+        Just(Activation::LeakyRelu(0.1)),
+        Just(Activation::ThresholdedRelu(0.3)),
+        Just(Activation::ScaledTanh(1.5, 0.5)),
+        Just(Activation::HardSigmoid(0.2, 0.5)),
+        Just(Activation::Elu(0.7)),
+        Just(Activation::Softsign),
+        Just(Activation::Softplus),
+    ]
+    .boxed()
+}
+
+fn cell() -> BoxedStrategy<Cell> {
+    prop_oneof![
+        any::<bool>().prop_map(|input_forget| Cell::Lstm { input_forget }),
+        any::<bool>().prop_map(|linear_before_reset| Cell::Gru { linear_before_reset }),
+        Just(Cell::Rnn),
+    ]
+    .boxed()
+}
+
+fn direction() -> BoxedStrategy<Direction> {
+    prop_oneof![Just(Direction::Forward), Just(Direction::Reverse), Just(Direction::Bidirectional)]
+        .boxed()
+}
+
+fn strat() -> BoxedStrategy<RecProblem> {
+    (cell(), direction(), any::<bool>(), 1usize..4, 1usize..3, 1usize..3, 1usize..3)
+        .prop_flat_map(|(cell, direction, batch_first, seq_length, batch_size, input, hidden)| {
+            let dirs = direction.chunks().len();
+            let gates = cell.gates();
+            let with_c = matches!(cell, Cell::Lstm { .. });
+            let state = batch_size * dirs * hidden;
+            (
+                Just((cell.clone(), direction, batch_first, seq_length, batch_size, input, hidden)),
+                proptest::option::of(prop_oneof![Just(0.5f32), Just(2.0)]),
+                proptest::collection::vec(activation(), cell.activation_count() * dirs),
+                values(seq_length * batch_size * input),
+                values(dirs * gates * hidden * input),
+                values(dirs * gates * hidden * hidden),
+                proptest::option::of(values(dirs * 2 * gates * hidden)),
+                if with_c {
+                    proptest::option::of(values(dirs * 3 * hidden)).boxed()
+                } else {
+                    Just(None).boxed()
+                },
+                proptest::option::of(values(state)),
+                if with_c {
+                    proptest::option::of(values(state)).boxed()
+                } else {
+                    Just(None).boxed()
+                },
+            )
+        })
+        .prop_map(
+            |(
+                (cell, direction, batch_first, seq_length, batch_size, input, hidden),
+                clip,
+                activations,
+                x,
+                w,
+                r,
+                b,
+                p,
+                initial_h,
+                initial_c,
+            )| {
+                let dirs = direction.chunks().len();
+                let gates = cell.gates();
+                let state =
+                    |v: Vec<f32>| Array3::from_shape_vec((dirs, batch_size, hidden), v).unwrap();
+                RecProblem {
+                    cell,
+                    direction,
+                    batch_first,
+                    clip,
+                    activations,
+                    x: Array3::from_shape_vec((seq_length, batch_size, input), x).unwrap(),
+                    w: Array3::from_shape_vec((dirs, gates * hidden, input), w).unwrap(),
+                    r: Array3::from_shape_vec((dirs, gates * hidden, hidden), r).unwrap(),
+                    b: b.map(|b| Array2::from_shape_vec((dirs, 2 * gates * hidden), b).unwrap()),
+                    p: p.map(|p| Array2::from_shape_vec((dirs, 3 * hidden), p).unwrap()),
+                    initial_h: initial_h.map(state),
+                    initial_c: initial_c.map(state),
+                }
+            },
+        )
+        .boxed()
+}
+
+proptest::proptest! {
+    #[test]
+    fn proptest(pb in strat()) {
+        pb.check().unwrap()
+    }
+}
+
+#[test]
+fn bidirectional_lstm_peepholes_batch_first() {
+    RecProblem {
+        cell: Cell::Lstm { input_forget: false },
+        direction: Direction::Bidirectional,
+        batch_first: true,
+        clip: None,
+        activations: vec![Activation::Sigmoid, Activation::Tanh, Activation::Tanh],
+        x: tract_ndarray::arr3(&[[[1.0f32]], [[-1.0]], [[0.5]]]),
+        w: Array3::from_shape_fn((2, 4, 1), |(d, g, _)| d as f32 - g as f32 / 2.0),
+        r: Array3::from_shape_fn((2, 4, 1), |(d, g, _)| g as f32 / 2.0 - d as f32),
+        b: None,
+        p: Some(arr2(&[[1.0f32, -1.0, 0.5], [-0.5, 1.0, 0.0]])),
+        initial_h: Some(tract_ndarray::arr3(&[[[0.5f32]], [[-0.5]]])),
+        initial_c: Some(tract_ndarray::arr3(&[[[1.0f32]], [[-1.0]]])),
+    }
+    .check()
+    .unwrap()
+}
+
+#[test]
+fn reverse_gru_clip_activations() {
+    RecProblem {
+        cell: Cell::Gru { linear_before_reset: true },
+        direction: Direction::Reverse,
+        batch_first: false,
+        clip: Some(0.5),
+        activations: vec![Activation::HardSigmoid(0.2, 0.5), Activation::LeakyRelu(0.1)],
+        x: tract_ndarray::arr3(&[[[1.0f32, 2.0]], [[-1.0, 0.0]]]),
+        w: Array3::from_shape_fn((1, 3, 2), |(_, g, i)| g as f32 - i as f32),
+        r: Array3::from_shape_fn((1, 3, 1), |(_, g, _)| 1.0 - g as f32),
+        b: Some(arr2(&[[0.5f32, -0.5, 1.0, 0.0, 1.0, -1.0]])),
+        p: None,
+        initial_h: None,
+        initial_c: None,
+    }
+    .check()
+    .unwrap()
+}
+
+#[test]
+fn lstm_input_forget() {
+    RecProblem {
+        cell: Cell::Lstm { input_forget: true },
+        direction: Direction::Forward,
+        batch_first: false,
+        clip: None,
+        activations: vec![Activation::Sigmoid, Activation::Tanh, Activation::Tanh],
+        x: tract_ndarray::arr3(&[[[1.0f32], [2.0]], [[-1.0], [0.0]]]),
+        w: Array3::from_shape_fn((1, 4, 1), |(_, g, _)| g as f32 / 2.0),
+        r: Array3::from_shape_fn((1, 4, 1), |(_, g, _)| 1.0 - g as f32 / 2.0),
+        b: None,
+        p: None,
+        initial_h: None,
+        initial_c: Some(tract_ndarray::arr3(&[[[1.0f32], [-1.0]]])),
+    }
+    .check()
+    .unwrap()
+}
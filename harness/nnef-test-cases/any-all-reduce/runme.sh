@@ -0,0 +1,12 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:2,3,bool=true,false,false,true,true,true \
+    run -q \
+    --assert-output any:2,1,bool=true,true \
+    --assert-output all:2,1,bool=false,true
@@ -0,0 +1,13 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:1,1,2,4,f32=1,5,2,0,3,4,7,8 \
+    run -q \
+    --assert-output index:1,1,1,2,i64=1,7 \
+    --assert-output sampled:1,1,1,2,f32=5,8 \
+    --assert-output unpooled:1,1,2,4,f32=0,5,0,0,0,0,0,8
@@ -0,0 +1,11 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:1,2,2,2,f32=0,1,2,3,4,5,6,7 \
+    run -q \
+    --assert-output output:1,2,4,4,f32=0,0,0.25,0.25,0,0,0.25,0.25,0.5,0.5,0.75,0.75,0.5,0.5,0.75,0.75,1,1,1.25,1.25,1,1,1.25,1.25,1.5,1.5,1.75,1.75,1.5,1.5,1.75,1.75
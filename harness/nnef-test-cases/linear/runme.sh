@@ -0,0 +1,11 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:2,3,f32=1,2,3,4,5,6 \
+    run -q \
+    --assert-output output:2,2,f32=4.5,3.5,10.5,9.5
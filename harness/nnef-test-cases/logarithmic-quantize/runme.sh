@@ -0,0 +1,11 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:4,f32=3,-0.3,100,0.01 \
+    run -q \
+    --assert-output output:4,f32=4,-0.25,4,0.03125
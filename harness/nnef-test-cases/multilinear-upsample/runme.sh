@@ -0,0 +1,13 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:1,1,1,2,f32=1,2 \
+    run -q \
+    --assert-output symmetric:1,1,1,4,f32=1,1.25,1.75,2 \
+    --assert-output asymmetric:1,1,1,4,f32=1,1.5,2,1 \
+    --assert-output aligned:1,1,1,4,f32=1,1.3333334,1.6666666,2
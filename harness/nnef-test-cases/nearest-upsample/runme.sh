@@ -0,0 +1,11 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:1,1,2,3,f32=0,1,2,3,4,5 \
+    run -q \
+    --assert-output output:1,1,4,6,f32=0,0,1,1,2,2,0,0,1,1,2,2,3,3,4,4,5,5,3,3,4,4,5,5
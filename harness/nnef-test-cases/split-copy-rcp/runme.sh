@@ -0,0 +1,12 @@
+#!/bin/sh
+
+cd `dirname $0`
+set -x
+
+: ${TRACT_RUN:=cargo run -p tract $CARGO_OPTS --}
+
+$TRACT_RUN . -O \
+    -i input:1,6,f32=1,2,3,4,5,6 \
+    run -q \
+    --assert-output head:1,2,f32=1,2 \
+    --assert-output tail:1,4,f32=0.6666667,0.5,0.4,0.33333334
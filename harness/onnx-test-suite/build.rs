@@ -23,6 +23,12 @@ fn versions() -> Vec<&'static str> {
     if cfg!(feature = "onnx_1_10_1") {
         versions.push("1.10.1");
     }
+    if cfg!(feature = "onnx_1_13_0") {
+        versions.push("1.13.0");
+    }
+    if cfg!(feature = "onnx_1_14_0") {
+        versions.push("1.14.0");
+    }
     versions
 }
 
@@ -131,6 +131,16 @@ activation!(HardSigmoid, |op, name: &str, model: &mut TypedModel, inputs| {
     Ok(wire)
 });
 
+#[derive(Debug, Clone, new, Hash)]
+pub struct HardSwish;
+
+activation!(HardSwish, |_op, name: &str, model: &mut TypedModel, inputs| {
+    let wire =
+        HardSigmoid(1.0 / 6.0, 0.5).wire(&format!("{}.hard_sigmoid", name), model, inputs)?;
+    let wire = model.wire_node(name.to_string() + ".mul", mul(), &[inputs[0], wire[0]])?;
+    Ok(wire)
+});
+
 #[derive(Debug, Clone, new, Educe)]
 #[educe(Hash)]
 pub struct LeakyRelu(#[educe(Hash(method = "hash_f32"))] pub f32);
@@ -139,6 +149,16 @@ activation!(LeakyRelu, |op, name: &str, model: &mut TypedModel, inputs| {
     model.wire_node(name, tract_core::ops::nn::leaky_relu(op.0), inputs)
 });
 
+#[derive(Debug, Clone, new, Hash)]
+pub struct Mish;
+
+activation!(Mish, |_op, name: &str, model: &mut TypedModel, inputs| {
+    let wire = Softplus.wire(&format!("{}.softplus", name), model, inputs)?;
+    let wire = model.wire_node(name.to_string() + ".tanh", tanh(), &wire)?;
+    let wire = model.wire_node(name.to_string() + ".mul", mul(), &[inputs[0], wire[0]])?;
+    Ok(wire)
+});
+
 #[derive(Debug, Clone, new, Educe)]
 #[educe(Hash)]
 pub struct ParametricSoftplus(
@@ -1,10 +1,12 @@
 
 use crate::infer::*;
 use crate::internal::*;
+use tract_core::ops::array::ScatterReduction;
 
-#[derive(Debug, Clone, new, Default, Hash)]
+#[derive(Debug, Clone, new, Hash)]
 pub struct ScatterElements {
     axis: i64,
+    reduction: ScatterReduction,
 }
 impl_dyn_hash!(ScatterElements);
 
@@ -38,6 +40,10 @@ impl Expansion for ScatterElements {
     ) -> TractResult<TVec<OutletId>> {
         let input_rank = model.outlet_fact(inputs[0])?.rank();
         let axis = if self.axis < 0 { self.axis + input_rank as i64 } else { self.axis } as usize;
-        model.wire_node(prefix, tract_core::ops::array::ScatterElements { axis }, inputs)
+        model.wire_node(
+            prefix,
+            tract_core::ops::array::ScatterElements { axis, reduction: self.reduction },
+            inputs,
+        )
     }
 }
@@ -0,0 +1,30 @@
+use crate::infer::*;
+use crate::internal::*;
+
+pub use tract_core::ops::cnn::{GridSample, GridSampleInterpolation, GridSamplePadding};
+
+impl InferenceRulesOp for GridSample {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 4)?;
+        s.equals(&inputs[1].shape[3], 2.to_dim())?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.equals(&outputs[0].shape[2], &inputs[1].shape[1])?;
+        s.equals(&outputs[0].shape[3], &inputs[1].shape[2])?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
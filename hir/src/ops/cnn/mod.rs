@@ -1,6 +1,10 @@
 mod conv;
+mod grid_sample;
 mod pools;
+mod roi;
 
 pub use conv::Conv;
+pub use grid_sample::{GridSample, GridSampleInterpolation, GridSamplePadding};
 pub use pools::{MaxPool, SumPool};
+pub use roi::{RoiAlign, RoiPool, RoiPoolingMode};
 pub use tract_core::ops::cnn::{ConvUnary, PaddingSpec, PoolSpec};
@@ -0,0 +1,54 @@
+use crate::infer::*;
+use crate::internal::*;
+
+pub use tract_core::ops::cnn::{RoiAlign, RoiPool, RoiPoolingMode};
+
+fn rules_for_regions<'r, 'p: 'r, 's: 'r>(
+    output_shape: (usize, usize),
+    s: &mut Solver<'r>,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_input_arity(inputs, 3)?;
+    check_output_arity(outputs, 1)?;
+    s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+    s.equals(&inputs[0].rank, 4)?;
+    s.equals(&inputs[1].rank, 2)?;
+    s.equals(&inputs[1].shape[1], 4.to_dim())?;
+    s.equals(&inputs[2].rank, 1)?;
+    s.equals(&outputs[0].rank, 4)?;
+    s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+    s.equals(&outputs[0].shape[0], &inputs[2].shape[0])?;
+    s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+    s.equals(&outputs[0].shape[2], output_shape.0.to_dim())?;
+    s.equals(&outputs[0].shape[3], output_shape.1.to_dim())?;
+    Ok(())
+}
+
+impl InferenceRulesOp for RoiAlign {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        rules_for_regions(self.output_shape, s, inputs, outputs)
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl InferenceRulesOp for RoiPool {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        rules_for_regions(self.output_shape, s, inputs, outputs)
+    }
+
+    as_op!();
+    to_typed!();
+}
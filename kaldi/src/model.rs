@@ -11,8 +11,8 @@ pub struct KaldiProtoModel {
 
 #[derive(Clone, Debug)]
 pub struct ConfigLines {
-    pub input_name: String,
-    pub input_dim: usize,
+    /// Input nodes names and dims. They all stream along the same "S" symbol.
+    pub inputs: Vec<(String, usize)>,
     pub nodes: Vec<(String, NodeLine)>,
     pub outputs: Vec<OutputLine>,
 }
@@ -32,25 +32,56 @@ pub struct OutputLine {
 #[derive(Clone, Debug, PartialEq)]
 pub enum GeneralDescriptor {
     Append(Vec<GeneralDescriptor>),
+    Const(f32, usize),
     IfDefined(Box<GeneralDescriptor>),
     Name(String),
     Offset(Box<GeneralDescriptor>, isize),
+    ReplaceIndex(Box<GeneralDescriptor>, char, isize),
+    Round(Box<GeneralDescriptor>, usize),
+    Scale(f32, Box<GeneralDescriptor>),
+    Sum(Box<GeneralDescriptor>, Box<GeneralDescriptor>),
+    Switch(Vec<GeneralDescriptor>),
 }
 
 impl GeneralDescriptor {
     pub fn inputs(&self) -> TVec<&str> {
         match self {
-            GeneralDescriptor::Append(ref gds) => gds.iter().fold(tvec!(), |mut acc, gd| {
-                gd.inputs().iter().for_each(|i| {
-                    if !acc.contains(i) {
+            GeneralDescriptor::Append(ref gds) | GeneralDescriptor::Switch(ref gds) => {
+                gds.iter().fold(tvec!(), |mut acc, gd| {
+                    gd.inputs().iter().for_each(|i| {
+                        if !acc.contains(i) {
+                            acc.push(i)
+                        }
+                    });
+                    acc
+                })
+            }
+            GeneralDescriptor::Sum(ref a, ref b) => {
+                let mut acc = a.inputs();
+                b.inputs().into_iter().for_each(|i| {
+                    if !acc.contains(&i) {
                         acc.push(i)
                     }
                 });
                 acc
-            }),
+            }
+            GeneralDescriptor::Const(..) => tvec!(),
             GeneralDescriptor::IfDefined(ref gd) => gd.inputs(),
             GeneralDescriptor::Name(ref s) => tvec!(&**s),
             GeneralDescriptor::Offset(ref gd, _) => gd.inputs(),
+            GeneralDescriptor::ReplaceIndex(ref gd, _, _) => gd.inputs(),
+            GeneralDescriptor::Round(ref gd, _) => gd.inputs(),
+            GeneralDescriptor::Scale(_, ref gd) => gd.inputs(),
+        }
+    }
+
+    /// Descriptors producing a single frame, to be broadcast over the time axis.
+    fn is_frameless(&self) -> bool {
+        match self {
+            GeneralDescriptor::Const(..) | GeneralDescriptor::ReplaceIndex(_, 't', _) => true,
+            GeneralDescriptor::Scale(_, gd) => gd.is_frameless(),
+            GeneralDescriptor::Sum(a, b) => a.is_frameless() && b.is_frameless(),
+            _ => false,
         }
     }
 
@@ -96,16 +127,118 @@ impl GeneralDescriptor {
                     expand(tract_hir::ops::array::Concat::new(1)),
                     tvec!(InferenceFact::default()),
                 )?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
+                deferred.insert(inlet, name.clone());
+                let reference = appendees.iter().position(|app| !app.is_frameless());
                 for (ix, appendee) in appendees.iter().enumerate() {
                     let name = format!("{}-{}", name, ix);
-                    appendee.wire(
-                        InletId::new(id, ix),
-                        &name,
-                        model,
-                        deferred,
-                        adjust_final_offset,
-                    )?;
+                    match reference {
+                        Some(reference) if appendee.is_frameless() => {
+                            // repeat the single frame as many times as the reference appendee
+                            let name = format!("{}.broadcast", name);
+                            let bc = model.add_node(
+                                &*name,
+                                crate::ops::frames::BroadcastFrames,
+                                tvec!(InferenceFact::default()),
+                            )?;
+                            deferred.insert(InletId::new(id, ix), name.clone());
+                            appendee.wire(
+                                InletId::new(bc, 0),
+                                &name,
+                                model,
+                                deferred,
+                                adjust_final_offset,
+                            )?;
+                            appendees[reference].wire(
+                                InletId::new(bc, 1),
+                                &format!("{}.reference", name),
+                                model,
+                                deferred,
+                                adjust_final_offset,
+                            )?;
+                        }
+                        _ => appendee.wire(
+                            InletId::new(id, ix),
+                            &name,
+                            model,
+                            deferred,
+                            adjust_final_offset,
+                        )?,
+                    }
+                }
+                return Ok(());
+            }
+            Const(value, dim) => {
+                let value = tract_ndarray::Array2::from_elem((1, *dim), *value);
+                let name = format!("{}.Const", name);
+                model.add_const(&*name, value.into_tensor())?;
+                deferred.insert(inlet, name);
+                return Ok(());
+            }
+            Sum(a, b) => {
+                let name = format!("{}.Sum", name);
+                let id = model.add_node(
+                    &*name,
+                    tract_hir::ops::math::Add.into_hir(),
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.clone());
+                for (ix, term) in [a, b].iter().enumerate() {
+                    let name = format!("{}-{}", name, ix);
+                    term.wire(InletId::new(id, ix), &name, model, deferred, adjust_final_offset)?;
+                }
+                return Ok(());
+            }
+            Scale(scale, gd) => {
+                let name = format!("{}.Scale", name);
+                let factor = format!("{}.factor", name);
+                model.add_const(&*factor, tensor2(&[[*scale]]))?;
+                let id = model.add_node(
+                    &*name,
+                    tract_hir::ops::math::Mul.into_hir(),
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.clone());
+                deferred.insert(InletId::new(id, 1), factor);
+                gd.wire(InletId::new(id, 0), &name, model, deferred, adjust_final_offset)?;
+                return Ok(());
+            }
+            ReplaceIndex(gd, 'x', 0) => {
+                return gd.wire(inlet, name, model, deferred, adjust_final_offset);
+            }
+            ReplaceIndex(gd, 't', index) if *index >= 0 => {
+                let name = format!("{}.ReplaceIndex", name);
+                let index = *index as usize;
+                let id = model.add_node(
+                    &*name,
+                    tract_hir::ops::array::Slice::new(0, index, index + 1),
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.clone());
+                gd.wire(InletId::new(id, 0), &name, model, deferred, adjust_final_offset)?;
+                return Ok(());
+            }
+            Round(gd, modulus) => {
+                let name = format!("{}.Round", name);
+                let id = model.add_node(
+                    &*name,
+                    crate::ops::frames::RoundFrames::new(*modulus),
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.clone());
+                gd.wire(InletId::new(id, 0), &name, model, deferred, adjust_final_offset)?;
+                return Ok(());
+            }
+            Switch(cases) => {
+                let name = format!("{}.Switch", name);
+                let id = model.add_node(
+                    &*name,
+                    crate::ops::frames::SwitchFrames,
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.clone());
+                for (ix, case) in cases.iter().enumerate() {
+                    let name = format!("{}-{}", name, ix);
+                    case.wire(InletId::new(id, ix), &name, model, deferred, adjust_final_offset)?;
                 }
                 return Ok(());
             }
@@ -134,7 +267,7 @@ impl GeneralDescriptor {
                     expand(tract_hir::ops::array::Crop::new(0, crop as usize, 0)),
                     tvec!(InferenceFact::default()),
                 )?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
+                deferred.insert(inlet, name.clone());
                 n.wire(InletId::new(id, 0), &name, model, deferred, adjust_final_offset)?;
                 return Ok(());
             }
@@ -163,10 +296,42 @@ pub struct Component {
     pub attributes: HashMap<String, Arc<Tensor>>,
 }
 
+impl Component {
+    pub fn attribute(&self, name: &str) -> TractResult<&Arc<Tensor>> {
+        self.attributes.get(name).with_context(|| format!("missing attribute {}", name))
+    }
+
+    /// Integer attribute. The text format does not tell integers from floats, so any
+    /// scalar is accepted.
+    pub fn usize_attribute(&self, name: &str) -> TractResult<usize> {
+        Ok(self.attribute(name)?.cast_to_scalar::<i64>()? as usize)
+    }
+
+    pub fn f32_attribute(&self, name: &str, default: f32) -> TractResult<f32> {
+        self.attributes.get(name).map(|t| t.cast_to_scalar::<f32>()).unwrap_or(Ok(default))
+    }
+}
+
 pub struct ParsingContext<'a> {
     pub proto_model: &'a KaldiProtoModel,
 }
 
+impl<'a> ParsingContext<'a> {
+    /// The component a component-node refers to.
+    pub fn component(&self, name: &str) -> TractResult<&'a Component> {
+        let node = self.proto_model.config_lines.nodes.iter().find(|l| l.0 == name);
+        let line = if let Some((_, NodeLine::Component(line))) = node {
+            line
+        } else {
+            bail!("Could not find component {}", name);
+        };
+        self.proto_model
+            .components
+            .get(&line.component)
+            .with_context(|| format!("Could not find component {}", line.component))
+    }
+}
+
 type OpBuilder = fn(&ParsingContext, node: &str) -> TractResult<Box<dyn InferenceOp>>;
 
 #[derive(Clone, Default)]
@@ -201,10 +366,9 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
             InferenceModel { symbol_table: symbols.to_owned(), ..InferenceModel::default() };
 
         let s = model.symbol_table.sym("S");
-        model.add_source(
-            proto_model.config_lines.input_name.clone(),
-            f32::fact(dims!(s, proto_model.config_lines.input_dim)).into(),
-        )?;
+        for (name, dim) in &proto_model.config_lines.inputs {
+            model.add_source(name.clone(), f32::fact(dims!(s, dim)).into())?;
+        }
         let mut inputs_to_wire: BTreeMap<InletId, String> = Default::default();
         for (name, node) in &proto_model.config_lines.nodes {
             match node {
@@ -2,11 +2,17 @@ use crate::model::KaldiOpRegister;
 use tract_hir::internal::*;
 
 pub(crate) mod affine;
+mod block;
+mod convolution;
+mod dropout;
+pub(crate) mod frames;
 pub(crate) mod lstm_nonlin;
 pub(crate) mod memory;
 mod renorm;
+mod scale_and_offset;
 
-pub const AFFINE: &[&str] = &["FixedAffineComponent", "NaturalGradientAffineComponent"];
+pub const AFFINE: &[&str] =
+    &["FixedAffineComponent", "NaturalGradientAffineComponent", "LinearComponent"];
 
 pub fn register_all_ops(reg: &mut KaldiOpRegister) {
     for affine in AFFINE {
@@ -15,9 +21,21 @@ pub fn register_all_ops(reg: &mut KaldiOpRegister) {
     reg.insert("BackpropTruncationComponent", |_, _| {
         Ok(Box::new(tract_hir::ops::identity::Identity::default()))
     });
+    reg.insert("BatchNormComponent", scale_and_offset::batch_norm);
+    reg.insert("DropoutMaskComponent", dropout::dropout_mask);
+    reg.insert("ElementwiseProductComponent", block::elementwise_product);
+    reg.insert("GeneralDropoutComponent", |_, _| {
+        Ok(Box::new(tract_hir::ops::identity::Identity::default()))
+    });
     reg.insert("NormalizeComponent", renorm::renorm);
     reg.insert("LstmNonlinearityComponent", lstm_nonlin::lstm_nonlin);
     reg.insert("RectifiedLinearComponent", |_, _| {
         Ok(expand(tract_hir::ops::activations::Clip::new(Some(0.0), None)))
     });
+    reg.insert("ScaleAndOffsetComponent", scale_and_offset::scale_and_offset);
+    reg.insert("SigmoidComponent", |_, _| Ok(tract_hir::ops::nn::sigmoid().into_hir()));
+    reg.insert("SumBlockComponent", block::sum_block);
+    reg.insert("TanhComponent", |_, _| Ok(tract_hir::ops::math::tanh().into_hir()));
+    reg.insert("TdnnComponent", affine::tdnn_component);
+    reg.insert("TimeHeightConvolutionComponent", convolution::time_height_convolution);
 }
@@ -12,20 +12,55 @@ pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn
     };
     let component = &ctx.proto_model.components[&line.component];
     let (kernel_len, dilation) = line.input.as_conv_shape_dilation().unwrap_or((1, 1));
-    let kernel: &Tensor =
-        component.attributes.get("LinearParams").context("missing attribute LinearParams")?;
-    let bias = component.attributes.get("BiasParams").context("missing attribute BiasParams")?;
-    // O•TI -> t -> TI•O -> T•I•O = HWIO
-    let o_ti = kernel.to_array_view::<f32>()?;
-    let t_i_o_shape = (kernel_len, kernel.len() / kernel_len / bias.len(), bias.len());
-    let t_i_o =
-        tract_ndarray::Array::from_shape_vec(t_i_o_shape, o_ti.t().iter().cloned().collect())?;
-    Ok(expand(Affine {
-        kernel_len,
-        dilation,
-        linear_params: t_i_o.into_arc_tensor(),
-        bias_params: Arc::clone(bias),
-    }))
+    let offsets: Vec<isize> = (0..kernel_len).map(|t| (t * dilation) as isize).collect();
+    // LinearComponent has no bias and calls its kernel Params
+    let kernel = if let Some(kernel) = component.attributes.get("Params") {
+        kernel
+    } else {
+        component.attribute("LinearParams")?
+    };
+    affine(kernel, component.attributes.get("BiasParams"), &offsets)
+}
+
+pub fn tdnn_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let offsets = component.attribute("TimeOffsets")?.cast_to::<i64>()?;
+    let offsets: Vec<isize> = offsets.as_slice::<i64>()?.iter().map(|&o| o as isize).collect();
+    affine(component.attribute("LinearParams")?, component.attributes.get("BiasParams"), &offsets)
+}
+
+/// Convolution over time from a Kaldi O•TI kernel, spliced from `offsets`.
+fn affine(
+    kernel: &Tensor,
+    bias: Option<&Arc<Tensor>>,
+    offsets: &[isize],
+) -> TractResult<Box<dyn InferenceOp>> {
+    ensure!(offsets.len() > 0, "Expected at least one time offset");
+    ensure!(offsets.windows(2).all(|w| w[0] < w[1]), "Time offsets must be increasing");
+    let dilation = offsets.windows(2).map(|w| (w[1] - w[0]) as usize).reduce(gcd).unwrap_or(1);
+    let kernel_len = (offsets[offsets.len() - 1] - offsets[0]) as usize / dilation + 1;
+    // O•TI -> t -> TI•O -> T•I•O = HWIO, with zeroes for the missing offsets
+    let o_ti = kernel.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+    let (o, ti) = o_ti.dim();
+    let i = ti / offsets.len();
+    let mut t_i_o = tract_ndarray::Array3::<f32>::zeros((kernel_len, i, o));
+    for (ix, offset) in offsets.iter().enumerate() {
+        let t = (offset - offsets[0]) as usize / dilation;
+        t_i_o
+            .index_axis_mut(tract_ndarray::Axis(0), t)
+            .assign(&o_ti.slice(tract_ndarray::s![.., ix * i..(ix + 1) * i]).t());
+    }
+    // empty bias vectors come out of the binary parser as a single zero
+    let bias_params = bias.filter(|b| b.len() == o).cloned();
+    Ok(expand(Affine { kernel_len, dilation, linear_params: t_i_o.into_arc_tensor(), bias_params }))
+}
+
+pub(crate) fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
 }
 
 #[derive(Clone, Debug, new, Hash)]
@@ -33,7 +68,7 @@ struct Affine {
     kernel_len: usize,
     dilation: usize,
     linear_params: Arc<Tensor>, // TIO
-    bias_params: Arc<Tensor>,
+    bias_params: Option<Arc<Tensor>>,
 }
 
 impl_dyn_hash!(Affine);
@@ -95,15 +130,44 @@ impl Expansion for Affine {
                     PaddingSpec::Valid,
                     Some(tvec!(self.dilation)),
                     None,
-                    Some(self.bias_params.len()),
+                    Some(self.linear_params.shape()[2]),
                 ),
                 kernel_fmt: KernelFormat::HWIO,
                 kernel: self.linear_params.clone(),
                 group: 1,
-                bias: Some(self.bias_params.clone().into_arc_tensor()),
+                bias: self.bias_params.clone(),
                 q_params: None,
             },
             inputs,
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tdnn_with_ivector() -> TractResult<()> {
+        let slice = r#"<Nnet3>
+input-node name=input dim=2
+input-node name=ivector dim=1
+component-node name=tdnn input=Append(input, ReplaceIndex(ivector, t, 0)) component=tdnn
+output-node name=output input=Sum(Scale(2, tdnn), Const(1, 1))
+
+<NumComponents> 1
+<ComponentName> tdnn <TdnnComponent> <MaxChange> 0.75 <L2Regularize> 0.01 <LearningRate> 0.001 <TimeOffsets> [ -1 1 ]
+<LinearParams> [
+  1 2 1 10 20 0 ]
+<BiasParams> [ 0.5 ]
+<OrthonormalConstraint> 0 <UseNaturalGradient> T <NumSamplesHistory> 2000 <AlphaInOut> 4 4 <RankInOut> 20 20 </TdnnComponent>
+</Nnet3>"#;
+        let proto = crate::parser::nnet3(slice.as_bytes())?;
+        let model = crate::kaldi().model_for_proto_model(&proto)?.into_typed()?;
+        let input = tensor2(&[[1f32, 2.], [3., 4.], [5., 6.], [7., 8.]]);
+        let ivector = tensor2(&[[100f32], [0.], [0.], [0.]]);
+        let output = model.into_runnable()?.run(tvec!(input.into(), ivector.into()))?;
+        assert_eq!(*output[0], tensor2(&[[552f32], [684.]]));
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::nn::{Reduce, Reducer};
+
+use crate::model::ParsingContext;
+
+pub fn elementwise_product(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let input_dim = component.usize_attribute("InputDim")?;
+    let output_dim = component.usize_attribute("OutputDim")?;
+    Ok(expand(BlockReduce::new(Reducer::Prod, input_dim, output_dim, 1.0)))
+}
+
+pub fn sum_block(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let input_dim = component.usize_attribute("InputDim")?;
+    let output_dim = component.usize_attribute("OutputDim")?;
+    let scale = component.f32_attribute("Scale", 1.0)?;
+    Ok(expand(BlockReduce::new(Reducer::Sum, input_dim, output_dim, scale)))
+}
+
+/// Reduces the consecutive blocks of `output_dim` features together.
+#[derive(Clone, Debug, new, Educe)]
+#[educe(Hash)]
+struct BlockReduce {
+    reducer: Reducer,
+    input_dim: usize,
+    output_dim: usize,
+    #[educe(Hash(method = "hash_f32"))]
+    scale: f32,
+}
+
+impl_dyn_hash!(BlockReduce);
+
+impl Expansion for BlockReduce {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "BlockReduce".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&inputs[0].shape[1], self.input_dim.to_dim())?;
+        s.equals(&outputs[0].shape[1], self.output_dim.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        ensure!(
+            self.input_dim % self.output_dim == 0,
+            "Input dim {} is not a multiple of output dim {}",
+            self.input_dim,
+            self.output_dim
+        );
+        let blocks = self.input_dim / self.output_dim;
+        let mut wire = model.wire_node(
+            prefix.to_string() + ".split",
+            AxisOp::Reshape(
+                1,
+                tvec!(self.input_dim.to_dim()),
+                tvec!(blocks.to_dim(), self.output_dim.to_dim()),
+            ),
+            inputs,
+        )?;
+        wire = model.wire_node(
+            prefix.to_string() + ".reduce",
+            Reduce::new(tvec!(1), self.reducer),
+            &wire,
+        )?;
+        if self.scale != 1.0 {
+            let scale = tensor0(self.scale).broadcast_into_rank(3)?.into_arc_tensor();
+            let scale = model.add_const(prefix.to_string() + ".scale", scale)?;
+            wire = model.wire_node(
+                prefix.to_string() + ".scaled",
+                tract_hir::ops::math::mul(),
+                &[wire[0], scale],
+            )?;
+        }
+        model.wire_node(prefix, AxisOp::Rm(1), &wire)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(component: &str, input: Tensor) -> TractResult<Tensor> {
+        let slice = format!(
+            r#"<Nnet3>
+input-node name=input dim={}
+component-node name=block input=input component=block
+output-node name=output input=block
+
+<NumComponents> 1
+<ComponentName> block {}
+</Nnet3>"#,
+            input.shape()[1],
+            component
+        );
+        let proto = crate::parser::nnet3(slice.as_bytes())?;
+        let model = crate::kaldi().model_for_proto_model(&proto)?.into_typed()?;
+        Ok(model.into_runnable()?.run(tvec!(input.into()))?.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn sum_block() -> TractResult<()> {
+        let output = run(
+            "<SumBlockComponent> <InputDim> 6 <OutputDim> 2 <Scale> 0.5 </SumBlockComponent>",
+            tensor2(&[[1f32, 2., 3., 4., 5., 6.]]),
+        )?;
+        assert_eq!(output, tensor2(&[[4.5f32, 6.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn elementwise_product() -> TractResult<()> {
+        let output = run(
+            "<ElementwiseProductComponent> <InputDim> 4 <OutputDim> 2 </ElementwiseProductComponent>",
+            tensor2(&[[1f32, 2., 3., 4.], [0., 1., 2., 3.]]),
+        )?;
+        assert_eq!(output, tensor2(&[[3f32, 8.], [0., 3.]]));
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+use tract_hir::tract_core::ops::nn::DataFormat;
+
+use crate::model::ParsingContext;
+use crate::ops::affine::gcd;
+
+/// TimeHeightConvolutionComponent: features are a height•filters image, convolved over
+/// time and height at the (time, height) offsets of the convolution model.
+pub fn time_height_convolution(
+    ctx: &ParsingContext,
+    name: &str,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let filters_in = component.usize_attribute("Model.NumFiltersIn")?;
+    let filters_out = component.usize_attribute("Model.NumFiltersOut")?;
+    let height_in = component.usize_attribute("Model.HeightIn")?;
+    let height_out = component.usize_attribute("Model.HeightOut")?;
+    let subsample = component.usize_attribute("Model.HeightSubsampleOut")?;
+    let offsets = component.attribute("Model.Offsets")?.cast_to::<i64>()?;
+    let offsets = offsets.to_array_view::<i64>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+    ensure!(offsets.ncols() == 2 && offsets.nrows() > 0, "Expected (time, height) offset pairs");
+    let params = component.attribute("LinearParams")?;
+    let params = params.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+    ensure!(
+        params.dim() == (filters_out, offsets.nrows() * filters_in),
+        "Expected {}x{} LinearParams, got {:?}",
+        filters_out,
+        offsets.nrows() * filters_in,
+        params.dim()
+    );
+
+    // dense kernel over the offsets bounding box, strided by the offsets gcd on each axis
+    let axis = |a: usize| -> (isize, usize, usize) {
+        let mut values: Vec<isize> = offsets.column(a).iter().map(|&v| v as isize).collect();
+        values.sort();
+        values.dedup();
+        let dilation = values.windows(2).map(|w| (w[1] - w[0]) as usize).reduce(gcd).unwrap_or(1);
+        let len = (values[values.len() - 1] - values[0]) as usize / dilation + 1;
+        (values[0], dilation, len)
+    };
+    let (time_min, time_dilation, time_len) = axis(0);
+    let (height_min, height_dilation, height_len) = axis(1);
+    let mut kernel =
+        tract_ndarray::Array4::<f32>::zeros((time_len, height_len, filters_in, filters_out));
+    for (ix, offset) in offsets.outer_iter().enumerate() {
+        let t = (offset[0] as isize - time_min) as usize / time_dilation;
+        let h = (offset[1] as isize - height_min) as usize / height_dilation;
+        let block = params.slice(tract_ndarray::s![.., ix * filters_in..(ix + 1) * filters_in]);
+        kernel.slice_mut(tract_ndarray::s![t, h, .., ..]).assign(&block.t());
+    }
+
+    // output height h reads the input at h * subsample + height offset: shift and pad the
+    // input so the first kernel row lands at the lowest offset
+    let (height_start, pad_before) =
+        if height_min >= 0 { (height_min as usize, 0) } else { (0, (-height_min) as usize) };
+    let height_max = height_min + ((height_len - 1) * height_dilation) as isize;
+    let last = (height_out as isize - 1) * subsample as isize + height_max + 1;
+    let pad_after = (last - height_in as isize).max(0) as usize;
+
+    let bias = component.attributes.get("BiasParams").filter(|b| b.len() == filters_out).cloned();
+    Ok(expand(TimeHeightConvolution {
+        filters_in,
+        filters_out,
+        height_in,
+        height_out,
+        height_start,
+        padding: (pad_before, pad_after),
+        subsample,
+        dilations: (time_dilation, height_dilation),
+        kernel: kernel.into_arc_tensor(),
+        bias,
+    }))
+}
+
+#[derive(Clone, Debug, Hash)]
+struct TimeHeightConvolution {
+    filters_in: usize,
+    filters_out: usize,
+    height_in: usize,
+    height_out: usize,
+    height_start: usize,
+    padding: (usize, usize),
+    subsample: usize,
+    dilations: (usize, usize),
+    kernel: Arc<Tensor>, // T•H•I•O = HWIO
+    bias: Option<Arc<Tensor>>,
+}
+
+impl_dyn_hash!(TimeHeightConvolution);
+
+impl TimeHeightConvolution {
+    fn kernel_time_extent(&self) -> usize {
+        (self.kernel.shape()[0] - 1) * self.dilations.0 + 1
+    }
+}
+
+impl Expansion for TimeHeightConvolution {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "TimeHeightConvolution".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], (self.height_in * self.filters_in).to_dim())?;
+        s.equals(&outputs[0].shape[1], (self.height_out * self.filters_out).to_dim())?;
+        s.given(&inputs[0].shape[0], move |s, frames| {
+            s.equals(&outputs[0].shape[0], frames - self.kernel_time_extent() + 1)
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut wire = model.wire_node(
+            prefix.to_string() + ".split",
+            AxisOp::Reshape(
+                1,
+                tvec!((self.height_in * self.filters_in).to_dim()),
+                tvec!(self.height_in.to_dim(), self.filters_in.to_dim()),
+            ),
+            inputs,
+        )?;
+        if self.height_start > 0 {
+            wire = model.wire_node(
+                prefix.to_string() + ".shift",
+                tract_hir::ops::array::Slice::new(1, self.height_start, self.height_in),
+                &wire,
+            )?;
+        }
+        wire = model.wire_node(
+            prefix.to_string() + ".conv",
+            ConvUnary {
+                pool_spec: PoolSpec::new(
+                    DataFormat::HWC,
+                    self.kernel.shape()[0..2].into(),
+                    PaddingSpec::Explicit(
+                        tvec!(0, self.padding.0),
+                        tvec!(0, self.padding.1),
+                        false,
+                    ),
+                    Some(tvec!(self.dilations.0, self.dilations.1)),
+                    Some(tvec!(1, self.subsample)),
+                    Some(self.filters_out),
+                ),
+                kernel_fmt: KernelFormat::HWIO,
+                kernel: self.kernel.clone(),
+                group: 1,
+                bias: self.bias.clone(),
+                q_params: None,
+            },
+            &wire,
+        )?;
+        let height = model.outlet_fact(wire[0])?.shape[1].to_usize()?;
+        if height > self.height_out {
+            wire = model.wire_node(
+                prefix.to_string() + ".crop",
+                tract_hir::ops::array::Slice::new(1, 0, self.height_out),
+                &wire,
+            )?;
+        }
+        model.wire_node(
+            prefix,
+            AxisOp::Reshape(
+                1,
+                tvec!(self.height_out.to_dim(), self.filters_out.to_dim()),
+                tvec!((self.height_out * self.filters_out).to_dim()),
+            ),
+            &wire,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 1 filter in, 2 filters out, heights 3 -> 2 with subsampling. Offsets: (-1, 0), (0, -1),
+    // (0, 1), weighted 1, 10 and 100 by filter 0 and summed by filter 1.
+    const MODEL: &str = r#"<Nnet3>
+input-node name=input dim=3
+component-node name=conv input=input component=conv
+output-node name=output input=conv
+
+<NumComponents> 1
+<ComponentName> conv <TimeHeightConvolutionComponent> <LearningRate> 0.001 <Model> <ConvolutionModel> <NumFiltersIn> 1 <NumFiltersOut> 2 <HeightIn> 3 <HeightOut> 2 <HeightSubsampleOut> 2 <Offsets> [ -1,0 0,-1 0,1 ] <RequiredTimeOffsets> [ -1 0 ] </ConvolutionModel> <LinearParams> [
+  1 10 100
+  1 1 1 ]
+<BiasParams> [ 0 0.5 ]
+<MaxMemoryMb> 200 <UseNaturalGradient> T <NumMinibatchesHistory> 4 <AlphaInOut> 4 4 <RankInOut> 20 20 </TimeHeightConvolutionComponent>
+</Nnet3>"#;
+
+    #[test]
+    fn time_height_convolution() -> TractResult<()> {
+        let proto = crate::parser::nnet3(MODEL.as_bytes())?;
+        let model = crate::kaldi().model_for_proto_model(&proto)?.into_typed()?;
+        let input = tensor2(&[[1f32, 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+        let output = model.into_runnable()?.run(tvec!(input.into()))?;
+        // frame t, height h: x[t-1][2h] + 10 * x[t][2h-1] + 100 * x[t][2h+1]
+        let expected = |t: usize, h: usize, f: usize| {
+            let x = |t: usize, h: isize| {
+                if (0..3).contains(&h) {
+                    (t * 3 + h as usize + 1) as f32
+                } else {
+                    0.
+                }
+            };
+            let h = 2 * h as isize;
+            let terms = [x(t - 1, h), x(t, h - 1), x(t, h + 1)];
+            if f == 0 {
+                terms[0] + 10. * terms[1] + 100. * terms[2]
+            } else {
+                terms.iter().sum::<f32>() + 0.5
+            }
+        };
+        let expected: Vec<f32> = (1..3)
+            .flat_map(|t| (0..2).flat_map(move |h| (0..2).map(move |f| expected(t, h, f))))
+            .collect();
+        assert_eq!(*output[0], Tensor::from_shape(&[2, 4], &expected)?);
+        Ok(())
+    }
+}
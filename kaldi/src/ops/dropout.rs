@@ -0,0 +1,57 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+
+/// DropoutMaskComponent at test time: its expected value, as the mask is random.
+pub fn dropout_mask(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let output_dim = component.usize_attribute("OutputDim")?;
+    let proportion = component.f32_attribute("DropoutProportion", 0.0)?;
+    let continuous = component.attributes.contains_key("Continuous");
+    // continuous masks are centered on one, binary ones keep 1 - p of the values
+    let value = if continuous { 1.0 } else { 1.0 - proportion };
+    Ok(expand(DropoutMask::new(output_dim, value)))
+}
+
+/// Constant mask, with as many frames as the input.
+#[derive(Clone, Debug, new, Educe)]
+#[educe(Hash)]
+struct DropoutMask {
+    output_dim: usize,
+    #[educe(Hash(method = "hash_f32"))]
+    value: f32,
+}
+
+impl_dyn_hash!(DropoutMask);
+
+impl Expansion for DropoutMask {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "DropoutMask".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.output_dim.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mask = tract_ndarray::Array2::from_elem((1, self.output_dim), self.value);
+        let mask = model.add_const(prefix.to_string() + ".mask", mask.into_arc_tensor())?;
+        model.wire_node(prefix, crate::ops::frames::BroadcastFrames, &[mask, inputs[0]])
+    }
+}
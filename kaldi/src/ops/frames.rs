@@ -0,0 +1,217 @@
+//! Ops picking frames along the time axis, for descriptors that remap time indices.
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::array::MultiBroadcastTo;
+
+/// Repeats the single frame of its first input as many times as its second input has frames.
+#[derive(Clone, Debug, Hash)]
+pub struct BroadcastFrames;
+
+impl_dyn_hash!(BroadcastFrames);
+
+impl Op for BroadcastFrames {
+    fn name(&self) -> Cow<str> {
+        "BroadcastFrames".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for BroadcastFrames {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (frame, reference) = args_2!(inputs);
+        let mut shape: TVec<usize> = frame.shape().into();
+        shape[0] = reference.shape()[0];
+        dispatch_datum!(MultiBroadcastTo::eval_t(frame.datum_type())(&frame, &shape))
+    }
+}
+
+impl TypedOp for BroadcastFrames {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].shape[0] == 1.to_dim(), "Expected a single frame");
+        let mut shape = inputs[0].shape.clone();
+        shape.set(0, inputs[1].shape[0].clone());
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+}
+
+impl InferenceRulesOp for BroadcastFrames {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], 1.to_dim())?;
+        s.equals(&inputs[0].shape[1], &outputs[0].shape[1])?;
+        s.equals(&inputs[1].shape[0], &outputs[0].shape[0])?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+/// `Round(input, modulus)`: frame t is the input frame `t - t % modulus`.
+#[derive(Clone, Debug, new, Hash)]
+pub struct RoundFrames {
+    pub modulus: usize,
+}
+
+impl_dyn_hash!(RoundFrames);
+
+impl Op for RoundFrames {
+    fn name(&self) -> Cow<str> {
+        "RoundFrames".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("modulus: {}", self.modulus)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoundFrames {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let mut output = input.clone().into_tensor();
+        for t in 0..input.shape()[0] {
+            let src = t - t % self.modulus;
+            output.assign_slice(t..t + 1, &input, src..src + 1, 0)?;
+        }
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for RoundFrames {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].without_value()))
+    }
+}
+
+impl InferenceRulesOp for RoundFrames {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+/// `Switch(input_0, .., input_n-1)`: frame t is frame t of input `t % n`.
+#[derive(Clone, Debug, Hash)]
+pub struct SwitchFrames;
+
+impl_dyn_hash!(SwitchFrames);
+
+impl Op for SwitchFrames {
+    fn name(&self) -> Cow<str> {
+        "SwitchFrames".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SwitchFrames {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let mut output = inputs[0].clone().into_tensor();
+        for t in 0..output.shape()[0] {
+            output.assign_slice(t..t + 1, &inputs[t % inputs.len()], t..t + 1, 0)?;
+        }
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for SwitchFrames {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(
+            inputs.iter().all(|i| i.shape == inputs[0].shape),
+            "Switch inputs must have the same shape"
+        );
+        Ok(tvec!(inputs[0].without_value()))
+    }
+}
+
+impl InferenceRulesOp for SwitchFrames {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_output_arity(outputs, 1)?;
+        for input in inputs {
+            s.equals(&input.datum_type, &outputs[0].datum_type)?;
+            s.equals(&input.shape, &outputs[0].shape)?;
+        }
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frames(t: usize) -> TValue {
+        Tensor::from_shape(&[t, 1], &(0..t).map(|i| i as f32).collect::<Vec<_>>())
+            .unwrap()
+            .into_tvalue()
+    }
+
+    #[test]
+    fn round() -> TractResult<()> {
+        let output = RoundFrames::new(3).eval(tvec!(frames(7)))?;
+        assert_eq!(*output[0], tensor2(&[[0f32], [0.], [0.], [3.], [3.], [3.], [6.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn switch() -> TractResult<()> {
+        let negated = (-frames(5).into_tensor().into_array::<f32>()?).into_tvalue();
+        let output = SwitchFrames.eval(tvec!(frames(5), negated))?;
+        assert_eq!(*output[0], tensor2(&[[0f32], [-1.], [2.], [-3.], [4.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn broadcast() -> TractResult<()> {
+        let output = BroadcastFrames.eval(tvec!(tensor2(&[[1f32, 2.]]).into(), frames(3)))?;
+        assert_eq!(*output[0], tensor2(&[[1f32, 2.], [1., 2.], [1., 2.]]));
+        Ok(())
+    }
+}
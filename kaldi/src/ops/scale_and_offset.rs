@@ -0,0 +1,114 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+
+pub fn scale_and_offset(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = component.usize_attribute("Dim")?;
+    let scales = component.attribute("Scales")?.as_slice::<f32>()?.to_vec();
+    let offsets = component.attribute("Offsets")?.as_slice::<f32>()?.to_vec();
+    Ok(expand(ScaleAndOffset::tiled(dim, &scales, &offsets)?))
+}
+
+/// BatchNormComponent in test mode, using the accumulated statistics.
+pub fn batch_norm(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = component.usize_attribute("Dim")?;
+    let epsilon = component.f32_attribute("Epsilon", 0.001)?;
+    let target_rms = component.f32_attribute("TargetRms", 1.0)?;
+    let mean = component.attribute("StatsMean")?.as_slice::<f32>()?;
+    let var = component.attribute("StatsVar")?.as_slice::<f32>()?;
+    let scales: Vec<f32> = var.iter().map(|v| target_rms * (v + epsilon).powf(-0.5)).collect();
+    let offsets: Vec<f32> = mean.iter().zip(scales.iter()).map(|(m, s)| -m * s).collect();
+    Ok(expand(ScaleAndOffset::tiled(dim, &scales, &offsets)?))
+}
+
+#[derive(Clone, Debug, Hash)]
+struct ScaleAndOffset {
+    scales: Arc<Tensor>,
+    offsets: Arc<Tensor>,
+}
+
+impl_dyn_hash!(ScaleAndOffset);
+
+impl ScaleAndOffset {
+    /// Per-block parameters, repeated over the `dim` features.
+    fn tiled(dim: usize, scales: &[f32], offsets: &[f32]) -> TractResult<ScaleAndOffset> {
+        let block_dim = scales.len();
+        ensure!(
+            block_dim > 0 && offsets.len() == block_dim && dim % block_dim == 0,
+            "Inconsistent dim ({}), scales ({}) and offsets ({})",
+            dim,
+            block_dim,
+            offsets.len()
+        );
+        let tile = |v: &[f32]| -> TractResult<Arc<Tensor>> {
+            let data: Vec<f32> = v.iter().cycle().take(dim).cloned().collect();
+            Ok(Tensor::from_shape(&[1, dim], &data)?.into_arc_tensor())
+        };
+        Ok(ScaleAndOffset { scales: tile(scales)?, offsets: tile(offsets)? })
+    }
+}
+
+impl Expansion for ScaleAndOffset {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "ScaleAndOffset".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        s.equals(&inputs[0].shape[1], self.scales.shape()[1].to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let scales = model.add_const(prefix.to_string() + ".scales", self.scales.clone())?;
+        let offsets = model.add_const(prefix.to_string() + ".offsets", self.offsets.clone())?;
+        let scaled = model.wire_node(
+            prefix.to_string() + ".scale",
+            tract_hir::ops::math::mul(),
+            &[inputs[0], scales],
+        )?;
+        model.wire_node(prefix, tract_hir::ops::math::add(), &[scaled[0], offsets])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn batch_norm_blocks() -> TractResult<()> {
+        let slice = r#"<Nnet3>
+input-node name=input dim=4
+component-node name=bn input=input component=bn
+output-node name=output input=bn
+
+<NumComponents> 1
+<ComponentName> bn <BatchNormComponent> <Dim> 4 <BlockDim> 2 <Epsilon> 0 <TargetRms> 2 <TestMode> T <Count> 100 <StatsMean> [ 1 -1 ]
+<StatsVar> [ 4 16 ]
+</BatchNormComponent>
+</Nnet3>"#;
+        let proto = crate::parser::nnet3(slice.as_bytes())?;
+        let model = crate::kaldi().model_for_proto_model(&proto)?.into_typed()?;
+        let input = tensor2(&[[1f32, 3., 5., 7.]]);
+        let output = model.into_runnable()?.run(tvec!(input.into()))?;
+        assert_eq!(*output[0], tensor2(&[[0f32, 2., 4., 4.]]));
+        Ok(())
+    }
+}
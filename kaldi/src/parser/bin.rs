@@ -3,14 +3,64 @@ use tract_hir::internal::*;
 use nom::combinator::*;
 use nom::IResult;
 
-use super::components::COMPONENTS;
+use super::components::{KaldiAttributeKind, COMPONENTS};
 
 pub fn attributes<'a>(i: &'a [u8], klass: &str) -> IResult<&'a [u8], HashMap<String, Arc<Tensor>>> {
-    map(nom::multi::many0(|j| attribute(j, klass)), |v| v.into_iter().collect())(i)
+    map(nom::multi::many0(|j| attribute(j, klass)), |v| v.into_iter().flatten().collect())(i)
 }
 
-fn attribute<'a>(i: &'a [u8], klass: &str) -> IResult<&'a [u8], (String, Arc<Tensor>)> {
+fn attribute<'a>(i: &'a [u8], klass: &str) -> IResult<&'a [u8], Vec<(String, Arc<Tensor>)>> {
     let (i, name) = super::open_any(i)?;
-    let (i, value) = COMPONENTS[klass][name].parse_bin(i)?;
-    Ok((i, (name.to_string(), value.into_arc_tensor())))
+    if let KaldiAttributeKind::Nested(nested) = COMPONENTS[klass][name] {
+        // nested attributes are flattened as "<attribute>.<nested attribute>"
+        let (i, _) = super::open(i, nested)?;
+        let (i, attributes) = attributes(i, nested)?;
+        let (i, _) = super::close(i, nested)?;
+        let attributes =
+            attributes.into_iter().map(|(k, v)| (format!("{}.{}", name, k), v)).collect();
+        Ok((i, attributes))
+    } else {
+        let (i, value) = COMPONENTS[klass][name].parse_bin(i)?;
+        Ok((i, vec![(name.to_string(), value.into_arc_tensor())]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tdnn_and_dropout() {
+        let mut bin: Vec<u8> = b"<TimeOffsets> \x04".to_vec();
+        bin.extend([2i32, -1, 1].iter().flat_map(|i| i.to_le_bytes()));
+        bin.extend(b"<AlphaInOut> \x04");
+        bin.extend(4f32.to_le_bytes());
+        bin.push(4);
+        bin.extend(2f32.to_le_bytes());
+        bin.extend(b"<UseNaturalGradient> T</TdnnComponent>");
+        let (rest, tdnn) = attributes(&bin, "TdnnComponent").unwrap();
+        assert_eq!(rest, b"</TdnnComponent>");
+        assert_eq!(*tdnn["TimeOffsets"], tensor1(&[-1i32, 1]));
+        assert_eq!(*tdnn["AlphaInOut"], tensor1(&[4f32, 2.]));
+        assert_eq!(*tdnn["UseNaturalGradient"], tensor0(true));
+
+        let mut bin: Vec<u8> = b"<OutputDim> \x04".to_vec();
+        bin.extend(10i32.to_le_bytes());
+        bin.extend(b" <Continuous> </DropoutMaskComponent>");
+        let (_, dropout) = attributes(&bin, "DropoutMaskComponent").unwrap();
+        assert_eq!(*dropout["OutputDim"], tensor0(10i32));
+        assert_eq!(*dropout["Continuous"], tensor0(true));
+    }
+
+    #[test]
+    fn convolution_model() {
+        let mut bin: Vec<u8> = b"<Model> <ConvolutionModel> <NumFiltersIn> \x04".to_vec();
+        bin.extend(3i32.to_le_bytes());
+        bin.extend(b"<Offsets> \x04");
+        bin.extend([2i32, -1, 0, 0, 1].iter().flat_map(|i| i.to_le_bytes()));
+        bin.extend(b"</ConvolutionModel> </TimeHeightConvolutionComponent>");
+        let (_, conv) = attributes(&bin, "TimeHeightConvolutionComponent").unwrap();
+        assert_eq!(*conv["Model.NumFiltersIn"], tensor0(3i32));
+        assert_eq!(*conv["Model.Offsets"], tensor2(&[[-1i32, 0], [0, 1]]));
+    }
 }
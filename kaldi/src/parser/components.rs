@@ -10,7 +10,7 @@ use nom::{
     bytes::complete::*,
     combinator::*,
     multi::many_m_n,
-    number::complete::{le_f32, le_f64},
+    number::complete::{le_f32, le_f64, le_i32},
     sequence::*,
     IResult,
 };
@@ -21,6 +21,15 @@ pub enum KaldiAttributeKind {
     Float,
     FloatVector,
     FloatMatrix,
+    /// Two scalars in a row, like `<RankInOut>`.
+    IntPair,
+    FloatPair,
+    IntVector,
+    IntPairVector,
+    /// Token without value, present only when true (like `<Continuous>`).
+    Flag,
+    /// Embedded object with its own attributes, described by another entry in `COMPONENTS`.
+    Nested(&'static str),
 }
 
 impl KaldiAttributeKind {
@@ -34,6 +43,18 @@ impl KaldiAttributeKind {
             Float => map(Self::parse_float_value, Tensor::from)(i),
             FloatVector => preceded(multispaced(tag("FV")), Self::parse_float_vector)(i),
             FloatMatrix => preceded(multispaced(tag("FM")), Self::parse_float_matrix)(i),
+            IntPair => {
+                map(pair(super::integer(true), super::integer(true)), |(a, b)| tensor1(&[a, b]))(i)
+            }
+            FloatPair => map(pair(Self::parse_float_value, Self::parse_float_value), |(a, b)| {
+                tensor1(&[a, b])
+            })(i),
+            IntVector => map(Self::parse_int_vector(1), |v| tensor1(&v))(i),
+            IntPairVector => map_res(Self::parse_int_vector(2), |v| {
+                tract_ndarray::Array2::from_shape_vec((v.len() / 2, 2), v).map(Tensor::from)
+            })(i),
+            Flag => Ok((i, Tensor::from(true))),
+            Nested(_) => unreachable!("nested attributes are parsed by the caller"),
         }
     }
 
@@ -41,6 +62,20 @@ impl KaldiAttributeKind {
         alt((preceded(tag([4]), le_f32), map(preceded(tag([8]), le_f64), |f| f as f32)))(i)
     }
 
+    // integer vectors are not written through WriteBasicType: element size, length and
+    // data follow with no more size tags
+    fn parse_int_vector<'a>(arity: usize) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<i32>> {
+        move |i| {
+            let (i, len) = preceded(tag([4]), le_i32)(i)?;
+            let len = len as usize * arity;
+            if len == 0 {
+                Ok((i, vec![]))
+            } else {
+                many_m_n(len, len, le_i32)(i)
+            }
+        }
+    }
+
     fn parse_float_vector(i: &[u8]) -> IResult<&[u8], Tensor> {
         let (i, len) = super::integer(true)(i)?;
         // FIXME pending merge of https://github.com/Geal/nom/pull/995
@@ -73,6 +108,34 @@ use KaldiAttributeKind::*;
 
 lazy_static::lazy_static! {
     pub static ref COMPONENTS: HashMap<&'static str, HashMap<&'static str, KaldiAttributeKind>> = hashmap! {
+        "BatchNormComponent" => hashmap! {
+            "Dim" => Int,
+            "BlockDim" => Int,
+            "Epsilon" => Float,
+            "TargetRms" => Float,
+            "TestMode" => Bool,
+            "Count" => Float,
+            "StatsMean" => FloatVector,
+            "StatsVar" => FloatVector,
+        },
+        "ConvolutionModel" => hashmap! {
+            "NumFiltersIn" => Int,
+            "NumFiltersOut" => Int,
+            "HeightIn" => Int,
+            "HeightOut" => Int,
+            "HeightSubsampleOut" => Int,
+            "Offsets" => IntPairVector,
+            "RequiredTimeOffsets" => IntVector,
+        },
+        "DropoutMaskComponent" => hashmap! {
+            "OutputDim" => Int,
+            "DropoutProportion" => Float,
+            "Continuous" => Flag,
+        },
+        "ElementwiseProductComponent" => hashmap! {
+            "InputDim" => Int,
+            "OutputDim" => Int,
+        },
         "FixedAffineComponent" => hashmap! {
             "LinearParams" => FloatMatrix,
             "BiasParams" => FloatVector,
@@ -95,6 +158,24 @@ lazy_static::lazy_static! {
             "TargetRms" => Float,
             "AddLogStddev" => Bool,
         },
+        "GeneralDropoutComponent" => hashmap! {
+            "Dim" => Int,
+            "BlockDim" => Int,
+            "TimePeriod" => Int,
+            "DropoutProportion" => Float,
+            "SpecAugmentMaxProportion" => Float,
+            "SpecAugmentMaxRegions" => Int,
+            "Continuous" => Flag,
+        },
+        "LinearComponent" => updatable(hashmap! {
+            "Params" => FloatMatrix,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "RankInOut" => IntPair,
+            "Alpha" => Float,
+            "NumSamplesHistory" => Float,
+            "UpdatePeriod" => Int,
+        }),
         "FakeQuantizationComponent" => hashmap!{
             "Activated" => Bool,
             "Dim" => Int,
@@ -139,6 +220,72 @@ lazy_static::lazy_static! {
             "NumDimsSelfRepaired" => Float,
             "NumDimsProcessed" => Float,
             "SelfRepairScale" => Float,
-        }
+        },
+        "ScaleAndOffsetComponent" => updatable(hashmap! {
+            "Dim" => Int,
+            "Scales" => FloatVector,
+            "Offsets" => FloatVector,
+            "UseNaturalGradient" => Bool,
+            "Rank" => Int,
+        }),
+        "SigmoidComponent" => nonlinear(),
+        "SumBlockComponent" => hashmap! {
+            "InputDim" => Int,
+            "OutputDim" => Int,
+            "Scale" => Float,
+        },
+        "TanhComponent" => nonlinear(),
+        "TdnnComponent" => updatable(hashmap! {
+            "TimeOffsets" => IntVector,
+            "LinearParams" => FloatMatrix,
+            "BiasParams" => FloatVector,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "NumSamplesHistory" => Float,
+            "AlphaInOut" => FloatPair,
+            "RankInOut" => IntPair,
+        }),
+        "TimeHeightConvolutionComponent" => updatable(hashmap! {
+            "Model" => Nested("ConvolutionModel"),
+            "LinearParams" => FloatMatrix,
+            "BiasParams" => FloatVector,
+            "MaxMemoryMb" => Float,
+            "UseNaturalGradient" => Bool,
+            "NumMinibatchesHistory" => Float,
+            "AlphaInOut" => FloatPair,
+            "RankInOut" => IntPair,
+        }),
     };
 }
+
+/// Attributes common to all UpdatableComponent, written before the specific ones.
+fn updatable(
+    mut attributes: HashMap<&'static str, KaldiAttributeKind>,
+) -> HashMap<&'static str, KaldiAttributeKind> {
+    attributes.extend(hashmap! {
+        "LearningRateFactor" => Float,
+        "IsGradient" => Bool,
+        "MaxChange" => Float,
+        "L2Regularize" => Float,
+        "LearningRate" => Float,
+    });
+    attributes
+}
+
+/// Attributes of the NonlinearComponent family (statistics and self-repair settings).
+fn nonlinear() -> HashMap<&'static str, KaldiAttributeKind> {
+    hashmap! {
+        "Dim" => Int,
+        "BlockDim" => Int,
+        "ValueAvg" => FloatVector,
+        "DerivAvg" => FloatVector,
+        "Count" => Float,
+        "OderivRms" => FloatVector,
+        "OderivCount" => Float,
+        "NumDimsSelfRepaired" => Float,
+        "NumDimsProcessed" => Float,
+        "SelfRepairLowerThreshold" => Float,
+        "SelfRepairUpperThreshold" => Float,
+        "SelfRepairScale" => Float,
+    }
+}
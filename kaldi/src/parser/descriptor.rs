@@ -1,6 +1,7 @@
 use nom::IResult;
 use nom::{
-    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list0, sequence::*,
+    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list0,
+    number::complete::float, sequence::*,
 };
 
 use crate::model::GeneralDescriptor;
@@ -8,6 +9,8 @@ use crate::parser::spaced;
 
 pub fn parse_general(i: &str) -> IResult<&str, GeneralDescriptor> {
     spaced(nom::branch::alt((
+        parse_arithmetic,
+        parse_time,
         map(
             preceded(
                 tag("Append"),
@@ -41,6 +44,52 @@ pub fn parse_general(i: &str) -> IResult<&str, GeneralDescriptor> {
     )))(i)
 }
 
+// Sum, Scale and Const
+fn parse_arithmetic(i: &str) -> IResult<&str, GeneralDescriptor> {
+    nom::branch::alt((
+        map(
+            call("Sum", separated_pair(parse_general, spaced(tag(",")), parse_general)),
+            |(a, b)| GeneralDescriptor::Sum(Box::new(a), Box::new(b)),
+        ),
+        map(call("Scale", separated_pair(float, spaced(tag(",")), parse_general)), |(s, d)| {
+            GeneralDescriptor::Scale(s, Box::new(d))
+        }),
+        map(call("Const", separated_pair(float, spaced(tag(",")), integer)), |(v, dim)| {
+            GeneralDescriptor::Const(v, dim as usize)
+        }),
+    ))(i)
+}
+
+// Round, ReplaceIndex and Switch
+fn parse_time(i: &str) -> IResult<&str, GeneralDescriptor> {
+    nom::branch::alt((
+        map(call("Round", separated_pair(parse_general, spaced(tag(",")), integer)), |(d, m)| {
+            GeneralDescriptor::Round(Box::new(d), m as usize)
+        }),
+        map(
+            call(
+                "ReplaceIndex",
+                tuple((parse_general, spaced(tag(",")), one_of("tx"), spaced(tag(",")), integer)),
+            ),
+            |(d, _, var, _, value)| {
+                GeneralDescriptor::ReplaceIndex(Box::new(d), var, value as isize)
+            },
+        ),
+        map(call("Switch", separated_list0(spaced(tag(",")), parse_general)), |cases| {
+            GeneralDescriptor::Switch(cases)
+        }),
+    ))(i)
+}
+
+/// `name(args)`. Only commits once the opening parenthesis is found, so node names
+/// starting like a descriptor are still parsed as names.
+fn call<'a, O>(
+    name: &'static str,
+    args: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    preceded(pair(tag(name), spaced(tag("("))), cut(terminated(args, spaced(tag(")")))))
+}
+
 pub fn integer(i: &str) -> IResult<&str, i32> {
     map_res(recognize(pair(opt(tag("-")), digit1)), |s: &str| s.parse::<i32>())(i)
 }
@@ -71,6 +120,36 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_tdnnf() {
+        assert_eq!(
+            parse_general("Sum(Scale(0.66, tdnnf2.noop), tdnnf3.affine)").unwrap().1,
+            Sum(Scale(0.66, name("tdnnf2.noop").into()).into(), name("tdnnf3.affine").into())
+        )
+    }
+
+    #[test]
+    fn test_ivector() {
+        assert_eq!(
+            parse_general("Append(Offset(input, -1), input, ReplaceIndex(ivector, t, 0))")
+                .unwrap()
+                .1,
+            Append(vec!(
+                Offset(name("input").into(), -1),
+                name("input"),
+                ReplaceIndex(name("ivector").into(), 't', 0)
+            ))
+        )
+    }
+
+    #[test]
+    fn test_time_descriptors() {
+        assert_eq!(
+            parse_general("Switch(Round(a, 3), Const(1.5, 10), Scaled)").unwrap().1,
+            Switch(vec!(Round(name("a").into(), 3), Const(1.5, 10), name("Scaled")))
+        )
+    }
+
     #[test]
     fn test_lstm() {
         assert_eq!(
@@ -2,21 +2,56 @@ use tract_hir::internal::*;
 
 use nom::IResult;
 use nom::{
-    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list0,
-    number::complete::float, sequence::*,
+    bytes::complete::*,
+    character::complete::*,
+    combinator::*,
+    multi::{many0, separated_list0},
+    number::complete::float,
+    sequence::*,
 };
 
-use super::{integer, multispaced, open_any, spaced};
+use super::{close, integer, multispaced, open_any, spaced};
 
 pub fn attributes(i: &[u8]) -> IResult<&[u8], HashMap<String, Arc<Tensor>>> {
-    let (i, attributes) = nom::multi::many0(map(pair(open_any, tensor), |(k, v)| {
-        (k.to_string(), v.into_arc_tensor())
-    }))(i)?;
-    Ok((i, attributes.into_iter().collect()))
+    let (i, attributes) = many0(attribute)(i)?;
+    Ok((i, attributes.into_iter().flatten().collect()))
+}
+
+fn attribute(i: &[u8]) -> IResult<&[u8], Vec<(String, Arc<Tensor>)>> {
+    let (i, name) = open_any(i)?;
+    nom::branch::alt((
+        // nested attributes are flattened as "<attribute>.<nested attribute>"
+        map(nested, move |attributes| {
+            attributes.into_iter().map(|(k, v)| (format!("{}.{}", name, k), v)).collect()
+        }),
+        map(tensor, move |t| vec![(name.to_string(), t.into_arc_tensor())]),
+        // flags like <Continuous> have no value
+        map(success(()), move |_| vec![(name.to_string(), Tensor::from(true).into_arc_tensor())]),
+    ))(i)
+}
+
+fn nested(i: &[u8]) -> IResult<&[u8], HashMap<String, Arc<Tensor>>> {
+    let (i, klass) = open_any(i)?;
+    let (i, attributes) = attributes(i)?;
+    let (i, _) = close(i, klass)?;
+    Ok((i, attributes))
 }
 
 pub fn tensor(i: &[u8]) -> IResult<&[u8], Tensor> {
-    nom::branch::alt((scalar, vector, matrix))(i)
+    nom::branch::alt((scalars, vector, matrix, int_pair_vector))(i)
+}
+
+/// One scalar, or several on the same line (like `<RankInOut> 40 80`) as a vector.
+pub fn scalars(i: &[u8]) -> IResult<&[u8], Tensor> {
+    map_res(pair(scalar, many0(preceded(space1, float))), |(first, rest)| {
+        if rest.is_empty() {
+            Ok(first)
+        } else {
+            let mut values = vec![first.cast_to_scalar::<f32>()?];
+            values.extend(rest);
+            Ok::<_, TractError>(tensor1(&values))
+        }
+    })(i)
 }
 
 pub fn scalar(i: &[u8]) -> IResult<&[u8], Tensor> {
@@ -34,6 +69,20 @@ pub fn vector(i: &[u8]) -> IResult<&[u8], Tensor> {
     })(i)
 }
 
+pub fn int_pair_vector(i: &[u8]) -> IResult<&[u8], Tensor> {
+    map_res(
+        delimited(
+            spaced(tag("[")),
+            separated_list0(space1, separated_pair(integer(false), tag(","), integer(false))),
+            spaced(tag("]")),
+        ),
+        |pairs| {
+            let data = pairs.into_iter().flat_map(|(a, b)| [a, b]).collect::<Vec<i32>>();
+            tract_ndarray::Array2::from_shape_vec((data.len() / 2, 2), data).map(Tensor::from)
+        },
+    )(i)
+}
+
 pub fn matrix(i: &[u8]) -> IResult<&[u8], Tensor> {
     let (i, v) = delimited(
         multispaced(tag("[")),
@@ -70,6 +70,7 @@ pub struct TensorValues {
 
 fn parse_dt(dt: &str) -> TractResult<DatumType> {
     Ok(match dt.to_lowercase().as_ref() {
+        "bool" => DatumType::Bool,
         "f16" => DatumType::F16,
         "f32" => DatumType::F32,
         "f64" => DatumType::F64,
@@ -83,7 +84,8 @@ fn parse_dt(dt: &str) -> TractResult<DatumType> {
         "u64" => DatumType::U64,
         "tdim" => DatumType::TDim,
         _ => bail!(
-            "Type of the input should be f16, f32, f64, i8, i16, i16, i32, u8, u16, u32, u64, TDim."
+            "Type of the input should be bool, f16, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, \
+             TDim."
             ),
     })
 }
@@ -254,7 +256,11 @@ pub fn for_string(
                 .shape
                 .as_concrete_finite()?
                 .context("Must specify concrete shape when giving tensor value")?;
-            let tensor = dispatch_numbers!(parse_values(dt)(&*shape, value.collect()))?;
+            let tensor = if dt == bool::datum_type() {
+                parse_values::<bool>(&shape, value.collect())?
+            } else {
+                dispatch_numbers!(parse_values(dt)(&*shape, value.collect()))?
+            };
             Ok((name, tensor.into()))
         } else {
             Ok((name, parse_spec(symbol_table, value)?))
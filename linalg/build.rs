@@ -182,6 +182,8 @@ fn preprocess_files(
     suffix: &str,
     needs_pragma: bool,
 ) -> Vec<path::PathBuf> {
+    // picks up added templates
+    println!("cargo:rerun-if-changed={}", input.as_ref().to_string_lossy());
     let out_dir = path::PathBuf::from(var("OUT_DIR"));
     let mut files = vec![];
     let dir_entries = {
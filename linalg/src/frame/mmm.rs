@@ -25,6 +25,10 @@ pub fn no_prefetch(_ptr: *const u8, _len: usize) {}
 
 macro_rules! MMMKernel {
     ($ti:ident, $func:ident; $mr: expr, $nr: expr; $alignment_bytes_packed_a: expr, $alignment_bytes_packed_b: expr; $end_padding_packed_a: expr, $end_padding_packed_b: expr ; $prefetch: ident, $cond: expr) => {
+        MMMKernel!($ti [$ti], $func; $mr, $nr; $alignment_bytes_packed_a, $alignment_bytes_packed_b; $end_padding_packed_a, $end_padding_packed_b; $prefetch, $cond);
+    };
+    // $tp is the packed operands type, when it differs from the internal type
+    ($ti:ident [$tp:ident], $func:ident; $mr: expr, $nr: expr; $alignment_bytes_packed_a: expr, $alignment_bytes_packed_b: expr; $end_padding_packed_a: expr, $end_padding_packed_b: expr ; $prefetch: ident, $cond: expr) => {
         paste! {
             mod [<sys_ $func>] {
                 use crate::frame::mmm::*;
@@ -78,7 +82,7 @@ macro_rules! MMMKernel {
                 }
             }
         }
-        test_mmm_kernel!($ti, $func, $cond);
+        test_mmm_kernel!($tp, $func, $cond);
     };
 }
 
@@ -86,6 +90,9 @@ macro_rules! test_mmm_kernel {
     (f16, $func:ident, $cond: expr) => {
         test_mmm_kernel_f16!($func, $cond);
     };
+    (bf16, $func:ident, $cond: expr) => {
+        test_mmm_kernel_bf16!($func, $cond);
+    };
     (f32, $func:ident, $cond: expr) => {
         test_mmm_kernel_f32!($func, $cond);
     };
@@ -3,6 +3,7 @@ use std::fmt;
 use std::ops::Range;
 use tract_data::internal::*;
 use tract_data::internal::DynHash;
+use tract_data::internal::BlockQuant;
 
 use crate::frame::Packer;
 
@@ -34,6 +35,7 @@ pub enum InputStoreSpec {
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
 pub struct PackedStoreSpec {
     pub(crate) panel_bytes: usize,
+    pub(crate) block_quant: Option<BlockQuant>,
 }
 
 impl InputStoreSpec {
@@ -42,10 +44,7 @@ impl InputStoreSpec {
         use InputStore::*;
         use InputStoreSpec as S;
         match self {
-            S::Prepacked(PackedStoreSpec { panel_bytes }) => Ok(Packed(PackedStore {
-                ptr: tensor.as_ptr_unchecked::<u8>() as _,
-                panel_bytes: *panel_bytes as isize,
-            })),
+            S::Prepacked(spec) => Ok(Packed(spec.wrap(tensor))),
             S::LatePacking { packer, k_axis, mn_axis } => Ok(InputStore::LatePacking {
                 packer: packer.clone(),
                 ptr: tensor.as_ptr_unchecked::<u8>() as _,
@@ -81,6 +80,7 @@ impl PackedStoreSpec {
         PackedStore {
             ptr: tensor.as_ptr_unchecked::<u8>() as _,
             panel_bytes: self.panel_bytes as isize,
+            block_quant: self.block_quant.map(|format| (format, tensor.shape()[0])),
         }
     }
 }
@@ -109,6 +109,8 @@ pub enum InputStore {
 pub struct PackedStore {
     ptr: *const u8,
     panel_bytes: isize,
+    // block quantized rows (format, row count): panel_bytes is the size of a row
+    block_quant: Option<(BlockQuant, usize)>,
 }
 
 impl InputStore {
@@ -150,8 +152,65 @@ impl InputStore {
 }
 
 impl PackedStore {
+    pub(super) unsafe fn scratch_panel_buffer_layout(
+        &self,
+        packer: &Packer,
+        k: usize,
+        dt: DatumType,
+    ) -> Option<Layout> {
+        self.block_quant.map(|_| {
+            Layout::from_size_align_unchecked(
+                packer.single_panel_len(k) * dt.size_of(),
+                packer.alignment(),
+            )
+        })
+    }
+
     #[inline]
     pub(super) unsafe fn panel(&self, i: usize) -> *const u8 {
         self.ptr.offset(self.panel_bytes * i as isize)
     }
+
+    /// Same as panel(), but dequantizing block quantized rows in buffer.
+    #[inline]
+    pub(super) unsafe fn panel_a(
+        &self,
+        i: usize,
+        packer: &Packer,
+        k: usize,
+        dt: DatumType,
+        buffer: Option<*const u8>,
+    ) -> *const u8 {
+        if let Some((format, rows)) = self.block_quant {
+            let buffer = buffer.unwrap();
+            let mr = packer.r;
+            let mut block = [0f32; 32];
+            for r in 0..mr {
+                let row = i * mr + r;
+                let row_ptr = self.panel(row);
+                for b in 0..k / format.block_len() {
+                    if row < rows {
+                        let quant = std::slice::from_raw_parts(
+                            row_ptr.add(b * format.block_bytes()),
+                            format.block_bytes(),
+                        );
+                        format.dequant_block_f32(quant, &mut block);
+                    } else {
+                        block = [0f32; 32];
+                    }
+                    for (x, v) in block.iter().enumerate() {
+                        let offset = (b * format.block_len() + x) * mr + r;
+                        if dt == f16::datum_type() {
+                            *(buffer as *mut f16).add(offset) = f16::from_f32(*v);
+                        } else {
+                            *(buffer as *mut f32).add(offset) = *v;
+                        }
+                    }
+                }
+            }
+            buffer
+        } else {
+            self.panel(i)
+        }
+    }
 }
@@ -36,6 +36,27 @@ macro_rules! test_mmm_kernel_f16 {
                 mmm_kernel_tests!($cond, $k, f16, f16, f16, f16);
                 mmm_frame_tests!($cond, $k, f16, f16, f16, f16);
                 mmm_kernel_fuse_tests!($cond, $k, f16, f16);
+                mmm_block_quant_tests!($cond, $k, f16);
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! test_mmm_kernel_bf16 {
+    ($k: ident, $cond: expr) => {
+        paste! {
+            #[cfg(test)]
+            #[allow(non_snake_case)]
+            mod [<test_ $k>] {
+                mmm_kernel_tests!($cond, $k, bf16, bf16, f32, f32);
+                mmm_frame_tests!($cond, $k, bf16, bf16, f32, f32);
+                mmm_kernel_fuse_tests!($cond, $k, f32, f32);
+            }
+            #[cfg(test)]
+            #[allow(non_snake_case)]
+            mod [<test_bf16_out_ $k>] {
+                mmm_frame_tests!($cond, $k, bf16, bf16, bf16, f32);
             }
         }
     };
@@ -51,6 +72,7 @@ macro_rules! test_mmm_kernel_f32 {
                 mmm_kernel_tests!($cond, $k, f32, f32, f32, f32);
                 mmm_frame_tests!($cond, $k, f32, f32, f32, f32);
                 mmm_kernel_fuse_tests!($cond, $k, f32, f32);
+                mmm_block_quant_tests!($cond, $k, f32);
                 //qmmm_kernel_fuse_tests!($cond, $k, f32, f32, f32, f32);
             }
         }
@@ -116,7 +138,7 @@ pub mod test {
                 use num_traits::Zero;
                 use proptest::prelude::*;
                 #[allow(unused_imports)]
-                use tract_data::prelude::f16;
+                use tract_data::prelude::{bf16, f16};
                 #[allow(unused_imports)]
                 use $crate::frame::mmm::kernel::test;
                 use $crate::frame::mmm::kernel::test::PackedPackedProblem;
@@ -23,6 +23,9 @@ pub trait MatMatMul:
 
     unsafe fn a_packed(&self, item_size: usize, k: usize) -> PackedStoreSpec;
 
+    /// A stored as block quantized rows, dequantized panel by panel while running.
+    unsafe fn a_block_quant(&self, format: BlockQuant, k: usize) -> PackedStoreSpec;
+
     unsafe fn b_packed(&self, item_size: usize, k: usize) -> InputStoreSpec;
     unsafe fn b_late_packing(&self) -> InputStoreSpec {
         self.b_late_packing_with_axes(0, 1)
@@ -158,12 +161,16 @@ where
     }
 
     unsafe fn a_packed(&self, item_size: usize, k: usize) -> PackedStoreSpec {
-        PackedStoreSpec { panel_bytes: (k * K::mr() * item_size) }
+        PackedStoreSpec { panel_bytes: (k * K::mr() * item_size), block_quant: None }
+    }
+
+    unsafe fn a_block_quant(&self, format: BlockQuant, k: usize) -> PackedStoreSpec {
+        PackedStoreSpec { panel_bytes: format.storage_bytes(k), block_quant: Some(format) }
     }
 
     unsafe fn b_packed(&self, item_size: usize, k: usize) -> InputStoreSpec {
         let panel_bytes = k * K::nr() * item_size;
-        InputStoreSpec::Prepacked(PackedStoreSpec { panel_bytes })
+        InputStoreSpec::Prepacked(PackedStoreSpec { panel_bytes, block_quant: None })
     }
 
     unsafe fn b_late_packing_with_axes(&self, k_axis: usize, n_axis: usize) -> InputStoreSpec {
@@ -218,12 +225,12 @@ where
         for ia in 0..m / mr {
             scratch.for_valid_tile::<K>(non_linear, ia, 0);
             let err = K::kernel(scratch.uspecs());
-            debug_assert_eq!(err, 0, "Kernel return error {}", err);
+            anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
         }
         if m % mr != 0 {
             scratch.for_border_tile::<K>(non_linear, m / mr, 0);
             let err = K::kernel(scratch.uspecs());
-            debug_assert_eq!(err, 0, "Kernel return error {}", err);
+            anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
             scratch.postprocess_tile::<K>(non_linear, m / mr, 0, m % mr, 1);
         }
         Ok(())
@@ -246,12 +253,12 @@ where
             for ia in 0..m / mr {
                 scratch.for_valid_tile::<K>(non_linear, ia, ib);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
             }
             if m % mr != 0 {
                 scratch.for_border_tile::<K>(non_linear, m / mr, ib);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
                 scratch.postprocess_tile::<K>(non_linear, m / mr, ib, m % mr, nr);
             }
         }
@@ -259,13 +266,13 @@ where
             for ia in 0..m / mr {
                 scratch.for_border_tile::<K>(non_linear, ia, n / nr);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
                 scratch.postprocess_tile::<K>(non_linear, ia, n / nr, mr, n % nr);
             }
             if m % mr != 0 {
                 scratch.for_border_tile::<K>(non_linear, m / mr, n / nr);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
                 scratch.postprocess_tile::<K>(non_linear, m / mr, n / nr, m % mr, n % nr);
             }
         }
@@ -295,14 +302,14 @@ where
             for ib in 0..n / nr {
                 scratch.for_valid_tile::<K>(non_linear, ia, ib);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
             }
         }
         if m % mr != 0 {
             for ib in 0..n / nr {
                 scratch.for_border_tile::<K>(non_linear, m / mr, ib);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
                 scratch.postprocess_tile::<K>(non_linear, m / mr, ib, m % mr, nr);
             }
         }
@@ -310,13 +317,13 @@ where
             for ia in 0..m / mr {
                 scratch.for_border_tile::<K>(non_linear, ia, n / nr);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
                 scratch.postprocess_tile::<K>(non_linear, ia, n / nr, mr, n % nr);
             }
             if m % mr != 0 {
                 scratch.for_border_tile::<K>(non_linear, m / mr, n / nr);
                 let err = K::kernel(scratch.uspecs());
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+                anyhow::ensure!(err == 0, "Kernel {} returned error {}", K::name(), err);
                 scratch.postprocess_tile::<K>(non_linear, m / mr, n / nr, m % mr, n % nr);
             }
         }
@@ -5,6 +5,7 @@ use tract_data::internal::*;
 use crate::LADatum;
 
 use super::{BinOp, FusedKerSpec, FusedSpec, MatMatMulKer, OutputStoreKer};
+use crate::frame::Packer;
 use downcast_rs::{impl_downcast, Downcast};
 use tract_data::internal::num_integer::Integer;
 
@@ -36,6 +37,7 @@ struct LocDependant {
     uspec: usize,
     loc: *const u8,
     buffer: Option<*const u8>,
+    buffer_a: Option<*const u8>,
 }
 
 impl<TI: LADatum> ScratchSpace for ScratchSpaceFusedNonLinear<TI> {}
@@ -51,9 +53,14 @@ impl<TI: LADatum> Drop for ScratchSpaceFusedNonLinear<TI> {
     }
 }
 
-struct AddMatMulTemp(*const u8, usize);
+// current b panel and its column, current a panel and its row
+struct AddMatMulTemp(*const u8, usize, *const u8, usize);
 
 impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
+    fn a_packer<K: MatMatMulKer<TI>>() -> Packer {
+        Packer::new(K::mr(), K::alignment_bytes_packed_a(), K::end_padding_packed_a())
+    }
+
     pub unsafe fn prepare<K: MatMatMulKer<TI>>(&mut self, specs: &[FusedSpec]) {
         use FusedKerSpec as FKS;
         use FusedSpec as FS;
@@ -63,8 +70,9 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
         self.uspecs.push(FusedKerSpec::Clear);
         let mut offset = 0;
         let mut align = 1;
+        let a_packer = Self::a_packer::<K>();
         fn ld(spec: usize, uspec: usize, loc: *const u8) -> LocDependant {
-            LocDependant { spec, uspec, loc, buffer: None }
+            LocDependant { spec, uspec, loc, buffer: None, buffer_a: None }
         }
         // we're cheating here, storing offset as the buf pointer first
         for (ix, spec) in specs.iter().enumerate() {
@@ -100,7 +108,7 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
                     offset += TI::datum_type().size_of() * K::mr() * K::nr();
                     FusedKerSpec::Done
                 }
-                FS::AddMatMul { b, .. } => {
+                FS::AddMatMul { a, b, k } => {
                     let mut ld = ld(ix, self.uspecs.len(), offset as _);
                     offset += std::mem::size_of::<AddMatMulTemp>();
                     if let Some(tmp) = b.scratch_panel_buffer_layout() {
@@ -108,6 +116,15 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
                         offset = Integer::next_multiple_of(&offset, &tmp.align());
                         ld.buffer = Some(offset as _);
                         offset += tmp.size();
+                        // panels of narrower inputs (bf16, i8) can leave following tiles misaligned
+                        offset = Integer::next_multiple_of(&offset, &TI::datum_type().size_of());
+                    }
+                    if let Some(tmp) = a.scratch_panel_buffer_layout(&a_packer, *k, TI::datum_type())
+                    {
+                        align = tmp.align().lcm(&align);
+                        offset = Integer::next_multiple_of(&offset, &tmp.align());
+                        ld.buffer_a = Some(offset as _);
+                        offset += tmp.size();
                     }
                     self.loc_dependant.push(ld);
                     FusedKerSpec::Done
@@ -123,17 +140,21 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
             self.layout = Layout::from_size_align_unchecked(offset, align);
             self.buffer = std::alloc::alloc(self.layout);
         }
-        for LocDependant { loc, buffer, spec, .. } in &mut self.loc_dependant {
+        for LocDependant { loc, buffer, buffer_a, spec, .. } in &mut self.loc_dependant {
             *loc = self.buffer.offset(*loc as _);
             if let Some(b) = buffer {
                 *b = self.buffer.offset(*b as _);
             }
+            if let Some(b) = buffer_a {
+                *b = self.buffer.offset(*b as _);
+            }
             let spec = specs.get_unchecked(*spec);
             #[allow(clippy::single_match)]
             match spec {
                 FS::AddMatMul { .. } => {
                     let scratch = *loc as *mut AddMatMulTemp;
                     (*scratch).1 = usize::MAX;
+                    (*scratch).3 = usize::MAX;
                 }
                 _ => (),
             };
@@ -151,7 +172,7 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
         use FusedSpec as FS;
         let ScratchSpaceFusedNonLinear { uspecs, loc_dependant, .. } = self;
         debug_assert!(specs.len() + 2 == uspecs.len());
-        for LocDependant { spec, uspec, loc, buffer } in loc_dependant.iter_mut() {
+        for LocDependant { spec, uspec, loc, buffer, buffer_a } in loc_dependant.iter_mut() {
             let spec = specs.get_unchecked(*spec);
             *uspecs.get_unchecked_mut(*uspec) = match spec {
                 FS::BinPerRow(v, op) => {
@@ -184,9 +205,14 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
                 FS::AddUnicast(store) => FKS::AddUnicast(store.tile_c(down, right)),
                 FS::Store(c_store) => FKS::Store(c_store.tile_c(down, right)),
                 FS::AddMatMul { k, a, b } => {
-                    let pa = a.panel(down);
-                    K::prefetch(pa as _, 512);
                     let scratch = *loc as *mut AddMatMulTemp;
+                    if (*scratch).3 != down {
+                        (*scratch).2 =
+                            a.panel_a(down, &Self::a_packer::<K>(), *k, TI::datum_type(), *buffer_a);
+                        (*scratch).3 = down;
+                    }
+                    let pa = (*scratch).2;
+                    K::prefetch(pa as _, 512);
                     if (*scratch).1 != right {
                         (*scratch).0 = b.panel_b(right, *buffer);
                         (*scratch).1 = right;
@@ -209,7 +235,7 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
         use FusedSpec as FS;
         let ScratchSpaceFusedNonLinear { uspecs, loc_dependant, .. } = self;
         debug_assert!(specs.len() + 2 == uspecs.len());
-        for LocDependant { spec, uspec, loc, buffer } in loc_dependant.iter_mut() {
+        for LocDependant { spec, uspec, loc, buffer, buffer_a } in loc_dependant.iter_mut() {
             let spec = specs.get_unchecked(*spec);
             *uspecs.get_unchecked_mut(*uspec) = match spec {
                 FS::BinPerRow(v, op) => {
@@ -310,8 +336,9 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
                     let tile_offset = row_byte_stride * down as isize * K::mr() as isize
                         + col_byte_stride * right as isize * K::nr() as isize;
                     let tile_ptr = store.ptr.offset(tile_offset);
-                    let tmp_d_tile =
-                        std::slice::from_raw_parts_mut(*loc as *mut TI, K::mr() * K::nr());
+                    // the temporary tile keeps the store item type, narrower than TI or not
+                    let item_size = store.item_size;
+                    let tmp_d_tile = *loc as *mut u8;
                     let m = (store.m - down * K::mr()).min(K::mr());
                     let n = (store.n - right * K::nr()).min(K::nr());
                     for r in 0..m as isize {
@@ -320,16 +347,19 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
                             if inner_offset + tile_offset
                                 < (store.item_size * store.item_count) as isize
                             {
-                                *tmp_d_tile.get_unchecked_mut(r as usize + c as usize * K::mr()) =
-                                    *(tile_ptr.offset(inner_offset) as *const TI);
+                                std::ptr::copy_nonoverlapping(
+                                    tile_ptr.offset(inner_offset),
+                                    tmp_d_tile.add((r as usize + c as usize * K::mr()) * item_size),
+                                    item_size,
+                                );
                             }
                         }
                     }
                     FKS::AddUnicast(OutputStoreKer {
-                        ptr: tmp_d_tile.as_ptr() as _,
-                        row_byte_stride: std::mem::size_of::<TI>() as isize,
-                        col_byte_stride: (std::mem::size_of::<TI>() * K::mr()) as isize,
-                        item_size: std::mem::size_of::<TI>(),
+                        ptr: tmp_d_tile,
+                        row_byte_stride: item_size as isize,
+                        col_byte_stride: (item_size * K::mr()) as isize,
+                        item_size,
                     })
                 }
                 FS::Store(c_store) => {
@@ -342,9 +372,14 @@ impl<TI: LADatum> ScratchSpaceFusedNonLinear<TI> {
                     FKS::Store(tmpc)
                 }
                 FS::AddMatMul { k, a, b } => {
-                    let pa = a.panel(down);
-                    K::prefetch(pa as _, 512);
                     let scratch = *loc as *mut AddMatMulTemp;
+                    if (*scratch).3 != down {
+                        (*scratch).2 =
+                            a.panel_a(down, &Self::a_packer::<K>(), *k, TI::datum_type(), *buffer_a);
+                        (*scratch).3 = down;
+                    }
+                    let pa = (*scratch).2;
+                    K::prefetch(pa as _, 512);
                     if (*scratch).1 != right {
                         (*scratch).0 = b.panel_b(right, *buffer);
                         (*scratch).1 = right;
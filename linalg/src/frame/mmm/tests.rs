@@ -184,6 +184,96 @@ macro_rules! mmm_frame_tests {
     };
 }
 
+#[macro_export]
+macro_rules! mmm_block_quant_tests {
+    ($cond:expr, $ker:ident, $ti:ty) => {
+        mod block_quant {
+            #[allow(unused_imports)]
+            use $crate::frame::mmm::tests::*;
+            use tract_data::internal::*;
+            use super::super::$ker;
+
+            proptest::proptest! {
+                #[test]
+                fn block_quant_prop(pb in strat_block_quant::<$ti>()) {
+                    if $cond {
+                        test_block_quant::<$ker, $ti>(&pb)?
+                    }
+                }
+            }
+
+            #[test]
+            fn block_quant_border() {
+                if $cond {
+                    let a = Tensor::from_shape(&[3, 32], &(0..96).map(|i| (i % 16) as f32 - 8.0).collect::<Vec<_>>()).unwrap();
+                    let b = Tensor::from_shape(&[32, 2], &(0..64).map(|i| (i % 5) as f32 - 2.0).collect::<Vec<_>>()).unwrap();
+                    let b = b.cast_to::<$ti>().unwrap().into_owned();
+                    test_block_quant::<$ker, $ti>(&(BlockQuant::Q4_0, a, b)).unwrap()
+                }
+            }
+        }
+    };
+}
+
+pub fn strat_block_quant<TB: LADatum>() -> BoxedStrategy<(BlockQuant, Tensor, Tensor)> {
+    (any::<bool>(), 1usize..9, 1usize..3, 1usize..5)
+        .prop_flat_map(|(q4, m, blocks, n)| {
+            let k = blocks * 32;
+            let format = if q4 { BlockQuant::Q4_0 } else { BlockQuant::Q8_0 };
+            let a = proptest::collection::vec(-7i8..=7, m * k);
+            let b = proptest::collection::vec(-2i8..=2, k * n);
+            (Just(format), Just((m, k, n)), a, b)
+        })
+        .prop_map(|(format, (m, k, n), a, b)| {
+            let mut a = a.into_iter().map(|x| x as f32).collect::<Vec<_>>();
+            // pin the block scales to 1 so that f16 products and sums stay exact
+            for block in a.chunks_mut(32) {
+                block[0] = if format == BlockQuant::Q4_0 { -8.0 } else { 127.0 };
+            }
+            let a = Tensor::from_shape(&[m, k], &a).unwrap();
+            let b = tract_ndarray::Array2::from_shape_vec((k, n), b).unwrap().into_tensor();
+            (format, a, b.cast_to::<TB>().unwrap().into_owned())
+        })
+        .boxed()
+}
+
+/// A is f32 [m, k], block quantized before running. Expected product uses the dequantized A.
+pub fn test_block_quant<K: MatMatMulKer<TI> + 'static, TI>(
+    (format, a, b): &(BlockQuant, Tensor, Tensor),
+) -> Result<(), proptest::test_runner::TestCaseError>
+where
+    TI: LADatum + AsPrimitive<TI> + 'static,
+    f32: AsPrimitive<TI>,
+    i32: AsPrimitive<TI>,
+    usize: AsPrimitive<TI>,
+{
+    let (m, k, n) = (a.shape()[0], a.shape()[1], b.shape()[1]);
+    let quant = format.quant_f32(a.as_slice::<f32>().unwrap()).unwrap();
+    let quant = Tensor::from_shape(&[m, format.storage_bytes(k)], &quant).unwrap();
+    let dequant = format.dequant_f32(quant.as_slice::<u8>().unwrap()).unwrap();
+    let op = MatMatMulImpl::<K, TI>::default();
+    unsafe {
+        fused_ops::<K, TI, TI, TI, TI, _>(
+            m,
+            n,
+            &[FusedSpec::AddMatMul {
+                a: op.a_block_quant(*format, k).wrap(&quant.view()),
+                b: op.b_late_packing().wrap(&b.view()).unwrap(),
+                k,
+            }],
+            |r, c| {
+                let mut v: TI = TI::zero();
+                for i in 0..k {
+                    let a: TI = dequant[i + k * r].as_();
+                    let b: TI = b.as_slice::<TI>().unwrap()[c + i * n];
+                    v += a * b;
+                }
+                v
+            },
+        )
+    }
+}
+
 fn tensor(dt: DatumType, shape: Vec<usize>) -> BoxedStrategy<Tensor> {
     let len = shape.iter().product::<usize>();
     // for f16, positive numbers only to avoid worst rounding side effects
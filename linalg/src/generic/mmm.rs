@@ -71,11 +71,12 @@ where
 {
     #[inline(always)]
     fn name() -> &'static str {
-        match TI::datum_type() {
-            DatumType::F16 => "generic_f16_4x4",
-            DatumType::F32 => "generic_f32_4x4",
-            DatumType::I32 => "generic_i32_4x4",
-            DatumType::F64 => "generic_f64_4x4",
+        match (TA::datum_type(), TI::datum_type()) {
+            (DatumType::BF16, DatumType::F32) => "generic_bf16_4x4",
+            (_, DatumType::F16) => "generic_f16_4x4",
+            (_, DatumType::F32) => "generic_f32_4x4",
+            (_, DatumType::I32) => "generic_i32_4x4",
+            (_, DatumType::F64) => "generic_f64_4x4",
             _ => panic!(),
         }
     }
@@ -103,6 +104,9 @@ where
     }
     #[inline(never)]
     fn kernel(spec: &[FusedKerSpec<TI>]) -> isize {
+        if has_unsupported_tile::<TA, TI>(spec) {
+            return UNSUPPORTED_TILE;
+        }
         unsafe {
             let mut ab = [[TI::zero(); 4]; 4];
             let mut pnl = spec.as_ptr();
@@ -138,7 +142,7 @@ where
                             }
                         }
                     }
-                    FusedKerSpec::AddUnicast(tile) => add_unicast::<TI, _>(&tile, &mut ab),
+                    FusedKerSpec::AddUnicast(tile) => add_unicast::<TA, TI, _>(&tile, &mut ab),
                     FusedKerSpec::ShiftLeft(shift) => {
                         for i in 0..4 {
                             for j in 0..4 {
@@ -185,7 +189,7 @@ where
                             ab[3][3] += a[3].as_() * b[3].as_();
                         }
                     }
-                    FusedKerSpec::Store(tile) => store(&tile, &ab),
+                    FusedKerSpec::Store(tile) => store::<TA, TI, _>(&tile, &ab),
                 };
                 pnl = pnl.add(1);
             }
@@ -226,11 +230,12 @@ where
 {
     #[inline(always)]
     fn name() -> &'static str {
-        match TI::datum_type() {
-            DatumType::F16 => "generic_f16_4x1",
-            DatumType::F32 => "generic_f32_4x1",
-            DatumType::I32 => "generic_i32_4x1",
-            DatumType::F64 => "generic_f64_4x1",
+        match (TA::datum_type(), TI::datum_type()) {
+            (DatumType::BF16, DatumType::F32) => "generic_bf16_4x1",
+            (_, DatumType::F16) => "generic_f16_4x1",
+            (_, DatumType::F32) => "generic_f32_4x1",
+            (_, DatumType::I32) => "generic_i32_4x1",
+            (_, DatumType::F64) => "generic_f64_4x1",
             _ => panic!(),
         }
     }
@@ -258,6 +263,9 @@ where
     }
     #[inline(never)]
     fn kernel(spec: &[FusedKerSpec<TI>]) -> isize {
+        if has_unsupported_tile::<TA, TI>(spec) {
+            return UNSUPPORTED_TILE;
+        }
         unsafe {
             let mut ab = [[TI::zero(); 1]; 4];
             let mut pnl = spec.as_ptr();
@@ -292,7 +300,7 @@ where
                             ab[i][0] += *rows.add(i) * col;
                         }
                     }
-                    FusedKerSpec::AddUnicast(tile) => add_unicast::<TI, _>(
+                    FusedKerSpec::AddUnicast(tile) => add_unicast::<TA, TI, _>(
                         &tile,
                         &mut [
                             std::slice::from_raw_parts_mut(ab.as_ptr().offset(0) as _, 1),
@@ -328,7 +336,7 @@ where
                             ab[3][0] += a[3].as_() * b.as_();
                         }
                     }
-                    FusedKerSpec::Store(tile) => store(
+                    FusedKerSpec::Store(tile) => store::<TA, TI, _>(
                         &tile,
                         &[
                             std::slice::from_raw_parts(ab.as_ptr().offset(0) as _, 1),
@@ -381,11 +389,12 @@ where
 {
     #[inline(always)]
     fn name() -> &'static str {
-        match TI::datum_type() {
-            DatumType::F16 => "generic_f16_3x2",
-            DatumType::F32 => "generic_f32_3x2",
-            DatumType::I32 => "generic_i32_3x2",
-            DatumType::F64 => "generic_f64_3x2",
+        match (TA::datum_type(), TI::datum_type()) {
+            (DatumType::BF16, DatumType::F32) => "generic_bf16_3x2",
+            (_, DatumType::F16) => "generic_f16_3x2",
+            (_, DatumType::F32) => "generic_f32_3x2",
+            (_, DatumType::I32) => "generic_i32_3x2",
+            (_, DatumType::F64) => "generic_f64_3x2",
             _ => panic!(),
         }
     }
@@ -413,6 +422,9 @@ where
     }
     #[inline(never)]
     fn kernel(spec: &[FusedKerSpec<TI>]) -> isize {
+        if has_unsupported_tile::<TA, TI>(spec) {
+            return UNSUPPORTED_TILE;
+        }
         unsafe {
             let mut ab = [[TI::zero(); 2]; 3];
             let mut pnl = spec.as_ptr();
@@ -448,7 +460,7 @@ where
                             }
                         }
                     }
-                    FusedKerSpec::AddUnicast(tile) => add_unicast::<TI, _>(&tile, &mut ab),
+                    FusedKerSpec::AddUnicast(tile) => add_unicast::<TA, TI, _>(&tile, &mut ab),
                     FusedKerSpec::ShiftLeft(shift) => {
                         for i in 0..3 {
                             for j in 0..2 {
@@ -485,7 +497,7 @@ where
                             ab[2][1] += a[2].as_() * b[1].as_();
                         }
                     }
-                    FusedKerSpec::Store(tile) => store(&tile, &ab),
+                    FusedKerSpec::Store(tile) => store::<TA, TI, _>(&tile, &ab),
                 }
                 pnl = pnl.add(1);
             }
@@ -511,47 +523,104 @@ where
     }
 }
 
-unsafe fn store<TI, AB>(tile: &OutputStoreKer, ab: &[AB])
+// f32 accumulators narrowed to a bf16 output
+unsafe fn store_bf16<TI, AB>(tile: &OutputStoreKer, ab: &[AB])
+where
+    AB: AsRef<[TI]> + fmt::Debug,
+{
+    for i in 0usize..ab.len() {
+        for j in 0usize..ab[0].as_ref().len() {
+            let loc: *mut bf16 = tile
+                .ptr
+                .offset(tile.row_byte_stride * i as isize + tile.col_byte_stride * j as isize)
+                as _;
+            let val: *const f32 = (&ab[i].as_ref()[j]) as *const TI as _;
+            *loc = bf16::from_f32(*val)
+        }
+    }
+}
+
+/// Datum type of an output tile, if the kernel supports it.
+///
+/// Tiles only carry their item size: a narrower output than the accumulator is bf16 for the
+/// f32 accumulators of a bf16 kernel, and i8 (or u8) for i32 accumulators.
+fn tile_datum_type<TA: Datum, TI: Datum>(tile: &OutputStoreKer) -> Option<DatumType> {
+    match (TA::datum_type(), TI::datum_type(), tile.item_size) {
+        (_, ti, size) if size == ti.size_of() => Some(ti),
+        (DatumType::BF16, DatumType::F32, 2) => Some(DatumType::BF16),
+        (_, DatumType::I32, 1) => Some(DatumType::I8),
+        _ => None,
+    }
+}
+
+/// Kernel error code for an output tile the kernel can not store or add.
+const UNSUPPORTED_TILE: isize = 1;
+
+/// Unsupported output tiles are reported by the kernels before they run any operation.
+fn has_unsupported_tile<TA: Datum, TI: LADatum>(spec: &[FusedKerSpec<TI>]) -> bool {
+    spec.iter().any(|op| match op {
+        FusedKerSpec::Store(tile) | FusedKerSpec::AddUnicast(tile) => {
+            tile_datum_type::<TA, TI>(tile).is_none()
+        }
+        _ => false,
+    })
+}
+
+unsafe fn store<TA, TI, AB>(tile: &OutputStoreKer, ab: &[AB])
 where
+    TA: Datum,
+    TI: Datum + Copy,
     AB: AsRef<[TI]> + fmt::Debug,
 {
-    match tile.item_size {
-        1 => store_t::<u8, _, _>(tile, ab),
-        2 => store_t::<u16, _, _>(tile, ab),
-        4 => store_t::<u32, _, _>(tile, ab),
-        8 => store_t::<f64, _, _>(tile, ab),
-        _ => unimplemented!(),
+    match tile_datum_type::<TA, TI>(tile) {
+        Some(DatumType::BF16) => store_bf16(tile, ab),
+        // i32 accumulators are already clamped, keep their low byte
+        Some(DatumType::I8) if TI::datum_type() == i32::datum_type() => {
+            store_t::<u8, _, _>(tile, ab)
+        }
+        _ => store_t::<TI, _, _>(tile, ab),
     }
 }
 
-unsafe fn add_unicast<TI, AB>(tile: &OutputStoreKer, ab: &mut [AB])
+unsafe fn add_unicast<TA, TI, AB>(tile: &OutputStoreKer, ab: &mut [AB])
 where
+    TA: Datum,
     TI: LADatum + ops::AddAssign<TI>,
     AB: AsMut<[TI]> + fmt::Debug,
 {
-    if tile.item_size == TI::datum_type().size_of() {
-        for i in 0usize..ab.len() {
-            for j in 0usize..ab[0].as_mut().len() {
-                let value: *const TI = tile
-                    .ptr
-                    .offset(tile.row_byte_stride * i as isize + tile.col_byte_stride * j as isize)
-                    as _;
-                ab[i].as_mut()[j] += *value;
+    match tile_datum_type::<TA, TI>(tile) {
+        Some(DatumType::BF16) => {
+            for i in 0usize..ab.len() {
+                for j in 0usize..ab[0].as_mut().len() {
+                    let value: bf16 = *(tile.ptr.offset(
+                        tile.row_byte_stride * i as isize + tile.col_byte_stride * j as isize,
+                    ) as *const bf16);
+                    let acc: *mut f32 = ab[i].as_mut().as_mut_ptr().add(j) as *mut f32;
+                    *acc += value.to_f32();
+                }
             }
         }
-    } else if TI::datum_type() == i32::datum_type() && tile.item_size == 1 {
-        for i in 0usize..ab.len() {
-            for j in 0usize..ab[0].as_mut().len() {
-                let value: i8 = *(tile
-                    .ptr
-                    .offset(tile.row_byte_stride * i as isize + tile.col_byte_stride * j as isize)
-                    as *const i8);
-                let acc: *mut i32 = ab[i].as_mut().as_mut_ptr().add(j) as *mut i32;
-                *acc += value as i32;
+        Some(DatumType::I8) if TI::datum_type() == i32::datum_type() => {
+            for i in 0usize..ab.len() {
+                for j in 0usize..ab[0].as_mut().len() {
+                    let value: i8 = *(tile.ptr.offset(
+                        tile.row_byte_stride * i as isize + tile.col_byte_stride * j as isize,
+                    ) as *const i8);
+                    let acc: *mut i32 = ab[i].as_mut().as_mut_ptr().add(j) as *mut i32;
+                    *acc += value as i32;
+                }
+            }
+        }
+        _ => {
+            for i in 0usize..ab.len() {
+                for j in 0usize..ab[0].as_mut().len() {
+                    let value: *const TI = tile.ptr.offset(
+                        tile.row_byte_stride * i as isize + tile.col_byte_stride * j as isize,
+                    ) as _;
+                    ab[i].as_mut()[j] += *value;
+                }
             }
         }
-    } else {
-        unimplemented!("Missing AddUnicast type");
     }
 }
 
@@ -559,6 +628,10 @@ where
 pub type generic_f16_4x4 = GenericMmm4x4<f16, f16, f16>;
 test_mmm_kernel_f16!(generic_f16_4x4, true);
 
+#[allow(non_camel_case_types)]
+pub type generic_bf16_4x4 = GenericMmm4x4<bf16, bf16, f32>;
+test_mmm_kernel_bf16!(generic_bf16_4x4, true);
+
 #[allow(non_camel_case_types)]
 pub type generic_f32_4x4 = GenericMmm4x4<f32, f32, f32>;
 test_mmm_kernel_f32!(generic_f32_4x4, true);
@@ -575,6 +648,10 @@ test_mmm_kernel_i32!(generic_i32_4x4, true);
 pub type generic_f32_4x1 = GenericMmm4x1<f32, f32, f32>;
 test_mmm_kernel_f32!(generic_f32_4x1, true);
 
+#[allow(non_camel_case_types)]
+pub type generic_bf16_4x1 = GenericMmm4x1<bf16, bf16, f32>;
+test_mmm_kernel_bf16!(generic_bf16_4x1, true);
+
 #[allow(non_camel_case_types)]
 pub type generic_f64_4x1 = GenericMmm4x1<f64, f64, f64>;
 test_mmm_kernel_f64!(generic_f64_4x1, true);
@@ -592,3 +669,34 @@ test_mmm_kernel_f32!(generic_f32_3x2, true);
 #[allow(non_camel_case_types)]
 type generic_i32_3x2 = GenericMmmTest3x2<i8, i8, i32>;
 test_mmm_kernel_i32!(generic_i32_3x2, true);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tile(item_size: usize) -> OutputStoreKer {
+        OutputStoreKer {
+            ptr: std::ptr::null_mut(),
+            row_byte_stride: 0,
+            col_byte_stride: 0,
+            item_size,
+        }
+    }
+
+    #[test]
+    fn tile_datum_types() {
+        assert_eq!(tile_datum_type::<bf16, f32>(&tile(2)), Some(DatumType::BF16));
+        assert_eq!(tile_datum_type::<bf16, f32>(&tile(4)), Some(DatumType::F32));
+        assert_eq!(tile_datum_type::<f16, f16>(&tile(2)), Some(DatumType::F16));
+        assert_eq!(tile_datum_type::<i8, i32>(&tile(1)), Some(DatumType::I8));
+        assert_eq!(tile_datum_type::<f32, f32>(&tile(2)), None);
+    }
+
+    #[test]
+    fn f32_kernel_rejects_two_bytes_output() {
+        let spec = [FusedKerSpec::Store(tile(2)), FusedKerSpec::Done];
+        assert_eq!(generic_f32_4x4::kernel(&spec), UNSUPPORTED_TILE);
+        let spec = [FusedKerSpec::AddUnicast(tile(2)), FusedKerSpec::Done];
+        assert_eq!(generic_f32_4x1::kernel(&spec), UNSUPPORTED_TILE);
+    }
+}
@@ -49,6 +49,9 @@ pub struct Ops {
     mmm_f16: MMMImpl,
     mmv_f16: MMVImpl,
 
+    mmm_bf16: MMMImpl,
+    mmv_bf16: MMVImpl,
+
     qmmm_i32: MMMImpl,
     qmmv_i32: MMVImpl,
 
@@ -84,6 +87,9 @@ impl Ops {
             (F16, F16, F16) => {
                 Some(if n == Some(1) { (self.mmv_f16)(m, k) } else { (self.mmm_f16)(m, k, n) })
             }
+            (BF16, BF16, BF16) | (BF16, BF16, F32) => {
+                Some(if n == Some(1) { (self.mmv_bf16)(m, k) } else { (self.mmm_bf16)(m, k, n) })
+            }
             (I8, I8, I32) => {
                 Some(if n == Some(1) { (self.qmmv_i32)(m, k) } else { (self.qmmm_i32)(m, k, n) })
             }
@@ -104,6 +110,8 @@ pub fn generic() -> Ops {
         mmv_f32: Box::new(|_, _| generic::GenericMmm4x1::<f32, f32, f32>::mmm()),
         mmm_f16: Box::new(|_, _, _| generic::GenericMmm4x4::<f16, f16, f16>::mmm()),
         mmv_f16: Box::new(|_, _| generic::GenericMmm4x1::<f16, f16, f16>::mmm()),
+        mmm_bf16: Box::new(|_, _, _| generic::GenericMmm4x4::<bf16, bf16, f32>::mmm()),
+        mmv_bf16: Box::new(|_, _| generic::GenericMmm4x1::<bf16, bf16, f32>::mmm()),
         qmmm_i32: Box::new(|_, _, _| generic::GenericMmm4x4::<i8, i8, i32>::mmm()),
         qmmv_i32: Box::new(|_, _| generic::GenericMmm4x1::<i8, i8, i32>::mmm()),
         sigmoid_f16: Box::new(|| generic::HSigmoid8::ew()),
@@ -171,6 +179,13 @@ impl LADatum for f16 {
     }
 }
 
+impl LADatum for bf16 {
+    #[cfg(test)]
+    fn strat() -> BoxedStrategy<Self> {
+        f32::strat().prop_map(|f| f.as_()).boxed()
+    }
+}
+
 impl LADatum for f32 {
     #[cfg(test)]
     fn strat() -> BoxedStrategy<Self> {
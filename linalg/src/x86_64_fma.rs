@@ -9,6 +9,16 @@ mod intel;
 tanh_impl!(f32, fma_tanh_f32, 8, 8, is_x86_feature_detected!("fma"));
 sigmoid_impl!(f32, fma_sigmoid_f32, 8, 8, is_x86_feature_detected!("fma"));
 
+pub fn has_avx2_fma() -> bool {
+    is_x86_feature_detected!("fma") && is_x86_feature_detected!("avx2")
+}
+
+pub fn has_f16c() -> bool {
+    is_x86_feature_detected!("fma")
+        && is_x86_feature_detected!("avx2")
+        && is_x86_feature_detected!("f16c")
+}
+
 pub fn plug(ops: &mut Ops) {
     if is_x86_feature_detected!("fma") {
         ops.mmv_f32 = Box::new(|_, _| mmm::fma_mmm_f32_64x1::mmm());
@@ -88,6 +98,16 @@ pub fn plug(ops: &mut Ops) {
         ops.tanh_f32 = Box::new(|| fma_tanh_f32::ew());
         log::info!("mmm_f32, sigmoid_f32, tanh_f32: x86_64/fma activated");
     }
+    if has_f16c() {
+        ops.mmm_f16 = Box::new(|_, _, _| mmm::fma_mmm_f16_16x6::mmm());
+        ops.mmv_f16 = Box::new(|_, _| mmm::fma_mmm_f16_16x6::mmm());
+        log::info!("mmm_f16: x86_64/fma+f16c activated");
+    }
+    if has_avx2_fma() {
+        ops.mmm_bf16 = Box::new(|_, _, _| mmm::fma_mmm_bf16_16x6::mmm());
+        ops.mmv_bf16 = Box::new(|_, _| mmm::fma_mmm_bf16_16x6::mmm());
+        log::info!("mmm_bf16: x86_64/fma+avx2 activated");
+    }
     if is_x86_feature_detected!("avx2") {
         ops.qmmm_i32 = Box::new(|_, _, _| mmm::avx2_mmm_i32_8x8::mmm());
         log::info!("mmm_i8_i8 and mmm_i8_i32: x86_64/avx2 activated");
@@ -1,4 +1,5 @@
 use crate::frame::mmm::*;
+use tract_data::half::f16;
 
 MMMKernel!(f32, fma_mmm_f32_8x8; 8, 8; 32, 4; 0, 0; no_prefetch, is_x86_feature_detected!("fma"));
 MMMKernel!(f32, fma_mmm_f32_16x6; 16, 6; 32, 4; 0, 0; no_prefetch, is_x86_feature_detected!("fma"));
@@ -9,3 +10,7 @@ MMMKernel!(f32, fma_mmm_f32_40x2; 40, 2; 32, 4; 0, 0; no_prefetch, is_x86_featur
 MMMKernel!(f32, fma_mmm_f32_64x1; 64, 1; 32, 4; 0, 0; no_prefetch, is_x86_feature_detected!("fma"));
 
 MMMKernel!(i32, avx2_mmm_i32_8x8; 8, 8; 32, 4; 0, 0; no_prefetch, is_x86_feature_detected!("avx2"));
+
+MMMKernel!(f16, fma_mmm_f16_16x6; 16, 6; 32, 4; 0, 0; no_prefetch, crate::x86_64_fma::has_f16c());
+
+MMMKernel!(f32 [bf16], fma_mmm_bf16_16x6; 16, 6; 32, 4; 0, 0; no_prefetch, crate::x86_64_fma::has_avx2_fma());
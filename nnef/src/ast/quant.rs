@@ -2,7 +2,8 @@ use std::str::FromStr;
 
 use nom::branch::permutation;
 use nom::character::complete::digit1;
-use nom::combinator::map_res;
+use nom::combinator::{map_res, recognize};
+use nom::sequence::pair;
 use tract_core::internal::*;
 
 use nom::{bytes::complete::*, multi::*};
@@ -31,7 +32,7 @@ fn quantization(i: &str) -> IResult<&str, (String, QuantFormat)> {
 }
 
 fn integer_numeric<T: FromStr>(i: &str) -> IResult<&str, T> {
-    map_res(digit1, |s: &str| s.parse::<T>())(i)
+    map_res(recognize(pair(opt(tag("-")), digit1)), |s: &str| s.parse::<T>())(i)
 }
 
 // <qparam> ::= "<identifier>": <qparam>
@@ -128,6 +129,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_qparam_negative_zero_point() {
+        assert_eq!(
+            p(qparam, "zero_point_linear_quantize(zero_point = -71, scale = 0.5, bits = 8, signed = true, symmetric = false)"),
+            QuantFormat::Linear {
+                params: QParams::ZpScale { zero_point: -71, scale: 0.5 },
+                bits: 8,
+                signed: true
+            }
+        );
+    }
+
     #[test]
     fn test_quantization() {
         assert_eq!(
@@ -53,6 +53,23 @@ impl Nnef {
         self
     }
 
+    /// Load an operator plugin library, see tract-plugin.
+    #[cfg(feature = "plugin")]
+    pub fn with_plugin(self, path: impl AsRef<Path>) -> TractResult<Self> {
+        self.with_loaded_plugin(&tract_plugin::Plugin::load(path)?)
+    }
+
+    #[cfg(feature = "plugin")]
+    pub fn with_loaded_plugin(mut self, plugin: &tract_plugin::Plugin) -> TractResult<Self> {
+        plugin.register()?;
+        if let Some(registry) = self.registries.iter_mut().find(|r| r.id == "tract_plugin") {
+            crate::ops::plugin::register(registry, plugin);
+        } else {
+            self.registries.push(crate::ops::tract_plugin(plugin));
+        }
+        Ok(self)
+    }
+
     pub fn translate(
         &self,
         proto_model: &ProtoModel,
@@ -16,6 +16,8 @@ pub use ast::ProtoModel;
 pub use tract_core;
 pub use tract_core::prelude::tract_ndarray;
 pub use tract_core::prelude::tract_num_traits;
+#[cfg(feature = "plugin")]
+pub use tract_plugin;
 
 pub mod prelude {
     pub use tract_core;
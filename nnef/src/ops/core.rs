@@ -1,6 +1,7 @@
 use crate::internal::*;
 use tract_core::ops;
 
+mod block_quant;
 mod broadcast;
 mod cast;
 mod downsample;
@@ -19,10 +20,21 @@ mod source;
 pub fn register(registry: &mut Registry) {
     registry.register_unit_element_wise("tract_core_round_even", &ops::math::RoundHalfToEven {});
 
+    registry.register_unit_element_wise("tract_core_real", &ops::math::Real {});
+    registry.register_unit_element_wise("tract_core_imag", &ops::math::Imag {});
+    registry.register_unit_element_wise("tract_core_conj", &ops::math::Conj {});
+    registry.register_unit_element_wise("tract_core_complex_abs", &ops::math::ComplexAbs {});
+    registry.register_unit_element_wise("tract_core_angle", &ops::math::Angle {});
+
     registry.register_binary("tract_core_xor", &ops::logic::Xor {});
+    registry.register_binary("tract_core_bitand", &ops::logic::BitAnd {});
+    registry.register_binary("tract_core_bitor", &ops::logic::BitOr {});
+    registry.register_binary("tract_core_bitxor", &ops::logic::BitXor {});
+    registry.register_unit_element_wise("tract_core_bitnot", &ops::logic::BitNot {});
 
     registry.register_binary("tract_shl", &ops::math::ShiftLeft);
     registry.register_binary("tract_shr", &ops::math::ShiftRight);
+    block_quant::register(registry);
     broadcast::register(registry);
     cast::register(registry);
     downsample::register(registry);
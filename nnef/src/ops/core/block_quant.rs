@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::internal::BlockQuant;
+use tract_core::ops::matmul::{BlockQuantMatMul, MatMulAxes};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<BlockQuantMatMul>(), block_quant_matmul_dump);
+    registry.register_primitive(
+        "tract_core_matmul_block_quant",
+        &block_quant_matmul_parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        block_quant_matmul_load,
+    );
+}
+
+fn block_quant_matmul_parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("A"),
+        TypeName::Scalar.tensor().named("B"),
+        TypeName::String.named("format"),
+        TypeName::Integer.array().named("a_shape"),
+        TypeName::Integer.array().named("axes"),
+    ]
+}
+
+fn block_quant_matmul_dump(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<BlockQuantMatMul>().unwrap();
+    let a = ast.konst_variable(format!("{}.a", node.name), &op.a)?;
+    let b = ast.force_variable(format!("{}_b", node.name), &ast.mapping[&node.inputs[0]].clone());
+    let c = invocation(
+        "tract_core_matmul_block_quant",
+        &[a, b],
+        &[
+            ("format", string(op.format.to_string())),
+            ("a_shape", ints(&op.a_shape)),
+            ("axes", ints(&op.axes.to_array())),
+        ],
+    );
+    Ok(Some(ast.force_variable(&node.name, &c)))
+}
+
+fn block_quant_matmul_load(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let a: Arc<Tensor> = invocation.named_arg_as(builder, "A")?;
+    let b: OutletId = invocation.named_arg_as(builder, "B")?;
+    let format = BlockQuant::from_str(&invocation.named_arg_as::<String>(builder, "format")?)?;
+    let a_shape: TVec<usize> = invocation.named_arg_as(builder, "a_shape")?;
+    let axes: TVec<usize> = invocation.named_arg_as(builder, "axes")?;
+    let axes = MatMulAxes::from_array(&axes)?;
+    let (m, k) = (a_shape[axes.a_m], a_shape[axes.a_k]);
+    ensure!(
+        a.datum_type() == u8::datum_type() && a.shape() == [m, format.storage_bytes(k)],
+        "Expected {} weights as u8 [{}, {}], got {:?}",
+        format,
+        m,
+        format.storage_bytes(k),
+        a
+    );
+    builder.wire(BlockQuantMatMul { format, a_shape, a, axes }, &[b])
+}
@@ -42,7 +42,9 @@ fn qconv_unary_dump(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<A
     if op.q_params.is_none() || node.outputs[0].fact.datum_type.is_quantized() {
         return Ok(None);
     }
-    let mut named_args = make_conv_named_args(node, &op.pool_spec, op.group, false, None)?;
+    let input_shape = ast.model.outlet_fact(node.inputs[0])?.shape.to_tvec();
+    let mut named_args =
+        make_conv_named_args(node, &input_shape, &op.pool_spec, op.group, false, None)?;
 
     let [a0, a_scale, b0, b_scale, c0, c_scale] =
         qparams_to_rvalues(&op.q_params.as_ref().unwrap().1, &node.inputs, &ast.mapping)?;
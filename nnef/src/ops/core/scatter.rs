@@ -1,6 +1,7 @@
 use crate::internal::*;
 use tract_core::ops::array::ScatterElements;
 use tract_core::ops::array::ScatterNd;
+use tract_core::ops::array::ScatterReduction;
 
 pub fn register(registry: &mut Registry) {
     use crate::internal::*;
@@ -13,6 +14,7 @@ pub fn register(registry: &mut Registry) {
             TypeName::Scalar.tensor().named("indices"),
             TypeName::Scalar.tensor().named("updates"),
             TypeName::Integer.named("axis"),
+            TypeName::String.named("reduction").default("none"),
         ],
         &[("output", TypeName::Scalar.tensor())],
         de_scatter_elements,
@@ -25,6 +27,7 @@ pub fn register(registry: &mut Registry) {
             TypeName::Scalar.tensor().named("input"),
             TypeName::Scalar.tensor().named("indices"),
             TypeName::Scalar.tensor().named("updates"),
+            TypeName::String.named("reduction").default("none"),
         ],
         &[("output", TypeName::Scalar.tensor())],
         de_scatter_nd,
@@ -32,10 +35,15 @@ pub fn register(registry: &mut Registry) {
 }
 
 fn ser_scatter_nd(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op().downcast_ref::<ScatterNd>().unwrap();
     let wire = ast.mapping[&node.inputs[0]].clone();
     let indices = ast.mapping[&node.inputs[1]].clone();
     let updates = ast.mapping[&node.inputs[2]].clone();
-    Ok(Some(invocation("tract_core_scatter_nd", &[wire, indices, updates], &[])))
+    Ok(Some(invocation(
+        "tract_core_scatter_nd",
+        &[wire, indices, updates],
+        &[("reduction", string(op.reduction.as_str()))],
+    )))
 }
 
 fn de_scatter_nd(
@@ -45,7 +53,9 @@ fn de_scatter_nd(
     let wire = invocation.named_arg_as(builder, "input")?;
     let indices = invocation.named_arg_as(builder, "indices")?;
     let updates = invocation.named_arg_as(builder, "updates")?;
-    builder.wire(ScatterNd, &[wire, indices, updates])
+    let reduction =
+        ScatterReduction::parse(&invocation.named_arg_as::<String>(builder, "reduction")?)?;
+    builder.wire(ScatterNd::new(reduction), &[wire, indices, updates])
 }
 
 fn ser_scatter_elements(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
@@ -56,7 +66,7 @@ fn ser_scatter_elements(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Opti
     Ok(Some(invocation(
         "tract_core_scatter_elements",
         &[wire, indices, updates],
-        &[("axis", numeric(op.axis))],
+        &[("axis", numeric(op.axis)), ("reduction", string(op.reduction.as_str()))],
     )))
 }
 
@@ -68,5 +78,7 @@ fn de_scatter_elements(
     let indices = invocation.named_arg_as(builder, "indices")?;
     let updates = invocation.named_arg_as(builder, "updates")?;
     let axis = invocation.named_arg_as(builder, "axis")?;
-    builder.wire(ScatterElements::new(axis), &[wire, indices, updates])
+    let reduction =
+        ScatterReduction::parse(&invocation.named_arg_as::<String>(builder, "reduction")?)?;
+    builder.wire(ScatterElements::new(axis, reduction), &[wire, indices, updates])
 }
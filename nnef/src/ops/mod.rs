@@ -2,6 +2,8 @@ use crate::internal::*;
 
 pub(super) mod core;
 pub(super) mod nnef;
+#[cfg(feature = "plugin")]
+pub(super) mod plugin;
 pub(super) mod resource;
 
 pub use nnef::tract_nnef;
@@ -25,3 +27,14 @@ pub fn tract_resource() -> Registry {
     resource::register(&mut reg);
     reg
 }
+
+#[cfg(feature = "plugin")]
+pub fn tract_plugin(plugin: &tract_plugin::Plugin) -> Registry {
+    let mut reg = Registry::new("tract_plugin")
+        .with_doc("Extension `tract_plugin` exposes operators from plugin libraries,")
+        .with_doc("which must be loaded before the model.")
+        .with_doc("")
+        .with_doc("Add `extension tract_plugin` to `graph.nnef`");
+    plugin::register(&mut reg, plugin);
+    reg
+}
@@ -75,6 +75,44 @@ pub fn variable(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) ->
     builder.wire(tract_core::ops::konst::Const::new(tensor), &[])
 }
 
+// fragment constant<? = scalar>( shape: integer[], value: ?[] ) -> ( output: tensor<?> );
+pub fn constant(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let shape: TVec<usize> = invocation.named_arg_as(builder, "shape")?;
+    let type_name = invocation.invocation.generic_type_name.unwrap_or(TypeName::Scalar);
+    let value = match type_name {
+        TypeName::Scalar => tensor1(&invocation.named_arg_as::<TVec<f32>>(builder, "value")?),
+        TypeName::Integer => tensor1(&invocation.named_arg_as::<TVec<i64>>(builder, "value")?),
+        TypeName::Logical => tensor1(&invocation.named_arg_as::<TVec<bool>>(builder, "value")?),
+        _ => bail!("Unsupported constant type {:?}", type_name),
+    };
+    let tensor = if value.len() == 1 {
+        value.into_shape(&[])?.broadcast_scalar_to_shape(&shape)?
+    } else {
+        value.into_shape(&shape)?
+    };
+    builder.wire(tract_core::ops::konst::Const::new(tensor.into_arc_tensor()), &[])
+}
+
+// fragment update<?>( variable: tensor<?>, value: tensor<?> ) -> ( result: tensor<?> );
+//
+// Models are stateless in tract: the update is only visible through its result.
+pub fn update(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let value: OutletId = invocation.named_arg_as(builder, "value")?;
+    Ok(Value::Wire(value))
+}
+
+// fragment copy<?>( x: tensor<?> ) -> ( y: tensor<?> );
+pub fn copy(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let x: OutletId = invocation.named_arg_as(builder, "x")?;
+    builder.wire(ops::identity::Identity, &[x])
+}
+
+// fragment rcp( x: tensor<scalar> ) -> ( y: tensor<scalar> );
+pub fn rcp(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let x: OutletId = invocation.named_arg_as(builder, "x")?;
+    builder.wire(ops::math::recip(), &[x])
+}
+
 // fragment reshape<?>( input: tensor<?>, shape: integer[], axis_start: integer = 0, axis_count: integer = -1 )
 //      -> ( output: tensor<?> );
 pub fn reshape(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
@@ -129,6 +167,24 @@ pub fn concat(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> Tr
     builder.wire(ops::array::TypedConcat::new(axis), &values)
 }
 
+// fragment split<?>( value: tensor<?>, axis: integer, ratios: integer[] ) -> ( values: tensor<?>[] );
+pub fn split(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let value: OutletId = invocation.named_arg_as(builder, "value")?;
+    let axis: usize = invocation.named_arg_as(builder, "axis")?;
+    let ratios: TVec<usize> = invocation.named_arg_as(builder, "ratios")?;
+    let dim = builder.model.outlet_fact(value)?.shape[axis].clone();
+    let unit = dim.clone() / ratios.iter().sum::<usize>();
+    let mut start = 0.to_dim();
+    let mut values = vec![];
+    for ratio in ratios {
+        let end = start.clone() + unit.clone() * ratio;
+        let slice = ops::array::Slice { axis, start: start.clone(), end: end.clone() };
+        values.push(Value::Wire(builder.wire_as_outlets(slice, &[value])?[0]));
+        start = end;
+    }
+    Ok(Value::Array(values))
+}
+
 // fragment slice<?>( input: tensor<?>, axes: integer[], begin: integer[], end: integer[] ) -> ( output: tensor<?> );
 pub fn slice(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
     let wire = tvec!(invocation.named_arg_as(builder, "input")?);
@@ -495,6 +551,407 @@ pub fn sum_pool(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) ->
     builder.wire(op, &[input])
 }
 
+/*
+ * fragment debox( input: tensor<scalar>, size: integer[], border: string = 'constant', padding: (integer,integer)[] = [],
+ *   stride: integer[] = [], dilation: integer[] = [], output_shape: integer[] = [], normalize: logical = false )
+ * -> ( output: tensor<scalar> );
+ *
+ * Transposed box: a depthwise deconvolution with a kernel of ones.
+ */
+
+pub fn debox(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    if input_fact.rank() != size.len() || size[0] != 1 || size[1] != 1 {
+        bail!(
+            "Debox input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            input_fact,
+            size
+            );
+    }
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    ensure!(&*border == "ignore" || &*border == "constant", "Unsupported debox border {}", border);
+    let channels = input_fact.shape[1].to_usize().context("debox needs a known channel count")?;
+    let pool_spec = PoolSpec {
+        output_channel_override: Some(channels),
+        ..pool_spec_for_pools(builder, invocation, &size)?
+    };
+
+    let output_shape = invocation.named_arg_as::<TVec<usize>>(builder, "output_shape")?;
+    let adjustments = if output_shape.len() > 0 {
+        let spatial_output =
+            if output_shape.len() == size.len() { &output_shape[2..] } else { &output_shape[..] };
+        let input_shape =
+            &input_fact.shape.as_concrete().context("symbolic dimension not supported in debox")?
+                [2..];
+        adjustments(&pool_spec, input_shape, spatial_output)?
+    } else {
+        tvec!(0; pool_spec.rank())
+    };
+
+    let normalize: bool = invocation.named_arg_as(builder, "normalize")?;
+    let weight = if normalize { 1.0 / size.iter().product::<usize>() as f32 } else { 1.0 };
+    let op = depthwise_box_deconv(pool_spec, input_fact.datum_type, weight, adjustments)?;
+    builder.wire(op, &[input])
+}
+
+fn depthwise_box_deconv(
+    pool_spec: PoolSpec,
+    datum_type: DatumType,
+    weight: f32,
+    adjustments: TVec<usize>,
+) -> TractResult<ops::cnn::deconv::DeconvUnary> {
+    // one group per channel, so the tract O/g I H W kernel is 1 C H W
+    let channels = pool_spec.output_channel_override.unwrap();
+    let mut kernel_shape = tvec!(1, channels);
+    kernel_shape.extend(pool_spec.kernel_shape.iter().cloned());
+    let kernel =
+        tensor0(weight).cast_to_dt(datum_type)?.broadcast_scalar_to_shape(&kernel_shape)?;
+    Ok(ops::cnn::deconv::DeconvUnary::new(
+        pool_spec,
+        ops::cnn::KernelFormat::OIHW,
+        kernel.into_arc_tensor(),
+        None,
+        adjustments,
+        channels,
+    ))
+}
+
+/*
+ * fragment nearest_upsample( input: tensor<scalar>, factor: integer[] ) -> ( output: tensor<scalar> )
+ * {
+ *     dims = 2 + length_of(factor);
+ *     output = debox(input, size = [1,1] + factor, stride = [1,1] + factor, padding = [(0,0)] * dims);
+ * }
+ */
+
+pub fn nearest_upsample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let factor: TVec<usize> = invocation.named_arg_as(builder, "factor")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    ensure!(
+        input_fact.rank() == factor.len() + 2,
+        "Upsampling input expected as NCHW, with a factor per spatial axis. Got {:?} and {:?}",
+        input_fact,
+        factor
+    );
+    let channels =
+        input_fact.shape[1].to_usize().context("upsampling needs a known channel count")?;
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        factor.clone(),
+        PaddingSpec::Valid,
+        None,
+        Some(factor.clone()),
+        Some(channels),
+    );
+    let adjustments = tvec!(0; factor.len());
+    let op = depthwise_box_deconv(pool_spec, input_fact.datum_type, 1.0, adjustments)?;
+    builder.wire(op, &[input])
+}
+
+/*
+ * fragment argmax_pool( input: tensor<scalar>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [] )
+ * -> ( index: tensor<integer> );
+ */
+
+pub fn argmax_pool(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    if let Value::Tuple(outputs) = max_pool_with_index(builder, invocation)? {
+        Ok(outputs[1].clone())
+    } else {
+        bail!("Expected max pool values and indices")
+    }
+}
+
+fn flatten_spatial(
+    builder: &mut ModelBuilder,
+    wire: OutletId,
+    shape: &[TDim],
+) -> TractResult<OutletId> {
+    let volume = shape[2..].iter().product::<TDim>();
+    Ok(builder.wire_as_outlets(AxisOp::Reshape(2, shape[2..].into(), tvec!(volume)), &[wire])?[0])
+}
+
+/*
+ * fragment sample( input: tensor<scalar>, index: tensor<integer>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [] )
+ * -> ( output: tensor<scalar> );
+ *
+ * Indices are flattened over the spatial axes of the input (as produced by argmax_pool), so the
+ * pooling parameters do not participate.
+ */
+
+pub fn sample(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input: OutletId = invocation.named_arg_as(builder, "input")?;
+    let index: OutletId = invocation.named_arg_as(builder, "index")?;
+    let input_shape = builder.model.outlet_fact(input)?.shape.to_tvec();
+    let index_shape = builder.model.outlet_fact(index)?.shape.to_tvec();
+    ensure!(
+        input_shape.len() == index_shape.len() && input_shape.len() > 2,
+        "Sample input and index expected as NCHW. Got {:?} and {:?}",
+        input_shape,
+        index_shape
+    );
+    let input = flatten_spatial(builder, input, &input_shape)?;
+    let index = flatten_spatial(builder, index, &index_shape)?;
+    let wire = builder.wire_as_outlets(ops::array::GatherElements::new(2), &[input, index])?;
+    let volume = index_shape[2..].iter().product::<TDim>();
+    builder.wire(AxisOp::Reshape(2, tvec!(volume), index_shape[2..].into()), &wire)
+}
+
+/*
+ * fragment desample( input: tensor<scalar>, index: tensor<integer>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [], output_shape: integer[] = [] )
+ * -> ( output: tensor<scalar> );
+ *
+ * Inverse of sample: values are summed at their index in a zeroed output.
+ */
+
+pub fn desample(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    use ops::array::{ScatterElements, ScatterReduction};
+
+    let input: OutletId = invocation.named_arg_as(builder, "input")?;
+    let index: OutletId = invocation.named_arg_as(builder, "index")?;
+    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    let input_shape =
+        input_fact.shape.as_concrete().context("symbolic dimension not supported in desample")?;
+    if input_shape.len() != size.len() || input_shape.len() < 3 {
+        bail!(
+            "Desample input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            input_fact,
+            size
+            );
+    }
+    let output_shape = invocation.named_arg_as::<TVec<usize>>(builder, "output_shape")?;
+    let spatial_output: TVec<usize> = if output_shape.len() == size.len() {
+        output_shape[2..].into()
+    } else if output_shape.len() > 0 {
+        output_shape
+    } else {
+        let pool_spec = PoolSpec {
+            output_channel_override: Some(input_shape[1]),
+            ..pool_spec_for_pools(builder, invocation, &size)?
+        };
+        let adjustments = tvec!(0; pool_spec.rank());
+        ops::cnn::deconv::output_shape(&pool_spec, input_shape, &adjustments)?[2..].into()
+    };
+
+    let zeros = Tensor::zero_dt(
+        input_fact.datum_type,
+        &[input_shape[0], input_shape[1], spatial_output.iter().product()],
+    )?;
+    let zeros = builder.wire_as_outlets(ops::konst::Const::new(zeros.into_arc_tensor()), &[])?;
+    let index_shape = builder.model.outlet_fact(index)?.shape.to_tvec();
+    let input = flatten_spatial(builder, input, &input_fact.shape)?;
+    let index = flatten_spatial(builder, index, &index_shape)?;
+    let wire = builder.wire_as_outlets(
+        ScatterElements::new(2, ScatterReduction::Add),
+        &[zeros[0], index, input],
+    )?;
+    let spatial_output: TVec<TDim> = spatial_output.iter().map(|d| d.to_dim()).collect();
+    let volume = spatial_output.iter().product::<TDim>();
+    builder.wire(AxisOp::Reshape(2, tvec!(volume), spatial_output), &wire)
+}
+
+/*
+ * fragment multilinear_upsample( input: tensor<scalar>, factor: integer[], method: string = 'symmetric',
+ *   border: string = 'replicate' )
+ * -> ( output: tensor<scalar> );
+ *
+ * Each spatial axis is upsampled in turn, as the weighted sum of two gathers of the input.
+ */
+
+pub fn multilinear_upsample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let mut wire: OutletId = invocation.named_arg_as(builder, "input")?;
+    let factor: TVec<usize> = invocation.named_arg_as(builder, "factor")?;
+    let method: String = invocation.named_arg_as(builder, "method")?;
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    let input_fact = builder.model.outlet_fact(wire)?.clone();
+    ensure!(
+        input_fact.rank() == factor.len() + 2,
+        "Upsampling input expected as NCHW, with a factor per spatial axis. Got {:?} and {:?}",
+        input_fact,
+        factor
+    );
+    ensure!(
+        &*border == "replicate" || &*border == "constant",
+        "Unsupported multilinear_upsample border {}",
+        border
+    );
+    for (ix, &factor) in factor.iter().enumerate() {
+        let axis = ix + 2;
+        let len = input_fact.shape[axis]
+            .to_usize()
+            .context("symbolic dimension not supported in multilinear_upsample")?;
+        let mut weights_shape = tvec!(1; input_fact.rank());
+        weights_shape[axis] = len * factor;
+        let mut terms = tvec!();
+        for (indices, weights) in upsampling_taps(len, factor, &method, &*border == "constant")? {
+            let indices =
+                builder.wire_as_outlets(ops::konst::Const::new(rctensor1(&indices)), &[])?;
+            let gathered =
+                builder.wire_as_outlets(ops::array::Gather::new(axis), &[wire, indices[0]])?;
+            let weights = Tensor::from_shape(&weights_shape, &weights)?
+                .cast_to_dt(input_fact.datum_type)?
+                .into_owned();
+            let weights =
+                builder.wire_as_outlets(ops::konst::Const::new(weights.into_arc_tensor()), &[])?;
+            terms.push(builder.wire_as_outlets(ops::math::mul(), &[gathered[0], weights[0]])?[0]);
+        }
+        wire = builder.wire_as_outlets(ops::math::add(), &terms)?[0];
+    }
+    Ok(Value::Wire(wire))
+}
+
+/// Left and right source index and weight of each output position of a linear upsampling.
+fn upsampling_taps(
+    len: usize,
+    factor: usize,
+    method: &str,
+    zero_border: bool,
+) -> TractResult<[(Vec<i64>, Vec<f32>); 2]> {
+    let output_len = len * factor;
+    let mut taps = [(vec![], vec![]), (vec![], vec![])];
+    for o in 0..output_len {
+        let x = match method {
+            "symmetric" => (o as f32 + 0.5) / factor as f32 - 0.5,
+            "asymmetric" => o as f32 / factor as f32,
+            "aligned" if output_len > 1 => o as f32 * (len - 1) as f32 / (output_len - 1) as f32,
+            "aligned" => 0.0,
+            _ => bail!("Unsupported multilinear_upsample method {}", method),
+        };
+        let left = x.floor();
+        let frac = x - left;
+        for (tap, (pos, weight)) in
+            [(left as i64, 1.0 - frac), (left as i64 + 1, frac)].into_iter().enumerate()
+        {
+            let inside = pos >= 0 && pos < len as i64;
+            taps[tap].0.push(pos.max(0).min(len as i64 - 1));
+            taps[tap].1.push(if inside || !zero_border { weight } else { 0.0 });
+        }
+    }
+    Ok(taps)
+}
+
+/*
+ * fragment avg_roi_pool( input: tensor<scalar>, rois: tensor<scalar>, batch_index: tensor<integer>,
+ *   output_size: integer[] ) -> ( output: tensor<scalar> );
+ * and also max_roi_pool
+ *
+ * Regions are (x1, y1, x2, y2) in input coordinates.
+ */
+
+pub fn roi_pool(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let rois = invocation.named_arg_as(builder, "rois")?;
+    let batch_index = invocation.named_arg_as(builder, "batch_index")?;
+    let output_size: TVec<usize> = invocation.named_arg_as(builder, "output_size")?;
+    ensure!(output_size.len() == 2, "Only 2D regions are supported, got {:?}", output_size);
+    let mode = if invocation.invocation.id.starts_with("max") {
+        ops::cnn::RoiPoolingMode::Max
+    } else {
+        ops::cnn::RoiPoolingMode::Avg
+    };
+    let op = ops::cnn::RoiPool::new(mode, (output_size[0], output_size[1]), 1.0);
+    builder.wire(op, &[input, rois, batch_index])
+}
+
+/*
+ * fragment roi_resample( input: tensor<scalar>, rois: tensor<scalar>, batch_index: tensor<integer>,
+ *   output_size: integer[], method: string = 'symmetric' ) -> ( output: tensor<scalar> );
+ */
+
+pub fn roi_resample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let output_size: TVec<usize> = invocation.named_arg_as(builder, "output_size")?;
+    let method: String = invocation.named_arg_as(builder, "method")?;
+    let wire = wire_roi_align(builder, invocation, &output_size, &[1, 1], &method)?;
+    Ok(Value::Wire(wire))
+}
+
+/*
+ * fragment avg_roi_align( input: tensor<scalar>, rois: tensor<scalar>, batch_index: tensor<integer>,
+ *   output_size: integer[], sampling_rate: integer[], resize_method: string = 'symmetric' )
+ * -> ( output: tensor<scalar> )
+ * {
+ *     size = [for i in range_of(output_size) yield output_size[i] * sampling_rate[i]];
+ *     resized = roi_resample(input, rois, batch_index, output_size = size, method = resize_method);
+ *     output = avg_pool(resized, size = sampling_rate, stride = sampling_rate);
+ * }
+ * and also max_roi_align
+ */
+
+pub fn roi_align(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let output_size: TVec<usize> = invocation.named_arg_as(builder, "output_size")?;
+    let sampling_rate: TVec<usize> = invocation.named_arg_as(builder, "sampling_rate")?;
+    let method: String = invocation.named_arg_as(builder, "resize_method")?;
+    ensure!(
+        sampling_rate.len() == output_size.len(),
+        "Expected a sampling rate per output axis, got {:?} and {:?}",
+        output_size,
+        sampling_rate
+    );
+    if invocation.invocation.id.starts_with("avg") {
+        // averaging the resampled bins is exactly the multi-sample RoiAlign
+        let wire = wire_roi_align(builder, invocation, &output_size, &sampling_rate, &method)?;
+        return Ok(Value::Wire(wire));
+    }
+    let size: TVec<usize> = output_size.iter().zip(&sampling_rate).map(|(o, r)| o * r).collect();
+    let resized = wire_roi_align(builder, invocation, &size, &[1, 1], &method)?;
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        sampling_rate.clone(),
+        PaddingSpec::Valid,
+        None,
+        Some(sampling_rate),
+        None,
+    );
+    builder.wire(ops::cnn::MaxPool::new(pool_spec, None), &[resized])
+}
+
+fn wire_roi_align(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+    output_size: &[usize],
+    sampling_rate: &[usize],
+    method: &str,
+) -> TractResult<OutletId> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let rois = invocation.named_arg_as(builder, "rois")?;
+    let batch_index = invocation.named_arg_as(builder, "batch_index")?;
+    ensure!(output_size.len() == 2, "Only 2D regions are supported, got {:?}", output_size);
+    let half_pixel = match method {
+        "symmetric" => true,
+        "asymmetric" => false,
+        _ => bail!("Unsupported roi resize method: {}", method),
+    };
+    let op = ops::cnn::RoiAlign::new(
+        ops::cnn::RoiPoolingMode::Avg,
+        (output_size[0], output_size[1]),
+        (sampling_rate[0], sampling_rate[1]),
+        1.0,
+        half_pixel,
+    );
+    Ok(builder.wire_as_outlets(op, &[input, rois, batch_index])?[0])
+}
+
 /*
  *   fragment sum_reduce( input: tensor<scalar>, axes: integer[], normalize: logical = false ) -> ( output: tensor<scalar> );
  *   fragment max_reduce( input: tensor<scalar>, axes: integer[] ) -> ( output: tensor<scalar> );
@@ -504,6 +961,13 @@ pub fn reduce(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> Tr
     let input = invocation.named_arg_as(builder, "input")?;
     let axes: TVec<usize> = invocation.named_arg_as(builder, "axes")?;
     let reducer_name = invocation.invocation.id.split('_').next().unwrap();
+    if reducer_name == "any" || reducer_name == "all" {
+        let reducer =
+            if reducer_name == "any" { ops::nn::Reducer::Max } else { ops::nn::Reducer::Min };
+        let wire = builder.wire_as_outlets(ops::cast::cast(u8::datum_type()), &[input])?;
+        let wire = builder.wire_as_outlets(ops::nn::Reduce::new(axes, reducer), &wire)?;
+        return builder.wire(ops::cast::cast(bool::datum_type()), &wire);
+    }
     let reducer = match reducer_name {
         "sum" => ops::nn::Reducer::Sum,
         "min" => ops::nn::Reducer::Min,
@@ -532,43 +996,38 @@ pub fn reduce(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> Tr
 }
 
 /*
-/* override linear to manage quantization in intermediaries
- * fragment linear(
- *    input: tensor<scalar>,
- *    filter: tensor<scalar>,
- *    bias: tensor<scalar> = 0.0 ) -> ( output: tensor<scalar> )
- {
- output = matmul(input, filter, transposeB = true) + bias;
- }
+ * fragment linear( input: tensor<scalar>, filter: tensor<scalar>, bias: tensor<scalar> = 0.0 )
+ * -> ( output: tensor<scalar> )
+ * {
+ *     output = matmul(input, filter, transposeB = true) + bias;
+ * }
+ *
+ * Overridden to keep the bias in the accumulator when quantized.
  */
-pub fn linear(
-    builder: &mut ModelBuilder,
-    invocation: &ResolvedInvocation,
-    ) -> TractResult<TVec<OutletId>> {
+pub fn linear(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
     let input: OutletId = invocation.named_arg_as(builder, "input")?;
     let filter: OutletId = invocation.named_arg_as(builder, "filter")?;
     let bias: OutletId = invocation.named_arg_as(builder, "bias")?;
-    if let Some(Some(dt)) = &invocation.dt_from_quant_file.get(0) {
-        if let Some(_) = dt.qparams() {
-            return builder.wire(
-                ops::matmul::QMatMul {
-                    a_trans: false,
-                    b_trans: true,
-                    c_trans: false,
-                    output_type_foo: *dt,
-                    params: MatMulQParams::all_from_qtype(),
-                },
-                &[input, filter, bias],
-                );
-        }
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    let filter_fact = builder.model.outlet_fact(filter)?.clone();
+    let rank = input_fact.rank().max(filter_fact.rank());
+    let axes = MatMulAxes::default_for_ranks(input_fact.rank(), filter_fact.rank(), rank)
+        .transposing(false, true, false);
+    if input_fact.datum_type.is_quantized() || filter_fact.datum_type.is_quantized() {
+        let accum_dt = DatumType::QI32(QParams::ZpScale {
+            scale: input_fact.datum_type.zp_scale().1 * filter_fact.datum_type.zp_scale().1,
+            zero_point: 0,
+        });
+        let dt = invocation.dt_from_quant_file.get(0).cloned().flatten().unwrap_or(accum_dt);
+        return builder.wire(
+            ops::matmul::QMatMul { axes, output_type: dt, params: MatMulQParams::all_from_qtype() },
+            &[input, filter, bias],
+        );
     }
-    let mul = builder.wire(
-        ops::matmul::MatMul { a_trans: false, b_trans: true, c_trans: false },
-        &[input, filter],
-        )?[0];
-    builder.wire(ops::math::add::bin_typed(), &[bias, mul])
+    let product = builder.wire_as_outlets(ops::matmul::MatMul { axes }, &[input, filter])?[0];
+    let inputs = crate::registry::multicast(builder, &[product, bias])?;
+    builder.wire(ops::math::add(), &inputs)
 }
-*/
 
 /*
  * fragment matmul( A: tensor<scalar>, B: tensor<scalar>, transposeA: logical = false, transposeB: logical = false ) -> ( C: tensor<scalar> );
@@ -706,3 +1165,53 @@ pub fn softmax(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> T
 
     builder.wire(ops::nn::Softmax { axes, output_dt }, &[x])
 }
+
+/*
+ * fragment logarithmic_quantize( x: tensor<scalar>, max: tensor<scalar>, bits: integer ) -> ( y: tensor<scalar> )
+ * {
+ *     m = ceil(log2(max));
+ *     r = scalar(2 ^ bits - 1);
+ *     q = round(clamp(log2(abs(x)), m - r, m));
+ *     y = sign(x) * 2.0 ^ q;
+ * }
+ */
+
+pub fn logarithmic_quantize(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let x: OutletId = invocation.named_arg_as(builder, "x")?;
+    let max: OutletId = invocation.named_arg_as(builder, "max")?;
+    let bits: usize = invocation.named_arg_as(builder, "bits")?;
+    let dt = builder.model.outlet_fact(x)?.datum_type;
+    let scalar = |builder: &mut ModelBuilder, v: f32| -> TractResult<OutletId> {
+        let t = tensor0(v).cast_to_dt(dt)?.into_owned();
+        Ok(builder.wire_as_outlets(ops::konst::Const::new(t.into_arc_tensor()), &[])?[0])
+    };
+    let binary = |builder: &mut ModelBuilder,
+                  op: ops::binary::TypedBinOp,
+                  a: OutletId,
+                  b: OutletId|
+     -> TractResult<OutletId> {
+        let inputs = crate::registry::multicast(builder, &[a, b])?;
+        Ok(builder.wire_as_outlets(op, &inputs)?[0])
+    };
+    let inv_ln_2 = scalar(builder, std::f32::consts::LOG2_E)?;
+    let two = scalar(builder, 2.0)?;
+    let range = scalar(builder, ((1 << bits) - 1) as f32)?;
+
+    let max = builder.wire_as_outlets(ops::math::ln(), &[max])?[0];
+    let max = binary(builder, ops::math::mul(), max, inv_ln_2)?;
+    let max = builder.wire_as_outlets(ops::math::ceil(), &[max])?[0];
+    let min = binary(builder, ops::math::sub(), max, range)?;
+
+    let wire = builder.wire_as_outlets(ops::math::abs(), &[x])?[0];
+    let wire = builder.wire_as_outlets(ops::math::ln(), &[wire])?[0];
+    let wire = binary(builder, ops::math::mul(), wire, inv_ln_2)?;
+    let wire = binary(builder, ops::math::max(), wire, min)?;
+    let wire = binary(builder, ops::math::min(), wire, max)?;
+    let wire = builder.wire_as_outlets(ops::math::round(), &[wire])?[0];
+    let wire = binary(builder, ops::math::pow(), two, wire)?;
+    let sign = builder.wire_as_outlets(ops::math::sign(), &[x])?[0];
+    builder.wire(ops::math::mul(), &[sign, wire])
+}
@@ -25,12 +25,15 @@ pub fn tract_nnef() -> Registry {
     dumper!(ops::source::TypedSource, ser::source);
     primitive(&mut registry, "variable", deser::variable);
     dumper!(ops::konst::Const, ser::konst);
+    primitive(&mut registry, "constant", deser::constant);
+    primitive(&mut registry, "update", deser::update);
 
     primitive(&mut registry, "reshape", deser::reshape);
     primitive(&mut registry, "transpose", deser::transpose);
 
     primitive(&mut registry, "concat", deser::concat);
     dumper!(ops::array::TypedConcat, ser::concat);
+    primitive(&mut registry, "split", deser::split);
     primitive(&mut registry, "slice", deser::slice);
     dumper!(ops::array::Slice, ser::slice);
 
@@ -61,6 +64,8 @@ pub fn tract_nnef() -> Registry {
     registry.register_unit_element_wise("neg", &ops::math::Neg {});
     registry.register_unit_element_wise("sign", &ops::math::Sign {});
     registry.register_unit_element_wise("recip", &ops::math::Recip {});
+    primitive(&mut registry, "rcp", deser::rcp);
+    primitive(&mut registry, "copy", deser::copy);
 
     registry.register_unit_element_wise("tan", &ops::math::Tan {});
     registry.register_unit_element_wise("acos", &ops::math::Acos {});
@@ -116,7 +121,7 @@ pub fn tract_nnef() -> Registry {
     dumper!(ops::matmul::MatMulUnary, ser::matmul_unary);
     dumper!(ops::matmul::MatMul, ser::matmul);
     dumper!(ops::matmul::QMatMul, ser::qmatmul);
-//    primitive(&mut registry, "linear", deser::linear);
+    primitive(&mut registry, "linear", deser::linear);
 
     primitive(&mut registry, "conv", deser::conv);
     dumper!(ops::cnn::ConvUnary, ser::conv);
@@ -128,6 +133,8 @@ pub fn tract_nnef() -> Registry {
     primitive(&mut registry, "min_reduce", deser::reduce);
     primitive(&mut registry, "argmax_reduce", deser::reduce);
     primitive(&mut registry, "argmin_reduce", deser::reduce);
+    primitive(&mut registry, "any_reduce", deser::reduce);
+    primitive(&mut registry, "all_reduce", deser::reduce);
     dumper!(ops::nn::Reduce, ser::reduce);
 
     primitive(&mut registry, "softmax", deser::softmax);
@@ -137,6 +144,23 @@ pub fn tract_nnef() -> Registry {
     dumper!(ops::cnn::MaxPool, ser::max_pool);
     primitive(&mut registry, "box", deser::sum_pool);
     dumper!(ops::cnn::SumPool, ser::sum_pool);
+    primitive(&mut registry, "debox", deser::debox);
+    primitive(&mut registry, "argmax_pool", deser::argmax_pool);
+    primitive(&mut registry, "sample", deser::sample);
+    primitive(&mut registry, "desample", deser::desample);
+
+    primitive(&mut registry, "nearest_upsample", deser::nearest_upsample);
+    primitive(&mut registry, "multilinear_upsample", deser::multilinear_upsample);
+
+    primitive(&mut registry, "avg_roi_pool", deser::roi_pool);
+    primitive(&mut registry, "max_roi_pool", deser::roi_pool);
+    dumper!(ops::cnn::RoiPool, ser::roi_pool);
+    primitive(&mut registry, "roi_resample", deser::roi_resample);
+    primitive(&mut registry, "avg_roi_align", deser::roi_align);
+    primitive(&mut registry, "max_roi_align", deser::roi_align);
+    dumper!(ops::cnn::RoiAlign, ser::roi_align);
+
+    primitive(&mut registry, "logarithmic_quantize", deser::logarithmic_quantize);
 
     for frag in stdlib {
         if frag.body.is_some() {
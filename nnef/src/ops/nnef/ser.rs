@@ -135,8 +135,44 @@ fn data_from_ncwh(data_format: DataFormat, geo_rank: usize, mut wire: Arc<RValue
     wire
 }
 
+/// Paddings actually applied to the input, for schemes NNEF can not express.
+fn explicit_padding(
+    pool_spec: &PoolSpec,
+    input_shape: &[TDim],
+    adjustments: Option<&[usize]>,
+) -> TractResult<Vec<RValue>> {
+    let input_shape = pool_spec.data_format.shape(input_shape)?;
+    let computed = if let Some(adjustments) = adjustments {
+        pool_spec.padding.compute_for_deconv(
+            input_shape.hw_dims(),
+            &pool_spec.kernel_shape,
+            &pool_spec.dilations(),
+            &pool_spec.strides(),
+            adjustments,
+        )?
+    } else {
+        pool_spec.computed_padding(input_shape.hw_dims())
+    };
+    computed
+        .iter()
+        .map(|d| {
+            let (bef, after) = (d.pad_before.to_usize(), d.pad_after.to_usize());
+            if let (Ok(bef), Ok(after)) = (bef, after) {
+                Ok(tuple_2(numeric(bef), numeric(after)))
+            } else {
+                bail!(
+                    "Can not express {:?} with symbolic input {:?}",
+                    pool_spec.padding,
+                    input_shape
+                )
+            }
+        })
+        .collect()
+}
+
 pub fn make_conv_named_args<'a>(
     node: &'a TypedNode,
+    input_shape: &[TDim],
     pool_spec: &'a PoolSpec,
     group: usize,
     deconv: bool,
@@ -144,6 +180,7 @@ pub fn make_conv_named_args<'a>(
 ) -> TractResult<TVec<(&'a str, RValue)>> {
     use tract_core::ops::cnn::PaddingSpec;
     let output_shape = pool_spec.data_format.shape(node.outputs[0].fact.shape.to_tvec())?;
+    let adjusted = deconv && adjustments.unwrap().iter().any(|a| *a != 0);
     let padding = match &pool_spec.padding {
         PaddingSpec::Explicit(bef, after, _) => array(
             &bef.iter()
@@ -151,8 +188,10 @@ pub fn make_conv_named_args<'a>(
                 .map(|(a, b)| tuple_2(numeric(a), numeric(b)))
                 .collect::<Vec<_>>(),
         ),
-        PaddingSpec::SameUpper => array(&[]),
-        PaddingSpec::SameLower => bail!("Unsupported padding scheme"),
+        PaddingSpec::SameUpper if !adjusted => array(&[]),
+        PaddingSpec::SameUpper | PaddingSpec::SameLower => {
+            array(explicit_padding(pool_spec, input_shape, adjustments.filter(|_| deconv))?)
+        }
         PaddingSpec::Valid => array(
             (0..pool_spec.rank()).map(|_| tuple_2(numeric(0), numeric(0))).collect::<Vec<_>>(),
         ),
@@ -164,7 +203,7 @@ pub fn make_conv_named_args<'a>(
         ("groups", numeric(group)),
         ("padding", padding),
     ];
-    if deconv && adjustments.unwrap().iter().any(|a| *a != 0) {
+    if adjusted {
         let output_shape = output_shape
             .hw_dims()
             .iter()
@@ -204,14 +243,16 @@ pub fn conv_or_deconv(
         inputs.push(ast.konst(format!("{}_bias", node.name), bias)?);
     }
 
-    let named_args = make_conv_named_args(node, pool_spec, group, deconv, adjustments)?;
+    let input_shape = ast.model.outlet_fact(node.inputs[0])?.shape.to_tvec();
+    let named_args =
+        make_conv_named_args(node, &input_shape, pool_spec, group, deconv, adjustments)?;
 
     let name = if deconv { "deconv" } else { "conv" };
     wire = invocation(name, &inputs, &named_args);
     // need to force quantization storage as output code may miss it
     let var_name = format!("{}_{}", node.name, name);
     if let Some(qp) = QuantFormat::from_dt(node.outputs[0].fact.datum_type) {
-        ast.quantization.insert(var_name.clone(), qp);
+        ast.quantization.insert(ast.scoped_id(&var_name), qp);
     }
     wire = ast.force_variable(var_name, &wire);
 
@@ -334,7 +375,10 @@ fn cnn_pool(
                 .collect::<Vec<_>>(),
         ),
         PaddingSpec::SameUpper => None,
-        PaddingSpec::SameLower => bail!("Unsupported padding scheme"),
+        PaddingSpec::SameLower => {
+            let input_shape = ast.model.outlet_fact(node.inputs[0])?.shape.to_tvec();
+            Some(explicit_padding(pool_spec, &input_shape, None)?)
+        }
         PaddingSpec::Valid => {
             Some((0..pool_spec.rank()).map(|_| tuple_2(numeric(0), numeric(0))).collect::<Vec<_>>())
         }
@@ -364,7 +408,9 @@ fn cnn_pool(
         params.push(normalize_arg);
     };
     wire = invocation(&conv_fragment, &[wire], &params);
-    wire = ast.force_variable(&node.name, &wire);
+    if node.outputs.len() == 1 {
+        wire = ast.force_variable(&node.name, &wire);
+    }
     Ok(Some(wire))
 }
 
@@ -373,6 +419,13 @@ pub fn max_pool(
     node: &TypedNode,
     op: &ops::cnn::MaxPool,
 ) -> TractResult<Option<Arc<RValue>>> {
+    if op.with_index_outputs.is_some() {
+        // indices are only meaningful to nnef over NCHW planes
+        if op.pool_spec.data_format != DataFormat::NCHW {
+            return Ok(None);
+        }
+        return cnn_pool(ast, node, "max_pool_with_index", &op.pool_spec, None);
+    }
     cnn_pool(ast, node, "max_pool", &op.pool_spec, None)
 }
 
@@ -384,6 +437,66 @@ pub fn sum_pool(
     cnn_pool(ast, node, "box", &op.pool_spec, Some(("normalize", logical(op.normalize))))
 }
 
+/// Regions wire, scaled to input coordinates as nnef has no spatial scale.
+fn roi_regions(ast: &mut IntoAst, node: &TypedNode, spatial_scale: f32) -> Arc<RValue> {
+    let rois = ast.mapping[&node.inputs[1]].clone();
+    if spatial_scale == 1.0 {
+        rois
+    } else {
+        RValue::Binary(
+            rois.as_ref().clone().boxed(),
+            "*".to_string(),
+            numeric(spatial_scale).boxed(),
+        )
+        .into()
+    }
+}
+
+pub fn roi_pool(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::RoiPool,
+) -> TractResult<Option<Arc<RValue>>> {
+    let name = match op.mode {
+        ops::cnn::RoiPoolingMode::Avg => "avg_roi_pool",
+        ops::cnn::RoiPoolingMode::Max => "max_roi_pool",
+    };
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let rois = roi_regions(ast, node, op.spatial_scale);
+    let batch_index = ast.mapping[&node.inputs[2]].clone();
+    Ok(Some(invocation(
+        name,
+        &[input, rois, batch_index],
+        &[("output_size", ints(&[op.output_shape.0, op.output_shape.1]))],
+    )))
+}
+
+pub fn roi_align(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::RoiAlign,
+) -> TractResult<Option<Arc<RValue>>> {
+    // nnef has neither adaptive sampling nor a max of the samples
+    if op.mode != ops::cnn::RoiPoolingMode::Avg
+        || op.sampling_ratio.0 == 0
+        || op.sampling_ratio.1 == 0
+    {
+        return Ok(None);
+    }
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let rois = roi_regions(ast, node, op.spatial_scale);
+    let batch_index = ast.mapping[&node.inputs[2]].clone();
+    Ok(Some(invocation(
+        "avg_roi_align",
+        &[input, rois, batch_index],
+        &[
+            ("output_size", ints(&[op.output_shape.0, op.output_shape.1])),
+            ("sampling_rate", ints(&[op.sampling_ratio.0, op.sampling_ratio.1])),
+            ("resize_method", string(if op.half_pixel { "symmetric" } else { "asymmetric" })),
+        ],
+    )))
+}
+
 pub fn axis_op(
     ast: &mut IntoAst,
     node: &TypedNode,
@@ -0,0 +1,50 @@
+use crate::ast::Literal;
+use crate::internal::*;
+use crate::ser::*;
+use tract_plugin::{Plugin, PluginOp};
+
+pub fn register(registry: &mut Registry, plugin: &Plugin) {
+    registry.register_dumper(TypeId::of::<PluginOp>(), plugin_op_dump);
+    for op in &plugin.ops {
+        let results: Vec<_> = (0..op.n_outputs)
+            .map(|ix| (format!("output_{}", ix), TypeName::Any.tensor()))
+            .collect();
+        let results: Vec<_> = results.iter().map(|(name, ty)| (&**name, ty.clone())).collect();
+        registry
+            .register_primitive(&op.name, &plugin_op_parameters(), &results, plugin_op_load)
+            .with_doc(format!("Operator from plugin {}", plugin.name));
+    }
+}
+
+fn plugin_op_parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Any.tensor().array().named("inputs"),
+        TypeName::String.array().named("attribute_names").default(Literal::Array(vec![])),
+        TypeName::String.array().named("attribute_values").default(Literal::Array(vec![])),
+    ]
+}
+
+fn plugin_op_dump(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<PluginOp>().unwrap();
+    let inputs = node.inputs.iter().map(|i| (*ast.mapping[i]).clone()).collect::<Vec<_>>();
+    let (names, values): (Vec<_>, Vec<_>) =
+        op.attributes.iter().map(|(k, v)| (string(k), string(v))).unzip();
+    Ok(Some(invocation(
+        &op.def.name,
+        &[array(inputs).into()],
+        &[("attribute_names", array(names)), ("attribute_values", array(values))],
+    )))
+}
+
+fn plugin_op_load(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let inputs: TVec<OutletId> = invocation.named_arg_as(builder, "inputs")?;
+    let names: TVec<String> = invocation.named_arg_as(builder, "attribute_names")?;
+    let values: TVec<String> = invocation.named_arg_as(builder, "attribute_values")?;
+    ensure!(names.len() == values.len(), "Expected as many attribute names as values");
+    let attributes = names.into_iter().zip(values).collect();
+    let op = tract_plugin::instantiate(&invocation.invocation.id, attributes)?;
+    builder.wire(op, &inputs)
+}
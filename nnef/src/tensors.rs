@@ -1,8 +1,13 @@
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use std::io::Read;
 use tract_core::internal::*;
 
 const TRACT_ITEM_TYPE_VENDOR: u16 = (b'T' as u16) << 8u16 | b'R' as u16;
 
+// Strings are length-prefixed, so they have no item size. Their data size is not checked
+// against the shape: files written by earlier versions have a meaningless one.
+const STRING_BITS_PER_ITEM: u32 = 0xFFFF;
+
 #[repr(C)]
 #[derive(Debug)]
 struct Header {
@@ -48,7 +53,8 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
                     header.data_size_bytes
                 );
             }
-        } else if header.bits_per_item != 0xFFFFFFFF
+        } else if header.bits_per_item != STRING_BITS_PER_ITEM
+            && header.bits_per_item != 0xFFFFFFFF
             && len * (header.bits_per_item as usize / 8) != header.data_size_bytes as usize
         {
             bail!(
@@ -100,7 +106,8 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
 
             // 5 - 0b0101 - bool values, 1 bit or 8 bits (0 means false, non-zero means true)
             (0, 5, 1) => DatumType::Bool,
-            (TRACT_ITEM_TYPE_VENDOR, 0x1000, 0xFFFF) => DatumType::String,
+            (TRACT_ITEM_TYPE_VENDOR, 0x1000, STRING_BITS_PER_ITEM) => DatumType::String,
+            (TRACT_ITEM_TYPE_VENDOR, 0x1001, 16) => DatumType::BF16,
             (TRACT_ITEM_TYPE_VENDOR, 0, 32) => DatumType::ComplexF16,
             (TRACT_ITEM_TYPE_VENDOR, 0, 64) => DatumType::ComplexF32,
             (TRACT_ITEM_TYPE_VENDOR, 0, 128) => DatumType::ComplexF64,
@@ -131,13 +138,12 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
             }
             Ok(tensor)
         } else if dt == DatumType::String {
-            let mut tensor = Tensor::zero_dt(dt, &shape)?;
+            let mut tensor = Tensor::uninitialized_dt(dt, &shape)?;
             for item in tensor.as_slice_mut_unchecked::<String>() {
                 let len: u32 = reader.read_u32::<LE>()?;
-                let mut bytes = Vec::with_capacity(len as usize);
-                #[allow(clippy::uninit_vec)]
-                bytes.set_len(len as usize);
-                reader.read_exact(&mut bytes)?;
+                let mut bytes = vec![];
+                reader.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+                ensure!(bytes.len() == len as usize, "Truncated string in tensor");
                 *item = String::from_utf8(bytes)?;
             }
             Ok(tensor)
@@ -167,7 +173,10 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
         }
         header.data_size_bytes = (tensor.len() * tensor.datum_type().size_of()) as u32;
         header.bits_per_item = (tensor.datum_type().size_of() * 8) as u32;
-        header.item_type = if tensor.datum_type().is_float() {
+        header.item_type = if tensor.datum_type() == DatumType::BF16 {
+            header.item_type_vendor = TRACT_ITEM_TYPE_VENDOR;
+            0x1001
+        } else if tensor.datum_type().is_float() {
             0
         } else if tensor.datum_type().is_complex_float() {
             header.item_type_vendor = TRACT_ITEM_TYPE_VENDOR;
@@ -181,7 +190,12 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
             1
         } else if tensor.datum_type() == DatumType::String {
             header.item_type_vendor = TRACT_ITEM_TYPE_VENDOR;
-            header.bits_per_item = 0xFFFF;
+            header.bits_per_item = STRING_BITS_PER_ITEM;
+            header.data_size_bytes = tensor
+                .as_slice_unchecked::<String>()
+                .iter()
+                .map(|s| 4 + s.len() as u32)
+                .sum();
             0x1000
         } else {
             bail!("Don't know how to serialize {:?}", tensor.datum_type())
@@ -209,6 +223,41 @@ mod test {
         assert_eq!(std::mem::size_of::<Header>(), 128);
     }
 
+    #[test]
+    fn serde_tensor_bf16() -> TractResult<()> {
+        let t = tensor2(&[[1.0f32, -2.5, 3.0e20], [0.15625, 7.0, -1.0e-20]]).cast_to::<bf16>()?.into_owned();
+        let mut buffer = Vec::<u8>::new();
+        write_tensor(&mut buffer, &t)?;
+        let serde_tensor = read_tensor(buffer.as_slice())?;
+        assert_eq!(t, serde_tensor);
+        Ok(())
+    }
+
+    #[test]
+    fn serde_tensor_string() -> TractResult<()> {
+        let t = tensor1(&["foo".to_string(), "".to_string(), "barbaz".to_string()]);
+        let mut buffer = Vec::<u8>::new();
+        write_tensor(&mut buffer, &t)?;
+        let serde_tensor = read_tensor(buffer.as_slice())?;
+        assert_eq!(t, serde_tensor);
+        Ok(())
+    }
+
+    #[test]
+    fn read_legacy_tensor_string() -> TractResult<()> {
+        let t = tensor1(&["foo".to_string(), "barbaz".to_string()]);
+        let mut buffer = Vec::<u8>::new();
+        write_tensor(&mut buffer, &t)?;
+        // header fields up to data_size_bytes are stable on disk
+        assert_eq!(&buffer[0..4], &[0x4e, 0xef, 1, 0]);
+        assert_eq!(u32::from_le_bytes(buffer[44..48].try_into().unwrap()), 0xFFFF);
+        // earlier versions wrote the in-memory size of the strings as data size
+        let legacy_size = (2 * std::mem::size_of::<String>()) as u32;
+        buffer[4..8].copy_from_slice(&legacy_size.to_le_bytes());
+        assert_eq!(read_tensor(buffer.as_slice())?, t);
+        Ok(())
+    }
+
     #[test]
     fn serde_tensor_complex_f32() -> TractResult<()> {
         let t = tensor2(&[
@@ -0,0 +1,26 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::matmul::{BlockQuantMatMul, MatMulAxes};
+
+#[test]
+fn block_quant_matmul_roundtrip() -> TractResult<()> {
+    let a =
+        Tensor::from_shape(&[4, 64], &(0..256).map(|i| (i % 13) as f32 - 6.0).collect::<Vec<_>>())?;
+    let op = BlockQuantMatMul::quantize(&a, MatMulAxes::default(), BlockQuant::Q4_0)?;
+    let mut model = TypedModel::default();
+    let b = model.add_source("b", f32::fact([64, 3]))?;
+    let c = model.wire_node("c", op, &[b])?;
+    model.set_output_outlets(&c)?;
+
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+    let reloaded_op = reloaded.nodes.iter().find_map(|n| n.op_as::<BlockQuantMatMul>()).unwrap();
+    assert_eq!(reloaded_op.a, model.node(c[0].node).op_as::<BlockQuantMatMul>().unwrap().a);
+
+    let input =
+        Tensor::from_shape(&[64, 3], &(0..192).map(|i| (i % 7) as f32).collect::<Vec<_>>())?;
+    let expected = model.into_runnable()?.run(tvec!(input.clone().into()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input.into()))?;
+    assert_eq!(expected, found);
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::math;
+
+fn check_roundtrip(op: impl Into<Box<dyn TypedOp>>) -> TractResult<()> {
+    let op = op.into();
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", DatumType::ComplexF32.fact([3]))?;
+    let output = model.wire_node("op", op, &[source])?;
+    model.set_output_outlets(&output)?;
+
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+    assert_eq!(reloaded.output_fact(0)?, model.output_fact(0)?);
+
+    let input =
+        tensor1(&[Complex::new(3f32, 4.0), Complex::new(0.0, -1.0), Complex::new(0.0, 0.0)]);
+    let expected = model.into_runnable()?.run(tvec!(input.clone().into()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input.into()))?;
+    assert_eq!(expected, found);
+    Ok(())
+}
+
+#[test]
+fn complex_ops_roundtrip() -> TractResult<()> {
+    check_roundtrip(math::real())?;
+    check_roundtrip(math::imag())?;
+    check_roundtrip(math::conj())?;
+    check_roundtrip(math::complex_abs())?;
+    check_roundtrip(math::angle())
+}
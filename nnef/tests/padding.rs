@@ -0,0 +1,102 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::cnn::deconv::DeconvUnary;
+use tract_nnef::tract_core::ops::cnn::*;
+use tract_nnef::tract_core::ops::nn::DataFormat;
+
+fn paddings() -> Vec<PaddingSpec> {
+    vec![
+        PaddingSpec::Valid,
+        PaddingSpec::SameUpper,
+        PaddingSpec::SameLower,
+        PaddingSpec::Explicit(tvec!(1, 0), tvec!(0, 2), false),
+    ]
+}
+
+fn pool_spec(padding: PaddingSpec, strides: usize) -> PoolSpec {
+    PoolSpec::new(
+        DataFormat::NCHW,
+        tvec!(3, 2),
+        padding,
+        None,
+        Some(tvec!(strides, strides)),
+        Some(2),
+    )
+}
+
+fn kernel() -> Arc<Tensor> {
+    Tensor::from_shape(&[2, 2, 3, 2], &(0..24).map(|i| (i % 5) as f32 - 2.0).collect::<Vec<_>>())
+        .unwrap()
+        .into_arc_tensor()
+}
+
+fn check_roundtrip(op: impl Into<Box<dyn TypedOp>>) -> TractResult<()> {
+    let op = op.into();
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", f32::fact([1, 2, 7, 6]))?;
+    let output = model.wire_node("op", op, &[source])?;
+    model.set_output_outlets(&output)?;
+
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?.into_decluttered()?;
+
+    let input =
+        Tensor::from_shape(&[1, 2, 7, 6], &(0..84).map(|i| (i % 11) as f32).collect::<Vec<_>>())?;
+    let expected = model.into_runnable()?.run(tvec!(input.clone().into()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input.into()))?;
+    expected[0].close_enough(&found[0], true)
+}
+
+#[test]
+fn conv_paddings_roundtrip() -> TractResult<()> {
+    for padding in paddings() {
+        for strides in [1, 2] {
+            let op = ConvUnary {
+                pool_spec: pool_spec(padding.clone(), strides),
+                kernel_fmt: KernelFormat::OIHW,
+                kernel: kernel(),
+                group: 1,
+                bias: None,
+                q_params: None,
+            };
+            check_roundtrip(op).with_context(|| format!("{padding:?} strides: {strides}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn max_pool_paddings_roundtrip() -> TractResult<()> {
+    for padding in paddings() {
+        for strides in [1, 2] {
+            let op = MaxPool {
+                pool_spec: PoolSpec {
+                    output_channel_override: None,
+                    ..pool_spec(padding.clone(), strides)
+                },
+                with_index_outputs: None,
+            };
+            check_roundtrip(op).with_context(|| format!("{padding:?} strides: {strides}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn deconv_paddings_roundtrip() -> TractResult<()> {
+    for padding in paddings() {
+        for adjustments in [tvec!(0, 0), tvec!(1, 0)] {
+            let op = DeconvUnary {
+                pool_spec: pool_spec(padding.clone(), 2),
+                kernel_format: KernelFormat::OIHW,
+                kernel: kernel(),
+                bias: None,
+                adjustments: adjustments.clone(),
+                group: 1,
+            };
+            check_roundtrip(op)
+                .with_context(|| format!("{padding:?} adjustments: {adjustments:?}"))?;
+        }
+    }
+    Ok(())
+}
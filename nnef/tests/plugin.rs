@@ -0,0 +1,42 @@
+#![cfg(feature = "plugin")]
+use tract_nnef::internal::*;
+use tract_nnef::tract_plugin::PluginOp;
+
+#[path = "../../plugin/tests/scale_plugin/mod.rs"]
+mod scale_plugin;
+
+#[test]
+fn plugin_op_roundtrip() -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_loaded_plugin(&scale_plugin::plugin())?;
+    let op =
+        tract_nnef::tract_plugin::instantiate("test_scale", vec![("factor".into(), "3".into())])?;
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", f32::fact([3]))?;
+    let output = model.wire_node("scale", op, &[source])?;
+    model.set_output_outlets(&output)?;
+
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+    let reloaded_op = reloaded.nodes.iter().find_map(|n| n.op_as::<PluginOp>()).unwrap();
+    assert_eq!(reloaded_op.attributes, vec![("factor".to_string(), "3".to_string())]);
+    let result = reloaded.into_runnable()?.run(tvec!(tensor1(&[1f32, 2., 3.]).into()))?;
+    assert_eq!(*result[0], tensor1(&[3f32, 6., 9.]));
+    Ok(())
+}
+
+#[test]
+fn plugin_required_to_load() -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_loaded_plugin(&scale_plugin::plugin())?;
+    let op = tract_nnef::tract_plugin::instantiate(
+        "test_scale",
+        vec![("factor".into(), "3".into())],
+    )?;
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", f32::fact([3]))?;
+    let output = model.wire_node("scale", op, &[source])?;
+    model.set_output_outlets(&output)?;
+
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    assert!(tract_nnef::nnef().model_for_read(&mut &*buffer).is_err());
+    Ok(())
+}
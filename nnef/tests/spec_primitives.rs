@@ -0,0 +1,146 @@
+use tract_nnef::internal::*;
+
+/// Run a test case, checking it gives the same outputs once dumped and reloaded.
+fn run(case: &str, inputs: TVec<Tensor>) -> TractResult<TVec<TValue>> {
+    let path = format!("../harness/nnef-test-cases/{}", case);
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let model = nnef.model_for_path(path)?.into_decluttered()?;
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+    let inputs: TVec<TValue> = inputs.into_iter().map(|t| t.into_tvalue()).collect();
+    let expected = model.into_optimized()?.into_runnable()?.run(inputs.clone())?;
+    let found = reloaded.into_optimized()?.into_runnable()?.run(inputs)?;
+    ensure!(expected.len() == found.len());
+    for (e, f) in expected.iter().zip(found.iter()) {
+        f.close_enough(e, true).with_context(|| format!("Reloaded {} differs", case))?;
+    }
+    Ok(expected)
+}
+
+fn range(shape: &[usize]) -> TractResult<Tensor> {
+    let len = shape.iter().product::<usize>();
+    Tensor::from_shape(shape, &(0..len).map(|i| i as f32).collect::<Vec<_>>())
+}
+
+#[test]
+fn debox() -> TractResult<()> {
+    let output = run("debox", tvec!(range(&[1, 2, 2, 2])?))?;
+    let expected: Vec<f32> = (0..32)
+        .map(|i| {
+            let (c, y, x) = (i / 16, i / 4 % 4, i % 4);
+            (c * 4 + y / 2 * 2 + x / 2) as f32 / 4.0
+        })
+        .collect();
+    output[0].close_enough(&Tensor::from_shape(&[1, 2, 4, 4], &expected)?, true)
+}
+
+#[test]
+fn nearest_upsample() -> TractResult<()> {
+    let output = run("nearest-upsample", tvec!(range(&[1, 1, 2, 3])?))?;
+    let expected: Vec<f32> = (0..24).map(|i| (i / 12 * 3 + i % 6 / 2) as f32).collect();
+    output[0].close_enough(&Tensor::from_shape(&[1, 1, 4, 6], &expected)?, true)
+}
+
+#[test]
+fn multilinear_upsample() -> TractResult<()> {
+    let output = run("multilinear-upsample", tvec!(tensor4(&[[[[1f32, 2.]]]])))?;
+    output[0].close_enough(&tensor4(&[[[[1f32, 1.25, 1.75, 2.]]]]), true)?;
+    output[1].close_enough(&tensor4(&[[[[1f32, 1.5, 2., 1.]]]]), true)?;
+    output[2].close_enough(&tensor4(&[[[[1f32, 4. / 3., 5. / 3., 2.]]]]), true)
+}
+
+#[test]
+fn argmax_pool_sample() -> TractResult<()> {
+    let input = tensor4(&[[[[1f32, 5., 2., 0.], [3., 4., 7., 8.]]]]);
+    let output = run("argmax-pool-sample", tvec!(input))?;
+    assert_eq!(*output[0], tensor4(&[[[[1i64, 7]]]]));
+    assert_eq!(*output[1], tensor4(&[[[[5f32, 8.]]]]));
+    assert_eq!(*output[2], tensor4(&[[[[0f32, 5., 0., 0.], [0., 0., 0., 8.]]]]));
+    Ok(())
+}
+
+#[test]
+fn any_all_reduce() -> TractResult<()> {
+    let input = tensor2(&[[true, false, false], [true, true, true]]);
+    let output = run("any-all-reduce", tvec!(input))?;
+    assert_eq!(*output[0], tensor2(&[[true], [true]]));
+    assert_eq!(*output[1], tensor2(&[[false], [true]]));
+    Ok(())
+}
+
+#[test]
+fn linear() -> TractResult<()> {
+    let output = run("linear", tvec!(tensor2(&[[1f32, 2., 3.], [4., 5., 6.]])))?;
+    output[0].close_enough(&tensor2(&[[4.5f32, 3.5], [10.5, 9.5]]), true)
+}
+
+#[test]
+fn logarithmic_quantize() -> TractResult<()> {
+    let output = run("logarithmic-quantize", tvec!(tensor1(&[3f32, -0.3, 100., 0.01])))?;
+    output[0].close_enough(&tensor1(&[4f32, -0.25, 4., 0.03125]), true)
+}
+
+#[test]
+fn split_copy_rcp() -> TractResult<()> {
+    let output = run("split-copy-rcp", tvec!(tensor2(&[[1f32, 2., 3., 4., 5., 6.]])))?;
+    assert_eq!(*output[0], tensor2(&[[1f32, 2.]]));
+    output[1].close_enough(&tensor2(&[[2f32 / 3., 0.5, 0.4, 2. / 6.]]), true)
+}
+
+#[test]
+fn roi_align_pool() -> TractResult<()> {
+    let output = run("roi-align-pool", tvec!(range(&[1, 1, 4, 4])?))?;
+    output[0].close_enough(&tensor4(&[[[[2.5f32, 4.5], [10.5, 12.5]]]]), true)?;
+    output[1].close_enough(&tensor4(&[[[[5f32, 7.], [13., 15.]]]]), true)?;
+    output[2].close_enough(&tensor4(&[[[[2.5f32, 4.5], [10.5, 12.5]]]]), true)?;
+    assert_eq!(*output[3], tensor4(&[[[[5f32, 6.], [13., 14.]]]]));
+    output[4].close_enough(&tensor4(&[[[[2.5f32, 3.5], [10.5, 11.5]]]]), true)
+}
+
+#[test]
+fn roi_align_roundtrip() -> TractResult<()> {
+    use tract_nnef::tract_core::ops::cnn::{RoiAlign, RoiPoolingMode};
+    let mut model = TypedModel::default();
+    let input = model.add_source("input", f32::fact([1, 1, 8, 8]))?;
+    let rois = model.add_const("rois", tensor2(&[[0f32, 0., 8., 8.]]))?;
+    let batch = model.add_const("batch", tensor1(&[0i64]))?;
+    let op = RoiAlign::new(RoiPoolingMode::Avg, (2, 2), (2, 2), 0.5, true);
+    let output = model.wire_node("align", op, &[input, rois, batch])?;
+    model.set_output_outlets(&output)?;
+
+    let nnef = tract_nnef::nnef();
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?.into_decluttered()?;
+    let input = range(&[1, 1, 8, 8])?;
+    let expected = model.into_runnable()?.run(tvec!(input.clone().into()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input.into()))?;
+    found[0].close_enough(&expected[0], true)
+}
+
+#[test]
+fn max_pool_with_index_roundtrip() -> TractResult<()> {
+    use tract_nnef::tract_core::ops::cnn::{MaxPool, PaddingSpec, PoolSpec};
+    use tract_nnef::tract_core::ops::nn::DataFormat;
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", f32::fact([1, 1, 2, 4]))?;
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        tvec!(2, 2),
+        PaddingSpec::Valid,
+        None,
+        Some(tvec!(2, 2)),
+        None,
+    );
+    let outputs =
+        model.wire_node("pool", MaxPool::new(pool_spec, Some(i64::datum_type())), &[source])?;
+    model.set_output_outlets(&outputs)?;
+
+    let nnef = tract_nnef::nnef();
+    let buffer = nnef.write_to_tar(&model, vec![])?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?.into_decluttered()?;
+    let input = tensor4(&[[[[1f32, 5., 2., 0.], [3., 4., 7., 8.]]]]);
+    let found = reloaded.into_runnable()?.run(tvec!(input.into()))?;
+    assert_eq!(*found[0], tensor4(&[[[[5f32, 8.]]]]));
+    assert_eq!(*found[1], tensor4(&[[[[1i64, 7]]]]));
+    Ok(())
+}
@@ -1,12 +1,18 @@
 use tract_nnef::internal::*;
 
 pub mod category_mapper;
+pub mod probit;
+pub mod svm;
 pub mod tree;
 pub mod tree_ensemble_classifier;
+pub mod tree_ensemble_regressor;
 
 pub use category_mapper::{DirectLookup, ReverseLookup};
 
 pub fn register(registry: &mut Registry) {
     category_mapper::register(registry);
+    svm::register(registry);
     tree_ensemble_classifier::register(registry);
+    tree_ensemble_regressor::register(registry);
+    registry.register_unit_element_wise("tract_onnx_ml_probit", &probit::Probit {});
 }
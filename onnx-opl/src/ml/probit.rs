@@ -0,0 +1,34 @@
+use tract_nnef::internal::*;
+
+// The ONNX-ML PROBIT post transform is the inverse of the standard normal CDF.
+tract_core::element_wise!(probit, Probit,
+    [f32] => |_, xs| {
+        xs.iter_mut().for_each(|x| *x = std::f32::consts::SQRT_2 * erf_inv(2.0 * *x - 1.0));
+        Ok(())
+    };
+    prefix: "onnx.ml."
+);
+
+// Winitzki approximation, same as onnxruntime
+fn erf_inv(x: f32) -> f32 {
+    let sgn = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = (1.0 - x) * (1.0 + x);
+    let log = x.ln();
+    let v = 2.0 / (std::f32::consts::PI * 0.147) + 0.5 * log;
+    let v2 = 1.0 / 0.147 * log;
+    let v3 = -v + (v * v - v2).sqrt();
+    sgn * v3.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probit_values() {
+        for (p, z) in [(0.5f32, 0.0f32), (0.8413, 1.0), (0.0228, -2.0)] {
+            let found = std::f32::consts::SQRT_2 * erf_inv(2.0 * p - 1.0);
+            assert!((found - z).abs() < 1e-2, "probit({}) = {}, expected {}", p, found, z);
+        }
+    }
+}
@@ -0,0 +1,658 @@
+use tract_ndarray::prelude::*;
+use tract_nnef::internal::*;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_ml_svm_classifier",
+        &parameters_classifier(),
+        &[("winners", TypeName::Integer.tensor()), ("scores", TypeName::Scalar.tensor())],
+        load_classifier,
+    );
+    registry.register_primitive(
+        "tract_onnx_ml_svm_regressor",
+        &parameters_regressor(),
+        &[("output", TypeName::Scalar.tensor())],
+        load_regressor,
+    );
+    registry.register_dumper(TypeId::of::<SvmClassifier>(), dump_classifier);
+    registry.register_dumper(TypeId::of::<SvmRegressor>(), dump_regressor);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KernelType {
+    Linear,
+    Poly,
+    Rbf,
+    Sigmoid,
+}
+
+pub fn parse_kernel_type(s: &str) -> TractResult<KernelType> {
+    match s {
+        "LINEAR" => Ok(KernelType::Linear),
+        "POLY" => Ok(KernelType::Poly),
+        "RBF" => Ok(KernelType::Rbf),
+        "SIGMOID" => Ok(KernelType::Sigmoid),
+        _ => bail!("Invalid SVM kernel type: {}", s),
+    }
+}
+
+fn kernel_type_name(k: KernelType) -> &'static str {
+    match k {
+        KernelType::Linear => "LINEAR",
+        KernelType::Poly => "POLY",
+        KernelType::Rbf => "RBF",
+        KernelType::Sigmoid => "SIGMOID",
+    }
+}
+
+#[derive(Clone, Copy, Debug, Educe)]
+#[educe(Hash)]
+pub struct SvmKernel {
+    pub kernel_type: KernelType,
+    #[educe(Hash(method = "hash_f32"))]
+    pub gamma: f32,
+    #[educe(Hash(method = "hash_f32"))]
+    pub coef0: f32,
+    #[educe(Hash(method = "hash_f32"))]
+    pub degree: f32,
+}
+
+impl SvmKernel {
+    pub fn new(kernel_type: KernelType, params: &[f32]) -> SvmKernel {
+        let param = |ix: usize| params.get(ix).copied().unwrap_or(0.0);
+        SvmKernel { kernel_type, gamma: param(0), coef0: param(1), degree: param(2) }
+    }
+
+    fn compute(&self, a: &ArrayView1<f32>, b: &ArrayView1<f32>) -> f32 {
+        match self.kernel_type {
+            KernelType::Linear => a.dot(b),
+            KernelType::Poly => (self.gamma * a.dot(b) + self.coef0).powf(self.degree),
+            KernelType::Sigmoid => (self.gamma * a.dot(b) + self.coef0).tanh(),
+            KernelType::Rbf => {
+                let d: f32 = a.iter().zip(b.iter()).map(|(a, b)| (a - b) * (a - b)).sum();
+                (-self.gamma * d).exp()
+            }
+        }
+    }
+
+    fn params(&self) -> [f32; 3] {
+        [self.gamma, self.coef0, self.degree]
+    }
+}
+
+/// Support vector classifier.
+///
+/// Without support vectors, this is a linear model: one row of coefficients per class. With
+/// support vectors, classes are compared pairwise (one vs one) and a vote picks the winner.
+/// When Platt scaling coefficients (`prob_a`, `prob_b`) are present, pairwise probabilities
+/// are coupled into per-class probabilities and the most likely class wins.
+///
+/// First output is the index of the winning class, second one is the scores.
+#[derive(Clone, Debug, Hash)]
+pub struct SvmClassifier {
+    pub kernel: SvmKernel,
+    pub n_classes: usize,
+    // [n_sv, n_features], empty for linear mode
+    pub support_vectors: Arc<Tensor>,
+    pub vectors_per_class: Arc<Tensor>,
+    pub coefficients: Arc<Tensor>,
+    pub rho: Arc<Tensor>,
+    pub prob_a: Option<Arc<Tensor>>,
+    pub prob_b: Option<Arc<Tensor>>,
+}
+
+impl_dyn_hash!(SvmClassifier);
+
+impl SvmClassifier {
+    /// Checks the intercepts: one (shared) or one per class for the linear mode, one per class
+    /// pair with support vectors.
+    pub fn check(&self) -> TractResult<()> {
+        ensure!(self.n_classes >= 2, "SvmClassifier needs at least two classes");
+        let rho = self.rho.len();
+        if self.is_linear() {
+            ensure!(
+                rho == 1 || rho == self.n_classes,
+                "SvmClassifier expects 1 or {} rho values, got {}",
+                self.n_classes,
+                rho
+            );
+        } else {
+            ensure!(
+                rho == self.n_pairs(),
+                "SvmClassifier expects {} rho values, got {}",
+                self.n_pairs(),
+                rho
+            );
+        }
+        Ok(())
+    }
+
+    fn is_linear(&self) -> bool {
+        self.support_vectors.len() == 0
+    }
+
+    fn n_pairs(&self) -> usize {
+        self.n_classes * (self.n_classes - 1) / 2
+    }
+
+    pub fn n_scores(&self) -> usize {
+        if self.is_linear() || self.prob_a.is_some() || self.n_classes == 2 {
+            self.n_classes
+        } else {
+            self.n_pairs()
+        }
+    }
+
+    fn eval_linear(
+        &self,
+        x: &ArrayView1<f32>,
+        scores: &mut ArrayViewMut1<f32>,
+    ) -> TractResult<i32> {
+        let coefs = self.coefficients.to_array_view::<f32>()?;
+        let coefs = coefs.into_shape((self.n_classes, x.len()))?;
+        let rho = self.rho.as_slice::<f32>()?;
+        for c in 0..self.n_classes {
+            let rho = if rho.len() == 1 { rho[0] } else { rho[c] };
+            scores[c] = self.kernel.compute(x, &coefs.row(c)) + rho;
+        }
+        Ok(argmax(scores.as_slice().unwrap()))
+    }
+
+    fn eval_svc(&self, x: &ArrayView1<f32>, scores: &mut ArrayViewMut1<f32>) -> TractResult<i32> {
+        let svs = self.support_vectors.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let n_sv = svs.shape()[0];
+        let per_class = self.vectors_per_class.as_slice::<i64>()?;
+        let coefs = self.coefficients.as_slice::<f32>()?;
+        let rho = self.rho.as_slice::<f32>()?;
+        let kernels: Vec<f32> = svs.outer_iter().map(|sv| self.kernel.compute(x, &sv)).collect();
+        let starts: Vec<usize> = per_class
+            .iter()
+            .scan(0usize, |acc, &n| {
+                let start = *acc;
+                *acc += n as usize;
+                Some(start)
+            })
+            .collect();
+        let mut decisions = Vec::with_capacity(self.n_pairs());
+        let mut votes = vec![0usize; self.n_classes];
+        for i in 0..self.n_classes {
+            for j in i + 1..self.n_classes {
+                let (si, ni) = (starts[i], per_class[i] as usize);
+                let (sj, nj) = (starts[j], per_class[j] as usize);
+                let mut d = rho[decisions.len()];
+                for k in 0..ni {
+                    d += coefs[(j - 1) * n_sv + si + k] * kernels[si + k];
+                }
+                for k in 0..nj {
+                    d += coefs[i * n_sv + sj + k] * kernels[sj + k];
+                }
+                votes[if d > 0.0 { i } else { j }] += 1;
+                decisions.push(d);
+            }
+        }
+        if let (Some(a), Some(b)) = (&self.prob_a, &self.prob_b) {
+            let (a, b) = (a.as_slice::<f32>()?, b.as_slice::<f32>()?);
+            let mut r = Array2::<f32>::zeros((self.n_classes, self.n_classes));
+            let mut ix = 0;
+            for i in 0..self.n_classes {
+                for j in i + 1..self.n_classes {
+                    let p = sigmoid_predict(decisions[ix], a[ix], b[ix]).clamp(1e-7, 1.0 - 1e-7);
+                    r[(i, j)] = p;
+                    r[(j, i)] = 1.0 - p;
+                    ix += 1;
+                }
+            }
+            let probs = multiclass_probability(&r.view());
+            scores.iter_mut().zip(probs.iter()).for_each(|(s, p)| *s = *p);
+            Ok(argmax(&probs))
+        } else {
+            if self.n_classes == 2 {
+                scores[0] = decisions[0];
+                scores[1] = -decisions[0];
+            } else {
+                scores.iter_mut().zip(decisions.iter()).for_each(|(s, d)| *s = *d);
+            }
+            // first class with the most votes wins
+            let max = votes.iter().max().copied().unwrap_or(0);
+            Ok(votes.iter().position(|v| *v == max).unwrap_or(0) as i32)
+        }
+    }
+}
+
+fn argmax(v: &[f32]) -> i32 {
+    let mut best = 0;
+    for (ix, x) in v.iter().enumerate() {
+        if *x > v[best] {
+            best = ix;
+        }
+    }
+    best as i32
+}
+
+// libsvm Platt scaling
+fn sigmoid_predict(decision: f32, a: f32, b: f32) -> f32 {
+    let f_apb = decision * a + b;
+    if f_apb >= 0.0 {
+        (-f_apb).exp() / (1.0 + (-f_apb).exp())
+    } else {
+        1.0 / (1.0 + f_apb.exp())
+    }
+}
+
+// libsvm pairwise coupling (Wu, Lin and Weng, method 2)
+fn multiclass_probability(r: &ArrayView2<f32>) -> Vec<f32> {
+    let k = r.shape()[0];
+    let mut q = Array2::<f32>::zeros((k, k));
+    for t in 0..k {
+        for j in 0..k {
+            if j == t {
+                continue;
+            }
+            q[(t, t)] += r[(j, t)] * r[(j, t)];
+            q[(t, j)] = -r[(j, t)] * r[(t, j)];
+        }
+    }
+    let mut p = vec![1.0 / k as f32; k];
+    let mut qp = vec![0f32; k];
+    let eps = 0.005 / k as f32;
+    for _ in 0..100.max(k) {
+        let mut pqp = 0.0;
+        for t in 0..k {
+            qp[t] = (0..k).map(|j| q[(t, j)] * p[j]).sum();
+            pqp += p[t] * qp[t];
+        }
+        if qp.iter().all(|qp| (qp - pqp).abs() < eps) {
+            break;
+        }
+        for t in 0..k {
+            let diff = (-qp[t] + pqp) / q[(t, t)];
+            p[t] += diff;
+            pqp = (pqp + diff * (diff * q[(t, t)] + 2.0 * qp[t])) / (1.0 + diff) / (1.0 + diff);
+            for j in 0..k {
+                qp[j] = (qp[j] + diff * q[(t, j)]) / (1.0 + diff);
+                p[j] /= 1.0 + diff;
+            }
+        }
+    }
+    p
+}
+
+impl Op for SvmClassifier {
+    fn name(&self) -> Cow<str> {
+        "SvmClassifier".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "{:?} kernel, {} classes, {} support vectors",
+            self.kernel.kernel_type,
+            self.n_classes,
+            self.support_vectors.shape().first().copied().unwrap_or(0)
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SvmClassifier {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let input = input.cast_to::<f32>()?;
+        let input = input.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let n = input.shape()[0];
+        let mut winners = Array1::<i32>::zeros(n);
+        let mut scores = Array2::<f32>::zeros((n, self.n_scores()));
+        for (ix, x) in input.outer_iter().enumerate() {
+            winners[ix] = if self.is_linear() {
+                self.eval_linear(&x, &mut scores.row_mut(ix))?
+            } else {
+                self.eval_svc(&x, &mut scores.row_mut(ix))?
+            };
+        }
+        Ok(tvec!(winners.into_tvalue(), scores.into_tvalue()))
+    }
+}
+
+impl TypedOp for SvmClassifier {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 2, "SvmClassifier expects a 2D input");
+        let n = &inputs[0].shape[0];
+        Ok(tvec!(i32::fact([n.clone()]), f32::fact([n.clone(), self.n_scores().into()])))
+    }
+
+    as_op!();
+}
+
+/// Support vector regressor.
+///
+/// Without support vectors, the coefficients are a single linear model. With `one_class`, the
+/// output is mapped to 1 or -1 depending on its sign.
+#[derive(Clone, Debug, Hash)]
+pub struct SvmRegressor {
+    pub kernel: SvmKernel,
+    // [n_sv, n_features], empty for linear mode
+    pub support_vectors: Arc<Tensor>,
+    pub coefficients: Arc<Tensor>,
+    pub rho: Arc<Tensor>,
+    pub one_class: bool,
+}
+
+impl_dyn_hash!(SvmRegressor);
+
+impl Op for SvmRegressor {
+    fn name(&self) -> Cow<str> {
+        "SvmRegressor".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SvmRegressor {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let input = input.cast_to::<f32>()?;
+        let input = input.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let coefs = self.coefficients.to_array_view::<f32>()?.into_dimensionality::<Ix1>()?;
+        let rho = self.rho.as_slice::<f32>()?.first().copied().unwrap_or(0.0);
+        let svs = self.support_vectors.to_array_view::<f32>()?;
+        let mut output = Array2::<f32>::zeros((input.shape()[0], 1));
+        for (ix, x) in input.outer_iter().enumerate() {
+            let mut y = if self.support_vectors.len() == 0 {
+                self.kernel.compute(&x, &coefs)
+            } else {
+                let svs = svs.view().into_dimensionality::<Ix2>()?;
+                svs.outer_iter()
+                    .zip(coefs.iter())
+                    .map(|(sv, c)| c * self.kernel.compute(&x, &sv))
+                    .sum()
+            } + rho;
+            if self.one_class {
+                y = if y > 0.0 { 1.0 } else { -1.0 };
+            }
+            output[(ix, 0)] = y;
+        }
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for SvmRegressor {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 2, "SvmRegressor expects a 2D input");
+        Ok(tvec!(f32::fact(&[inputs[0].shape[0].clone(), 1.to_dim()])))
+    }
+
+    as_op!();
+}
+
+fn kernel_parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::String.named("kernel_type"),
+        TypeName::Scalar.array().named("kernel_params"),
+        TypeName::Scalar.tensor().named("support_vectors"),
+        TypeName::Scalar.tensor().named("coefficients"),
+        TypeName::Scalar.tensor().named("rho"),
+    ]
+}
+
+fn parameters_classifier() -> Vec<Parameter> {
+    let mut params = vec![TypeName::Scalar.tensor().named("input")];
+    params.extend(kernel_parameters());
+    params.push(TypeName::Integer.tensor().named("vectors_per_class"));
+    params.push(TypeName::Integer.named("n_classes"));
+    params.push(TypeName::Scalar.tensor().named("prob_a"));
+    params.push(TypeName::Scalar.tensor().named("prob_b"));
+    params
+}
+
+fn parameters_regressor() -> Vec<Parameter> {
+    let mut params = vec![TypeName::Scalar.tensor().named("input")];
+    params.extend(kernel_parameters());
+    params.push(TypeName::Logical.named("one_class").default(false));
+    params
+}
+
+fn dump_kernel(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    kernel: &SvmKernel,
+    support_vectors: &Arc<Tensor>,
+    coefficients: &Arc<Tensor>,
+    rho: &Arc<Tensor>,
+) -> TractResult<Vec<(&'static str, RValue)>> {
+    let svs = ast.konst_variable(format!("{}_support_vectors", node.name), support_vectors)?;
+    let coefs = ast.konst_variable(format!("{}_coefficients", node.name), coefficients)?;
+    let rho = ast.konst_variable(format!("{}_rho", node.name), rho)?;
+    Ok(vec![
+        ("kernel_type", string(kernel_type_name(kernel.kernel_type))),
+        (
+            "kernel_params",
+            tract_nnef::ser::array(kernel.params().iter().map(numeric).collect::<Vec<_>>()),
+        ),
+        ("support_vectors", (*svs).clone()),
+        ("coefficients", (*coefs).clone()),
+        ("rho", (*rho).clone()),
+    ])
+}
+
+fn load_kernel(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<SvmKernel> {
+    let kernel_type: String = invocation.named_arg_as(builder, "kernel_type")?;
+    let params: TVec<f32> = invocation.named_arg_as(builder, "kernel_params")?;
+    Ok(SvmKernel::new(parse_kernel_type(&kernel_type)?, &params))
+}
+
+fn dump_classifier(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<SvmClassifier>().context("wrong op")?;
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let mut named =
+        dump_kernel(ast, node, &op.kernel, &op.support_vectors, &op.coefficients, &op.rho)?;
+    let per_class =
+        ast.konst_variable(format!("{}_vectors_per_class", node.name), &op.vectors_per_class)?;
+    named.push(("vectors_per_class", (*per_class).clone()));
+    named.push(("n_classes", numeric(op.n_classes)));
+    // no probability calibration is dumped as empty tensors
+    let empty = rctensor1::<f32>(&[]);
+    let a =
+        ast.konst_variable(format!("{}_prob_a", node.name), op.prob_a.as_ref().unwrap_or(&empty))?;
+    let b =
+        ast.konst_variable(format!("{}_prob_b", node.name), op.prob_b.as_ref().unwrap_or(&empty))?;
+    named.push(("prob_a", (*a).clone()));
+    named.push(("prob_b", (*b).clone()));
+    Ok(Some(invocation("tract_onnx_ml_svm_classifier", &[input], &named)))
+}
+
+fn load_classifier(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let kernel = load_kernel(builder, invocation)?;
+    let support_vectors = invocation.named_arg_as(builder, "support_vectors")?;
+    let coefficients = invocation.named_arg_as(builder, "coefficients")?;
+    let rho = invocation.named_arg_as(builder, "rho")?;
+    let vectors_per_class = invocation.named_arg_as(builder, "vectors_per_class")?;
+    let n_classes = invocation.named_arg_as(builder, "n_classes")?;
+    let prob_a: Arc<Tensor> = invocation.named_arg_as(builder, "prob_a")?;
+    let prob_b: Arc<Tensor> = invocation.named_arg_as(builder, "prob_b")?;
+    let (prob_a, prob_b) =
+        if prob_a.len() > 0 { (Some(prob_a), Some(prob_b)) } else { (None, None) };
+    let op = SvmClassifier {
+        kernel,
+        n_classes,
+        support_vectors,
+        vectors_per_class,
+        coefficients,
+        rho,
+        prob_a,
+        prob_b,
+    };
+    op.check()?;
+    builder.wire(op, &[input])
+}
+
+fn dump_regressor(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<SvmRegressor>().context("wrong op")?;
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let mut named =
+        dump_kernel(ast, node, &op.kernel, &op.support_vectors, &op.coefficients, &op.rho)?;
+    named.push(("one_class", logical(op.one_class)));
+    Ok(Some(invocation("tract_onnx_ml_svm_regressor", &[input], &named)))
+}
+
+fn load_regressor(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let kernel = load_kernel(builder, invocation)?;
+    let support_vectors = invocation.named_arg_as(builder, "support_vectors")?;
+    let coefficients = invocation.named_arg_as(builder, "coefficients")?;
+    let rho = invocation.named_arg_as(builder, "rho")?;
+    let one_class = invocation.named_arg_as(builder, "one_class")?;
+    let op = SvmRegressor { kernel, support_vectors, coefficients, rho, one_class };
+    builder.wire(op, &[input])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rbf() -> SvmKernel {
+        SvmKernel::new(KernelType::Rbf, &[0.5, 0.0, 3.0])
+    }
+
+    #[test]
+    fn linear_classifier() -> TractResult<()> {
+        let op = SvmClassifier {
+            kernel: SvmKernel::new(KernelType::Linear, &[]),
+            n_classes: 3,
+            support_vectors: rctensor1::<f32>(&[]),
+            vectors_per_class: rctensor1::<i64>(&[]),
+            coefficients: rctensor1(&[1f32, 0., 0., 1., -1., -1.]),
+            rho: rctensor1(&[0f32, 0., 0.5]),
+            prob_a: None,
+            prob_b: None,
+        };
+        let input = tensor2(&[[2f32, 1.], [0., 3.], [-1., -1.]]);
+        let outputs = op.eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*outputs[0], tensor1(&[0i32, 1, 2]));
+        assert_eq!(*outputs[1], tensor2(&[[2f32, 1., -2.5], [0., 3., -2.5], [-1., -1., 2.5]]));
+        Ok(())
+    }
+
+    #[test]
+    fn check_rho() {
+        let op = SvmClassifier {
+            kernel: SvmKernel::new(KernelType::Linear, &[]),
+            n_classes: 3,
+            support_vectors: rctensor1::<f32>(&[]),
+            vectors_per_class: rctensor1::<i64>(&[]),
+            coefficients: rctensor1(&[1f32, 0., 0., 1., -1., -1.]),
+            rho: rctensor1(&[0.5f32]),
+            prob_a: None,
+            prob_b: None,
+        };
+        assert!(op.check().is_ok());
+        assert!(SvmClassifier { rho: rctensor1::<f32>(&[]), ..op.clone() }.check().is_err());
+        assert!(SvmClassifier { rho: rctensor1(&[0f32, 1.]), ..op }.check().is_err());
+    }
+
+    #[test]
+    fn svc_votes() -> TractResult<()> {
+        // one support vector per class, sitting on the class "center"
+        let op = SvmClassifier {
+            kernel: rbf(),
+            n_classes: 3,
+            support_vectors: rctensor2(&[[0f32, 0.], [4., 0.], [0., 4.]]),
+            vectors_per_class: rctensor1(&[1i64, 1, 1]),
+            coefficients: rctensor1(&[1f32, -1., -1., 1., 1., -1.]),
+            rho: rctensor1(&[0f32, 0., 0.]),
+            prob_a: None,
+            prob_b: None,
+        };
+        let input = tensor2(&[[0.1f32, 0.], [3.9, 0.1], [0.2, 3.5]]);
+        let outputs = op.eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*outputs[0], tensor1(&[0i32, 1, 2]));
+        assert_eq!(outputs[1].shape(), &[3, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn svc_probabilities() -> TractResult<()> {
+        let op = SvmClassifier {
+            kernel: rbf(),
+            n_classes: 2,
+            support_vectors: rctensor2(&[[0f32, 0.], [4., 0.]]),
+            vectors_per_class: rctensor1(&[1i64, 1]),
+            coefficients: rctensor1(&[1f32, -1.]),
+            rho: rctensor1(&[0f32]),
+            prob_a: Some(rctensor1(&[-3f32])),
+            prob_b: Some(rctensor1(&[0f32])),
+        };
+        let input = tensor2(&[[0.1f32, 0.], [3.9, 0.1]]);
+        let outputs = op.eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*outputs[0], tensor1(&[0i32, 1]));
+        let probs = outputs[1].to_array_view::<f32>()?;
+        for row in probs.outer_iter() {
+            assert!((row.sum() - 1.0).abs() < 1e-5);
+        }
+        assert!(probs[[0, 0]] > 0.9);
+        assert!(probs[[1, 1]] > 0.9);
+        Ok(())
+    }
+
+    #[test]
+    fn regressor() -> TractResult<()> {
+        let op = SvmRegressor {
+            kernel: SvmKernel::new(KernelType::Linear, &[]),
+            support_vectors: rctensor2(&[[1f32, 0.], [0., 1.]]),
+            coefficients: rctensor1(&[2f32, -1.]),
+            rho: rctensor1(&[0.5f32]),
+            one_class: false,
+        };
+        let input = tensor2(&[[1f32, 1.], [3., 0.]]);
+        let outputs = op.eval(tvec!(input.clone().into_tvalue()))?;
+        assert_eq!(*outputs[0], tensor2(&[[1.5f32], [6.5]]));
+        let one_class = SvmRegressor { one_class: true, rho: rctensor1(&[-2f32]), ..op };
+        let outputs = one_class.eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*outputs[0], tensor2(&[[-1f32], [1.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn nnef_round_trip() -> TractResult<()> {
+        use crate::WithOnnx;
+        let op = SvmClassifier {
+            kernel: rbf(),
+            n_classes: 2,
+            support_vectors: rctensor2(&[[0f32, 0.], [4., 0.]]),
+            vectors_per_class: rctensor1(&[1i64, 1]),
+            coefficients: rctensor1(&[1f32, -1.]),
+            rho: rctensor1(&[0f32]),
+            prob_a: Some(rctensor1(&[-3f32])),
+            prob_b: Some(rctensor1(&[0f32])),
+        };
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", f32::fact([2, 2]))?;
+        let outputs = model.wire_node("svc", op, &[source])?;
+        model.set_output_outlets(&outputs)?;
+        let nnef = tract_nnef::nnef().with_onnx();
+        let buffer = nnef.write_to_tar(&model, vec![])?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        let input = tensor2(&[[0.1f32, 0.], [3.9, 0.1]]);
+        let expected = model.into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let found = reloaded.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        assert_eq!(expected, found);
+        Ok(())
+    }
+}
@@ -52,7 +52,7 @@ impl Cmp {
 impl TryFrom<u8> for Cmp {
     type Error = TractError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if (1..=5).contains(&value) {
+        if (1..=6).contains(&value) {
             unsafe { Ok(std::mem::transmute(value)) }
         } else {
             bail!("Invalid value for Cmp: {}", value);
@@ -157,13 +157,8 @@ impl TreeEnsembleData {
         T: AsPrimitive<f32>,
     {
         let leaf = self.get_leaf_unchecked(tree, input);
-        for leaf in self
-            .leaves
-            .to_array_view_unchecked::<u32>()
-            .outer_iter()
-            .skip(leaf.start_id)
-            .take(leaf.end_id - leaf.start_id)
-        {
+        let leaves = &self.leaves.as_slice_unchecked::<u32>()[leaf.start_id * 2..leaf.end_id * 2];
+        for leaf in leaves.chunks_exact(2) {
             let class_id = leaf[0] as usize;
             let weight = f32::from_bits(leaf[1]);
             let agg_fn = aggs.get_unchecked_mut(class_id);
@@ -251,20 +246,34 @@ impl AggregateFn for AvgFn {
 }
 
 #[derive(Clone, Copy, Default, Debug)]
-pub struct MaxFn;
+pub struct MaxFn {
+    seen: bool,
+}
 
 impl AggregateFn for MaxFn {
     fn aggregate(&mut self, score: f32, total: &mut f32) {
-        *total = total.max(score);
+        *total = if self.seen { total.max(score) } else { score };
+        self.seen = true;
+    }
+
+    fn post_aggregate(&mut self, _total: &mut f32) {
+        self.seen = false;
     }
 }
 
 #[derive(Clone, Copy, Default, Debug)]
-pub struct MinFn;
+pub struct MinFn {
+    seen: bool,
+}
 
 impl AggregateFn for MinFn {
     fn aggregate(&mut self, score: f32, total: &mut f32) {
-        *total = total.min(score);
+        *total = if self.seen { total.min(score) } else { score };
+        self.seen = true;
+    }
+
+    fn post_aggregate(&mut self, _total: &mut f32) {
+        self.seen = false;
     }
 }
 
@@ -282,6 +291,9 @@ impl Default for Aggregate {
     }
 }
 
+// rows evaluated together, tree by tree
+const ROW_BLOCK: usize = 32;
+
 #[derive(Clone, Debug, Hash)]
 pub struct TreeEnsemble {
     pub data: TreeEnsembleData,
@@ -304,23 +316,6 @@ impl TreeEnsemble {
         self.n_classes
     }
 
-    unsafe fn eval_one_unchecked<A, T>(
-        &self,
-        input: &ArrayView1<T>,
-        output: &mut ArrayViewMut1<f32>,
-        aggs: &mut [A],
-    ) where
-        A: AggregateFn,
-        T: AsPrimitive<f32>,
-    {
-        for t in 0..self.data.trees.len() {
-            self.data.eval_unchecked(t, input, output, aggs)
-        }
-        for i in 0..self.n_classes {
-            aggs.get_unchecked_mut(i).post_aggregate(output.uget_mut(i));
-        }
-    }
-
     pub fn check_n_features(&self, n_features: usize) -> TractResult<()> {
         ensure!(
             n_features > self.max_used_feature,
@@ -338,16 +333,30 @@ impl TreeEnsemble {
     {
         self.check_n_features(input.shape()[1])?;
         let n = input.shape()[0];
-        let mut output = Array2::zeros((n, self.n_classes));
-        let mut aggs: tract_smallvec::SmallVec<[A; 16]> =
-            iter::repeat_with(Default::default).take(self.n_classes).collect();
-        for i in 0..n {
-            unsafe {
-                self.eval_one_unchecked::<A, T>(
-                    &input.index_axis(Axis(0), i),
-                    &mut output.index_axis_mut(Axis(0), i),
-                    &mut aggs,
-                );
+        let n_classes = self.n_classes;
+        let mut output = Array2::zeros((n, n_classes));
+        let mut aggs: Vec<A> =
+            iter::repeat_with(Default::default).take(ROW_BLOCK * n_classes).collect();
+        // rows are evaluated by blocks, one tree at a time, so that the nodes of a tree stay in
+        // cache while all the rows of the block walk it
+        for start in (0..n).step_by(ROW_BLOCK) {
+            let end = (start + ROW_BLOCK).min(n);
+            for t in 0..self.data.trees.len() {
+                for row in start..end {
+                    unsafe {
+                        self.data.eval_unchecked(
+                            t,
+                            &input.index_axis(Axis(0), row),
+                            &mut output.index_axis_mut(Axis(0), row),
+                            &mut aggs[(row - start) * n_classes..][..n_classes],
+                        );
+                    }
+                }
+            }
+            for row in start..end {
+                for c in 0..n_classes {
+                    aggs[(row - start) * n_classes + c].post_aggregate(&mut output[(row, c)]);
+                }
             }
         }
         Ok(output)
@@ -358,14 +367,8 @@ impl TreeEnsemble {
         A: AggregateFn,
         T: AsPrimitive<f32>,
     {
-        self.check_n_features(input.len())?;
-        let mut output = Array1::zeros(self.n_classes);
-        let mut aggs: tract_smallvec::SmallVec<[A; 16]> =
-            iter::repeat_with(Default::default).take(self.n_classes).collect();
-        unsafe {
-            self.eval_one_unchecked::<A, T>(input, &mut output.view_mut(), &mut aggs);
-        }
-        Ok(output)
+        let output = self.eval_2d::<A, T>(&input.view().insert_axis(Axis(0)))?;
+        Ok(output.index_axis_move(Axis(0), 0))
     }
 
     pub fn eval<'i, I, T>(&self, input: I) -> TractResult<ArrayD<f32>>
@@ -580,4 +583,51 @@ mod tests {
         let output = ensemble.eval(&input.view().into_dyn()).unwrap();
         assert_eq!(output, generate_gbm_raw_output().into_dyn());
     }
+
+    fn generate_stumps(aggregate_fn: Aggregate) -> TreeEnsemble {
+        // two stumps on feature 0, with two targets
+        let trees = rctensor1(&[0u32, 3]);
+        let nodes = rctensor2(&[
+            b(0, Cmp::LessEqual, 0, 0.5, 1, 2, false),
+            l(0, 0, 2),
+            l(0, 2, 4),
+            b(3, Cmp::GreaterEqual, 0, 1.5, 1, 2, false),
+            l(4, 0, 2),
+            l(4, 2, 4),
+        ]);
+        let leaves = rctensor2(&[
+            w(0, 1.0),
+            w(1, 2.0),
+            w(0, 3.0),
+            w(1, 4.0),
+            w(0, 5.0),
+            w(1, 6.0),
+            w(0, 7.0),
+            w(1, 8.0),
+        ]);
+        let data = TreeEnsembleData { trees, nodes, leaves };
+        TreeEnsemble::build(data, 0, 2, aggregate_fn).unwrap()
+    }
+
+    #[test]
+    fn test_aggregates() {
+        let input = arr2(&[[0f32], [1.], [2.]]).into_dyn();
+        let min = generate_stumps(Aggregate::Min).eval(input.view()).unwrap();
+        assert_eq!(min, arr2(&[[1f32, 2.], [3., 4.], [3., 4.]]).into_dyn());
+        let max = generate_stumps(Aggregate::Max).eval(input.view()).unwrap();
+        assert_eq!(max, arr2(&[[7f32, 8.], [7., 8.], [5., 6.]]).into_dyn());
+        let avg = generate_stumps(Aggregate::Avg).eval(input.view()).unwrap();
+        assert_eq!(avg, arr2(&[[4f32, 5.], [5., 6.], [4., 5.]]).into_dyn());
+    }
+
+    #[test]
+    fn test_batch_matches_rows() {
+        let ensemble = TreeEnsemble::build(generate_gbm_trees(), 3, 3, Aggregate::Sum).unwrap();
+        let input = Array2::from_shape_fn((100, 4), |(i, j)| ((i * 7 + j * 3) % 13) as f32 * 0.5);
+        let batch = ensemble.eval(input.view().into_dyn()).unwrap();
+        for (ix, row) in input.outer_iter().enumerate() {
+            let single = ensemble.eval(row.into_dyn()).unwrap();
+            assert_eq!(single, batch.index_axis(Axis(0), ix));
+        }
+    }
 }
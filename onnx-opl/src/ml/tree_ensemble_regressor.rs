@@ -0,0 +1,110 @@
+pub use super::tree::{Aggregate, Cmp, TreeEnsemble, TreeEnsembleData};
+use super::tree_ensemble_classifier::parse_aggregate;
+use tract_nnef::internal::*;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_ml_tree_ensemble_regressor",
+        &parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(TypeId::of::<TreeEnsembleRegressor>(), dump);
+}
+
+/// Raw, aggregated, predictions of a tree ensemble: [N, n_targets].
+#[derive(Debug, Clone, Hash)]
+pub struct TreeEnsembleRegressor {
+    pub ensemble: TreeEnsemble,
+}
+
+impl_dyn_hash!(TreeEnsembleRegressor);
+
+impl Op for TreeEnsembleRegressor {
+    fn name(&self) -> Cow<str> {
+        "TreeEnsembleRegressor".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "{} trees, {} targets, aggregate: {:?}",
+            self.ensemble.data.trees.len(),
+            self.ensemble.n_classes(),
+            self.ensemble.aggregate_fn
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for TreeEnsembleRegressor {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let input = input.cast_to::<f32>()?;
+        let input = input.to_array_view::<f32>()?;
+        let output = self.ensemble.eval(input)?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for TreeEnsembleRegressor {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let n = &inputs[0].shape[0];
+        Ok(tvec!(f32::fact([n.clone(), self.ensemble.n_classes().into()])))
+    }
+
+    as_op!();
+}
+
+fn parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Scalar.tensor().named("trees"),
+        TypeName::Scalar.tensor().named("nodes"),
+        TypeName::Scalar.tensor().named("leaves"),
+        TypeName::Integer.named("max_used_feature"),
+        TypeName::Integer.named("n_targets"),
+        TypeName::String.named("aggregate_fn"),
+    ]
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<TreeEnsembleRegressor>().context("wrong op")?;
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let trees = ast.konst_variable(format!("{}_trees", node.name), &op.ensemble.data.trees)?;
+    let nodes = ast.konst_variable(format!("{}_nodes", node.name), &op.ensemble.data.nodes)?;
+    let leaves = ast.konst_variable(format!("{}_leaves", node.name), &op.ensemble.data.leaves)?;
+    let agg = match op.ensemble.aggregate_fn {
+        Aggregate::Min => "MIN",
+        Aggregate::Max => "MAX",
+        Aggregate::Sum => "SUM",
+        Aggregate::Avg => "AVERAGE",
+    };
+    Ok(Some(invocation(
+        "tract_onnx_ml_tree_ensemble_regressor",
+        &[input, trees, nodes, leaves],
+        &[
+            ("max_used_feature", numeric(op.ensemble.max_used_feature)),
+            ("n_targets", numeric(op.ensemble.n_classes)),
+            ("aggregate_fn", string(agg)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let trees = invocation.named_arg_as(builder, "trees")?;
+    let nodes = invocation.named_arg_as(builder, "nodes")?;
+    let leaves = invocation.named_arg_as(builder, "leaves")?;
+    let max_used_feature = invocation.named_arg_as(builder, "max_used_feature")?;
+    let n_targets = invocation.named_arg_as(builder, "n_targets")?;
+    let aggregate_fn: String = invocation.named_arg_as(builder, "aggregate_fn")?;
+    let aggregate_fn = parse_aggregate(&aggregate_fn)?;
+    let data = TreeEnsembleData { trees, nodes, leaves };
+    let ensemble = TreeEnsemble::build(data, max_used_feature, n_targets, aggregate_fn)?;
+    builder.wire(TreeEnsembleRegressor { ensemble }, &[input])
+}
@@ -236,9 +236,9 @@ impl Onnx {
         let graph =
             proto.graph.as_ref().ok_or_else(|| anyhow!("model proto does not contain a graph"))?;
         debug!("ONNX operator set version: {:?}", onnx_operator_set_version);
-        if onnx_operator_set_version != 0 && !(9..14).contains(&onnx_operator_set_version) {
+        if onnx_operator_set_version != 0 && !(9..20).contains(&onnx_operator_set_version) {
             warn!("ONNX operator for your model is {}, tract is tested against \
-                  operator set 9, 10, 11 and 12 only. Your model may still work so this is not a hard fail.",
+                  operator set 9 to 19 only. Your model may still work so this is not a hard fail.",
                   onnx_operator_set_version);
         }
         let ctx = ParsingContext {
@@ -261,6 +261,22 @@ impl Onnx {
         Self { ignore_output_types: ignore, ..self }
     }
 
+    /// Load an operator plugin library, see tract-plugin.
+    #[cfg(feature = "plugin")]
+    pub fn with_plugin(self, path: impl AsRef<path::Path>) -> TractResult<Onnx> {
+        self.with_loaded_plugin(&tract_nnef::tract_plugin::Plugin::load(path)?)
+    }
+
+    #[cfg(feature = "plugin")]
+    pub fn with_loaded_plugin(
+        mut self,
+        plugin: &tract_nnef::tract_plugin::Plugin,
+    ) -> TractResult<Onnx> {
+        plugin.register()?;
+        crate::ops::register_plugin_ops(&mut self.op_register, plugin);
+        Ok(self)
+    }
+
     pub fn determinize(model: &mut InferenceModel) -> TractResult<()> {
         use crate::ops::multinomial::Multinomial;
         for node in model.nodes_mut() {
@@ -5,6 +5,7 @@ mod pad;
 mod slice;
 mod split;
 mod squeeze;
+mod trilu;
 mod unsqueeze;
 
 use tract_hir::internal::*;
@@ -32,7 +33,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Reshape", |_, _| Ok((expand(array::Reshape::default()), vec![])));
     reg.insert("Scatter", scatter_elements);
     reg.insert("ScatterElements", scatter_elements);
-    reg.insert("ScatterND", |_, _| Ok((Box::new(array::ScatterNd), vec![])));
+    reg.insert("ScatterND", scatter_nd);
     reg.insert("Shape", |_, _| Ok((expand(array::Shape::new(DatumType::TDim)), vec![])));
     reg.insert("Size", |_, _| Ok((expand(array::Size::new(DatumType::TDim)), vec![])));
     reg.insert("Slice", slice::slice);
@@ -40,6 +41,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Squeeze", squeeze::squeeze);
     reg.insert("Tile", |_, _| Ok((expand(array::Tile::default()), vec![])));
     reg.insert("Transpose", transpose);
+    reg.insert("Trilu", trilu::trilu);
     reg.insert("Unsqueeze", unsqueeze::unsqueeze);
 }
 
@@ -137,7 +139,21 @@ pub fn scatter_elements(
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
     let axis = node.get_attr_opt("axis")?.unwrap_or(0);
-    Ok((expand(array::ScatterElements::new(axis)), vec![]))
+    let reduction = scatter_reduction(node)?;
+    Ok((expand(array::ScatterElements::new(axis, reduction)), vec![]))
+}
+
+pub fn scatter_nd(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let reduction = scatter_reduction(node)?;
+    Ok((Box::new(array::ScatterNd::new(reduction)), vec![]))
+}
+
+fn scatter_reduction(node: &NodeProto) -> TractResult<array::ScatterReduction> {
+    let reduction = node.get_attr_opt("reduction")?.unwrap_or("none");
+    node.check_value("reduction", array::ScatterReduction::parse(reduction).map_err(|_| reduction))
 }
 
 pub fn transpose(
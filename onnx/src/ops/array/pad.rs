@@ -7,10 +7,10 @@ pub fn pad(
     ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    match ctx.onnx_operator_set_version {
-        2..=10 => pad_2(ctx, node),
-        11.. => pad_11(ctx, node),
-        _ => bail!("Unsupported operator set for Pad operator"),
+    if ctx.onnx_operator_set_version >= 11 || node.input.len() > 1 {
+        pad_11(ctx, node)
+    } else {
+        pad_2(ctx, node)
     }
 }
 
@@ -47,7 +47,8 @@ pub fn pad_11(
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
     let mode = pad_mode(node)?;
-    let op = Pad11::new(mode, if node.input.len() == 3 { Some(2) } else { None });
+    let mut options = crate::model::optional_inputs(node).skip(2);
+    let op = Pad11::new(mode, options.next().unwrap(), options.next().unwrap());
     Ok((expand(op), vec![]))
 }
 
@@ -55,6 +56,34 @@ pub fn pad_11(
 pub struct Pad11 {
     mode: array::PadMode,
     constant_input: Option<usize>,
+    axes_input: Option<usize>,
+}
+
+impl Pad11 {
+    /// Pads before and after each axis, from the pads input and the optional axes input.
+    fn pads<D: Clone + tract_num_traits::Zero>(
+        rank: usize,
+        pads: &[D],
+        axes: Option<&[i64]>,
+    ) -> TractResult<Vec<(D, D)>> {
+        let axes: Vec<usize> = if let Some(axes) = axes {
+            axes.iter().map(|&a| if a < 0 { a + rank as i64 } else { a } as usize).collect()
+        } else {
+            (0..rank).collect()
+        };
+        ensure!(
+            pads.len() == 2 * axes.len(),
+            "Expected {} pads, got {}",
+            2 * axes.len(),
+            pads.len()
+        );
+        let mut result = vec![(D::zero(), D::zero()); rank];
+        for (ix, &axis) in axes.iter().enumerate() {
+            ensure!(axis < rank, "Invalid pad axis {} for rank {}", axis, rank);
+            result[axis] = (pads[ix].clone(), pads[ix + axes.len()].clone());
+        }
+        Ok(result)
+    }
 }
 
 impl_dyn_hash!(Pad11);
@@ -71,7 +100,10 @@ impl Expansion for Pad11 {
         inputs: &'p [TensorProxy],
         outputs: &'p [TensorProxy],
     ) -> InferenceResult {
-        check_input_arity(inputs, 2 + self.constant_input.is_some() as usize)?;
+        check_input_arity(
+            inputs,
+            2 + self.constant_input.is_some() as usize + self.axes_input.is_some() as usize,
+        )?;
         check_output_arity(outputs, 1)?;
         if let Some(input) = self.constant_input {
             s.equals(&inputs[0].datum_type, &inputs[input].datum_type)?;
@@ -80,19 +112,33 @@ impl Expansion for Pad11 {
         s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
         s.equals(&inputs[0].rank, &outputs[0].rank)?;
         s.equals(&inputs[1].rank, 1)?;
-        s.equals(&inputs[1].shape[0], 2 * inputs[0].rank.bex().to_dim())?;
-        s.given(&inputs[1].value, move |s, pads| {
-            let pads = pads.cast_to::<TDim>()?;
-            let pads = pads.as_slice::<TDim>()?;
-            let rank = pads.len() / 2;
-            for i in 0..rank {
-                let left = pads[i].clone();
-                let right = pads[i + rank].clone();
-                s.equals(&outputs[0].shape[i], inputs[0].shape[i].bex() + left + right)?;
-            }
-            Ok(())
-        })?;
-        Ok(())
+        let output_shape =
+            move |s: &mut Solver<'r>, rank: i64, pads: Arc<Tensor>, axes: Option<Arc<Tensor>>| {
+                let pads = pads.cast_to::<TDim>()?;
+                let axes =
+                    axes.map(|axes| axes.cast_to::<i64>().map(|a| a.into_owned())).transpose()?;
+                let axes = axes.as_ref().map(|axes| axes.as_slice::<i64>()).transpose()?;
+                let pads = Self::pads(rank as usize, pads.as_slice::<TDim>()?, axes)?;
+                for (i, (left, right)) in pads.into_iter().enumerate() {
+                    s.equals(&outputs[0].shape[i], inputs[0].shape[i].bex() + left + right)?;
+                }
+                Ok(())
+            };
+        if let Some(axes) = self.axes_input {
+            s.equals(&inputs[axes].rank, 1)?;
+            s.equals(&inputs[1].shape[0], 2 * inputs[axes].shape[0].bex())?;
+            s.given_3(
+                &inputs[0].rank,
+                &inputs[1].value,
+                &inputs[axes].value,
+                move |s, rank, pads, axes| output_shape(s, rank, pads, Some(axes)),
+            )
+        } else {
+            s.equals(&inputs[1].shape[0], 2 * inputs[0].rank.bex().to_dim())?;
+            s.given_2(&inputs[0].rank, &inputs[1].value, move |s, rank, pads| {
+                output_shape(s, rank, pads, None)
+            })
+        }
     }
 
     fn wire(
@@ -116,9 +162,24 @@ impl Expansion for Pad11 {
             .as_ref()
             .context("Expect padding to be constant")?
             .cast_to::<i64>()?;
-        let pads = pads.as_slice::<i64>()?;
-        let rank = pads.len() / 2;
-        let pads = (0..rank).map(|ax| (pads[ax] as usize, pads[ax + rank] as usize)).collect();
+        let axes = if let Some(axes) = self.axes_input {
+            let axes = model
+                .outlet_fact(inputs[axes])?
+                .konst
+                .as_ref()
+                .context("Expect pad axes to be constant")?
+                .cast_to::<i64>()?
+                .into_owned();
+            Some(axes)
+        } else {
+            None
+        };
+        let axes = axes.as_ref().map(|axes| axes.as_slice::<i64>()).transpose()?;
+        let rank = model.outlet_fact(inputs[0])?.rank();
+        let pads = Self::pads(rank, pads.as_slice::<i64>()?, axes)?
+            .into_iter()
+            .map(|(before, after)| (before as usize, after as usize))
+            .collect();
         model.wire_node(name, array::Pad { mode, pads }, &inputs[0..1])
     }
 }
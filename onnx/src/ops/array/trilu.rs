@@ -0,0 +1,110 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+
+pub fn trilu(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let upper: i64 = node.get_attr_opt("upper")?.unwrap_or(1);
+    let has_k = node.input.len() == 2 && !node.input[1].is_empty();
+    Ok((Box::new(Trilu::new(upper == 1, has_k)), vec![]))
+}
+
+/// Keeps the upper (or lower) triangle of the two innermost axes, above (or below) the k-th
+/// diagonal, and zeroes the rest.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Trilu {
+    upper: bool,
+    has_k: bool,
+}
+
+impl_dyn_hash!(Trilu);
+
+impl Trilu {
+    unsafe fn eval_t<T: Datum + Default>(&self, input: TValue, k: i64) -> TractResult<TValue> {
+        let dt = input.datum_type();
+        let mut data = input.into_tensor().into_array_unchecked::<T>();
+        let rank = data.ndim();
+        for (coords, value) in data.indexed_iter_mut() {
+            let diagonal = coords[rank - 1] as i64 - coords[rank - 2] as i64;
+            let keep = if self.upper { diagonal >= k } else { diagonal <= k };
+            if !keep {
+                *value = T::default();
+            }
+        }
+        let mut tensor = data.into_tensor();
+        tensor.set_datum_type(dt);
+        Ok(tensor.into_tvalue())
+    }
+}
+
+impl Op for Trilu {
+    fn name(&self) -> Cow<str> {
+        "Trilu".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("upper: {}", self.upper)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Trilu {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let k = if self.has_k { inputs[1].cast_to_scalar::<i64>()? } else { 0 };
+        let input = inputs[0].clone();
+        ensure!(input.rank() >= 2, "Trilu expects an input of rank 2 or more");
+        let output =
+            unsafe { dispatch_datum_by_size!(Self::eval_t(input.datum_type())(self, input, k))? };
+        Ok(tvec!(output))
+    }
+}
+
+impl InferenceRulesOp for Trilu {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> TractResult<()> {
+        check_input_arity(inputs, 1 + self.has_k as usize)?;
+        check_output_arity(outputs, 1)?;
+        if self.has_k {
+            s.equals(&inputs[1].rank, 0)?;
+        }
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Trilu {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].without_value()))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lower_below_diagonal() -> TractResult<()> {
+        let input = tensor2(&[[1i32, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        let output = Trilu::new(false, true).eval(tvec!(input.into(), tensor0(-1i64).into()))?;
+        assert_eq!(*output[0], tensor2(&[[0i32, 0, 0], [4, 0, 0], [7, 8, 0]]));
+        Ok(())
+    }
+}
@@ -14,6 +14,13 @@ pub fn cast(
     Ok((ElementWiseOp(Box::new(Cast::new(to))).into_hir(), vec![]))
 }
 
+pub fn cast_like(
+    _ctx: &ParsingContext,
+    _node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    Ok((expand(CastLike), vec![]))
+}
+
 #[derive(Debug, Clone, new, Hash)]
 pub struct Cast {
     to: DatumType,
@@ -70,3 +77,38 @@ impl ElementWiseMiniOp for Cast {
         }
     }
 }
+
+/// Casts its first input to the type of its second input.
+#[derive(Debug, Clone, Hash)]
+pub struct CastLike;
+
+impl_dyn_hash!(CastLike);
+
+impl Expansion for CastLike {
+    fn name(&self) -> Cow<str> {
+        "CastLike".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[1].datum_type)?;
+        s.equals(&outputs[0].shape, &inputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let to = model.outlet_fact(inputs[1])?.datum_type;
+        model.wire_node(prefix, ElementWiseOp(Box::new(Cast::new(to))), &inputs[0..1])
+    }
+}
@@ -12,6 +12,11 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Or", |_, _| Ok((ops::logic::Or.into_hir(), vec![])));
     reg.insert("Xor", |_, _| Ok((ops::logic::Xor.into_hir(), vec![])));
 
+    reg.insert("BitwiseNot", |_, _| Ok((ops::logic::bitnot().into_hir(), vec![])));
+    reg.insert("BitwiseAnd", |_, _| Ok((ops::logic::BitAnd.into_hir(), vec![])));
+    reg.insert("BitwiseOr", |_, _| Ok((ops::logic::BitOr.into_hir(), vec![])));
+    reg.insert("BitwiseXor", |_, _| Ok((ops::logic::BitXor.into_hir(), vec![])));
+
     reg.insert("Equal", |_, _| Ok((ops::logic::Equals.into_hir(), vec![])));
     reg.insert("Greater", |_, _| Ok((ops::logic::Greater.into_hir(), vec![])));
     reg.insert("Less", |_, _| Ok((ops::logic::Less.into_hir(), vec![])));
@@ -57,6 +57,12 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Sign", |_, _| Ok((ops::math::sign().into_hir(), vec![])));
     reg.insert("Reciprocal", |_, _| Ok((ops::math::recip().into_hir(), vec![])));
 
+    reg.insert("Real", |_, _| Ok((ops::math::real().into_hir(), vec![])));
+    reg.insert("Imag", |_, _| Ok((ops::math::imag().into_hir(), vec![])));
+    reg.insert("Conj", |_, _| Ok((ops::math::conj().into_hir(), vec![])));
+    reg.insert("ComplexAbs", |_, _| Ok((ops::math::complex_abs().into_hir(), vec![])));
+    reg.insert("Angle", |_, _| Ok((ops::math::angle().into_hir(), vec![])));
+
     reg.insert("Pow", pow::pow);
 
     reg.insert("MatMul", |_, _| Ok((expand(ops::matmul::MatMulInference::default()), vec![])));
@@ -7,10 +7,10 @@ pub fn clip(
     ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    match ctx.onnx_operator_set_version {
-        6 | 7 | 8 | 9 | 10 => clip_6(ctx, node),
-        v if v >= 10 => clip_11(ctx, node),
-        _ => bail!("Unsupported operator set for Clip operator"),
+    if ctx.onnx_operator_set_version >= 11 || node.input.len() > 1 {
+        clip_11(ctx, node)
+    } else {
+        clip_6(ctx, node)
     }
 }
 
@@ -45,7 +45,6 @@ impl Expansion for Clip11 {
         "Clip".into()
     }
 
-
     fn rules<'r, 'p: 'r, 's: 'r>(
         &'s self,
         s: &mut Solver<'r>,
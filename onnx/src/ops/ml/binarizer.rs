@@ -0,0 +1,68 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("Binarizer", binarizer);
+}
+
+fn binarizer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let threshold = node.get_attr_opt::<f32>("threshold")?.unwrap_or(0.0);
+    Ok((expand(Binarizer { threshold }), vec![]))
+}
+
+/// 1 where the input is strictly greater than the threshold, 0 elsewhere.
+#[derive(Debug, Clone, Educe)]
+#[educe(Hash)]
+pub struct Binarizer {
+    #[educe(Hash(method = "hash_f32"))]
+    pub threshold: f32,
+}
+
+impl_dyn_hash!(Binarizer);
+
+impl Expansion for Binarizer {
+    fn name(&self) -> Cow<str> {
+        "Binarizer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let fact = model.outlet_fact(inputs[0])?.clone();
+        let threshold = tensor0(self.threshold)
+            .cast_to_dt(fact.datum_type)?
+            .into_owned()
+            .broadcast_into_rank(fact.rank())?;
+        let threshold = model.add_const(format!("{}.threshold", prefix), threshold)?;
+        let greater = model.wire_node(
+            format!("{}.greater", prefix),
+            tract_core::ops::logic::greater(),
+            &[inputs[0], threshold],
+        )?;
+        model.wire_node(
+            format!("{}.cast", prefix),
+            tract_core::ops::cast::cast(fact.datum_type),
+            &greater,
+        )
+    }
+}
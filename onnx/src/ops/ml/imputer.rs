@@ -0,0 +1,89 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("Imputer", imputer);
+}
+
+fn imputer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let floats: Option<Vec<f32>> = node.get_attr_opt_vec("imputed_value_floats")?;
+    let ints: Option<Vec<i64>> = node.get_attr_opt_vec("imputed_value_int64s")?;
+    let op = match (floats, ints) {
+        (Some(values), None) => Imputer {
+            imputed: rctensor1(&values),
+            replaced: rctensor0(node.get_attr_opt::<f32>("replaced_value_float")?.unwrap_or(0.0)),
+        },
+        (None, Some(values)) => Imputer {
+            imputed: rctensor1(&values),
+            replaced: rctensor0(node.get_attr_opt::<i64>("replaced_value_int64")?.unwrap_or(0)),
+        },
+        _ => bail!("Imputer requires exactly one of imputed_value_floats and imputed_value_int64s"),
+    };
+    Ok((expand(op), vec![]))
+}
+
+/// Replace `replaced` values (NaN included) by the imputed value of their feature (last axis).
+#[derive(Debug, Clone, Hash)]
+pub struct Imputer {
+    pub imputed: Arc<Tensor>,
+    pub replaced: Arc<Tensor>,
+}
+
+impl_dyn_hash!(Imputer);
+
+impl Expansion for Imputer {
+    fn name(&self) -> Cow<str> {
+        "Imputer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, self.imputed.datum_type())?;
+        s.equals(&outputs[0].datum_type, self.imputed.datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = model.outlet_fact(inputs[0])?.rank();
+        let is_nan = self.replaced.datum_type() == f32::datum_type()
+            && self.replaced.to_scalar::<f32>()?.is_nan();
+        let mask = if is_nan {
+            model.wire_node(
+                format!("{}.is_nan", prefix),
+                tract_onnx_opl::is_nan::is_nan(),
+                inputs,
+            )?
+        } else {
+            let replaced = (*self.replaced).clone().broadcast_into_rank(rank)?.into_arc_tensor();
+            let replaced = model.add_const(format!("{}.replaced", prefix), replaced)?;
+            model.wire_node(
+                format!("{}.mask", prefix),
+                tract_core::ops::logic::equals(),
+                &[inputs[0], replaced],
+            )?
+        };
+        let imputed = (*self.imputed).clone().broadcast_into_rank(rank)?.into_arc_tensor();
+        let imputed = model.add_const(format!("{}.imputed", prefix), imputed)?;
+        model.wire_node(
+            format!("{}.iff", prefix),
+            tract_core::ops::logic::Iff,
+            &[mask[0], imputed, inputs[0]],
+        )
+    }
+}
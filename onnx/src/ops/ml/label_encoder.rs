@@ -0,0 +1,139 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::ml::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("LabelEncoder", label_encoder);
+}
+
+fn label_encoder(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let mappings = if let Some(classes) = node.get_attr_opt_vec::<String>("classes_strings")? {
+        // opset 1: strings to their index, or indices to strings, depending on the input type
+        let default_int = node.get_attr_opt::<i64>("default_int64")?.unwrap_or(-1);
+        let default_string =
+            node.get_attr_opt::<String>("default_string")?.unwrap_or_else(|| "_Unused".into());
+        let indices: Vec<i64> = (0..classes.len() as i64).collect();
+        tvec!(
+            LabelMapping {
+                keys: rctensor1(&classes),
+                values: rctensor1(&indices),
+                default: rctensor0(default_int),
+            },
+            LabelMapping {
+                keys: rctensor1(&indices),
+                values: rctensor1(&classes),
+                default: rctensor0(default_string),
+            }
+        )
+    } else {
+        let keys = if let Some(keys) = node.get_attr_opt_vec::<String>("keys_strings")? {
+            rctensor1(&keys)
+        } else if let Some(keys) = node.get_attr_opt_vec::<i64>("keys_int64s")? {
+            rctensor1(&keys)
+        } else if node.get_attr_opt_vec::<f32>("keys_floats")?.is_some() {
+            bail!("LabelEncoder with float keys is not supported")
+        } else {
+            bail!("LabelEncoder requires one of keys_strings or keys_int64s")
+        };
+        let (values, default) = if let Some(values) =
+            node.get_attr_opt_vec::<String>("values_strings")?
+        {
+            let default =
+                node.get_attr_opt::<String>("default_string")?.unwrap_or_else(|| "_Unused".into());
+            (rctensor1(&values), rctensor0(default))
+        } else if let Some(values) = node.get_attr_opt_vec::<i64>("values_int64s")? {
+            let default = node.get_attr_opt::<i64>("default_int64")?.unwrap_or(-1);
+            (rctensor1(&values), rctensor0(default))
+        } else if let Some(values) = node.get_attr_opt_vec::<f32>("values_floats")? {
+            let default = node.get_attr_opt::<f32>("default_float")?.unwrap_or(-0.0);
+            (rctensor1(&values), rctensor0(default))
+        } else {
+            bail!("LabelEncoder requires one of values_strings, values_int64s or values_floats")
+        };
+        node.expect_attr("values", keys.len() == values.len(), "as many values as keys")?;
+        tvec!(LabelMapping { keys, values, default })
+    };
+    Ok((expand(LabelEncoder { mappings }), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct LabelMapping {
+    pub keys: Arc<Tensor>,
+    pub values: Arc<Tensor>,
+    pub default: Arc<Tensor>,
+}
+
+/// Maps keys to values. The mapping is picked according to the input type (strings or ints).
+#[derive(Debug, Clone, Hash)]
+pub struct LabelEncoder {
+    pub mappings: TVec<LabelMapping>,
+}
+
+impl_dyn_hash!(LabelEncoder);
+
+impl LabelEncoder {
+    fn mapping(&self, input: DatumType) -> TractResult<&LabelMapping> {
+        let is_string = input == String::datum_type();
+        self.mappings
+            .iter()
+            .find(|m| (m.keys.datum_type() == String::datum_type()) == is_string)
+            .with_context(|| format!("No mapping in LabelEncoder for input of type {:?}", input))
+    }
+}
+
+impl Expansion for LabelEncoder {
+    fn name(&self) -> Cow<str> {
+        "LabelEncoder".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        if self.mappings.len() == 1 {
+            s.equals(&outputs[0].datum_type, self.mappings[0].values.datum_type())?;
+        } else {
+            s.given(&inputs[0].datum_type, move |s, dt| {
+                s.equals(&outputs[0].datum_type, self.mapping(dt)?.values.datum_type())
+            })?;
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut wire = inputs[0];
+        let input_dt = model.outlet_fact(wire)?.datum_type;
+        let mapping = self.mapping(input_dt)?;
+        if input_dt != mapping.keys.datum_type() {
+            wire = model.wire_node(
+                format!("{}.cast", prefix),
+                tract_core::ops::cast::cast(mapping.keys.datum_type()),
+                &[wire],
+            )?[0];
+        }
+        let wire = model.wire_node(
+            format!("{}.reverse", prefix),
+            ReverseLookup::new(mapping.keys.clone(), -1)?,
+            &[wire],
+        )?;
+        model.wire_node(
+            format!("{}.direct", prefix),
+            DirectLookup::new(mapping.values.clone(), mapping.default.clone())?,
+            &wire,
+        )
+    }
+}
@@ -0,0 +1,232 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_core::ops::matmul::{MatMulAxes, MatMulUnary};
+use tract_hir::internal::*;
+use tract_hir::ops::array::TypedConcat;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("LinearClassifier", linear_classifier);
+    reg.insert("LinearRegressor", linear_regressor);
+}
+
+fn linear_classifier(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let class_labels = parse_class_labels(node, "classlabels_ints", "classlabels_strings")?;
+    let coefficients: Vec<f32> = node.get_attr_vec("coefficients")?;
+    let intercepts: Option<Vec<f32>> = node.get_attr_opt_vec("intercepts")?;
+    let n_targets = intercepts.as_ref().map(|i| i.len()).unwrap_or(class_labels.len());
+    node.expect_attr("intercepts", n_targets > 0, "at least one target")?;
+    node.expect_attr("coefficients", coefficients.len() % n_targets == 0, || {
+        format!("a multiple of {} coefficients, got {}", n_targets, coefficients.len())
+    })?;
+    let binary = n_targets == 1 && class_labels.len() == 2;
+    node.expect_attr("coefficients", binary || n_targets == class_labels.len(), || {
+        format!("as many coefficient rows as classes ({}), got {}", class_labels.len(), n_targets)
+    })?;
+    let n_features = coefficients.len() / n_targets;
+    let coefficients =
+        tensor1(&coefficients).into_shape(&[n_targets, n_features])?.into_arc_tensor();
+    let intercepts = intercepts
+        .map(|i| tensor1(&i).broadcast_into_rank(2))
+        .transpose()?
+        .map(|t| t.into_arc_tensor());
+    Ok((
+        expand(LinearClassifier {
+            coefficients,
+            intercepts,
+            class_labels,
+            post_transform: post_transform_attr(node)?,
+            binary,
+        }),
+        vec![],
+    ))
+}
+
+fn linear_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let n_targets: usize = node.get_attr_opt("targets")?.unwrap_or(1);
+    let coefficients: Vec<f32> = node.get_attr_vec("coefficients")?;
+    let intercepts = get_vec_attr_opt::<f32>(node, "intercepts", n_targets)?;
+    node.expect_attr("coefficients", coefficients.len() % n_targets == 0, || {
+        format!("a multiple of {} coefficients, got {}", n_targets, coefficients.len())
+    })?;
+    let n_features = coefficients.len() / n_targets;
+    let coefficients =
+        tensor1(&coefficients).into_shape(&[n_targets, n_features])?.into_arc_tensor();
+    let intercepts = intercepts
+        .map(|i| tensor1(&i).broadcast_into_rank(2))
+        .transpose()?
+        .map(|t| t.into_arc_tensor());
+    Ok((
+        expand(LinearRegressor {
+            coefficients,
+            intercepts,
+            post_transform: post_transform_attr(node)?,
+        }),
+        vec![],
+    ))
+}
+
+/// Wire X·Wᵀ + b, with W as [targets, features].
+fn wire_linear(
+    prefix: &str,
+    model: &mut TypedModel,
+    coefficients: &Arc<Tensor>,
+    intercepts: Option<&Arc<Tensor>>,
+    input: OutletId,
+) -> TractResult<TVec<OutletId>> {
+    let input = wire_as_f32(prefix, model, input)?;
+    let mut scores = model.wire_node(
+        format!("{}.matmul", prefix),
+        MatMulUnary::new(
+            coefficients.clone(),
+            MatMulAxes::default_for_rank(2).transposing_b().transposing_c(),
+        ),
+        &[input],
+    )?;
+    if let Some(intercepts) = intercepts {
+        let intercepts = model.add_const(format!("{}.intercepts", prefix), intercepts.clone())?;
+        scores = model.wire_node(
+            format!("{}.add_intercepts", prefix),
+            tract_core::ops::math::add(),
+            &[scores[0], intercepts],
+        )?;
+    }
+    Ok(scores)
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct LinearClassifier {
+    pub coefficients: Arc<Tensor>,
+    pub intercepts: Option<Arc<Tensor>>,
+    pub class_labels: Arc<Tensor>,
+    pub post_transform: Option<PostTransform>,
+    // a single row of coefficients for two classes: scores for the positive class
+    pub binary: bool,
+}
+
+impl_dyn_hash!(LinearClassifier);
+
+impl Expansion for LinearClassifier {
+    fn name(&self) -> Cow<str> {
+        "LinearClassifier".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 2)?;
+
+        s.equals(&outputs[0].datum_type, self.class_labels.datum_type())?;
+        s.equals(&outputs[1].datum_type, DatumType::F32)?;
+
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.coefficients.shape()[1].to_dim())?;
+        s.equals(&outputs[0].rank, 1)?;
+        s.equals(&outputs[1].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[1], self.class_labels.len().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut scores =
+            wire_linear(prefix, model, &self.coefficients, self.intercepts.as_ref(), inputs[0])?;
+        if self.binary {
+            if self.post_transform == Some(PostTransform::Logistic) {
+                // [1 - p, p]
+                scores = wire_post_transform(prefix, model, self.post_transform, scores)?;
+                let one = model.add_const(prefix.to_string() + ".one", rctensor2(&[[1f32]]))?;
+                let complement = model.wire_node(
+                    format!("{}.binary_result_complement", prefix),
+                    tract_core::ops::math::sub(),
+                    &[one, scores[0]],
+                )?;
+                scores = model.wire_node(
+                    format!("{}.binary_result", prefix),
+                    TypedConcat::new(1),
+                    &[complement[0], scores[0]],
+                )?;
+            } else {
+                // [-s, s]
+                let opposite = model.wire_node(
+                    format!("{}.binary_result_opposite", prefix),
+                    tract_core::ops::math::neg(),
+                    &scores,
+                )?;
+                scores = model.wire_node(
+                    format!("{}.binary_result", prefix),
+                    TypedConcat::new(1),
+                    &[opposite[0], scores[0]],
+                )?;
+                scores = wire_post_transform(prefix, model, self.post_transform, scores)?;
+            }
+        } else {
+            scores = wire_post_transform(prefix, model, self.post_transform, scores)?;
+        }
+        let labels = wire_argmax_labels(prefix, model, &self.class_labels, &scores)?;
+        Ok(tvec!(labels, scores[0]))
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct LinearRegressor {
+    pub coefficients: Arc<Tensor>,
+    pub intercepts: Option<Arc<Tensor>>,
+    pub post_transform: Option<PostTransform>,
+}
+
+impl_dyn_hash!(LinearRegressor);
+
+impl Expansion for LinearRegressor {
+    fn name(&self) -> Cow<str> {
+        "LinearRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.coefficients.shape()[1].to_dim())?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.coefficients.shape()[0].to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let scores =
+            wire_linear(prefix, model, &self.coefficients, self.intercepts.as_ref(), inputs[0])?;
+        wire_post_transform(prefix, model, self.post_transform, scores)
+    }
+}
@@ -1,9 +1,410 @@
+//! ONNX-ML operators.
+//!
+//! SVM and tree ensembles are dedicated tract-onnx-opl operators with their own NNEF
+//! serialization. The other ML operators are expansions: they are lowered to core and
+//! tract-onnx-opl operators at typing time, so typed models using them go through NNEF
+//! without needing ser/de of their own.
+mod binarizer;
 mod category_mapper;
+mod imputer;
+mod label_encoder;
+mod linear;
+mod normalizer;
+mod one_hot_encoder;
+mod scaler;
+mod svm;
 mod tree_ensemble_classifier;
+mod tree_ensemble_regressor;
+mod zip_map;
 
 use crate::model::OnnxOpRegister;
+use crate::pb::NodeProto;
+use crate::pb_helpers::*;
+use tract_hir::internal::*;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    binarizer::register_all_ops(reg);
     category_mapper::register_all_ops(reg);
+    imputer::register_all_ops(reg);
+    label_encoder::register_all_ops(reg);
+    linear::register_all_ops(reg);
+    normalizer::register_all_ops(reg);
+    one_hot_encoder::register_all_ops(reg);
+    scaler::register_all_ops(reg);
+    svm::register_all_ops(reg);
     tree_ensemble_classifier::register_all_ops(reg);
+    tree_ensemble_regressor::register_all_ops(reg);
+    zip_map::register_all_ops(reg);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PostTransform {
+    Softmax,
+    Logistic,
+    SoftmaxZero,
+    Probit,
+}
+
+pub fn parse_post_transform(s: &str) -> TractResult<Option<PostTransform>> {
+    match s {
+        "NONE" => Ok(None),
+        "SOFTMAX" => Ok(Some(PostTransform::Softmax)),
+        "LOGISTIC" => Ok(Some(PostTransform::Logistic)),
+        "SOFTMAX_ZERO" => Ok(Some(PostTransform::SoftmaxZero)),
+        "PROBIT" => Ok(Some(PostTransform::Probit)),
+        _ => bail!("Invalid post transform: {}", s),
+    }
+}
+
+fn post_transform_attr(node: &NodeProto) -> TractResult<Option<PostTransform>> {
+    Ok(node.get_attr_opt("post_transform")?.map(parse_post_transform).transpose()?.flatten())
+}
+
+/// Apply a post transform on [N, C] scores, along the class axis.
+pub fn wire_post_transform(
+    prefix: &str,
+    model: &mut TypedModel,
+    post_transform: Option<PostTransform>,
+    scores: TVec<OutletId>,
+) -> TractResult<TVec<OutletId>> {
+    match post_transform {
+        None => Ok(scores),
+        Some(PostTransform::Softmax) => tract_hir::ops::nn::LayerSoftmax::new(1, false).wire(
+            &format!("{}.softmax", prefix),
+            model,
+            &scores,
+        ),
+        Some(PostTransform::Logistic) => {
+            model.wire_node(format!("{}.logistic", prefix), tract_core::ops::nn::sigmoid(), &scores)
+        }
+        Some(PostTransform::SoftmaxZero) => wire_softmax_zero(prefix, model, scores[0]),
+        Some(PostTransform::Probit) => model.wire_node(
+            format!("{}.probit", prefix),
+            tract_onnx_opl::ml::probit::probit(),
+            &scores,
+        ),
+    }
+}
+
+/// Softmax where zero scores are left out of the normalization, and stay zero.
+fn wire_softmax_zero(
+    prefix: &str,
+    model: &mut TypedModel,
+    scores: OutletId,
+) -> TractResult<TVec<OutletId>> {
+    use tract_core::ops::math;
+    use tract_core::ops::nn::{Reduce, Reducer};
+    let max = model.wire_node(
+        format!("{}.softmax_zero.max", prefix),
+        Reduce::new(tvec!(1), Reducer::Max),
+        &[scores],
+    )?;
+    let centered =
+        model.wire_node(format!("{}.softmax_zero.sub", prefix), math::sub(), &[scores, max[0]])?;
+    let exp = model.wire_node(format!("{}.softmax_zero.exp", prefix), math::exp(), &centered)?;
+    let zero = model.add_const(format!("{}.softmax_zero.zero", prefix), rctensor2(&[[0f32]]))?;
+    let is_zero = model.wire_node(
+        format!("{}.softmax_zero.is_zero", prefix),
+        tract_core::ops::logic::equals(),
+        &[scores, zero],
+    )?;
+    let exp = model.wire_node(
+        format!("{}.softmax_zero.mask", prefix),
+        tract_core::ops::logic::Iff,
+        &[is_zero[0], zero, exp[0]],
+    )?;
+    let sum = model.wire_node(
+        format!("{}.softmax_zero.sum", prefix),
+        Reduce::new(tvec!(1), Reducer::Sum),
+        &exp,
+    )?;
+    model.wire_node(format!("{}.softmax_zero", prefix), math::div(), &[exp[0], sum[0]])
+}
+
+/// Map i32 class indices to labels (ints or strings). Out of range indices map to a zero (or empty)
+/// label.
+pub fn wire_labels(
+    prefix: &str,
+    model: &mut TypedModel,
+    class_labels: &Arc<Tensor>,
+    indices: &[OutletId],
+) -> TractResult<OutletId> {
+    let fallback = if class_labels.datum_type() == String::datum_type() {
+        rctensor0(String::new())
+    } else {
+        Tensor::zero_dt(class_labels.datum_type(), &[])?.into_arc_tensor()
+    };
+    Ok(model.wire_node(
+        format!("{}.labels", prefix),
+        tract_onnx_opl::ml::DirectLookup::new(class_labels.clone(), fallback)?,
+        indices,
+    )?[0])
+}
+
+/// Pick the label of the best scoring class for each row of [N, C] scores.
+pub fn wire_argmax_labels(
+    prefix: &str,
+    model: &mut TypedModel,
+    class_labels: &Arc<Tensor>,
+    scores: &[OutletId],
+) -> TractResult<OutletId> {
+    use tract_core::ops::nn::*;
+    let winners = model.wire_node(
+        format!("{}.argmax", prefix),
+        Reduce::new(tvec!(1), Reducer::ArgMax(false)),
+        scores,
+    )?;
+    let reduced = model.wire_node(
+        format!("{}.rm_axis", prefix),
+        tract_core::ops::change_axes::AxisOp::Rm(1),
+        &winners,
+    )?;
+    let casted = model.wire_node(
+        format!("{}.casted", prefix),
+        tract_core::ops::cast::cast(i32::datum_type()),
+        &reduced,
+    )?;
+    wire_labels(prefix, model, class_labels, &casted)
+}
+
+/// Make sure the input is f32, casting it if necessary.
+pub fn wire_as_f32(prefix: &str, model: &mut TypedModel, input: OutletId) -> TractResult<OutletId> {
+    if model.outlet_fact(input)?.datum_type == f32::datum_type() {
+        Ok(input)
+    } else {
+        Ok(model.wire_node(
+            format!("{}.as_f32", prefix),
+            tract_core::ops::cast::cast(f32::datum_type()),
+            &[input],
+        )?[0])
+    }
+}
+
+fn get_vec_attr<'a, T>(node: &'a NodeProto, attr: &str, n: usize) -> TractResult<Vec<T>>
+where
+    T: AttrTVecType<'a>,
+{
+    let vec = node.get_attr_vec(attr)?;
+    node.expect_attr(attr, vec.len() == n, || format!("length {}, got {}", vec.len(), n))?;
+    Ok(vec)
+}
+
+fn get_vec_attr_opt<'a, T>(node: &'a NodeProto, attr: &str, n: usize) -> TractResult<Option<Vec<T>>>
+where
+    T: AttrTVecType<'a>,
+{
+    match node.get_attr_opt_vec(attr)? {
+        Some(vec) => {
+            node.expect_attr(attr, vec.len() == n, || {
+                format!("length {} (or undefined), got {}", vec.len(), n)
+            })?;
+            Ok(Some(vec))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Parse class labels, given either as ints or as strings.
+fn parse_class_labels(node: &NodeProto, ints: &str, strings: &str) -> TractResult<Arc<Tensor>> {
+    let ints_value = node.get_attr_opt_slice::<i64>(ints)?;
+    let strs_value = node.get_attr_opt_tvec::<&str>(strings)?;
+    match (ints_value, strs_value) {
+        (Some(n), None) => Ok(rctensor1(n)),
+        (None, Some(n)) => Ok(rctensor1(&n.iter().map(|d| d.to_string()).collect::<Vec<_>>())),
+        (None, None) => bail!("cannot find neither '{}' not '{}'", ints, strings),
+        (Some(_), Some(_)) => bail!("only one of '{}' and '{}' can be set", ints, strings),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the op, checking its decluttered form round-trips through NNEF.
+    fn run(op: Box<dyn InferenceOp>, input: Tensor) -> TractResult<TVec<TValue>> {
+        use tract_onnx_opl::WithOnnx;
+        let mut model = InferenceModel::default();
+        let source = model.add_source("input", InferenceFact::dt_shape_from_tensor(&input))?;
+        let outputs = model.wire_node("op", op, &[source])?;
+        model.set_output_outlets(&outputs)?;
+        let model = model.into_typed()?.into_decluttered()?;
+        let nnef = tract_nnef::nnef().with_tract_core().with_onnx();
+        let mut buffer = vec![];
+        nnef.write_to_tar(&model, &mut buffer)?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        let expected = model.into_optimized()?.into_runnable()?.run(tvec!(input.clone().into()))?;
+        let found = reloaded.into_optimized()?.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        assert_eq!(expected, found);
+        Ok(found)
+    }
+
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn binary_linear_classifier_with_string_labels() -> TractResult<()> {
+        let op = linear::LinearClassifier {
+            coefficients: rctensor2(&[[1f32, -1.]]),
+            intercepts: Some(rctensor2(&[[0.5f32]])),
+            class_labels: rctensor1(&strings(&["no", "yes"])),
+            post_transform: None,
+            binary: true,
+        };
+        let outputs = run(expand(op), tensor2(&[[1f32, 0.], [0., 2.]]))?;
+        assert_eq!(*outputs[0], tensor1(&strings(&["yes", "no"])));
+        assert_eq!(*outputs[1], tensor2(&[[-1.5f32, 1.5], [1.5, -1.5]]));
+        Ok(())
+    }
+
+    #[test]
+    fn l1_normalizer() -> TractResult<()> {
+        let op = normalizer::Normalizer { norm: normalizer::Norm::L1 };
+        let outputs = run(expand(op), tensor2(&[[1f32, -3.], [2., 2.]]))?;
+        assert_eq!(*outputs[0], tensor2(&[[0.25f32, -0.75], [0.5, 0.5]]));
+        Ok(())
+    }
+
+    #[test]
+    fn normalizer_zero_rows() -> TractResult<()> {
+        for norm in [normalizer::Norm::Max, normalizer::Norm::L1, normalizer::Norm::L2] {
+            let op = normalizer::Normalizer { norm };
+            let outputs = run(expand(op), tensor2(&[[0f32, 0.], [-4., 2.]]))?;
+            let output = outputs[0].to_array_view::<f32>()?;
+            assert!(output.iter().all(|x| x.is_finite()));
+            assert_eq!(output[[0, 0]], 0.);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn max_normalizer() -> TractResult<()> {
+        let op = normalizer::Normalizer { norm: normalizer::Norm::Max };
+        let outputs = run(expand(op), tensor2(&[[-4f32, 2.]]))?;
+        assert_eq!(*outputs[0], tensor2(&[[-1f32, 0.5]]));
+        Ok(())
+    }
+
+    #[test]
+    fn linear_regressor() -> TractResult<()> {
+        let op = linear::LinearRegressor {
+            coefficients: rctensor2(&[[1f32, 2.]]),
+            intercepts: Some(rctensor2(&[[1f32]])),
+            post_transform: None,
+        };
+        let outputs = run(expand(op), tensor2(&[[1f32, 1.], [0., 2.]]))?;
+        assert_eq!(*outputs[0], tensor2(&[[4f32], [5.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn scaler() -> TractResult<()> {
+        let op = scaler::Scaler { offset: rctensor1(&[1f32, 2.]), scale: rctensor1(&[2f32]) };
+        let outputs = run(expand(op), tensor2(&[[1f32, 1.]]))?;
+        assert_eq!(*outputs[0], tensor2(&[[0f32, -2.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn imputer() -> TractResult<()> {
+        let op =
+            imputer::Imputer { imputed: rctensor1(&[7f32, 8.]), replaced: rctensor0(f32::NAN) };
+        let outputs = run(expand(op), tensor2(&[[f32::NAN, 1.], [2., f32::NAN]]))?;
+        assert_eq!(*outputs[0], tensor2(&[[7f32, 1.], [2., 8.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn binarizer() -> TractResult<()> {
+        let op = binarizer::Binarizer { threshold: 0.5 };
+        let outputs = run(expand(op), tensor1(&[0f32, 0.5, 1.]))?;
+        assert_eq!(*outputs[0], tensor1(&[0f32, 0., 1.]));
+        Ok(())
+    }
+
+    #[test]
+    fn one_hot_encoder_with_unknown_category() -> TractResult<()> {
+        let op =
+            one_hot_encoder::OneHotEncoder { categories: rctensor1(&strings(&["a", "b", "c"])) };
+        let outputs = run(expand(op), tensor1(&strings(&["b", "z"])))?;
+        assert_eq!(*outputs[0], tensor2(&[[0f32, 1., 0.], [0., 0., 0.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn label_encoder_indices_to_strings() -> TractResult<()> {
+        let indices = rctensor1(&[0i64, 1]);
+        let op = label_encoder::LabelEncoder {
+            mappings: tvec!(
+                label_encoder::LabelMapping {
+                    keys: rctensor1(&strings(&["a", "b"])),
+                    values: indices.clone(),
+                    default: rctensor0(-1i64),
+                },
+                label_encoder::LabelMapping {
+                    keys: indices,
+                    values: rctensor1(&strings(&["a", "b"])),
+                    default: rctensor0("_Unused".to_string()),
+                }
+            ),
+        };
+        let outputs = run(expand(op), tensor1(&[1i64, 0, 7]))?;
+        assert_eq!(*outputs[0], tensor1(&strings(&["b", "a", "_Unused"])));
+        Ok(())
+    }
+
+    // the reverse lookup is loaded back with its keys and fallback, the direct lookup with
+    // float values
+    #[test]
+    fn label_encoder_strings_to_floats() -> TractResult<()> {
+        let op = label_encoder::LabelEncoder {
+            mappings: tvec!(label_encoder::LabelMapping {
+                keys: rctensor1(&strings(&["a", "b"])),
+                values: rctensor1(&[0.5f32, 1.5]),
+                default: rctensor0(-1f32),
+            }),
+        };
+        let outputs = run(expand(op), tensor1(&strings(&["b", "z", "a"])))?;
+        assert_eq!(*outputs[0], tensor1(&[1.5f32, -1., 0.5]));
+        Ok(())
+    }
+
+    #[test]
+    fn softmax_zero() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", f32::fact([1, 3]))?;
+        let output =
+            wire_post_transform("pt", &mut model, Some(PostTransform::SoftmaxZero), tvec!(source))?;
+        model.set_output_outlets(&output)?;
+        let input = tensor2(&[[0f32, 2f32.ln(), 6f32.ln()]]);
+        let output = model.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        output[0].close_enough(&tensor2(&[[0f32, 0.25, 0.75]]), Approximation::Approximate)
+    }
+
+    #[test]
+    fn tree_ensemble_regressor() -> TractResult<()> {
+        use tract_onnx_opl::ml::tree::*;
+        // a single stump: feature 0 <= 0.5 ? [1, 2] : [3, 4]
+        let trees = rctensor1(&[0u32]);
+        let nodes = rctensor2(&[
+            [0u32, 1, 2, 0.5f32.to_bits(), Cmp::LessEqual as u32],
+            [0, 2, 0, 0, 0],
+            [2, 4, 0, 0, 0],
+        ]);
+        let leaves = rctensor2(&[
+            [0u32, 1f32.to_bits()],
+            [1, 2f32.to_bits()],
+            [0, 3f32.to_bits()],
+            [1, 4f32.to_bits()],
+        ]);
+        let data = TreeEnsembleData { trees, nodes, leaves };
+        let op = tree_ensemble_regressor::TreeEnsembleRegressor {
+            ensemble: TreeEnsemble::build(data, 0, 2, Aggregate::Sum)?,
+            base_values: Some(rctensor1(&[0.5f32, -0.5])),
+            post_transform: None,
+        };
+        let outputs = run(expand(op), tensor2(&[[0f32], [1.]]))?;
+        assert_eq!(*outputs[0], tensor2(&[[1.5f32, 1.5], [3.5, 3.5]]));
+        Ok(())
+    }
 }
@@ -0,0 +1,105 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_core::ops::nn::{Reduce, Reducer};
+use tract_hir::internal::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("Normalizer", normalizer);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Norm {
+    Max,
+    L1,
+    L2,
+}
+
+fn normalizer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let norm = match node.get_attr_opt("norm")?.unwrap_or("MAX") {
+        "MAX" => Norm::Max,
+        "L1" => Norm::L1,
+        "L2" => Norm::L2,
+        s => bail!("Invalid norm: {}", s),
+    };
+    Ok((expand(Normalizer { norm }), vec![]))
+}
+
+/// Normalize each row (last axis) by its max (absolute value), L1 or L2 norm.
+#[derive(Debug, Clone, Hash)]
+pub struct Normalizer {
+    pub norm: Norm,
+}
+
+impl_dyn_hash!(Normalizer);
+
+impl Expansion for Normalizer {
+    fn name(&self) -> Cow<str> {
+        "Normalizer".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("norm: {:?}", self.norm)])
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_core::ops::math;
+        let rank = model.outlet_fact(inputs[0])?.rank();
+        let axis = rank.saturating_sub(1);
+        let input = wire_as_f32(prefix, model, inputs[0])?;
+        let norm = match self.norm {
+            Norm::Max => {
+                let abs = model.wire_node(format!("{}.abs", prefix), math::abs(), &[input])?;
+                model.wire_node(
+                    format!("{}.max", prefix),
+                    Reduce::new(tvec!(axis), Reducer::Max),
+                    &abs,
+                )?
+            }
+            Norm::L1 => {
+                let abs = model.wire_node(format!("{}.abs", prefix), math::abs(), &[input])?;
+                model.wire_node(
+                    format!("{}.sum", prefix),
+                    Reduce::new(tvec!(axis), Reducer::Sum),
+                    &abs,
+                )?
+            }
+            Norm::L2 => {
+                let sqr = model.wire_node(format!("{}.sqr", prefix), math::square(), &[input])?;
+                let sum = model.wire_node(
+                    format!("{}.sum", prefix),
+                    Reduce::new(tvec!(axis), Reducer::Sum),
+                    &sqr,
+                )?;
+                model.wire_node(format!("{}.sqrt", prefix), math::sqrt(), &sum)?
+            }
+        };
+        // all-zero rows are left untouched instead of becoming NaN, like the ONNX reference does
+        let epsilon = tensor0(1e-30f32).broadcast_into_rank(rank)?;
+        let epsilon = model.add_const(format!("{}.epsilon", prefix), epsilon)?;
+        let norm =
+            model.wire_node(format!("{}.guard", prefix), math::max(), &[norm[0], epsilon])?;
+        model.wire_node(format!("{}.div", prefix), math::div(), &[input, norm[0]])
+    }
+}
@@ -0,0 +1,85 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::array::Slice;
+use tract_onnx_opl::ml::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("OneHotEncoder", one_hot_encoder);
+}
+
+fn one_hot_encoder(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let categories = parse_class_labels(node, "cats_int64s", "cats_strings")?;
+    Ok((expand(OneHotEncoder { categories }), vec![]))
+}
+
+/// One hot encoding of categories, on a new trailing axis. Unknown categories are all zeros.
+#[derive(Debug, Clone, Hash)]
+pub struct OneHotEncoder {
+    pub categories: Arc<Tensor>,
+}
+
+impl_dyn_hash!(OneHotEncoder);
+
+impl Expansion for OneHotEncoder {
+    fn name(&self) -> Cow<str> {
+        "OneHotEncoder".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, inputs[0].rank.bex() + 1)?;
+        s.given(&inputs[0].shape, move |s, shape| {
+            let mut shape = shape;
+            shape.push(self.categories.len().to_dim());
+            s.equals(&outputs[0].shape, shape)
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let n_cats = self.categories.len();
+        let mut wire = inputs[0];
+        let fact = model.outlet_fact(wire)?.clone();
+        if fact.datum_type != self.categories.datum_type() {
+            wire = model.wire_node(
+                format!("{}.cast", prefix),
+                tract_core::ops::cast::cast(self.categories.datum_type()),
+                &[wire],
+            )?[0];
+        }
+        // unknown categories are sent to an extra column that is sliced away afterwards
+        let indices = model.wire_node(
+            format!("{}.reverse", prefix),
+            ReverseLookup::new(self.categories.clone(), n_cats as i32)?,
+            &[wire],
+        )?;
+        let one_hot = model.wire_node(
+            format!("{}.one_hot", prefix),
+            tract_core::ops::array::OneHot {
+                axis: fact.rank(),
+                dim: n_cats + 1,
+                off: rctensor0(0f32),
+                on: rctensor0(1f32),
+            },
+            &indices,
+        )?;
+        model.wire_node(format!("{}.slice", prefix), Slice::new(fact.rank(), 0, n_cats), &one_hot)
+    }
+}
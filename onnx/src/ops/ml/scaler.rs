@@ -0,0 +1,69 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("Scaler", scaler);
+}
+
+fn scaler(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let offset: Vec<f32> = node.get_attr_opt_vec("offset")?.unwrap_or_else(|| vec![0.0]);
+    let scale: Vec<f32> = node.get_attr_opt_vec("scale")?.unwrap_or_else(|| vec![1.0]);
+    Ok((expand(Scaler { offset: rctensor1(&offset), scale: rctensor1(&scale) }), vec![]))
+}
+
+/// (x - offset) * scale, with offset and scale either scalars or per feature (last axis).
+#[derive(Debug, Clone, Hash)]
+pub struct Scaler {
+    pub offset: Arc<Tensor>,
+    pub scale: Arc<Tensor>,
+}
+
+impl_dyn_hash!(Scaler);
+
+impl Expansion for Scaler {
+    fn name(&self) -> Cow<str> {
+        "Scaler".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = model.outlet_fact(inputs[0])?.rank();
+        let input = wire_as_f32(prefix, model, inputs[0])?;
+        let offset = (*self.offset).clone().broadcast_into_rank(rank)?.into_arc_tensor();
+        let offset = model.add_const(format!("{}.offset", prefix), offset)?;
+        let scale = (*self.scale).clone().broadcast_into_rank(rank)?.into_arc_tensor();
+        let scale = model.add_const(format!("{}.scale", prefix), scale)?;
+        let centered = model.wire_node(
+            format!("{}.sub", prefix),
+            tract_core::ops::math::sub(),
+            &[input, offset],
+        )?;
+        model.wire_node(
+            format!("{}.mul", prefix),
+            tract_core::ops::math::mul(),
+            &[centered[0], scale],
+        )
+    }
+}
@@ -0,0 +1,196 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::ml::svm::{parse_kernel_type, SvmKernel};
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("SVMClassifier", svm_classifier);
+    reg.insert("SVMRegressor", svm_regressor);
+}
+
+fn parse_kernel(node: &NodeProto) -> TractResult<SvmKernel> {
+    let kernel_type = parse_kernel_type(node.get_attr_opt("kernel_type")?.unwrap_or("LINEAR"))?;
+    let params: Vec<f32> = node.get_attr_opt_vec("kernel_params")?.unwrap_or_default();
+    Ok(SvmKernel::new(kernel_type, &params))
+}
+
+fn parse_support_vectors(node: &NodeProto, n_sv: usize) -> TractResult<Arc<Tensor>> {
+    let svs: Vec<f32> = node.get_attr_opt_vec("support_vectors")?.unwrap_or_default();
+    if n_sv == 0 {
+        return Ok(rctensor1::<f32>(&[]));
+    }
+    node.expect_attr("support_vectors", svs.len() % n_sv == 0, || {
+        format!("a multiple of {} values, got {}", n_sv, svs.len())
+    })?;
+    Ok(tensor1(&svs).into_shape(&[n_sv, svs.len() / n_sv])?.into_arc_tensor())
+}
+
+fn svm_classifier(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let class_labels = parse_class_labels(node, "classlabels_ints", "classlabels_strings")?;
+    let n_classes = class_labels.len();
+    node.expect_attr("classlabels_ints", n_classes >= 2, "at least two classes")?;
+    let kernel = parse_kernel(node)?;
+    let vectors_per_class: Vec<i64> =
+        node.get_attr_opt_vec("vectors_per_class")?.unwrap_or_default();
+    let n_sv = vectors_per_class.iter().sum::<i64>() as usize;
+    let support_vectors = parse_support_vectors(node, n_sv)?;
+    let coefficients: Vec<f32> = node.get_attr_vec("coefficients")?;
+    let n_pairs = n_classes * (n_classes - 1) / 2;
+    let rho: Vec<f32> = if n_sv == 0 {
+        let rho = node.get_attr_opt_vec("rho")?.unwrap_or_else(|| vec![0.0]);
+        node.expect_attr("rho", rho.len() == 1 || rho.len() == n_classes, || {
+            format!("one value, or one per class ({}), got {}", n_classes, rho.len())
+        })?;
+        rho
+    } else {
+        node.expect_attr("vectors_per_class", vectors_per_class.len() == n_classes, || {
+            format!("one value per class ({}), got {}", n_classes, vectors_per_class.len())
+        })?;
+        node.expect_attr("coefficients", coefficients.len() == (n_classes - 1) * n_sv, || {
+            format!("(classes - 1) * support vectors values, got {}", coefficients.len())
+        })?;
+        get_vec_attr(node, "rho", n_pairs)?
+    };
+    let prob_a = get_vec_attr_opt::<f32>(node, "prob_a", n_pairs)?;
+    let prob_b = get_vec_attr_opt::<f32>(node, "prob_b", n_pairs)?;
+    // probabilities are only meaningful for support vector classification
+    let (prob_a, prob_b) = match (prob_a, prob_b) {
+        (Some(a), Some(b)) if n_sv > 0 => (Some(rctensor1(&a)), Some(rctensor1(&b))),
+        _ => (None, None),
+    };
+    let op = tract_onnx_opl::ml::svm::SvmClassifier {
+        kernel,
+        n_classes,
+        support_vectors,
+        vectors_per_class: rctensor1(&vectors_per_class),
+        coefficients: rctensor1(&coefficients),
+        rho: rctensor1(&rho),
+        prob_a,
+        prob_b,
+    };
+    Ok((
+        expand(SvmClassifier { op, class_labels, post_transform: post_transform_attr(node)? }),
+        vec![],
+    ))
+}
+
+fn svm_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let kernel = parse_kernel(node)?;
+    let n_sv: usize = node.get_attr_opt("n_supports")?.unwrap_or(0);
+    let support_vectors = parse_support_vectors(node, n_sv)?;
+    let coefficients: Vec<f32> = node.get_attr_vec("coefficients")?;
+    if n_sv > 0 {
+        node.expect_attr("coefficients", coefficients.len() == n_sv, || {
+            format!("one value per support vector ({}), got {}", n_sv, coefficients.len())
+        })?;
+    }
+    let rho: Vec<f32> = node.get_attr_opt_vec("rho")?.unwrap_or_else(|| vec![0.0]);
+    let one_class = node.get_attr_opt::<i64>("one_class")?.unwrap_or(0) != 0;
+    let op = tract_onnx_opl::ml::svm::SvmRegressor {
+        kernel,
+        support_vectors,
+        coefficients: rctensor1(&coefficients),
+        rho: rctensor1(&rho),
+        one_class,
+    };
+    Ok((expand(SvmRegressor { op, post_transform: post_transform_attr(node)? }), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SvmClassifier {
+    pub op: tract_onnx_opl::ml::svm::SvmClassifier,
+    pub class_labels: Arc<Tensor>,
+    pub post_transform: Option<PostTransform>,
+}
+
+impl_dyn_hash!(SvmClassifier);
+
+impl Expansion for SvmClassifier {
+    fn name(&self) -> Cow<str> {
+        "SVMClassifier".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 2)?;
+
+        s.equals(&outputs[0].datum_type, self.class_labels.datum_type())?;
+        s.equals(&outputs[1].datum_type, DatumType::F32)?;
+
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 1)?;
+        s.equals(&outputs[1].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[1], self.op.n_scores().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let outputs = model.wire_node(format!("{}.classifier", prefix), self.op.clone(), inputs)?;
+        let scores = wire_post_transform(prefix, model, self.post_transform, tvec!(outputs[1]))?;
+        let labels = wire_labels(prefix, model, &self.class_labels, &outputs[0..1])?;
+        Ok(tvec!(labels, scores[0]))
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SvmRegressor {
+    pub op: tract_onnx_opl::ml::svm::SvmRegressor,
+    pub post_transform: Option<PostTransform>,
+}
+
+impl_dyn_hash!(SvmRegressor);
+
+impl Expansion for SvmRegressor {
+    fn name(&self) -> Cow<str> {
+        "SVMRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], 1.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let output = model.wire_node(format!("{}.regressor", prefix), self.op.clone(), inputs)?;
+        wire_post_transform(prefix, model, self.post_transform, output)
+    }
+}
@@ -0,0 +1,77 @@
+use super::tree_ensemble_classifier::parse_nodes_data;
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::ml::tree::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("TreeEnsembleRegressor", tree_regressor);
+}
+
+fn tree_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let ensemble = parse_nodes_data(node, false)?;
+    let base_values =
+        get_vec_attr_opt::<f32>(node, "base_values", ensemble.n_classes())?.map(|t| rctensor1(&t));
+    let post_transform = post_transform_attr(node)?;
+    Ok((expand(TreeEnsembleRegressor { ensemble, base_values, post_transform }), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct TreeEnsembleRegressor {
+    pub ensemble: TreeEnsemble,
+    pub base_values: Option<Arc<Tensor>>,
+    pub post_transform: Option<PostTransform>,
+}
+
+impl_dyn_hash!(TreeEnsembleRegressor);
+
+impl Expansion for TreeEnsembleRegressor {
+    fn name(&self) -> Cow<str> {
+        "TreeEnsembleRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.ensemble.n_classes().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut output = model.wire_node(
+            format!("{}.regressor", prefix),
+            tract_onnx_opl::ml::tree_ensemble_regressor::TreeEnsembleRegressor {
+                ensemble: self.ensemble.clone(),
+            },
+            inputs,
+        )?;
+        if let Some(base_values) = self.base_values.as_deref() {
+            let base = base_values.clone().broadcast_into_rank(2)?.into_arc_tensor();
+            let base = model.add_const(prefix.to_string() + ".base", base)?;
+            output = model.wire_node(
+                format!("{}.base_values", prefix),
+                tract_core::ops::math::add(),
+                &[output[0], base],
+            )?;
+        }
+        wire_post_transform(prefix, model, self.post_transform, output)
+    }
+}
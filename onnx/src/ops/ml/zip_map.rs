@@ -0,0 +1,62 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("ZipMap", zip_map);
+}
+
+fn zip_map(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let class_labels = parse_class_labels(node, "classlabels_int64s", "classlabels_strings")?;
+    Ok((expand(ZipMap { class_labels }), vec![]))
+}
+
+/// ZipMap turns [N, C] scores into a sequence of maps from class labels to scores.
+///
+/// tract has no map or sequence types: the scores tensor is passed through, its columns are
+/// in the order of `class_labels`.
+#[derive(Debug, Clone, Hash)]
+pub struct ZipMap {
+    pub class_labels: Arc<Tensor>,
+}
+
+impl_dyn_hash!(ZipMap);
+
+impl Expansion for ZipMap {
+    fn name(&self) -> Cow<str> {
+        "ZipMap".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("labels: {:?}", self.class_labels)])
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.class_labels.len().to_dim())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        _prefix: &str,
+        _model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        Ok(inputs.into())
+    }
+}
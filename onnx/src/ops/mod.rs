@@ -17,10 +17,20 @@ pub mod rec;
 mod resize;
 mod non_max_suppression;
 pub mod multinomial;
+#[cfg(feature = "plugin")]
+mod plugin;
 mod s2d;
 
+#[cfg(feature = "plugin")]
+pub fn register_plugin_ops(reg: &mut OnnxOpRegister, plugin: &tract_nnef::tract_plugin::Plugin) {
+    for op in &plugin.ops {
+        reg.0.insert(op.name.clone(), plugin::plugin_op);
+    }
+}
+
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Cast", cast::cast);
+    reg.insert("CastLike", cast::cast_like);
     reg.insert("Constant", konst);
     reg.insert("Einsum", einsum::einsum);
     reg.insert("Identity", |_, _| Ok((Box::new(ops::identity::Identity::default()), vec![])));
@@ -0,0 +1,203 @@
+use tract_hir::internal::*;
+use tract_ndarray::IxDyn;
+
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+
+pub fn col2im(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let dilations = node.get_attr_opt_tvec("dilations")?;
+    let pads = node.get_attr_opt_tvec("pads")?;
+    let strides = node.get_attr_opt_tvec("strides")?;
+    Ok((Box::new(Col2Im::new(dilations, pads, strides)), vec![]))
+}
+
+/// Rearranges columns of sliding blocks back into a batched image, summing the overlaps.
+/// Image and block shapes are the second and third inputs, and must be known at compile time.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Col2Im {
+    dilations: Option<TVec<usize>>,
+    pads: Option<TVec<usize>>,
+    strides: Option<TVec<usize>>,
+}
+
+impl_dyn_hash!(Col2Im);
+
+struct Geometry {
+    image: TVec<usize>,
+    block: TVec<usize>,
+    dilations: TVec<usize>,
+    pads_before: TVec<usize>,
+    strides: TVec<usize>,
+    blocks_per_axis: TVec<usize>,
+}
+
+impl Col2Im {
+    fn geometry(&self, image: &Tensor, block: &Tensor) -> TractResult<Geometry> {
+        let image: TVec<usize> =
+            image.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|&d| d as usize).collect();
+        let block: TVec<usize> =
+            block.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|&d| d as usize).collect();
+        let rank = image.len();
+        ensure!(
+            block.len() == rank,
+            "Image shape {:?} and block shape {:?} mismatch",
+            image,
+            block
+        );
+        let dilations = self.dilations.clone().unwrap_or_else(|| tvec!(1; rank));
+        let pads = self.pads.clone().unwrap_or_else(|| tvec!(0; 2 * rank));
+        let strides = self.strides.clone().unwrap_or_else(|| tvec!(1; rank));
+        ensure!(
+            dilations.len() == rank && pads.len() == 2 * rank && strides.len() == rank,
+            "Inconsistent Col2Im attributes for {} spatial axes",
+            rank
+        );
+        let blocks_per_axis = (0..rank)
+            .map(|ax| {
+                let padded = image[ax] + pads[ax] + pads[ax + rank];
+                let extent = dilations[ax] * (block[ax] - 1) + 1;
+                ensure!(padded >= extent, "Block does not fit in padded image on axis {}", ax);
+                Ok((padded - extent) / strides[ax] + 1)
+            })
+            .collect::<TractResult<_>>()?;
+        Ok(Geometry {
+            image,
+            block,
+            dilations,
+            pads_before: pads[0..rank].into(),
+            strides,
+            blocks_per_axis,
+        })
+    }
+
+    fn eval_t<T: Datum + Copy + std::ops::AddAssign + tract_num_traits::Zero>(
+        &self,
+        input: &Tensor,
+        geo: &Geometry,
+    ) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let (batch, rows) = (input.shape()[0], input.shape()[1]);
+        let block_len: usize = geo.block.iter().product();
+        let channels = rows / block_len;
+        let mut output_shape = tvec!(batch, channels);
+        output_shape.extend(geo.image.iter().cloned());
+        let mut output = Tensor::zero::<T>(&output_shape)?;
+        let mut view = output.to_array_view_mut::<T>()?;
+        let mut coords = IxDyn(&output_shape);
+        for n in 0..batch {
+            coords[0] = n;
+            for c in 0..channels {
+                coords[1] = c;
+                for (b, block) in tract_ndarray::indices(&*geo.block).into_iter().enumerate() {
+                    let row = c * block_len + b;
+                    'blocks: for (l, grid) in
+                        tract_ndarray::indices(&*geo.blocks_per_axis).into_iter().enumerate()
+                    {
+                        for ax in 0..geo.image.len() {
+                            let pos = (grid[ax] * geo.strides[ax] + block[ax] * geo.dilations[ax])
+                                as isize
+                                - geo.pads_before[ax] as isize;
+                            if pos < 0 || pos as usize >= geo.image[ax] {
+                                continue 'blocks;
+                            }
+                            coords[2 + ax] = pos as usize;
+                        }
+                        view[&coords] += input[[n, row, l]];
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl Op for Col2Im {
+    fn name(&self) -> Cow<str> {
+        "Col2Im".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Col2Im {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, image, block) = args_3!(inputs);
+        let geo = self.geometry(&image, &block)?;
+        ensure!(input.rank() == 3, "Col2Im expects a rank 3 input");
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(self, &input, &geo))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl InferenceRulesOp for Col2Im {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> TractResult<()> {
+        check_input_arity(inputs, 3)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 3)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.given(&inputs[1].value, move |s, image| {
+            let image = image.cast_to::<i64>()?;
+            s.equals(&outputs[0].rank, 2 + image.len() as i64)?;
+            for (ax, &d) in image.as_slice::<i64>()?.iter().enumerate() {
+                s.equals(&outputs[0].shape[2 + ax], d.to_dim())?;
+            }
+            Ok(())
+        })?;
+        s.given_2(&inputs[0].shape[1], &inputs[2].value, move |s, rows, block| {
+            let block = block.cast_to::<i64>()?;
+            let block_len: i64 = block.as_slice::<i64>()?.iter().product();
+            s.equals(&outputs[0].shape[1], rows / block_len as u64)
+        })?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Col2Im {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let geo = if let (Some(image), Some(block)) = (&inputs[1].konst, &inputs[2].konst) {
+            self.geometry(image, block)?
+        } else {
+            bail!("Col2Im image and block shapes must be constant")
+        };
+        let block_len: usize = geo.block.iter().product();
+        let mut shape = tvec!(inputs[0].shape[0].clone(), inputs[0].shape[1].clone() / block_len);
+        shape.extend(geo.image.iter().map(|d| d.to_dim()));
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overlapping_blocks() -> TractResult<()> {
+        // 1D image of 4, blocks of 2 with stride 1: 3 blocks overlapping on the inner pixels
+        let op = Col2Im::new(None, None, None);
+        let input = tensor3(&[[[1f32, 2., 3.], [10., 20., 30.]]]);
+        let output =
+            op.eval(tvec!(input.into(), tensor1(&[4i64]).into(), tensor1(&[2i64]).into()))?;
+        assert_eq!(*output[0], tensor3(&[[[1f32, 12., 23., 30.]]]));
+        Ok(())
+    }
+}
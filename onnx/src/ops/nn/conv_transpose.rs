@@ -45,6 +45,33 @@ pub struct ConvTranspose {
 
 impl_dyn_hash!(ConvTranspose);
 
+impl ConvTranspose {
+    /// With an explicit output shape, ONNX computes SAME paddings from it.
+    fn same_as_explicit(
+        &self,
+        pool_spec: &PoolSpec,
+        x_shape: &[usize],
+        output_shape: &[usize],
+    ) -> PaddingSpec {
+        let mut before = tvec!();
+        let mut after = tvec!();
+        for ix in 0..x_shape.len() {
+            let kernel_field = (pool_spec.kernel_shape[ix] - 1) * pool_spec.dilation(ix) + 1;
+            let adjustment = self.adjustments.as_ref().map(|a| a[ix]).unwrap_or(0);
+            let total = ((x_shape[ix] - 1) * pool_spec.stride(ix) + kernel_field + adjustment)
+                .saturating_sub(output_shape[ix]);
+            let (b, a) = if self.padding_spec == PaddingSpec::SameUpper {
+                (total / 2, total - total / 2)
+            } else {
+                (total - total / 2, total / 2)
+            };
+            before.push(b);
+            after.push(a);
+        }
+        PaddingSpec::Explicit(before, after, false)
+    }
+}
+
 impl Expansion for ConvTranspose {
     fn name(&self) -> Cow<str> {
         "ConvTranspose".into()
@@ -136,7 +163,9 @@ impl Expansion for ConvTranspose {
 
             let op = if let Some(output_shape) = &self.output_shape {
                 let x_shape = &target.outlet_fact(inputs[0])?.shape;
-                let pool_spec = PoolSpec::new(
+                let x_shape =
+                    &x_shape.as_concrete().context("expects concrete dim for deconv")?[2..];
+                let mut pool_spec = PoolSpec::new(
                     DataFormat::NCHW,
                     kernel.shape()[2..].into(),
                     self.padding_spec.clone(),
@@ -144,11 +173,10 @@ impl Expansion for ConvTranspose {
                     self.strides.clone(),
                     Some(kernel.shape()[0] * self.group),
                 );
-                let adjustments = adjustments(
-                    &pool_spec,
-                    &x_shape.as_concrete().context("expects concrete dim for deconv")?[2..],
-                    output_shape,
-                )?;
+                if matches!(self.padding_spec, PaddingSpec::SameUpper | PaddingSpec::SameLower) {
+                    pool_spec.padding = self.same_as_explicit(&pool_spec, x_shape, output_shape);
+                }
+                let adjustments = adjustments(&pool_spec, x_shape, output_shape)?;
                 tract_core::ops::cnn::DeconvUnary::new(
                     pool_spec,
                     KernelFormat::OIHW,
@@ -7,11 +7,13 @@ use crate::pb::NodeProto;
 use crate::pb_helpers::OptionExt;
 
 mod batch_norm;
+mod col2im;
 mod conv_transpose;
 mod dropout;
 mod instance_norm;
 mod lrn;
 mod reduce;
+mod roi;
 
 pub fn arg_max_min(
     _ctx: &ParsingContext,
@@ -33,6 +35,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("ArgMin", arg_max_min);
     reg.insert("AveragePool", average_pool);
     reg.insert("BatchNormalization", batch_normalization);
+    reg.insert("Col2Im", col2im::col2im);
     reg.insert("Conv", conv);
     reg.insert("ConvInteger", conv_integer);
     reg.insert("ConvTranspose", conv_transpose::conv_transpose);
@@ -41,13 +44,17 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("GlobalAveragePool", |_, _| Ok((expand(ops::nn::GlobalAvgPool), vec![])));
     reg.insert("GlobalLpPool", global_lp_pool);
     reg.insert("GlobalMaxPool", |_, _| Ok((expand(ops::nn::GlobalMaxPool), vec![])));
+    reg.insert("GridSample", roi::grid_sample);
     reg.insert("Hardmax", layer_hard_max);
     reg.insert("HardSigmoid", hard_sigmoid);
+    reg.insert("HardSwish", |_, _| Ok((expand(ops::activations::HardSwish), vec![])));
     reg.insert("InstanceNormalization", instance_norm::instance_normalization);
     reg.insert("LeakyRelu", leaky_relu);
     reg.insert("LogSoftmax", layer_log_soft_max);
     reg.insert("LRN", lrn::lrn);
     reg.insert("MaxPool", max_pool);
+    reg.insert("MaxRoiPool", roi::max_roi_pool);
+    reg.insert("Mish", |_, _| Ok((expand(ops::activations::Mish), vec![])));
     reg.insert("ParametricSoftplus", parametric_softplus);
     reg.insert("QLinearConv", conv_qlinear);
     reg.insert("PRelu", |_, _| Ok((expand(Prelu), vec![])));
@@ -62,6 +69,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("ReduceSum", |c, node| reduce::reduce(c, node, nn::Reducer::Sum));
     reg.insert("ReduceSumSquare", |c, node| reduce::reduce(c, node, nn::Reducer::SumSquare));
     reg.insert("Relu", |_, _| Ok((expand(ops::activations::Clip::new(Some(0.0), None)), vec![])));
+    reg.insert("RoiAlign", roi::roi_align);
     reg.insert("ScaledTanh", scaled_tanh);
     reg.insert("Shrink", shrink);
     reg.insert("ThresholdedRelu", thresholded_relu);
@@ -7,7 +7,10 @@ pub(crate) fn reduce(
     node: &NodeProto,
     reducer: tract_hir::ops::nn::Reducer,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    if ctx.onnx_operator_set_version >= 13 && "ReduceSum" == node.op_type {
+    // axes became an input in opset 13 for ReduceSum, and in opset 18 for the other reducers
+    if ctx.onnx_operator_set_version >= 18
+        || (ctx.onnx_operator_set_version >= 13 && "ReduceSum" == node.op_type)
+    {
         let have_axis_input = node.input.len() == 2;
         let keep_dims = node.get_attr_opt("keepdims")?.unwrap_or(1i64) == 1;
         let noop_with_empty_axes = node.get_attr_opt("noop_with_empty_axes")?.unwrap_or(0i64) == 1;
@@ -104,6 +107,7 @@ impl Expansion for ReduceSum13 {
                 .konst
                 .as_ref()
                 .context("expected axes as a constant")?
+                .cast_to::<i64>()?
                 .as_slice::<i64>()?
                 .to_vec()
         } else {
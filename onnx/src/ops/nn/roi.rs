@@ -0,0 +1,123 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::array::Slice;
+use tract_hir::ops::cnn::{
+    GridSample, GridSampleInterpolation, GridSamplePadding, RoiAlign, RoiPool, RoiPoolingMode,
+};
+
+pub fn roi_align(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let mode = match node.get_attr_opt("mode")?.unwrap_or("avg") {
+        "avg" => RoiPoolingMode::Avg,
+        "max" => RoiPoolingMode::Max,
+        s => bail!("Unsupported RoiAlign mode: {}", s),
+    };
+    // opset 10 only knows the legacy (output_half_pixel) coordinates
+    let default_transform =
+        if ctx.onnx_operator_set_version >= 16 { "half_pixel" } else { "output_half_pixel" };
+    let half_pixel =
+        match node.get_attr_opt("coordinate_transformation_mode")?.unwrap_or(default_transform) {
+            "half_pixel" => true,
+            "output_half_pixel" => false,
+            s => bail!("Unsupported RoiAlign coordinate_transformation_mode: {}", s),
+        };
+    let output_shape = (
+        node.get_attr_opt::<usize>("output_height")?.unwrap_or(1),
+        node.get_attr_opt::<usize>("output_width")?.unwrap_or(1),
+    );
+    let sampling_ratio = node.get_attr_opt::<usize>("sampling_ratio")?.unwrap_or(0);
+    let spatial_scale = node.get_attr_opt("spatial_scale")?.unwrap_or(1.0);
+    let op = RoiAlign::new(
+        mode,
+        output_shape,
+        (sampling_ratio, sampling_ratio),
+        spatial_scale,
+        half_pixel,
+    );
+    Ok((Box::new(op), vec![]))
+}
+
+pub fn max_roi_pool(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let pooled_shape = node.get_attr_tvec::<usize>("pooled_shape")?;
+    node.expect_attr("pooled_shape", pooled_shape.len() == 2, "height and width")?;
+    let spatial_scale = node.get_attr_opt("spatial_scale")?.unwrap_or(1.0);
+    let op = RoiPool::new(RoiPoolingMode::Max, (pooled_shape[0], pooled_shape[1]), spatial_scale);
+    Ok((expand(MaxRoiPool(op)), vec![]))
+}
+
+/// ONNX MaxRoiPool, taking regions as R•5 (batch index, x1, y1, x2, y2).
+#[derive(Debug, Clone, Hash)]
+pub struct MaxRoiPool(RoiPool);
+
+impl_dyn_hash!(MaxRoiPool);
+
+impl Expansion for MaxRoiPool {
+    fn name(&self) -> Cow<str> {
+        "MaxRoiPool".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[1], 5.to_dim())?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.equals(&outputs[0].shape[2], self.0.output_shape.0.to_dim())?;
+        s.equals(&outputs[0].shape[3], self.0.output_shape.1.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let batch =
+            model.wire_node(format!("{}.batch", prefix), Slice::new(1, 0, 1), &[inputs[1]])?;
+        let batch = model.wire_node(format!("{}.batch.rm", prefix), AxisOp::Rm(1), &batch)?;
+        let batch = model.wire_node(
+            format!("{}.batch.cast", prefix),
+            tract_hir::ops::cast::cast(i64::datum_type()),
+            &batch,
+        )?;
+        let rois =
+            model.wire_node(format!("{}.rois", prefix), Slice::new(1, 1, 5), &[inputs[1]])?;
+        model.wire_node(prefix, self.0.clone(), &[inputs[0], rois[0], batch[0]])
+    }
+}
+
+pub fn grid_sample(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let interpolation = match node.get_attr_opt("mode")?.unwrap_or("bilinear") {
+        "bilinear" | "linear" => GridSampleInterpolation::Bilinear,
+        "nearest" => GridSampleInterpolation::Nearest,
+        "bicubic" | "cubic" => GridSampleInterpolation::Bicubic,
+        s => bail!("Unsupported GridSample mode: {}", s),
+    };
+    let padding = match node.get_attr_opt("padding_mode")?.unwrap_or("zeros") {
+        "zeros" => GridSamplePadding::Zeros,
+        "border" => GridSamplePadding::Border,
+        "reflection" => GridSamplePadding::Reflection,
+        s => bail!("Unsupported GridSample padding_mode: {}", s),
+    };
+    let align_corners = node.get_attr_opt::<i64>("align_corners")?.unwrap_or(0) == 1;
+    Ok((Box::new(GridSample::new(interpolation, padding, align_corners)), vec![]))
+}
@@ -0,0 +1,84 @@
+use crate::model::ParsingContext;
+use crate::pb::attribute_proto::AttributeType;
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_nnef::tract_plugin::{self, PluginOp};
+
+pub fn plugin_op(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let attributes = node
+        .attribute
+        .iter()
+        .map(|attr| Ok((attr.name.clone(), attribute_as_string(attr)?)))
+        .collect::<TractResult<Vec<_>>>()?;
+    let op = tract_plugin::instantiate(&node.op_type, attributes)?;
+    Ok((expand(Plugin(op)), vec![]))
+}
+
+/// Plugins get attributes as strings, lists are comma separated.
+fn attribute_as_string(attr: &AttributeProto) -> TractResult<String> {
+    fn join<T: ToString>(items: &[T]) -> String {
+        items.iter().map(|it| it.to_string()).collect::<Vec<_>>().join(",")
+    }
+    Ok(match attr.r#type() {
+        AttributeType::Float => attr.f.to_string(),
+        AttributeType::Int => attr.i.to_string(),
+        AttributeType::String => String::from_utf8(attr.s.clone())?,
+        AttributeType::Floats => join(&attr.floats),
+        AttributeType::Ints => join(&attr.ints),
+        AttributeType::Strings => attr
+            .strings
+            .iter()
+            .map(|s| Ok(String::from_utf8(s.clone())?))
+            .collect::<TractResult<Vec<_>>>()?
+            .join(","),
+        other => bail!("Attribute {} of type {:?} can not be passed to a plugin", attr.name, other),
+    })
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct Plugin(PluginOp);
+
+impl_dyn_hash!(Plugin);
+
+impl Expansion for Plugin {
+    fn name(&self) -> Cow<str> {
+        self.0.name()
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.0.def.n_outputs)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_output_arity(outputs, self.0.def.n_outputs)?;
+        s.given_all(inputs.iter().map(|i| &i.datum_type), move |s, types: Vec<DatumType>| {
+            s.given_all(inputs.iter().map(|i| &i.shape), move |s, shapes: Vec<TVec<TDim>>| {
+                let facts: TVec<TypedFact> =
+                    types.iter().zip(shapes.iter()).map(|(dt, shape)| dt.fact(shape)).collect();
+                let facts: TVec<&TypedFact> = facts.iter().collect();
+                for (output, fact) in outputs.iter().zip(self.0.output_facts(&facts)?) {
+                    s.equals(&output.datum_type, fact.datum_type)?;
+                    s.equals(&output.shape, fact.shape.to_tvec())?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::model::OnnxOpRegister;
 
+pub mod common;
 pub mod gru;
 pub mod lstm;
 pub mod rnn;
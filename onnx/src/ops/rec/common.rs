@@ -0,0 +1,629 @@
+use std::fmt::Debug;
+
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_hir::ops;
+use tract_hir::ops::activations::{self as act, broadcast_scalar};
+use tract_hir::tract_core::dyn_clone::{clone_trait_object, DynClone};
+use tract_hir::tract_core::ops::scan::ScanInfo;
+
+/// Which way(s) a recurrent operator runs over the sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Forward,
+    Reverse,
+    Bidirectional,
+}
+
+impl Direction {
+    pub fn from_node(pb: &NodeProto) -> TractResult<Direction> {
+        match pb.get_attr_opt("direction")?.unwrap_or("forward") {
+            "forward" => Ok(Direction::Forward),
+            "reverse" => Ok(Direction::Reverse),
+            "bidirectional" => Ok(Direction::Bidirectional),
+            s => bail!("Unsupported recurrent direction: {}", s),
+        }
+    }
+
+    /// Scan chunk of each direction, in num_directions order.
+    pub fn chunks(&self) -> TVec<isize> {
+        match self {
+            Direction::Forward => tvec!(1),
+            Direction::Reverse => tvec!(-1),
+            Direction::Bidirectional => tvec!(1, -1),
+        }
+    }
+}
+
+/// Activation functions of the recurrent operators, with their alpha and beta parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Educe)]
+#[educe(Hash)]
+pub enum Activation {
+    Relu,
+    Tanh,
+    Sigmoid,
+    Affine(#[educe(Hash(method = "hash_f32"))] f32, #[educe(Hash(method = "hash_f32"))] f32),
+    LeakyRelu(#[educe(Hash(method = "hash_f32"))] f32),
+    ThresholdedRelu(#[educe(Hash(method = "hash_f32"))] f32),
+    ScaledTanh(#[educe(Hash(method = "hash_f32"))] f32, #[educe(Hash(method = "hash_f32"))] f32),
+    HardSigmoid(#[educe(Hash(method = "hash_f32"))] f32, #[educe(Hash(method = "hash_f32"))] f32),
+    Elu(#[educe(Hash(method = "hash_f32"))] f32),
+    Softsign,
+    Softplus,
+}
+
+impl Activation {
+    /// Parses the `activations` attribute, consuming `activation_alpha` and
+    /// `activation_beta` values in order for the functions using them.
+    pub fn from_node(
+        pb: &NodeProto,
+        defaults: &[Activation],
+        direction: Direction,
+    ) -> TractResult<Vec<Activation>> {
+        let directions = direction.chunks().len();
+        let names = if let Some(names) = pb.get_attr_opt_tvec::<&str>("activations")? {
+            names
+        } else {
+            return Ok(defaults
+                .iter()
+                .cycle()
+                .take(defaults.len() * directions)
+                .cloned()
+                .collect());
+        };
+        pb.expect_attr("activations", names.len() == defaults.len() * directions, || {
+            format!("{} functions per direction", defaults.len())
+        })?;
+        let mut alphas = pb.get_attr_opt_slice::<f32>("activation_alpha")?.unwrap_or(&[]).iter();
+        let mut betas = pb.get_attr_opt_slice::<f32>("activation_beta")?.unwrap_or(&[]).iter();
+        let mut alpha = |default: f32| alphas.next().cloned().unwrap_or(default);
+        let mut beta = |default: f32| betas.next().cloned().unwrap_or(default);
+        names
+            .iter()
+            .map(|name| {
+                Ok(match *name {
+                    "Relu" => Activation::Relu,
+                    "Tanh" => Activation::Tanh,
+                    "Sigmoid" => Activation::Sigmoid,
+                    "Affine" => Activation::Affine(alpha(1.0), beta(0.0)),
+                    "LeakyRelu" => Activation::LeakyRelu(alpha(0.01)),
+                    "ThresholdedRelu" => Activation::ThresholdedRelu(alpha(1.0)),
+                    "ScaledTanh" => Activation::ScaledTanh(alpha(1.0), beta(1.0)),
+                    "HardSigmoid" => Activation::HardSigmoid(alpha(0.2), beta(0.5)),
+                    "Elu" => Activation::Elu(alpha(1.0)),
+                    "Softsign" => Activation::Softsign,
+                    "Softplus" => Activation::Softplus,
+                    _ => bail!("Unsupported recurrent activation: {}", name),
+                })
+            })
+            .collect()
+    }
+
+    pub fn wire(
+        &self,
+        name: &str,
+        model: &mut TypedModel,
+        input: OutletId,
+    ) -> TractResult<OutletId> {
+        let inputs = &[input];
+        let wire = match *self {
+            Activation::Relu => act::Clip::new(Some(0.0), None).wire(name, model, inputs)?,
+            Activation::Tanh => model.wire_node(name, ops::math::tanh(), inputs)?,
+            Activation::Sigmoid => model.wire_node(name, ops::nn::sigmoid(), inputs)?,
+            Activation::Affine(alpha, beta) => {
+                let alpha = model.add_const(
+                    format!("{}.alpha", name),
+                    broadcast_scalar(alpha, model, inputs)?,
+                )?;
+                let beta = model
+                    .add_const(format!("{}.beta", name), broadcast_scalar(beta, model, inputs)?)?;
+                let wire = model.wire_node(
+                    format!("{}.mul_alpha", name),
+                    ops::math::mul(),
+                    &[alpha, input],
+                )?;
+                model.wire_node(name, ops::math::add(), &[wire[0], beta])?
+            }
+            Activation::LeakyRelu(alpha) => act::LeakyRelu(alpha).wire(name, model, inputs)?,
+            Activation::ThresholdedRelu(alpha) => {
+                act::ThresholdRelu(alpha).wire(name, model, inputs)?
+            }
+            Activation::ScaledTanh(alpha, beta) => {
+                act::ScaledTanh(alpha, beta).wire(name, model, inputs)?
+            }
+            Activation::HardSigmoid(alpha, beta) => {
+                act::HardSigmoid(alpha, beta).wire(name, model, inputs)?
+            }
+            Activation::Elu(alpha) => act::Elu(alpha).wire(name, model, inputs)?,
+            Activation::Softsign => act::Softsign.wire(name, model, inputs)?,
+            Activation::Softplus => act::Softplus.wire(name, model, inputs)?,
+        };
+        Ok(wire[0])
+    }
+}
+
+/// Activations of the direction `dir`, the first ones being used for every direction if the
+/// list is too short.
+pub fn direction_activations(
+    activations: &[Activation],
+    count: usize,
+    dir: usize,
+) -> TractResult<&[Activation]> {
+    let from = if activations.len() >= count * (dir + 1) { count * dir } else { 0 };
+    activations.get(from..from + count).with_context(|| {
+        format!("Expected {} activations per direction, got {:?}", count, activations)
+    })
+}
+
+/// Inner interface of a recurrent cell time step, as wired in the scan body.
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// [batch_size, input_size]
+    pub x: OutletId,
+    /// [gates*hidden_size, input_size]
+    pub w: OutletId,
+    /// [gates*hidden_size, hidden_size]
+    pub r: OutletId,
+    /// [1, 2*gates*hidden_size]
+    pub b: Option<OutletId>,
+    /// [1, 3*hidden_size]
+    pub p: Option<OutletId>,
+    /// previous hidden state: [batch_size, hidden_size]
+    pub h: OutletId,
+    /// previous cell state: [batch_size, hidden_size]
+    pub c: Option<OutletId>,
+    pub hidden_size: TDim,
+    pub clip: Option<f32>,
+}
+
+impl Step {
+    /// Gate `ix` from stacked gates along `axis`.
+    pub fn gate(
+        &self,
+        name: &str,
+        body: &mut TypedModel,
+        stacked: OutletId,
+        axis: usize,
+        ix: usize,
+    ) -> TractResult<OutletId> {
+        let h = &self.hidden_size;
+        let op = ops::array::Slice::new(axis, h.clone() * ix, h.clone() * (ix + 1));
+        Ok(body.wire_node(name, op, &[stacked])?[0])
+    }
+
+    /// Applies an activation to a gate, after clipping it if required.
+    pub fn activate(
+        &self,
+        name: &str,
+        body: &mut TypedModel,
+        activation: &Activation,
+        wire: OutletId,
+    ) -> TractResult<OutletId> {
+        let wire = if let Some(clip) = self.clip {
+            act::Clip::new(Some(-clip), Some(clip)).wire(
+                &format!("{}.clip", name),
+                body,
+                &[wire],
+            )?[0]
+        } else {
+            wire
+        };
+        activation.wire(name, body, wire)
+    }
+}
+
+/// Cell specific part of a recurrent operator.
+pub trait WireBody: Debug + DynClone + DynHash + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Number of gates stacked in W and R (and twice in B).
+    fn gates(&self) -> usize;
+
+    /// Whether the cell carries a C state (and accepts peepholes).
+    fn has_cell_state(&self) -> bool {
+        false
+    }
+
+    /// Wires one time step for the direction `dir`, returning the new H and C.
+    fn wire_body(
+        &self,
+        prefix: &str,
+        body: &mut TypedModel,
+        step: &Step,
+        dir: usize,
+    ) -> TractResult<(OutletId, Option<OutletId>)>;
+}
+
+clone_trait_object!(WireBody);
+
+impl Hash for Box<dyn WireBody> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::hash::Hash::hash(self.name(), state);
+        self.dyn_hash(state)
+    }
+}
+
+/// ONNX LSTM, GRU and RNN: expands into one Scan per direction running the cell body.
+#[derive(Debug, Clone, Educe)]
+#[educe(Hash)]
+pub struct CommonRec {
+    pub optional_bias_input: Option<usize>,
+    pub optional_sequence_lens_input: Option<usize>,
+    pub optional_initial_h_input: Option<usize>,
+    pub optional_initial_c_input: Option<usize>,
+    pub optional_p_input: Option<usize>,
+    pub optional_y_output: Option<usize>,
+    pub optional_y_h_output: Option<usize>,
+    pub optional_y_c_output: Option<usize>,
+    pub direction: Direction,
+    /// layout=1: X, Y and states are batch first
+    pub batch_first: bool,
+    #[educe(Hash(method = "hash_opt_f32"))]
+    pub clip: Option<f32>,
+    pub body: Box<dyn WireBody>,
+}
+
+impl_dyn_hash!(CommonRec);
+
+impl CommonRec {
+    pub fn new(body: Box<dyn WireBody>) -> CommonRec {
+        CommonRec {
+            optional_bias_input: None,
+            optional_sequence_lens_input: None,
+            optional_initial_h_input: None,
+            optional_initial_c_input: None,
+            optional_p_input: None,
+            optional_y_output: None,
+            optional_y_h_output: None,
+            optional_y_c_output: None,
+            direction: Direction::Forward,
+            batch_first: false,
+            clip: None,
+            body,
+        }
+    }
+
+    pub fn from_node(pb: &NodeProto, body: Box<dyn WireBody>) -> TractResult<CommonRec> {
+        let mut rec = CommonRec::new(body);
+        let with_c = rec.body.has_cell_state();
+
+        let mut options = crate::model::optional_inputs(pb).skip(3);
+        rec.optional_bias_input = options.next().unwrap();
+        rec.optional_sequence_lens_input = options.next().unwrap();
+        rec.optional_initial_h_input = options.next().unwrap();
+        if with_c {
+            rec.optional_initial_c_input = options.next().unwrap();
+            rec.optional_p_input = options.next().unwrap();
+        }
+
+        let mut options = crate::model::optional_outputs(pb);
+        rec.optional_y_output = options.next().unwrap();
+        rec.optional_y_h_output = options.next().unwrap();
+        if with_c {
+            rec.optional_y_c_output = options.next().unwrap();
+        }
+
+        rec.direction = Direction::from_node(pb)?;
+        rec.batch_first = pb.get_attr_opt::<i64>("layout")?.unwrap_or(0) == 1;
+        rec.clip = pb.get_attr_opt("clip")?;
+        Ok(rec)
+    }
+
+    fn input_count(&self) -> usize {
+        3 + self.optional_bias_input.is_some() as usize
+            + self.optional_sequence_lens_input.is_some() as usize
+            + self.optional_initial_h_input.is_some() as usize
+            + self.optional_initial_c_input.is_some() as usize
+            + self.optional_p_input.is_some() as usize
+    }
+
+    #[allow(non_snake_case)]
+    fn wire_one_side(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+        dir: usize,
+        chunk: isize,
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_hir::ops::{array, scan};
+
+        let x_fact = target.outlet_fact(inputs[0])?.clone();
+        let r_fact = target.outlet_fact(inputs[2])?.clone();
+
+        let dt = x_fact.datum_type;
+        let b_size = x_fact.shape[!self.batch_first as usize].clone();
+        let h_size = r_fact.shape[2].clone();
+        let with_c = self.body.has_cell_state();
+
+        let mut body = TypedModel::default();
+        let mut outer_inputs = vec![];
+        let mut input_mapping = vec![];
+
+        macro_rules! target_wire {
+            ($name: ident = $op: expr, $($param: expr),*) => {
+                let $name = target.wire_node(
+                    format!("{}.{}", prefix, stringify!($name)),
+                    $op, [$($param),*].as_ref())?[0];
+            }
+        }
+
+        // X: onnx interface: [seq_length, batch_size, input_size] (or batch first)
+        // scan outer interface: [batch_size, seq_length, input_size]
+        // scan inner interface: [batch_size, chunk=1, input_size]
+        // step interface: [batch_size, input_size]
+        let x_batch_first = if self.batch_first {
+            inputs[0]
+        } else {
+            target_wire!(x_batch_first = AxisOp::Move(1, 0), inputs[0]);
+            x_batch_first
+        };
+        input_mapping.push(scan::InputMapping::Scan(ScanInfo {
+            slot: outer_inputs.len(),
+            axis: 1,
+            chunk,
+        }));
+        outer_inputs.push(x_batch_first);
+        let mut x_source_fact = target.outlet_fact(x_batch_first)?.without_value();
+        x_source_fact.shape.set(1, 1.to_dim());
+        let x_source = body.add_source("x_source", x_source_fact)?;
+        let Xt = body.wire_node(format!("{}.Xt", prefix), AxisOp::Rm(1), &[x_source])?[0];
+
+        // W and R: onnx interface: [num_directions, gates*hidden_size, input_size|hidden_size]
+        // scan interfaces: [gates*hidden_size, input_size|hidden_size]
+        let mut full = |name: &str, outlet: OutletId, rm: bool| -> TractResult<OutletId> {
+            let mut wire = target.wire_node(
+                format!("{}.{}_dir", prefix, name),
+                array::Slice::new(0, dir, dir + 1),
+                &[outlet],
+            )?;
+            if rm {
+                wire = target.wire_node(format!("{}.{}", prefix, name), AxisOp::Rm(0), &wire)?;
+            }
+            input_mapping.push(scan::InputMapping::Full { slot: outer_inputs.len() });
+            outer_inputs.push(wire[0]);
+            body.add_source(name, target.outlet_fact(wire[0])?.clone())
+        };
+        let W = full("w", inputs[1], true)?;
+        let R = full("r", inputs[2], true)?;
+        // B: onnx interface: [num_directions, 2*gates*hidden_size]
+        // scan interfaces: [1, 2*gates*hidden_size]
+        let b = self.optional_bias_input.map(|slot| full("b", inputs[slot], false)).transpose()?;
+        // P: onnx interface: [num_directions, 3*hidden_size]
+        // scan interfaces: [1, 3*hidden_size]
+        let p = self.optional_p_input.map(|slot| full("p", inputs[slot], false)).transpose()?;
+
+        let seq_length_input_slot = self.optional_sequence_lens_input.map(|slot| {
+            outer_inputs.push(inputs[slot]);
+            outer_inputs.len() - 1
+        });
+
+        // initial states, optional: onnx: [num_directions, batch_size, hidden_size] (or batch first)
+        // scan outer: [batch_size, chunk=1, hidden_size]
+        // scan inner: [batch_size, chunk=1, hidden_size]
+        // step interface: [batch_size, hidden_size]
+        let mut state = |name: &str, initial: Option<usize>| -> TractResult<OutletId> {
+            let initializer = if let Some(slot) = initial {
+                let dir_axis = self.batch_first as usize;
+                let mut wire = target.wire_node(
+                    format!("{}.{}_dir", prefix, name),
+                    array::Slice::new(dir_axis, dir, dir + 1),
+                    &[inputs[slot]],
+                )?;
+                if !self.batch_first {
+                    wire = target.wire_node(
+                        format!("{}.{}_chunk", prefix, name),
+                        AxisOp::Move(0, 1),
+                        &wire,
+                    )?;
+                }
+                outer_inputs.push(wire[0]);
+                scan::StateInitializer::FromInput(outer_inputs.len() - 1)
+            } else {
+                let shape = [
+                    b_size
+                        .to_usize()
+                        .context("Default initial state requires a known batch size")?,
+                    1,
+                    h_size.to_usize()?,
+                ];
+                scan::StateInitializer::Value(Tensor::zero_dt(dt, &shape)?.into_arc_tensor())
+            };
+            input_mapping.push(scan::InputMapping::State { initializer });
+            let source = body.add_source(
+                format!("{}_source", name),
+                dt.fact(&[b_size.clone(), 1.to_dim(), h_size.clone()]),
+            )?;
+            Ok(body.wire_node(format!("{}.{}_prev", prefix, name), AxisOp::Rm(1), &[source])?[0])
+        };
+        let Ht_1 = state("h", self.optional_initial_h_input)?;
+        let Ct_1 = if with_c { Some(state("c", self.optional_initial_c_input)?) } else { None };
+
+        let step = Step {
+            x: Xt,
+            w: W,
+            r: R,
+            b,
+            p,
+            h: Ht_1,
+            c: Ct_1,
+            hidden_size: h_size,
+            clip: self.clip,
+        };
+        let (Ht, Ct) = self.body.wire_body(prefix, &mut body, &step, dir)?;
+
+        // add sequence axis (chunk == 1)
+        let mut body_outputs = tvec!();
+        body_outputs
+            .push(body.wire_node(format!("{}.Ht_fixed", prefix), AxisOp::Add(1), &[Ht])?[0]);
+        let mut output_mapping = vec![scan::OutputMapping {
+            state: true,
+            full_dim_hint: None,
+            last_value_slot: self.optional_y_h_output,
+            scan: self.optional_y_output.map(|slot| ScanInfo { slot, axis: 1, chunk }),
+        }];
+        if let Some(Ct) = Ct {
+            body_outputs
+                .push(body.wire_node(format!("{}.Ct_fixed", prefix), AxisOp::Add(1), &[Ct])?[0]);
+            output_mapping.push(scan::OutputMapping {
+                state: true,
+                full_dim_hint: None,
+                last_value_slot: self.optional_y_c_output,
+                scan: None,
+            });
+        }
+        body.set_output_outlets(&body_outputs)?;
+
+        let scan_outputs = target.wire_node(
+            prefix,
+            scan::Scan::new(body, input_mapping, output_mapping, seq_length_input_slot, 0)?,
+            &outer_inputs,
+        )?;
+
+        // Y: scan outer: [batch_size, seq_length, hidden_size]
+        // onnx: [seq_length, num_directions, batch_size, hidden_size] (or batch first)
+        // Y_h and Y_c: scan outer: [batch_size, 1, hidden_size]
+        // onnx: [num_directions, batch_size, hidden_size] (or batch first)
+        let mut result = tvec!();
+        if let Some(slot) = self.optional_y_output {
+            if self.batch_first {
+                target_wire!(y = AxisOp::Add(2), scan_outputs[slot]);
+                result.push(y);
+            } else {
+                target_wire!(y_batch_middle = AxisOp::Move(1, 0), scan_outputs[slot]);
+                target_wire!(y = AxisOp::Add(1), y_batch_middle);
+                result.push(y);
+            }
+        }
+        for (slot, name) in
+            [(self.optional_y_h_output, "y_h"), (self.optional_y_c_output, "y_c")].iter()
+        {
+            if let Some(slot) = slot {
+                if self.batch_first {
+                    result.push(scan_outputs[*slot]);
+                } else {
+                    result.push(
+                        target.wire_node(
+                            format!("{}.{}_batch_middle", prefix, name),
+                            AxisOp::Move(1, 0),
+                            &[scan_outputs[*slot]],
+                        )?[0],
+                    );
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Expansion for CommonRec {
+    fn name(&self) -> Cow<str> {
+        self.body.name().into()
+    }
+
+    fn validation(&self) -> Validation {
+        Validation::Rounding
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> TractResult<()> {
+        check_input_arity(inputs, self.input_count())?;
+        check_output_arity(outputs, self.nboutputs()?)?;
+        let gates = self.body.gates() as i64;
+        let directions = self.direction.chunks().len() as i64;
+        // axes of batch_size and num_directions in the X and state interfaces
+        let (batch, dirs) = if self.batch_first { (0, 1) } else { (1, 0) };
+        s.equals(&inputs[0].datum_type, &inputs[1].datum_type)?;
+        s.equals(&inputs[0].datum_type, &inputs[2].datum_type)?;
+        s.equals(&inputs[0].rank, 3)?;
+        s.equals(&inputs[1].rank, 3)?;
+        s.equals(&inputs[2].rank, 3)?;
+        s.equals(&inputs[1].shape[0], directions.to_dim())?; // num_directions
+        s.equals(&inputs[1].shape[0], &inputs[2].shape[0])?; // num_directions
+        s.equals(&inputs[1].shape[1], &inputs[2].shape[1])?; // gates*hidden_size
+        s.equals(&inputs[2].shape[1], gates * inputs[2].shape[2].bex())?; // hidden_size
+        s.equals(&inputs[1].shape[2], &inputs[0].shape[2])?; // input_size
+        if let Some(b) = self.optional_bias_input {
+            s.equals(&inputs[b].datum_type, &inputs[0].datum_type)?;
+            s.equals(&inputs[b].rank, 2)?;
+            s.equals(&inputs[b].shape[0], &inputs[2].shape[0])?; // num_directions
+            s.equals(&inputs[b].shape[1], 2 * gates * inputs[2].shape[2].bex())?;
+        }
+        if let Some(seq_len) = self.optional_sequence_lens_input {
+            s.equals(&inputs[seq_len].rank, 1)?;
+            s.equals(&inputs[seq_len].shape[0], &inputs[0].shape[batch])?; // batch_size
+        }
+        for state in [self.optional_initial_h_input, self.optional_initial_c_input].iter().flatten()
+        {
+            s.equals(&inputs[*state].datum_type, &inputs[0].datum_type)?;
+            s.equals(&inputs[*state].rank, 3)?;
+            s.equals(&inputs[*state].shape[dirs], &inputs[1].shape[0])?; // num_directions
+            s.equals(&inputs[*state].shape[batch], &inputs[0].shape[batch])?; // batch_size
+            s.equals(&inputs[*state].shape[2], &inputs[2].shape[2])?; // hidden_size
+        }
+        if let Some(p) = self.optional_p_input {
+            s.equals(&inputs[p].datum_type, &inputs[0].datum_type)?;
+            s.equals(&inputs[p].rank, 2)?;
+            s.equals(&inputs[p].shape[0], &inputs[1].shape[0])?; // num_directions
+            s.equals(&inputs[p].shape[1], 3 * inputs[2].shape[2].bex())?; // 3*hidden_size
+        }
+        if let Some(y) = self.optional_y_output {
+            let (seq, y_dirs, y_batch) = if self.batch_first { (1, 2, 0) } else { (0, 1, 2) };
+            s.equals(&outputs[y].datum_type, &inputs[0].datum_type)?;
+            s.equals(&outputs[y].rank, 4)?;
+            s.equals(&outputs[y].shape[seq], &inputs[0].shape[1 - batch])?; // seq_length
+            s.equals(&outputs[y].shape[y_dirs], &inputs[1].shape[0])?; // num_directions
+            s.equals(&outputs[y].shape[y_batch], &inputs[0].shape[batch])?; // batch_size
+            s.equals(&outputs[y].shape[3], &inputs[2].shape[2])?; // hidden_size
+        }
+        for state in [self.optional_y_h_output, self.optional_y_c_output].iter().flatten() {
+            s.equals(&outputs[*state].datum_type, &inputs[0].datum_type)?;
+            s.equals(&outputs[*state].rank, 3)?;
+            s.equals(&outputs[*state].shape[dirs], &inputs[1].shape[0])?; // num_directions
+            s.equals(&outputs[*state].shape[batch], &inputs[0].shape[batch])?; // batch_size
+            s.equals(&outputs[*state].shape[2], &inputs[2].shape[2])?; // hidden_size
+        }
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.optional_y_output.is_some() as usize
+            + self.optional_y_h_output.is_some() as usize
+            + self.optional_y_c_output.is_some() as usize)
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_hir::tract_core::ops::array::TypedConcat;
+        let chunks = self.direction.chunks();
+        if chunks.len() == 1 {
+            return self.wire_one_side(prefix, target, inputs, 0, chunks[0]);
+        }
+        let fore = self.wire_one_side(&format!("{}.fore", prefix), target, inputs, 0, chunks[0])?;
+        let back = self.wire_one_side(&format!("{}.back", prefix), target, inputs, 1, chunks[1])?;
+        let state_axis = self.batch_first as usize;
+        let mut outputs = tvec!(0.into(); self.nboutputs()?);
+        for (slot, name, axis) in [
+            (self.optional_y_output, "y", state_axis + 1),
+            (self.optional_y_h_output, "y_h", state_axis),
+            (self.optional_y_c_output, "y_c", state_axis),
+        ] {
+            if let Some(ix) = slot {
+                outputs[ix] = target.wire_node(
+                    format!("{}.merge_{}_output", prefix, name),
+                    TypedConcat::new(axis),
+                    &[fore[ix], back[ix]],
+                )?[0];
+            }
+        }
+        Ok(outputs)
+    }
+}
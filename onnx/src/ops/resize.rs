@@ -11,6 +11,8 @@ pub fn resize(
         match node.get_attr_opt("coordinate_transformation_mode")?.unwrap_or("half_pixel") {
             "align_corners" => CoordTransformer::AlignCorners,
             "half_pixel" => CoordTransformer::HalfPixel,
+            "half_pixel_symmetric" => CoordTransformer::HalfPixelSymmetric,
+            "pytorch_half_pixel" => CoordTransformer::PytorchHalfPixel,
             "asymmetric" => CoordTransformer::Asymmetric,
             s => todo!("coordinate_transformation_mode: {}", s),
         };
@@ -26,6 +28,15 @@ pub fn resize(
         "round_prefer_ceil" => Nearest::RoundPreferCeil,
         s => todo!("nearest_mode: {}", s),
     };
+    let keep_aspect_ratio_policy =
+        match node.get_attr_opt("keep_aspect_ratio_policy")?.unwrap_or("stretch") {
+            "stretch" => KeepAspectRatioPolicy::Stretch,
+            "not_larger" => KeepAspectRatioPolicy::NotLarger,
+            "not_smaller" => KeepAspectRatioPolicy::NotSmaller,
+            s => bail!("Unsupported keep_aspect_ratio_policy: {}", s),
+        };
+    let antialias = node.get_attr_opt::<i64>("antialias")?.unwrap_or(0) == 1;
+    let axes = node.get_attr_opt_vec::<i64>("axes")?;
     let mut options = crate::model::optional_inputs(node).skip(2);
     Ok((
         Box::new(Resize {
@@ -34,6 +45,9 @@ pub fn resize(
             coord_transformer,
             interpolator,
             nearest,
+            antialias,
+            axes,
+            keep_aspect_ratio_policy,
         }),
         vec![],
     ))
@@ -42,6 +56,8 @@ pub fn resize(
 #[derive(Clone, Debug, Hash)]
 enum CoordTransformer {
     HalfPixel,
+    HalfPixelSymmetric,
+    PytorchHalfPixel,
     AlignCorners,
     Asymmetric,
 }
@@ -50,6 +66,18 @@ impl CoordTransformer {
     fn transform(&self, x_out: usize, scale: f32, len_in: usize, len_out: usize) -> f32 {
         match self {
             CoordTransformer::HalfPixel => (x_out as f32 + 0.5) / scale - 0.5,
+            CoordTransformer::HalfPixelSymmetric => {
+                let adjustment = len_out as f32 / (scale * len_in as f32);
+                let offset = len_in as f32 / 2.0 * (1.0 - adjustment);
+                offset + (x_out as f32 + 0.5) / scale - 0.5
+            }
+            CoordTransformer::PytorchHalfPixel => {
+                if len_out > 1 {
+                    (x_out as f32 + 0.5) / scale - 0.5
+                } else {
+                    0.0
+                }
+            }
             CoordTransformer::AlignCorners => {
                 (x_out as f32 * (len_in as f32 - 1.0)) / (len_out as f32 - 1.0)
             }
@@ -98,13 +126,24 @@ enum Nearest {
     RoundPreferCeil,
 }
 
-#[derive(Clone, new, Debug, Hash)]
+/// How sizes are honoured when they do not preserve the aspect ratio of the resized axes.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum KeepAspectRatioPolicy {
+    Stretch,
+    NotLarger,
+    NotSmaller,
+}
+
+#[derive(Clone, Debug, Hash)]
 struct Resize {
     coord_transformer: CoordTransformer,
     interpolator: Interpolator,
     nearest: Nearest,
     optional_scales_input: Option<usize>,
     optional_sizes_input: Option<usize>,
+    antialias: bool,
+    axes: Option<Vec<i64>>,
+    keep_aspect_ratio_policy: KeepAspectRatioPolicy,
 }
 
 impl_dyn_hash!(Resize);
@@ -118,26 +157,57 @@ impl Op for Resize {
 }
 
 impl Resize {
+    fn axes(&self, rank: usize) -> TVec<usize> {
+        if let Some(axes) = &self.axes {
+            axes.iter().map(|&a| if a < 0 { a + rank as i64 } else { a } as usize).collect()
+        } else {
+            (0..rank).collect()
+        }
+    }
+
+    /// Output shape, and the scale applied to each axis.
     fn compute_output_shape(
         &self,
         input_shape: &[usize],
         input_scale: Option<&Tensor>,
         input_sizes: Option<&Tensor>,
-    ) -> TractResult<TVec<usize>> {
+    ) -> TractResult<(TVec<usize>, TVec<f32>)> {
+        let axes = self.axes(input_shape.len());
+        let mut output_shape: TVec<usize> = input_shape.into();
+        let mut scales = tvec!(1.0f32; input_shape.len());
         if let Some(scale) = input_scale {
-            if scale.len() == input_shape.len() {
-                let scales = scale.cast_to::<f32>()?;
-                return Ok(input_shape
-                    .iter()
-                    .zip(scales.as_slice::<f32>()?.iter())
-                    .map(|(input, scale)| ((*input as f32) * scale) as usize)
-                    .collect());
+            if scale.len() == axes.len() {
+                let given = scale.cast_to::<f32>()?;
+                for (&axis, &scale) in axes.iter().zip(given.as_slice::<f32>()?.iter()) {
+                    output_shape[axis] = ((input_shape[axis] as f32) * scale) as usize;
+                    scales[axis] = scale;
+                }
+                return Ok((output_shape, scales));
             }
         }
         if let Some(sizes) = input_sizes {
-            if sizes.len() == input_shape.len() {
-                let size = sizes.cast_to::<i64>()?;
-                return Ok(size.as_slice::<i64>()?.iter().map(|i| *i as usize).collect());
+            if sizes.len() == axes.len() {
+                let sizes = sizes.cast_to::<i64>()?;
+                let sizes = sizes.as_slice::<i64>()?;
+                let ratios = axes
+                    .iter()
+                    .zip(sizes.iter())
+                    .map(|(&axis, &size)| size as f32 / input_shape[axis] as f32);
+                let common = match self.keep_aspect_ratio_policy {
+                    KeepAspectRatioPolicy::Stretch => None,
+                    KeepAspectRatioPolicy::NotLarger => ratios.reduce(f32::min),
+                    KeepAspectRatioPolicy::NotSmaller => ratios.reduce(f32::max),
+                };
+                for (&axis, &size) in axes.iter().zip(sizes.iter()) {
+                    if let Some(scale) = common {
+                        output_shape[axis] = (input_shape[axis] as f32 * scale + 0.5) as usize;
+                        scales[axis] = scale;
+                    } else {
+                        output_shape[axis] = size as usize;
+                        scales[axis] = size as f32 / input_shape[axis] as f32;
+                    }
+                }
+                return Ok((output_shape, scales));
             }
         }
         bail!(
@@ -147,6 +217,45 @@ impl Resize {
             input_sizes,
         );
     }
+
+    /// Value at output position `x_out` of `axis`, reading input positions through `co_i`.
+    fn sample(
+        &self,
+        data: &tract_ndarray::ArrayD<f32>,
+        mut co_i: tract_ndarray::IxDyn,
+        axis: usize,
+        scale: f32,
+        len_out: usize,
+    ) -> f32 {
+        let len_in = data.shape()[axis];
+        let x_in = self.coord_transformer.transform(co_i[axis], scale, len_in, len_out);
+        if self.antialias && scale < 1.0 && matches!(self.interpolator, Interpolator::Linear) {
+            // triangle filter stretched to cover the input span of one output sample
+            let support = 1.0 / scale;
+            let low = (x_in - support).ceil().max(0.0) as isize;
+            let high = ((x_in + support).floor() as isize).min(len_in as isize - 1);
+            let (mut sum, mut weights) = (0.0, 0.0);
+            for x in low..=high {
+                let weight = (1.0 - (x as f32 - x_in).abs() * scale).max(0.0);
+                co_i[axis] = x as usize;
+                sum += weight * data[&co_i];
+                weights += weight;
+            }
+            if weights > 0.0 {
+                sum / weights
+            } else {
+                0.0
+            }
+        } else {
+            let x_floor = x_in.floor();
+            let clamp = |x: f32| (x.max(0.0) as usize).min(len_in - 1);
+            co_i[axis] = clamp(x_floor);
+            let y_left = data[&co_i];
+            co_i[axis] = clamp(x_floor + 1.0);
+            let y_right = data[&co_i];
+            self.interpolator.interpolate(y_left, y_right, x_in - x_floor, self.nearest)
+        }
+    }
 }
 
 impl EvalOp for Resize {
@@ -157,39 +266,21 @@ impl EvalOp for Resize {
     fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let scales = self.optional_scales_input.and_then(|ix| inputs.get(ix));
         let sizes = self.optional_sizes_input.and_then(|ix| inputs.get(ix));
-        let output_shape = self.compute_output_shape(
+        let (output_shape, scales) = self.compute_output_shape(
             inputs[0].shape(),
             scales.map(|t| &**t),
             sizes.map(|t| &**t),
         )?;
         let mut data = inputs.remove(0).into_tensor().into_array::<f32>()?;
         for axis in 0..data.ndim() {
-            #[allow(clippy::comparison_chain)]
             if output_shape[axis] == data.shape()[axis] {
                 continue;
-            } else if output_shape[axis] > data.shape()[axis] {
-                let scale = output_shape[axis] as f32 / data.shape()[axis] as f32;
-                let mut new_shape: TVec<usize> = data.shape().into();
-                new_shape[axis] = output_shape[axis];
-                data = tract_ndarray::ArrayD::from_shape_fn(&*new_shape, |co_o| -> f32 {
-                    let x_out = co_o[axis];
-                    let x_in = self.coord_transformer.transform(
-                        x_out,
-                        scale,
-                        data.shape()[axis],
-                        new_shape[axis],
-                    );
-                    let mut co_i = co_o;
-                    let x_left = (x_in as usize).min(data.shape()[axis] - 1).max(0);
-                    co_i[axis] = x_left;
-                    let y_left = data[&co_i];
-                    let x_right = (x_left + 1).min(data.shape()[axis] - 1);
-                    co_i[axis] = x_right;
-                    let y_right = data[&co_i];
-                    let x_frac = x_in - x_left as f32;
-                    self.interpolator.interpolate(y_left, y_right, x_frac, self.nearest)
-                })
             }
+            let mut new_shape: TVec<usize> = data.shape().into();
+            new_shape[axis] = output_shape[axis];
+            data = tract_ndarray::ArrayD::from_shape_fn(&*new_shape, |co_o| -> f32 {
+                self.sample(&data, co_o, axis, scales[axis], new_shape[axis])
+            })
         }
         Ok(tvec!(data.into_tvalue()))
     }
@@ -205,23 +296,23 @@ impl InferenceRulesOp for Resize {
         check_output_arity(outputs, 1)?;
         s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
         s.equals(&inputs[0].rank, &outputs[0].rank)?;
-        if inputs.len() == 3 && self.optional_scales_input == Some(2) {
-            rules_with_scales(self, s, inputs, outputs)
-        } else if inputs.len() == 3 && self.optional_sizes_input == Some(2) {
-            rules_with_sizes(self, s, inputs, outputs)
-        } else {
-            // bogus 4 inputs case
-            s.given_2(
+        match (self.optional_scales_input, self.optional_sizes_input) {
+            (Some(_), None) => rules_with_scales(self, s, inputs, outputs),
+            (None, Some(_)) => rules_with_sizes(self, s, inputs, outputs),
+            // bogus case: both inputs are present, only one of them is not empty
+            (Some(scales), Some(_)) => s.given_2(
                 &inputs[0].rank,
-                &inputs[self.optional_scales_input.unwrap()].shape,
+                &inputs[scales].shape,
                 move |s, input_rank, scale_shape| {
-                    if scale_shape.len() == 0 || scale_shape[0] != input_rank.to_dim() {
+                    let resized = self.axes(input_rank as usize).len();
+                    if scale_shape.len() == 0 || scale_shape[0] != resized.to_dim() {
                         rules_with_sizes(self, s, inputs, outputs)
                     } else {
                         rules_with_scales(self, s, inputs, outputs)
                     }
                 },
-            )
+            ),
+            (None, None) => bail!("Resize expects either scales or sizes"),
         }
     }
 
@@ -238,21 +329,14 @@ fn rules_with_scales<'r, 'p: 'r, 's: 'r>(
     let scales = &inputs[op.optional_scales_input.unwrap()];
     s.equals(&scales.datum_type, f32::datum_type())?;
     s.equals(&scales.rank, 1)?;
-    s.equals(&scales.shape[0], inputs[0].rank.bex().to_dim())?;
-    s.given_2(
-        &inputs[0].shape,
-        &inputs[op.optional_scales_input.unwrap()].value,
-        move |s, input_shape, scales| {
-            let input_shape =
-                input_shape.iter().map(|d| d.to_usize()).collect::<TractResult<TVec<usize>>>()?;
-            let output_size = op.compute_output_shape(&input_shape, Some(scales.as_ref()), None)?;
-            let rank = input_shape.len();
-            for i in 0..rank {
-                s.equals(&outputs[0].shape[i], output_size[i].to_dim())?;
-            }
-            Ok(())
-        },
-    )
+    if let Some(axes) = &op.axes {
+        s.equals(&scales.shape[0], axes.len().to_dim())?;
+    } else {
+        s.equals(&scales.shape[0], inputs[0].rank.bex().to_dim())?;
+    }
+    s.given_2(&inputs[0].shape, &scales.value, move |s, input_shape, scales| {
+        rules_with_output_shape(op, s, outputs, input_shape, Some(scales.as_ref()), None)
+    })
 }
 
 fn rules_with_sizes<'r, 'p: 'r, 's: 'r>(
@@ -263,6 +347,14 @@ fn rules_with_sizes<'r, 'p: 'r, 's: 'r>(
 ) -> InferenceResult {
     let sizes = &inputs[op.optional_sizes_input.unwrap()];
     s.equals(&sizes.rank, 1)?;
+    if op.axes.is_some() || op.keep_aspect_ratio_policy != KeepAspectRatioPolicy::Stretch {
+        if let Some(axes) = &op.axes {
+            s.equals(&sizes.shape[0], axes.len().to_dim())?;
+        }
+        return s.given_2(&inputs[0].shape, &sizes.value, move |s, input_shape, sizes| {
+            rules_with_output_shape(op, s, outputs, input_shape, None, Some(sizes.as_ref()))
+        });
+    }
     s.equals(&sizes.shape[0], inputs[0].rank.bex().to_dim())?;
     s.given(&inputs[0].rank, move |s, rank| {
         for i in 0..(rank as usize) {
@@ -272,6 +364,23 @@ fn rules_with_sizes<'r, 'p: 'r, 's: 'r>(
     })
 }
 
+fn rules_with_output_shape<'r, 'p: 'r, 's: 'r>(
+    op: &'s Resize,
+    s: &mut Solver<'r>,
+    outputs: &'p [TensorProxy],
+    input_shape: TVec<TDim>,
+    scales: Option<&Tensor>,
+    sizes: Option<&Tensor>,
+) -> InferenceResult {
+    let input_shape =
+        input_shape.iter().map(|d| d.to_usize()).collect::<TractResult<TVec<usize>>>()?;
+    let (output_shape, _) = op.compute_output_shape(&input_shape, scales, sizes)?;
+    for (i, d) in output_shape.into_iter().enumerate() {
+        s.equals(&outputs[0].shape[i], d.to_dim())?;
+    }
+    Ok(())
+}
+
 impl TypedOp for Resize {
     as_op!();
 
@@ -283,7 +392,7 @@ impl TypedOp for Resize {
         };
         let scales = self.optional_scales_input.and_then(|ix| inputs.get(ix));
         let sizes = self.optional_sizes_input.and_then(|ix| inputs.get(ix));
-        let output_shape = self.compute_output_shape(
+        let (output_shape, _) = self.compute_output_shape(
             input_shape,
             scales.and_then(|f| f.konst.as_deref()),
             sizes.and_then(|f| f.konst.as_deref()),
@@ -299,3 +408,39 @@ impl TypedOp for Resize {
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn linear(antialias: bool, axes: Option<Vec<i64>>, policy: KeepAspectRatioPolicy) -> Resize {
+        Resize {
+            coord_transformer: CoordTransformer::HalfPixel,
+            interpolator: Interpolator::Linear,
+            nearest: Nearest::RoundPreferFloor,
+            optional_scales_input: Some(1),
+            optional_sizes_input: None,
+            antialias,
+            axes,
+            keep_aspect_ratio_policy: policy,
+        }
+    }
+
+    #[test]
+    fn sizes_not_larger_on_axes() -> TractResult<()> {
+        let op = linear(false, Some(vec![-2, -1]), KeepAspectRatioPolicy::NotLarger);
+        let (shape, scales) =
+            op.compute_output_shape(&[1, 3, 4, 8], None, Some(&tensor1(&[2i64, 2])))?;
+        assert_eq!(&*shape, &[1, 3, 1, 2]);
+        assert_eq!(&*scales, &[1.0, 1.0, 0.25, 0.25]);
+        Ok(())
+    }
+
+    #[test]
+    fn antialiased_downsampling() -> TractResult<()> {
+        let op = linear(true, None, KeepAspectRatioPolicy::Stretch);
+        let input = tensor1(&[0f32, 1., 2., 3., 4., 5., 6., 7.]);
+        let output = op.eval(tvec!(input.into(), tensor1(&[0.5f32]).into()))?;
+        output[0].close_enough(&tensor1(&[1.25f32 / 1.75, 2.5, 4.5, 11. / 1.75]), true)
+    }
+}
@@ -21,6 +21,7 @@ impl TryFrom<DataType> for DatumType {
             DataType::Int32 => Ok(DatumType::I32),
             DataType::Int64 => Ok(DatumType::I64),
             DataType::Float16 => Ok(DatumType::F16),
+            DataType::Bfloat16 => Ok(DatumType::BF16),
             DataType::Float => Ok(DatumType::F32),
             DataType::Double => Ok(DatumType::F64),
             DataType::String => Ok(DatumType::String),
@@ -82,6 +83,7 @@ fn create_tensor(shape: Vec<usize>, dt: DatumType, data: &[u8]) -> TractResult<T
             DatumType::I32 => Tensor::from_raw::<i32>(&shape, data),
             DatumType::I64 => Tensor::from_raw::<i64>(&shape, data),
             DatumType::F16 => Tensor::from_raw::<f16>(&shape, data),
+            DatumType::BF16 => Tensor::from_raw::<bf16>(&shape, data),
             DatumType::F32 => Tensor::from_raw::<f32>(&shape, data),
             DatumType::F64 => Tensor::from_raw::<f64>(&shape, data),
             DatumType::Bool => Ok(Tensor::from_raw::<u8>(&shape, data)?
@@ -135,6 +137,12 @@ fn common_tryfrom(t: &TensorProto, path: Option<&str>) -> TractResult<Tensor> {
             }
             DatumType::I32 => Array::from_shape_vec(&*shape, t.int32_data.to_vec())?.into(),
             DatumType::I64 => Array::from_shape_vec(&*shape, t.int64_data.to_vec())?.into(),
+            // bfloat16 values are stored as their bits in int32_data
+            DatumType::BF16 => Array::from_shape_vec(
+                &*shape,
+                t.int32_data.iter().map(|&x| bf16::from_bits(x as u16)).collect(),
+            )?
+            .into(),
             DatumType::F32 => Array::from_shape_vec(&*shape, t.float_data.to_vec())?.into(),
             DatumType::F64 => Array::from_shape_vec(&*shape, t.double_data.to_vec())?.into(),
             DatumType::String => {
@@ -184,3 +192,26 @@ pub fn proto_from_reader<R: ::std::io::Read>(mut r: R) -> TractResult<TensorProt
 pub fn from_reader<R: ::std::io::Read>(r: R) -> TractResult<Tensor> {
     proto_from_reader(r)?.try_into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_bf16() -> TractResult<()> {
+        let values = [1.0f32, -2.5, 0.15625];
+        let bits: Vec<u16> = values.iter().map(|v| bf16::from_f32(*v).to_bits()).collect();
+        let mut proto = TensorProto {
+            data_type: DataType::Bfloat16 as i32,
+            dims: vec![3],
+            int32_data: bits.iter().map(|b| *b as i32).collect(),
+            ..TensorProto::default()
+        };
+        let expected = tensor1(&values).cast_to::<bf16>()?.into_owned();
+        assert_eq!(Tensor::try_from(&proto)?, expected);
+        proto.int32_data.clear();
+        proto.raw_data = bits.iter().flat_map(|b| b.to_le_bytes()).collect();
+        assert_eq!(Tensor::try_from(&proto)?, expected);
+        Ok(())
+    }
+}
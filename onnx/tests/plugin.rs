@@ -0,0 +1,57 @@
+#![cfg(feature = "plugin")]
+use tract_onnx::pb::attribute_proto::AttributeType;
+use tract_onnx::pb::tensor_shape_proto::{dimension, Dimension};
+use tract_onnx::pb::*;
+use tract_onnx::prelude::*;
+
+#[path = "../../plugin/tests/scale_plugin/mod.rs"]
+mod scale_plugin;
+
+fn value_info(name: &str, shape: &[i64]) -> ValueInfoProto {
+    let dim = shape
+        .iter()
+        .map(|d| Dimension { value: Some(dimension::Value::DimValue(*d)), ..Default::default() })
+        .collect();
+    let tensor = type_proto::Tensor {
+        elem_type: tensor_proto::DataType::Float as i32,
+        shape: Some(TensorShapeProto { dim }),
+    };
+    ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(TypeProto {
+            value: Some(type_proto::Value::TensorType(tensor)),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn plugin_op_in_onnx_graph() -> TractResult<()> {
+    let onnx = tract_onnx::onnx().with_loaded_plugin(&scale_plugin::plugin())?;
+    let node = NodeProto {
+        name: "scale".into(),
+        op_type: "test_scale".into(),
+        domain: "com.example".into(),
+        input: vec!["input".into()],
+        output: vec!["output".into()],
+        attribute: vec![AttributeProto {
+            name: "factor".into(),
+            r#type: AttributeType::Float as i32,
+            f: 0.5,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let graph = GraphProto {
+        node: vec![node],
+        input: vec![value_info("input", &[2, 2])],
+        output: vec![value_info("output", &[2, 2])],
+        ..Default::default()
+    };
+    let proto = ModelProto { graph: Some(graph), ..Default::default() };
+    let model = onnx.model_for_proto_model(&proto)?.into_optimized()?.into_runnable()?;
+    let result = model.run(tvec!(tensor2(&[[2f32, 4.], [6., 8.]]).into()))?;
+    assert_eq!(*result[0], tensor2(&[[1f32, 2.], [3., 4.]]));
+    Ok(())
+}
@@ -0,0 +1,84 @@
+#ifndef TRACT_PLUGIN_H
+#define TRACT_PLUGIN_H
+
+/*
+ * tract operator plugin ABI.
+ *
+ * A plugin is a shared library exporting `tract_plugin_register`, returning a
+ * static description of the operators it implements. tract looks operators up
+ * by name, in ONNX graphs (op_type) and NNEF documents (primitive name).
+ *
+ * All functions returning int32_t return 0 on success.
+ *
+ * Thread safety: tract shares op instances between threads. `output_facts`
+ * and `eval` may be called concurrently on the same instance, from any thread,
+ * and `destroy` may run on another thread than `create`. Plugins keeping
+ * mutable state in an instance must synchronize it themselves.
+ */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define TRACT_PLUGIN_ABI_VERSION 1
+#define TRACT_PLUGIN_MAX_RANK 8
+
+/* Same values as TractDatumType in tract.h */
+#define TRACT_PLUGIN_DATUM_TYPE_BOOL 0x01
+#define TRACT_PLUGIN_DATUM_TYPE_U8 0x11
+#define TRACT_PLUGIN_DATUM_TYPE_U16 0x12
+#define TRACT_PLUGIN_DATUM_TYPE_U32 0x14
+#define TRACT_PLUGIN_DATUM_TYPE_U64 0x18
+#define TRACT_PLUGIN_DATUM_TYPE_I8 0x21
+#define TRACT_PLUGIN_DATUM_TYPE_I16 0x22
+#define TRACT_PLUGIN_DATUM_TYPE_I32 0x24
+#define TRACT_PLUGIN_DATUM_TYPE_I64 0x28
+#define TRACT_PLUGIN_DATUM_TYPE_F16 0x32
+#define TRACT_PLUGIN_DATUM_TYPE_F32 0x34
+#define TRACT_PLUGIN_DATUM_TYPE_F64 0x38
+
+/* Type and shape of a value. Dimensions are always known: tract rejects models
+ * where a plugin operator gets an input with symbolic dimensions. */
+typedef struct {
+    int32_t datum_type;
+    size_t rank;
+    int64_t shape[TRACT_PLUGIN_MAX_RANK];
+} TractPluginFact;
+
+/* A contiguous, row-major tensor. Inputs are read-only, outputs are allocated
+ * by tract according to the facts returned by `output_facts`. */
+typedef struct {
+    TractPluginFact fact;
+    void *data;
+} TractPluginTensor;
+
+/* Node attributes, as strings. Lists are comma separated. */
+typedef struct {
+    const char *name;
+    const char *value;
+} TractPluginAttribute;
+
+typedef struct {
+    const char *name;
+    size_t n_outputs;
+    /* Instantiate the operator for a node. Returns NULL if the attributes are invalid. */
+    void *(*create)(const TractPluginAttribute *attributes, size_t n_attributes);
+    void (*destroy)(void *op);
+    /* Fill n_outputs facts from the input facts. */
+    int32_t (*output_facts)(const void *op, const TractPluginFact *inputs, size_t n_inputs,
+                            TractPluginFact *outputs);
+    /* Compute n_outputs tensors from the inputs. Output facts must be left as
+     * allocated by tract: they are checked against the declared output facts. */
+    int32_t (*eval)(const void *op, const TractPluginTensor *inputs, size_t n_inputs,
+                    TractPluginTensor *outputs);
+} TractPluginOpDef;
+
+typedef struct {
+    uint32_t abi_version;
+    const char *name;
+    size_t n_ops;
+    const TractPluginOpDef *ops;
+} TractPluginDef;
+
+const TractPluginDef *tract_plugin_register(void);
+
+#endif
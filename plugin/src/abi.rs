@@ -0,0 +1,132 @@
+//! `repr(C)` mirror of `include/tract_plugin.h`.
+//!
+//! Plugins written in Rust can use these types directly, and export
+//! `tract_plugin_register` returning a `&'static TractPluginDef`. The thread safety
+//! requirements of the header apply: op instances are called concurrently.
+use std::ffi::{c_char, c_void};
+
+use tract_core::internal::*;
+
+pub const TRACT_PLUGIN_ABI_VERSION: u32 = 1;
+pub const TRACT_PLUGIN_MAX_RANK: usize = 8;
+pub const TRACT_PLUGIN_REGISTER_SYMBOL: &[u8] = b"tract_plugin_register";
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TractPluginFact {
+    pub datum_type: i32,
+    pub rank: usize,
+    pub shape: [i64; TRACT_PLUGIN_MAX_RANK],
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TractPluginTensor {
+    pub fact: TractPluginFact,
+    pub data: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TractPluginAttribute {
+    pub name: *const c_char,
+    pub value: *const c_char,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TractPluginOpDef {
+    pub name: *const c_char,
+    pub n_outputs: usize,
+    pub create:
+        unsafe extern "C" fn(attributes: *const TractPluginAttribute, n: usize) -> *mut c_void,
+    pub destroy: unsafe extern "C" fn(op: *mut c_void),
+    pub output_facts: unsafe extern "C" fn(
+        op: *const c_void,
+        inputs: *const TractPluginFact,
+        n_inputs: usize,
+        outputs: *mut TractPluginFact,
+    ) -> i32,
+    pub eval: unsafe extern "C" fn(
+        op: *const c_void,
+        inputs: *const TractPluginTensor,
+        n_inputs: usize,
+        outputs: *mut TractPluginTensor,
+    ) -> i32,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TractPluginDef {
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub n_ops: usize,
+    pub ops: *const TractPluginOpDef,
+}
+
+// Definitions are static data in the plugin library.
+unsafe impl Send for TractPluginOpDef {}
+unsafe impl Sync for TractPluginOpDef {}
+unsafe impl Send for TractPluginDef {}
+unsafe impl Sync for TractPluginDef {}
+
+const DATUM_TYPES: &[(i32, DatumType)] = &[
+    (0x01, DatumType::Bool),
+    (0x11, DatumType::U8),
+    (0x12, DatumType::U16),
+    (0x14, DatumType::U32),
+    (0x18, DatumType::U64),
+    (0x21, DatumType::I8),
+    (0x22, DatumType::I16),
+    (0x24, DatumType::I32),
+    (0x28, DatumType::I64),
+    (0x32, DatumType::F16),
+    (0x34, DatumType::F32),
+    (0x38, DatumType::F64),
+];
+
+pub fn datum_type_to_abi(dt: DatumType) -> TractResult<i32> {
+    DATUM_TYPES
+        .iter()
+        .find(|(_, d)| *d == dt)
+        .map(|(code, _)| *code)
+        .with_context(|| format!("{:?} is not supported by tract plugins", dt))
+}
+
+pub fn datum_type_from_abi(code: i32) -> TractResult<DatumType> {
+    DATUM_TYPES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, dt)| *dt)
+        .with_context(|| format!("Invalid plugin datum type code 0x{:x}", code))
+}
+
+impl TractPluginFact {
+    pub fn new<D: DimLike>(dt: DatumType, shape: &[D]) -> TractResult<TractPluginFact> {
+        ensure!(
+            shape.len() <= TRACT_PLUGIN_MAX_RANK,
+            "Plugin facts are limited to rank {}, got {:?}",
+            TRACT_PLUGIN_MAX_RANK,
+            shape
+        );
+        let mut fact = TractPluginFact {
+            datum_type: datum_type_to_abi(dt)?,
+            rank: shape.len(),
+            shape: [0; TRACT_PLUGIN_MAX_RANK],
+        };
+        for (ix, d) in shape.iter().enumerate() {
+            fact.shape[ix] = d
+                .to_i64()
+                .with_context(|| format!("Plugin facts need concrete shapes, got {:?}", shape))?;
+        }
+        Ok(fact)
+    }
+
+    pub fn datum_type(&self) -> TractResult<DatumType> {
+        datum_type_from_abi(self.datum_type)
+    }
+
+    pub fn shape(&self) -> &[i64] {
+        &self.shape[..self.rank.min(TRACT_PLUGIN_MAX_RANK)]
+    }
+}
@@ -0,0 +1,141 @@
+//! Operators implemented out of tree, in shared libraries loaded at runtime.
+//!
+//! A plugin exports `tract_plugin_register` (see `include/tract_plugin.h`).
+//! Loading it registers its operators in a process-wide table, where the ONNX
+//! and NNEF loaders find them by name.
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::sync::Mutex;
+
+use tract_core::internal::*;
+
+pub mod abi;
+mod op;
+
+pub use op::PluginOp;
+
+use abi::*;
+
+pub use tract_core;
+
+/// An operator definition from a plugin.
+#[derive(Debug)]
+pub struct PluginOpDef {
+    pub name: String,
+    pub plugin: String,
+    pub n_outputs: usize,
+    def: *const TractPluginOpDef,
+    _library: Option<Arc<libloading::Library>>,
+}
+
+// The definition points to static data in the plugin library, kept alive by _library.
+unsafe impl Send for PluginOpDef {}
+unsafe impl Sync for PluginOpDef {}
+
+impl PluginOpDef {
+    pub(crate) fn abi(&self) -> &TractPluginOpDef {
+        unsafe { &*self.def }
+    }
+}
+
+#[derive(Debug)]
+pub struct Plugin {
+    pub name: String,
+    pub ops: Vec<Arc<PluginOpDef>>,
+}
+
+impl Plugin {
+    /// Load a plugin library. Its initialization code runs in process, so the library must
+    /// be trusted.
+    pub fn load(path: impl AsRef<Path>) -> TractResult<Plugin> {
+        let path = path.as_ref();
+        unsafe {
+            let library = libloading::Library::new(path)
+                .with_context(|| format!("Loading plugin {}", path.display()))?;
+            let register: libloading::Symbol<unsafe extern "C" fn() -> *const TractPluginDef> =
+                library.get(TRACT_PLUGIN_REGISTER_SYMBOL).with_context(|| {
+                    format!("{} does not export tract_plugin_register", path.display())
+                })?;
+            let def = register();
+            Self::from_def_and_library(def, Some(Arc::new(library)))
+        }
+    }
+
+    /// Wrap a plugin definition linked in the current binary.
+    ///
+    /// # Safety
+    ///
+    /// `def` must point to a valid `TractPluginDef` living for the whole process.
+    pub unsafe fn from_def(def: *const TractPluginDef) -> TractResult<Plugin> {
+        Self::from_def_and_library(def, None)
+    }
+
+    unsafe fn from_def_and_library(
+        def: *const TractPluginDef,
+        library: Option<Arc<libloading::Library>>,
+    ) -> TractResult<Plugin> {
+        ensure!(!def.is_null(), "Plugin returned a null definition");
+        let def = &*def;
+        ensure!(
+            def.abi_version == TRACT_PLUGIN_ABI_VERSION,
+            "Plugin ABI version is {}, tract expects {}",
+            def.abi_version,
+            TRACT_PLUGIN_ABI_VERSION
+        );
+        let name = CStr::from_ptr(def.name).to_str()?.to_string();
+        let ops = (0..def.n_ops)
+            .map(|ix| {
+                let op = def.ops.add(ix);
+                Ok(Arc::new(PluginOpDef {
+                    name: CStr::from_ptr((*op).name).to_str()?.to_string(),
+                    plugin: name.clone(),
+                    n_outputs: (*op).n_outputs,
+                    def: op,
+                    _library: library.clone(),
+                }))
+            })
+            .collect::<TractResult<Vec<_>>>()?;
+        Ok(Plugin { name, ops })
+    }
+
+    /// Make the plugin operators available to the model loaders.
+    pub fn register(&self) -> TractResult<()> {
+        let mut table = REGISTERED_OPS.lock().unwrap();
+        for op in &self.ops {
+            if let Some(previous) = table.iter().find(|o| o.name == op.name) {
+                ensure!(
+                    previous.def == op.def,
+                    "Operator {} from plugin {} is already defined by plugin {}",
+                    op.name,
+                    op.plugin,
+                    previous.plugin
+                );
+            } else {
+                table.push(op.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTERED_OPS: Mutex<Vec<Arc<PluginOpDef>>> = Mutex::new(vec![]);
+}
+
+/// Look up a registered plugin operator.
+pub fn registered_op(name: &str) -> Option<Arc<PluginOpDef>> {
+    REGISTERED_OPS.lock().unwrap().iter().find(|op| op.name == name).cloned()
+}
+
+/// Instantiate a registered plugin operator.
+pub fn instantiate(name: &str, attributes: Vec<(String, String)>) -> TractResult<PluginOp> {
+    let def = registered_op(name).with_context(|| format!("No plugin defines {}", name))?;
+    PluginOp::new(def, attributes)
+}
+
+pub(crate) fn c_strings(attributes: &[(String, String)]) -> TractResult<Vec<(CString, CString)>> {
+    attributes
+        .iter()
+        .map(|(k, v)| Ok((CString::new(k.as_str())?, CString::new(v.as_str())?)))
+        .collect()
+}
@@ -0,0 +1,195 @@
+use std::ffi::c_void;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use tract_core::internal::*;
+
+use crate::abi::*;
+use crate::PluginOpDef;
+
+/// Owns the plugin side state of an operator, as returned by `create`.
+struct Instance {
+    def: Arc<PluginOpDef>,
+    ptr: *mut c_void,
+}
+
+// The ABI requires instances to accept concurrent calls from any thread (see the thread
+// safety section of tract_plugin.h).
+unsafe impl Send for Instance {}
+unsafe impl Sync for Instance {}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        unsafe { (self.def.abi().destroy)(self.ptr) }
+    }
+}
+
+#[derive(Clone)]
+pub struct PluginOp {
+    pub def: Arc<PluginOpDef>,
+    pub attributes: Vec<(String, String)>,
+    instance: Arc<Instance>,
+}
+
+impl PluginOp {
+    pub fn new(def: Arc<PluginOpDef>, attributes: Vec<(String, String)>) -> TractResult<PluginOp> {
+        let c_strings = crate::c_strings(&attributes)?;
+        let c_attributes: Vec<TractPluginAttribute> = c_strings
+            .iter()
+            .map(|(k, v)| TractPluginAttribute { name: k.as_ptr(), value: v.as_ptr() })
+            .collect();
+        let ptr = unsafe { (def.abi().create)(c_attributes.as_ptr(), c_attributes.len()) };
+        ensure!(
+            !ptr.is_null(),
+            "Plugin operator {} rejected attributes {:?}",
+            def.name,
+            attributes
+        );
+        let instance = Arc::new(Instance { def: def.clone(), ptr });
+        Ok(PluginOp { def, attributes, instance })
+    }
+
+    fn plugin_output_facts(&self, inputs: &[TractPluginFact]) -> TractResult<Vec<TractPluginFact>> {
+        let mut outputs =
+            vec![
+                TractPluginFact { datum_type: 0, rank: 0, shape: [0; TRACT_PLUGIN_MAX_RANK] };
+                self.def.n_outputs
+            ];
+        let status = unsafe {
+            (self.def.abi().output_facts)(
+                self.instance.ptr,
+                inputs.as_ptr(),
+                inputs.len(),
+                outputs.as_mut_ptr(),
+            )
+        };
+        ensure!(status == 0, "Plugin operator {} failed to compute output facts", self.def.name);
+        Ok(outputs)
+    }
+}
+
+impl fmt::Debug for PluginOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PluginOp({}, {:?})", self.def.name, self.attributes)
+    }
+}
+
+impl Hash for PluginOp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.def.name.hash(state);
+        self.attributes.hash(state);
+    }
+}
+
+impl_dyn_hash!(PluginOp);
+
+impl Op for PluginOp {
+    fn name(&self) -> Cow<str> {
+        self.def.name.clone().into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("plugin: {}", self.def.plugin),
+            format!("attributes: {:?}", self.attributes),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for PluginOp {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input_facts = inputs
+            .iter()
+            .map(|t| TractPluginFact::new(t.datum_type(), t.shape()))
+            .collect::<TractResult<Vec<_>>>()?;
+        let typed_input_facts: TVec<TypedFact> =
+            inputs.iter().map(|t| t.datum_type().fact(t.shape())).collect();
+        let declared = self.output_facts(&typed_input_facts.iter().collect::<TVec<_>>())?;
+        let mut outputs = declared
+            .iter()
+            .map(|f| Tensor::zero_dt(f.datum_type, f.shape.as_concrete().unwrap()))
+            .collect::<TractResult<TVec<Tensor>>>()?;
+        let c_inputs: Vec<TractPluginTensor> = inputs
+            .iter()
+            .zip(input_facts.iter())
+            .map(|(t, f)| TractPluginTensor {
+                fact: *f,
+                data: unsafe { t.as_ptr_unchecked::<u8>() as *mut c_void },
+            })
+            .collect();
+        let mut c_outputs: Vec<TractPluginTensor> = outputs
+            .iter_mut()
+            .map(|t| {
+                Ok(TractPluginTensor {
+                    fact: TractPluginFact::new(t.datum_type(), t.shape())?,
+                    data: unsafe { t.as_ptr_mut_unchecked::<u8>() as *mut c_void },
+                })
+            })
+            .collect::<TractResult<_>>()?;
+        let status = unsafe {
+            (self.def.abi().eval)(
+                self.instance.ptr,
+                c_inputs.as_ptr(),
+                c_inputs.len(),
+                c_outputs.as_mut_ptr(),
+            )
+        };
+        ensure!(status == 0, "Plugin operator {} failed to evaluate", self.def.name);
+        for (ix, (c_output, output)) in c_outputs.iter().zip(outputs.iter()).enumerate() {
+            let expected = TractPluginFact::new(output.datum_type(), output.shape())?;
+            ensure!(
+                c_output.fact == expected,
+                "Plugin operator {} output #{} does not match its declared fact {:?}: {:?}",
+                self.def.name,
+                ix,
+                declared[ix],
+                c_output.fact
+            );
+        }
+        Ok(outputs.into_iter().map(|t| t.into_tvalue()).collect())
+    }
+}
+
+impl TypedOp for PluginOp {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        for (ix, input) in inputs.iter().enumerate() {
+            ensure!(
+                input.shape.is_concrete(),
+                "Plugin operator {} does not support symbolic dimensions (input #{} is {:?}): \
+                 concretize the model input shapes before using plugin operators",
+                self.def.name,
+                ix,
+                input
+            );
+        }
+        let input_facts = inputs
+            .iter()
+            .map(|f| TractPluginFact::new(f.datum_type, &f.shape))
+            .collect::<TractResult<Vec<_>>>()?;
+        self.plugin_output_facts(&input_facts)?
+            .iter()
+            .map(|f| {
+                let shape = f
+                    .shape()
+                    .iter()
+                    .map(|d| usize::try_from(*d))
+                    .collect::<Result<TVec<_>, _>>()
+                    .with_context(|| {
+                        format!(
+                            "Plugin operator {} could not infer output shape from {:?}",
+                            self.def.name, inputs
+                        )
+                    })?;
+                Ok(f.datum_type()?.fact(&shape))
+            })
+            .collect()
+    }
+
+    as_op!();
+}
@@ -0,0 +1,68 @@
+use tract_plugin::tract_core::internal::*;
+use tract_plugin::Plugin;
+
+mod scale_plugin;
+use scale_plugin::plugin;
+
+#[test]
+fn run_plugin_op() -> TractResult<()> {
+    let plugin = plugin();
+    assert_eq!(plugin.name, "test");
+    let op = tract_plugin::instantiate("test_scale", vec![("factor".into(), "2.5".into())])?;
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", f32::fact([2, 3]))?;
+    let output = model.wire_node("scale", op, &[source])?;
+    model.set_output_outlets(&output)?;
+    assert_eq!(model.outlet_fact(output[0])?, &f32::fact([2, 3]));
+    let model = model.into_optimized()?.into_runnable()?;
+    let result = model.run(tvec!(tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]).into()))?;
+    assert_eq!(*result[0], tensor2(&[[2.5f32, 5., 7.5], [10., 12.5, 15.]]));
+    Ok(())
+}
+
+#[test]
+fn reject_invalid_attributes() {
+    plugin();
+    assert!(tract_plugin::instantiate("test_scale", vec![]).is_err());
+}
+
+#[test]
+fn reject_invalid_input_type() -> TractResult<()> {
+    plugin();
+    let op = tract_plugin::instantiate("test_scale", vec![("factor".into(), "2".into())])?;
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", i32::fact([2]))?;
+    assert!(model.wire_node("scale", op, &[source]).is_err());
+    Ok(())
+}
+
+#[test]
+fn reject_symbolic_input() -> TractResult<()> {
+    plugin();
+    let op = tract_plugin::instantiate("test_scale", vec![("factor".into(), "2".into())])?;
+    let mut model = TypedModel::default();
+    let n = model.symbol_table.sym("N");
+    let source = model.add_source("input", f32::fact([n.to_dim(), 3.to_dim()]))?;
+    let err = model.wire_node("scale", op, &[source]).unwrap_err();
+    assert!(format!("{:?}", err).contains("symbolic"));
+    Ok(())
+}
+
+#[test]
+fn reject_output_not_matching_declared_fact() -> TractResult<()> {
+    plugin();
+    let op =
+        tract_plugin::instantiate("test_scale_flattened", vec![("factor".into(), "2".into())])?;
+    let mut model = TypedModel::default();
+    let source = model.add_source("input", f32::fact([2, 3]))?;
+    let output = model.wire_node("scale", op, &[source])?;
+    model.set_output_outlets(&output)?;
+    let model = model.into_runnable()?;
+    assert!(model.run(tvec!(Tensor::zero::<f32>(&[2, 3])?.into())).is_err());
+    Ok(())
+}
+
+#[test]
+fn missing_library() {
+    assert!(Plugin::load("/nonexistent/libplugin.so").is_err());
+}
@@ -0,0 +1,98 @@
+use std::ffi::{c_char, c_void, CStr};
+
+use tract_plugin::abi::*;
+use tract_plugin::Plugin;
+
+// A plugin multiplying a f32 tensor by its `factor` attribute.
+
+unsafe extern "C" fn create(attributes: *const TractPluginAttribute, n: usize) -> *mut c_void {
+    let attributes = std::slice::from_raw_parts(attributes, n);
+    let factor = attributes
+        .iter()
+        .find(|a| CStr::from_ptr(a.name).to_str() == Ok("factor"))
+        .and_then(|a| CStr::from_ptr(a.value).to_str().ok()?.parse::<f32>().ok());
+    if let Some(factor) = factor {
+        Box::into_raw(Box::new(factor)) as *mut c_void
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+unsafe extern "C" fn destroy(op: *mut c_void) {
+    drop(Box::from_raw(op as *mut f32))
+}
+
+unsafe extern "C" fn output_facts(
+    _op: *const c_void,
+    inputs: *const TractPluginFact,
+    n_inputs: usize,
+    outputs: *mut TractPluginFact,
+) -> i32 {
+    if n_inputs != 1 || (*inputs).datum_type != 0x34 {
+        return 1;
+    }
+    *outputs = *inputs;
+    0
+}
+
+unsafe extern "C" fn eval(
+    op: *const c_void,
+    inputs: *const TractPluginTensor,
+    _n_inputs: usize,
+    outputs: *mut TractPluginTensor,
+) -> i32 {
+    let factor = *(op as *const f32);
+    let len = (*inputs).fact.shape().iter().product::<i64>() as usize;
+    let input = std::slice::from_raw_parts((*inputs).data as *const f32, len);
+    let output = std::slice::from_raw_parts_mut((*outputs).data as *mut f32, len);
+    for (o, i) in output.iter_mut().zip(input.iter()) {
+        *o = i * factor;
+    }
+    0
+}
+
+// Same as eval, but reports a flattened output shape it was never allocated for.
+unsafe extern "C" fn eval_flattened(
+    op: *const c_void,
+    inputs: *const TractPluginTensor,
+    n_inputs: usize,
+    outputs: *mut TractPluginTensor,
+) -> i32 {
+    let status = eval(op, inputs, n_inputs, outputs);
+    let len = (*outputs).fact.shape().iter().product::<i64>();
+    (*outputs).fact.rank = 1;
+    (*outputs).fact.shape[0] = len;
+    status
+}
+
+static OPS: [TractPluginOpDef; 2] = [
+    TractPluginOpDef {
+        name: b"test_scale\0".as_ptr() as *const c_char,
+        n_outputs: 1,
+        create,
+        destroy,
+        output_facts,
+        eval,
+    },
+    TractPluginOpDef {
+        name: b"test_scale_flattened\0".as_ptr() as *const c_char,
+        n_outputs: 1,
+        create,
+        destroy,
+        output_facts,
+        eval: eval_flattened,
+    },
+];
+
+static PLUGIN: TractPluginDef = TractPluginDef {
+    abi_version: TRACT_PLUGIN_ABI_VERSION,
+    name: b"test\0".as_ptr() as *const c_char,
+    n_ops: 2,
+    ops: &OPS as *const TractPluginOpDef,
+};
+
+pub fn plugin() -> Plugin {
+    let plugin = unsafe { Plugin::from_def(&PLUGIN) }.unwrap();
+    plugin.register().unwrap();
+    plugin
+}
@@ -49,6 +49,15 @@ pub struct PulsedSameAxisConcatState {
 }
 
 impl OpState for PulsedSameAxisConcatState {
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        Ok(tvec!(("current_pos".to_string(), tensor0(self.current_pos as i64))))
+    }
+
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        self.current_pos = tract_core::snapshot::saved_usize(saved, "current_pos")?;
+        Ok(())
+    }
+
     fn eval(
         &mut self,
         session: &mut SessionState,
@@ -61,6 +61,20 @@ pub struct DeconvDelayState {
 }
 
 impl OpState for DeconvDelayState {
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        let mut saved = tvec!(("valid_inputed".to_string(), tensor0(self.valid_inputed as i64)));
+        if let Some(buffer) = &self.buffer {
+            saved.push(("buffer".to_string(), buffer.clone()));
+        }
+        Ok(saved)
+    }
+
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        self.valid_inputed = tract_core::snapshot::saved_isize(saved, "valid_inputed")?;
+        self.buffer = tract_core::snapshot::saved_opt_tensor(saved, "buffer");
+        Ok(())
+    }
+
     fn eval(
         &mut self,
         session: &mut SessionState,
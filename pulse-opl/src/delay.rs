@@ -73,6 +73,15 @@ impl DelayState {
 }
 
 impl OpState for DelayState {
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        Ok(self.buffer.iter().map(|b| ("buffer".to_string(), b.clone())).collect())
+    }
+
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        self.buffer = tract_core::snapshot::saved_opt_tensor(saved, "buffer");
+        Ok(())
+    }
+
     fn eval(
         &mut self,
         _state: &mut SessionState,
@@ -4,6 +4,8 @@ mod concat;
 mod deconv_delay;
 mod delay;
 mod pad;
+mod running_reduce;
+mod running_softmax;
 mod slice;
 
 pub use tract_nnef;
@@ -18,6 +20,8 @@ pub mod ops {
     pub use super::deconv_delay::DeconvDelay;
     pub use super::delay::{ Delay, DelayState };
     pub use super::pad::PulsePad;
+    pub use super::running_reduce::{RunningReduce, RunningReducer};
+    pub use super::running_softmax::RunningSoftmax;
     pub use super::slice::PulsedAxisSlice;
 }
 
@@ -9,6 +9,20 @@ struct PulsePadOpState {
 }
 
 impl OpState for PulsePadOpState {
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        let mut saved = tvec!(("current_pos".to_string(), tensor0(self.current_pos as i64)));
+        if let Some(frame) = &self.last_valid_frame {
+            saved.push(("last_valid_frame".to_string(), frame.clone()));
+        }
+        Ok(saved)
+    }
+
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        self.current_pos = tract_core::snapshot::saved_usize(saved, "current_pos")?;
+        self.last_valid_frame = tract_core::snapshot::saved_opt_tensor(saved, "last_valid_frame");
+        Ok(())
+    }
+
     fn eval(
         &mut self,
         session: &mut SessionState,
@@ -0,0 +1,178 @@
+use tract_core::ndarray::*;
+use tract_core::ops::nn::Reducer;
+use tract_nnef::internal::*;
+use tract_nnef::tract_num_traits::Float;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunningReducer {
+    Sum,
+    Mean,
+    Max,
+    Min,
+}
+
+impl RunningReducer {
+    fn frame_reducer(&self) -> Reducer {
+        match self {
+            RunningReducer::Sum | RunningReducer::Mean => Reducer::Sum,
+            RunningReducer::Max => Reducer::Max,
+            RunningReducer::Min => Reducer::Min,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Hash)]
+struct RunningReduceState {
+    current_pos: usize,
+    reduced_values: usize,
+    accumulator: Option<Tensor>,
+}
+
+impl OpState for RunningReduceState {
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        let mut saved = tvec!(
+            ("current_pos".to_string(), tensor0(self.current_pos as i64)),
+            ("reduced_values".to_string(), tensor0(self.reduced_values as i64)),
+        );
+        if let Some(acc) = &self.accumulator {
+            saved.push(("accumulator".to_string(), acc.clone()));
+        }
+        Ok(saved)
+    }
+
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        self.current_pos = tract_core::snapshot::saved_usize(saved, "current_pos")?;
+        self.reduced_values = tract_core::snapshot::saved_usize(saved, "reduced_values")?;
+        self.accumulator = tract_core::snapshot::saved_opt_tensor(saved, "accumulator");
+        Ok(())
+    }
+
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let op = op.downcast_ref::<RunningReduce>().ok_or_else(|| format_err!("Wrong Op type"))?;
+        let end_input =
+            op.end_input.eval(&session.resolved_symbols).to_usize().unwrap_or(usize::MAX);
+        let frame_len = op.axes.iter().map(|&ax| input.shape()[ax]).product::<usize>();
+        let frames = op.reducer.frame_reducer().reduce(&op.axes, &input)?;
+        let output = dispatch_floatlike!(Self::accumulate(input.datum_type())(
+            self, op, &frames, frame_len, end_input
+        ))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl RunningReduceState {
+    fn accumulate<T: Datum + Float>(
+        &mut self,
+        op: &RunningReduce,
+        frames: &Tensor,
+        frame_len: usize,
+        end_input: usize,
+    ) -> TractResult<Tensor> {
+        let frames = frames.to_array_view::<T>()?;
+        let pulse = frames.shape()[op.axis];
+        if self.accumulator.is_none() {
+            let init = match op.reducer {
+                RunningReducer::Sum | RunningReducer::Mean => T::zero(),
+                RunningReducer::Max => T::neg_infinity(),
+                RunningReducer::Min => T::infinity(),
+            };
+            let shape = frames.index_axis(Axis(op.axis), 0).shape().to_vec();
+            self.accumulator = Some(ArrayD::from_elem(shape, init).into_tensor());
+        }
+        let mut acc = self.accumulator.as_mut().unwrap().to_array_view_mut::<T>()?;
+        for i in 0..pulse {
+            let pos = self.current_pos + i;
+            if pos >= op.begin_input && pos < end_input {
+                let frame = frames.index_axis(Axis(op.axis), i);
+                match op.reducer {
+                    RunningReducer::Sum | RunningReducer::Mean => {
+                        Zip::from(&mut acc).and(&frame).for_each(|a, &f| *a = *a + f)
+                    }
+                    RunningReducer::Max => {
+                        Zip::from(&mut acc).and(&frame).for_each(|a, &f| *a = a.max(f))
+                    }
+                    RunningReducer::Min => {
+                        Zip::from(&mut acc).and(&frame).for_each(|a, &f| *a = a.min(f))
+                    }
+                }
+                self.reduced_values += frame_len;
+            }
+        }
+        self.current_pos += pulse;
+        let mut output = acc.to_owned();
+        if op.reducer == RunningReducer::Mean && self.reduced_values > 0 {
+            let count = T::from(self.reduced_values).unwrap();
+            output.mapv_inplace(|a| a / count);
+        }
+        Ok(output.insert_axis(Axis(op.axis)).into_tensor())
+    }
+}
+
+/// Stateful reduction along the streaming axis.
+///
+/// The streaming axis is reduced to one frame, like in the regular model. After each pulse, the
+/// output holds the reduction of all valid input frames seen so far, so once the input stream
+/// is over it holds the value the regular model computes. Other reduced axes are reduced within
+/// each frame.
+#[derive(Debug, Clone, Hash)]
+pub struct RunningReduce {
+    pub axes: TVec<usize>,
+    pub axis: usize,
+    pub reducer: RunningReducer,
+    pub begin_input: usize,
+    pub end_input: TDim,
+}
+
+impl_dyn_hash!(RunningReduce);
+
+impl Op for RunningReduce {
+    fn name(&self) -> Cow<str> {
+        format!("RunningReduce<{:?}>", self.reducer).into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "stream axis: {}, other axes: {:?}, valid input: {}..{}",
+            self.axis, self.axes, self.begin_input, self.end_input
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RunningReduce {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::<RunningReduceState>::default()))
+    }
+}
+
+impl TypedOp for RunningReduce {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(
+            inputs[0].datum_type.is_float(),
+            "RunningReduce only supports float inputs, got {:?}",
+            inputs[0].datum_type
+        );
+        let mut fact = inputs[0].without_value();
+        for &ax in self.axes.iter().chain(std::iter::once(&self.axis)) {
+            fact.shape.set(ax, 1.to_dim());
+        }
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+}
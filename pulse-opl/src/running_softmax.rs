@@ -0,0 +1,157 @@
+use tract_core::ndarray::*;
+use tract_core::ops::nn::Reducer;
+use tract_nnef::internal::*;
+use tract_nnef::tract_num_traits::Float;
+
+#[derive(Debug, Clone, Default, Hash)]
+struct RunningSoftmaxState {
+    current_pos: usize,
+    max: Option<Tensor>,
+    sum: Option<Tensor>,
+}
+
+impl OpState for RunningSoftmaxState {
+    fn save(&self) -> TractResult<TVec<(String, Tensor)>> {
+        let mut saved = tvec!(("current_pos".to_string(), tensor0(self.current_pos as i64)));
+        if let (Some(max), Some(sum)) = (&self.max, &self.sum) {
+            saved.push(("max".to_string(), max.clone()));
+            saved.push(("sum".to_string(), sum.clone()));
+        }
+        Ok(saved)
+    }
+
+    fn load(&mut self, saved: &[(String, Tensor)]) -> TractResult<()> {
+        self.current_pos = tract_core::snapshot::saved_usize(saved, "current_pos")?;
+        self.max = tract_core::snapshot::saved_opt_tensor(saved, "max");
+        self.sum = tract_core::snapshot::saved_opt_tensor(saved, "sum");
+        Ok(())
+    }
+
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let op = op.downcast_ref::<RunningSoftmax>().ok_or_else(|| format_err!("Wrong Op type"))?;
+        let end_input =
+            op.end_input.eval(&session.resolved_symbols).to_usize().unwrap_or(usize::MAX);
+        let maxes = Reducer::Max.reduce(&op.axes, &input)?;
+        let output = dispatch_floatlike!(Self::normalize(input.datum_type())(
+            self, op, &input, &maxes, end_input
+        ))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl RunningSoftmaxState {
+    fn normalize<T: Datum + Float>(
+        &mut self,
+        op: &RunningSoftmax,
+        input: &Tensor,
+        maxes: &Tensor,
+        end_input: usize,
+    ) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let maxes = maxes.to_array_view::<T>()?;
+        let pulse = input.shape()[op.axis];
+        if self.max.is_none() {
+            let shape = maxes.index_axis(Axis(op.axis), 0).shape().to_vec();
+            self.max = Some(ArrayD::from_elem(&*shape, T::neg_infinity()).into_tensor());
+            self.sum = Some(ArrayD::from_elem(&*shape, T::zero()).into_tensor());
+        }
+        let mut max = self.max.as_mut().unwrap().to_array_view_mut::<T>()?;
+        let mut sum = self.sum.as_mut().unwrap().to_array_view_mut::<T>()?;
+        // softmax axes, once the streaming axis is indexed out
+        let frame_axes: TVec<usize> =
+            op.axes.iter().map(|&ax| if ax > op.axis { ax - 1 } else { ax }).collect();
+        let mut output = ArrayD::<T>::zeros(input.shape());
+        for i in 0..pulse {
+            let pos = self.current_pos + i;
+            if pos < op.begin_input || pos >= end_input {
+                continue;
+            }
+            let frame = input.index_axis(Axis(op.axis), i);
+            // rescale the running sum when the running max moves up
+            Zip::from(&mut sum).and(&mut max).and(&maxes.index_axis(Axis(op.axis), i)).for_each(
+                |s, m, &f| {
+                    if f > *m {
+                        *s = *s * (*m - f).exp();
+                        *m = f;
+                    }
+                },
+            );
+            let mut slot = output.index_axis_mut(Axis(op.axis), i);
+            Zip::from(&mut slot)
+                .and(&frame)
+                .and_broadcast(&max)
+                .for_each(|o, &x, &m| *o = (x - m).exp());
+            let mut frame_sum = slot.to_owned();
+            for &ax in &frame_axes {
+                frame_sum = frame_sum.sum_axis(Axis(ax)).insert_axis(Axis(ax));
+            }
+            Zip::from(&mut sum).and(&frame_sum).for_each(|s, &f| *s = *s + f);
+            Zip::from(&mut slot).and_broadcast(&sum).for_each(|o, &s| *o = *o / s);
+        }
+        self.current_pos += pulse;
+        Ok(output.into_tensor())
+    }
+}
+
+/// Softmax along the streaming axis, normalized over the valid input frames seen so far.
+///
+/// Each output frame is the last frame of the softmax of the signal up to it, so the stream
+/// keeps its length and delay, and the last valid frame holds the value the regular model
+/// computes. Other softmax axes are normalized within each frame.
+#[derive(Debug, Clone, Hash)]
+pub struct RunningSoftmax {
+    pub axes: TVec<usize>,
+    pub axis: usize,
+    pub begin_input: usize,
+    pub end_input: TDim,
+}
+
+impl_dyn_hash!(RunningSoftmax);
+
+impl Op for RunningSoftmax {
+    fn name(&self) -> Cow<str> {
+        "RunningSoftmax".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "stream axis: {}, other axes: {:?}, valid input: {}..{}",
+            self.axis, self.axes, self.begin_input, self.end_input
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RunningSoftmax {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::<RunningSoftmaxState>::default()))
+    }
+}
+
+impl TypedOp for RunningSoftmax {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(
+            inputs[0].datum_type.is_float(),
+            "RunningSoftmax only supports float inputs, got {:?}",
+            inputs[0].datum_type
+        );
+        Ok(tvec!(inputs[0].without_value()))
+    }
+
+    as_op!();
+}
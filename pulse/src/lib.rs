@@ -5,6 +5,7 @@ pub mod macros;
 pub mod fact;
 pub mod model;
 pub mod ops;
+pub mod stream;
 
 pub mod internal {
     pub use std::fmt;
@@ -16,6 +17,7 @@ pub mod internal {
     pub use crate::fact::PulsedFact;
     pub use crate::model::{PulsedModel, PulsedModelExt};
     pub use crate::ops::{OpPulsifier, PulsedOp};
+    pub use crate::stream::PulsedStream;
 }
 
 use std::ops::ControlFlow;
@@ -40,16 +40,14 @@ impl PulsedModelExt for PulsedModel {
             .unwrap()
             .stream
             .is_some()));
-        ensure!(self.output_outlets()?.iter().all(|o| self
-            .outlet_fact(*o)
-            .unwrap()
-            .stream
-            .is_some()));
+        // non streaming outputs (like running reductions) get no delay, and -1 as axis
         let delays = tensor1(
             &self
                 .output_outlets()?
                 .iter()
-                .map(|oo| Ok(self.outlet_fact(*oo)?.stream.as_ref().unwrap().delay as _))
+                .map(|oo| {
+                    Ok(self.outlet_fact(*oo)?.stream.as_ref().map(|s| s.delay as _).unwrap_or(0))
+                })
                 .collect::<TractResult<TVec<i64>>>()?,
         );
         typed.properties.insert("pulse.delay".to_string(), delays.into_arc_tensor());
@@ -65,7 +63,9 @@ impl PulsedModelExt for PulsedModel {
             &self
                 .output_outlets()?
                 .iter()
-                .map(|oo| Ok(self.outlet_fact(*oo)?.stream.as_ref().unwrap().axis as _))
+                .map(|oo| {
+                    Ok(self.outlet_fact(*oo)?.stream.as_ref().map(|s| s.axis as _).unwrap_or(-1))
+                })
                 .collect::<TractResult<TVec<i64>>>()?,
         );
         typed.properties.insert("pulse.output_axes".to_string(), output_axes.into_arc_tensor());
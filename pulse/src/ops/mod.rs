@@ -6,6 +6,7 @@ pub mod cnn;
 pub mod delay;
 pub mod downsample;
 pub mod dummy;
+pub mod reduce;
 pub mod scan;
 pub mod slice;
 pub mod source;
@@ -42,7 +43,7 @@ pub(crate) fn sync_inputs(
     Ok(inputs)
 }
 
-register_all_mod!(array, cnn, downsample, scan, source);
+register_all_mod!(array, cnn, downsample, reduce, scan, source);
 
 type PulsifierFn = fn(
     &TypedModel,
@@ -0,0 +1,301 @@
+use crate::internal::*;
+use tract_core::ops::binary::TypedBinOp;
+use tract_core::ops::cast::Cast;
+use tract_core::ops::math::Div;
+use tract_core::ops::nn::{Reduce, Reducer, Softmax};
+use tract_pulse_opl::ops::{RunningReduce, RunningReducer, RunningSoftmax};
+
+register_all!(Reduce: pulsify, TypedBinOp: pulsify_mean, Softmax: pulsify_softmax);
+
+/// Reductions over the streaming axis become running reductions: the streaming axis is
+/// reduced to one frame as in the regular model, and the output is not a stream anymore. After
+/// each pulse it holds the reduction of the valid input frames seen so far, and the value the
+/// regular model computes once the input stream is over.
+fn pulsify(
+    op: &Reduce,
+    source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _symbol: &Symbol,
+    _pulse: &TDim,
+) -> TractResult<Option<TVec<OutletId>>> {
+    let input = mapping[&node.inputs[0]];
+    if op.reducer == Reducer::Sum {
+        // a mean is wired as a sum and a division: the division node is the one kept, and
+        // will pass the running mean through (see pulsify_mean)
+        let succs = &node.outputs[0].successors;
+        if succs.len() == 1 && is_mean(source, source.node(succs[0].node))? {
+            let div = source.node(succs[0].node);
+            return wire_running(op, &div.name, target, input, Some(RunningReducer::Mean));
+        }
+    }
+    let reducer = match op.reducer {
+        Reducer::Sum => Some(RunningReducer::Sum),
+        Reducer::Max => Some(RunningReducer::Max),
+        Reducer::Min => Some(RunningReducer::Min),
+        _ => None,
+    };
+    wire_running(op, &node.name, target, input, reducer)
+}
+
+fn wire_running(
+    op: &Reduce,
+    name: &str,
+    target: &mut PulsedModel,
+    input: OutletId,
+    reducer: Option<RunningReducer>,
+) -> TractResult<Option<TVec<OutletId>>> {
+    let fact = target.outlet_fact(input)?.clone();
+    let stream = if let Some(stream) = fact.stream.as_ref() {
+        stream
+    } else {
+        return Ok(None);
+    };
+    if !op.axes.contains(&stream.axis) {
+        return Ok(None);
+    }
+    let reducer = if let Some(reducer) = reducer {
+        reducer
+    } else {
+        bail!("Can not pulsify {:?} over the streaming axis", op.reducer)
+    };
+    let running = RunningReduce {
+        axes: op.axes.iter().copied().filter(|&ax| ax != stream.axis).collect(),
+        axis: stream.axis,
+        reducer,
+        begin_input: stream.delay,
+        end_input: stream.delay.to_dim() + &stream.dim,
+    };
+    target.wire_node(name, running, &[input]).map(Some)
+}
+
+/// Mean as wired by the frontends: Div(Reduce<Sum>, Cast(Const(cardinality))).
+fn is_mean(source: &TypedModel, node: &TypedNode) -> TractResult<bool> {
+    if !node.op_as::<TypedBinOp>().map(|op| op.0.is::<Div>()).unwrap_or(false) {
+        return Ok(false);
+    }
+    let sum = source.node(node.inputs[0].node);
+    let reduce = if let Some(reduce) = sum.op_as::<Reduce>() {
+        reduce
+    } else {
+        return Ok(false);
+    };
+    if reduce.reducer != Reducer::Sum {
+        return Ok(false);
+    }
+    let mut divisor = source.node(node.inputs[1].node);
+    if divisor.op_is::<Cast>() {
+        divisor = source.node(divisor.inputs[0].node);
+    }
+    let divisor = if let Some(k) = &divisor.outputs[0].fact.konst {
+        k.clone()
+    } else {
+        return Ok(false);
+    };
+    let input_fact = source.outlet_fact(sum.inputs[0])?;
+    let cardinality: TDim = reduce.axes.iter().map(|&ax| &input_fact.shape[ax]).product();
+    Ok(divisor.datum_type() == TDim::datum_type()
+        && divisor.len() == 1
+        && divisor.as_slice::<TDim>()?[0].clone().simplify() == cardinality.simplify())
+}
+
+/// The running mean has been wired in place of the sum, the division just passes it through.
+fn pulsify_mean(
+    _op: &TypedBinOp,
+    source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _symbol: &Symbol,
+    _pulse: &TDim,
+) -> TractResult<Option<TVec<OutletId>>> {
+    let input = mapping[&node.inputs[0]];
+    let running_mean = target
+        .node(input.node)
+        .op_as::<RunningReduce>()
+        .map(|op| op.reducer == RunningReducer::Mean)
+        .unwrap_or(false);
+    if running_mean && is_mean(source, node)? {
+        Ok(Some(tvec!(input)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Softmax over the streaming axis becomes a running softmax: each frame is normalized over
+/// the valid input frames seen so far. Only the last valid frame matches the regular model.
+fn pulsify_softmax(
+    op: &Softmax,
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _symbol: &Symbol,
+    _pulse: &TDim,
+) -> TractResult<Option<TVec<OutletId>>> {
+    let input = mapping[&node.inputs[0]];
+    let fact = target.outlet_fact(input)?.clone();
+    let stream = if let Some(stream) = fact.stream.as_ref() {
+        stream
+    } else {
+        return Ok(None);
+    };
+    if !op.axes.contains(&stream.axis) {
+        return Ok(None);
+    }
+    ensure!(
+        fact.datum_type.is_float() && op.output_dt == fact.datum_type,
+        "Can not pulsify {:?} softmax over the streaming axis",
+        fact.datum_type
+    );
+    let running = RunningSoftmax {
+        axes: op.axes.iter().copied().filter(|&ax| ax != stream.axis).collect(),
+        axis: stream.axis,
+        begin_input: stream.delay,
+        end_input: stream.delay.to_dim() + &stream.dim,
+    };
+    target.wire_node(&node.name, running, &[input]).map(Some)
+}
+
+impl PulsedOp for RunningReduce {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        for &ax in self.axes.iter().chain(std::iter::once(&self.axis)) {
+            fact.shape.set(ax, 1.to_dim());
+        }
+        fact.stream = None;
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+impl PulsedOp for RunningSoftmax {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::math::div;
+
+    // feeds the pulsed model pulse by pulse, returns what it output after each pulse
+    fn run_pulsed(model: &TypedModel, input: &Tensor, pulse: usize) -> TractResult<Vec<Tensor>> {
+        let s = model.symbol_table.sym("S");
+        let pulsed = PulsedModel::new(model, s, &pulse.to_dim())?;
+        let mut stream = PulsedStream::new(&pulsed)?;
+        let mut got = vec![];
+        let mut pos = 0;
+        while pos + pulse <= input.shape()[0] {
+            got.push(stream.push(tvec!(input.slice(0, pos, pos + pulse)?.into()))?.remove(0));
+            pos += pulse;
+        }
+        let last = input.slice(0, pos, input.shape()[0])?;
+        got.push(stream.push_last(tvec!(last.into()))?.remove(0));
+        Ok(got)
+    }
+
+    // checks the final value of a running reduction against the regular model
+    fn stream_check(model: &TypedModel, input: &[f32], pulse: usize) -> TractResult<Vec<Tensor>> {
+        let input = tensor1(input).into_shape(&[input.len(), 1])?;
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone().into()))?;
+        let got = run_pulsed(model, &input, pulse)?;
+        got.last().unwrap().close_enough(&expected[0], true)?;
+        Ok(got)
+    }
+
+    fn model(reducer: Reducer, mean: bool) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let source = model.add_source("source", f32::fact(dims!(s, 1)))?;
+        let mut wire = model.wire_node("reduce", Reduce::new(tvec!(0), reducer), &[source])?;
+        if mean {
+            let size = model.add_const("size", tensor2(&[[s.to_dim()]]))?;
+            let size =
+                model.wire_node("cast", tract_core::ops::cast::cast(f32::datum_type()), &[size])?;
+            wire = model.wire_node("mean", div(), &[wire[0], size[0]])?;
+        }
+        model.set_output_outlets(&wire)?;
+        Ok(model)
+    }
+
+    fn values(outputs: &[Tensor]) -> TractResult<Vec<f32>> {
+        outputs.iter().map(|t| Ok(*t.to_scalar::<f32>()?)).collect()
+    }
+
+    #[test]
+    fn running_sum() -> TractResult<()> {
+        let got = stream_check(&model(Reducer::Sum, false)?, &[1., 2., 3., 4., 5.], 2)?;
+        assert_eq!(got[0].shape(), &[1, 1]);
+        assert_eq!(values(&got)?, vec![3., 10., 15.]);
+        Ok(())
+    }
+
+    #[test]
+    fn running_max() -> TractResult<()> {
+        let got = stream_check(&model(Reducer::Max, false)?, &[1., 3., 2., 5., 4.], 2)?;
+        assert_eq!(values(&got)?, vec![3., 5., 5.]);
+        Ok(())
+    }
+
+    #[test]
+    fn running_mean() -> TractResult<()> {
+        let model = model(Reducer::Sum, true)?;
+        let got = stream_check(&model, &[1., 3., 2., 6., 3.], 2)?;
+        assert_eq!(values(&got)?, vec![2., 3., 3.]);
+        let s = model.symbol_table.sym("S");
+        let pulsed = PulsedModel::new(&model, s, &2.to_dim())?;
+        let running: Vec<_> =
+            pulsed.nodes().iter().filter_map(|n| n.op_as::<RunningReduce>()).collect();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].reducer, RunningReducer::Mean);
+        Ok(())
+    }
+
+    #[test]
+    fn running_sum_divided_by_constant() -> TractResult<()> {
+        let mut model = model(Reducer::Sum, false)?;
+        let two = model.add_const("two", tensor2(&[[2f32]]))?;
+        let half = model.wire_node("half", div(), &[model.output_outlets()?[0], two])?;
+        model.set_output_outlets(&half)?;
+        let got = stream_check(&model, &[1., 2., 3., 4., 5.], 2)?;
+        assert_eq!(values(&got)?, vec![1.5, 5., 7.5]);
+        Ok(())
+    }
+
+    #[test]
+    fn running_prod_is_rejected() -> TractResult<()> {
+        let model = model(Reducer::Prod, false)?;
+        let s = model.symbol_table.sym("S");
+        assert!(PulsedModel::new(&model, s, &2.to_dim()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn running_softmax() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let source = model.add_source("source", f32::fact(dims!(s, 1)))?;
+        let softmax = Softmax::new(tvec!(0), f32::datum_type());
+        let wire = model.wire_node("softmax", softmax, &[source])?;
+        model.set_output_outlets(&wire)?;
+        let input = [1f32, 3., -2., 5., 4.];
+        let got = run_pulsed(&model, &tensor1(&input).into_shape(&[5, 1])?, 2)?;
+        let got = Tensor::stack_tensors(0, &got)?;
+        assert_eq!(got.shape(), &[5, 1]);
+        // each frame is the last frame of the softmax over the signal up to it
+        for t in 0..input.len() {
+            let prefix = tensor1(&input[..=t]).into_shape(&[t + 1, 1])?;
+            let expected = model.clone().into_runnable()?.run(tvec!(prefix.into()))?;
+            got.slice(0, t, t + 1)?.close_enough(&expected[0].slice(0, t, t + 1)?, true)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+use crate::fact::StreamInfo;
+use crate::internal::*;
+use tract_core::model::typed::{TypedRunnableModel, TypedSimpleState};
+
+type Plan = Arc<TypedRunnableModel<TypedModel>>;
+
+/// Drives a pulsed model pulse by pulse, and takes care of the end of the stream.
+///
+/// Leading output frames that only account for the model delay are dropped, so the
+/// concatenation of everything returned by `push` and `push_last` is exactly what the
+/// non-pulsed model computes on the full signal. Non streaming outputs (running reductions)
+/// are returned as they are after each pulse: `push_last` gives their final value.
+#[derive(Debug)]
+pub struct PulsedStream {
+    state: TypedSimpleState<TypedModel, Plan>,
+    symbol: Symbol,
+    input_pulse: usize,
+    inputs: TVec<TypedFact>,
+    input_axes: TVec<usize>,
+    outputs: TVec<Option<StreamInfo>>,
+    consumed: usize,
+    emitted: TVec<usize>,
+    finished: bool,
+}
+
+impl PulsedStream {
+    pub fn new(model: &PulsedModel) -> TractResult<PulsedStream> {
+        let input_streams = model
+            .input_outlets()?
+            .iter()
+            .map(|i| model.outlet_fact(*i)?.stream.clone().context("Non streaming input"))
+            .collect::<TractResult<TVec<_>>>()?;
+        let mut symbols: Vec<Symbol> = input_streams.iter().flat_map(|s| s.dim.symbols()).collect();
+        symbols.dedup();
+        ensure!(symbols.len() == 1, "Expected inputs streaming over a single symbol");
+        let inputs = model
+            .input_outlets()?
+            .iter()
+            .map(|i| model.outlet_fact(*i).map(|f| f.to_pulse_fact()))
+            .collect::<TractResult<TVec<_>>>()?;
+        let input_axes: TVec<usize> = input_streams.iter().map(|s| s.axis).collect();
+        let input_pulse = model.input_fact(0)?.pulse().unwrap().to_usize()?;
+        for (fact, &axis) in inputs.iter().zip(input_axes.iter()) {
+            ensure!(
+                fact.shape[axis].to_usize()? == input_pulse,
+                "All inputs must have the same pulse"
+            );
+        }
+        let outputs = model
+            .output_outlets()?
+            .iter()
+            .map(|o| Ok(model.outlet_fact(*o)?.stream.clone()))
+            .collect::<TractResult<TVec<_>>>()?;
+        let plan = Arc::new(model.clone().into_typed()?.into_optimized()?.into_runnable()?);
+        Ok(PulsedStream {
+            state: TypedSimpleState::new(plan)?,
+            symbol: symbols.remove(0),
+            input_pulse,
+            inputs,
+            input_axes,
+            emitted: tvec!(0; outputs.len()),
+            outputs,
+            consumed: 0,
+            finished: false,
+        })
+    }
+
+    pub fn input_pulse(&self) -> usize {
+        self.input_pulse
+    }
+
+    /// Push one full pulse for each input. Returns the valid output frames it produced,
+    /// possibly none while the model delay is not absorbed.
+    pub fn push(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<Tensor>> {
+        ensure!(!self.finished, "Stream is already finished");
+        let outputs = self.state.run(inputs)?;
+        self.consumed += self.input_pulse;
+        self.collect(outputs, None)
+    }
+
+    /// Push the last chunk of the inputs, at most one pulse long (possibly empty) along
+    /// the streaming axis. The stream length becomes known, and the model is run until the
+    /// outputs cover the full signal. Returns the remaining output frames.
+    pub fn push_last(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<Tensor>> {
+        ensure!(!self.finished, "Stream is already finished");
+        ensure!(inputs.len() == self.inputs.len(), "Expected {} inputs", self.inputs.len());
+        let len = inputs[0].shape()[self.input_axes[0]];
+        ensure!(len <= self.input_pulse, "Last chunk is longer than a pulse");
+        ensure!(
+            inputs.iter().zip(self.input_axes.iter()).all(|(i, &axis)| i.shape()[axis] == len),
+            "Last chunks must all have the same length"
+        );
+        let stream_len = self.consumed + len;
+        let symbols = SymbolValues::default().with(&self.symbol, stream_len as i64);
+        self.state.session_state.resolved_symbols[&self.symbol] = Some(stream_len as i64);
+        let output_lens = self
+            .outputs
+            .iter()
+            .map(|s| s.as_ref().map(|s| s.dim.eval(&symbols).to_usize()).unwrap_or(Ok(0)))
+            .collect::<TractResult<TVec<_>>>()?;
+        let has_final_values = self.outputs.iter().any(|s| s.is_none());
+        let mut chunks: TVec<TVec<Tensor>> = tvec!(tvec!(); self.outputs.len());
+        let mut last = Some(inputs);
+        while (last.is_some() && has_final_values)
+            || self.emitted.iter().zip(self.outputs.iter()).zip(output_lens.iter()).any(
+                |((emitted, stream), len)| {
+                    stream.as_ref().map(|s| *emitted < s.delay + len).unwrap_or(false)
+                },
+            )
+        {
+            let inputs = if let Some(last) = last.take() {
+                self.pad_to_pulse(last)?
+            } else {
+                self.pad_to_pulse(tvec!())?
+            };
+            let outputs = self.state.run(inputs)?;
+            self.consumed += self.input_pulse;
+            for (ix, output) in self.collect(outputs, Some(&output_lens))?.into_iter().enumerate() {
+                chunks[ix].push(output);
+            }
+        }
+        self.finished = true;
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(ix, mut chunks)| {
+                let axis = if let Some(stream) = &self.outputs[ix] {
+                    stream.axis
+                } else {
+                    return chunks.pop().context("No final value");
+                };
+                if chunks.is_empty() {
+                    let fact = self.state.model().output_fact(ix)?;
+                    let mut shape: TVec<usize> =
+                        fact.shape.as_concrete().context("Symbolic output shape")?.into();
+                    shape[axis] = 0;
+                    Tensor::zero_dt(fact.datum_type, &shape)
+                } else {
+                    Tensor::stack_tensors(axis, &chunks)
+                }
+            })
+            .collect()
+    }
+
+    /// Build full pulse inputs from (shorter) chunks, or from nothing once the signal is over.
+    fn pad_to_pulse(&self, chunks: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        self.inputs
+            .iter()
+            .zip(self.input_axes.iter())
+            .enumerate()
+            .map(|(ix, (fact, &axis))| {
+                let shape = fact.shape.as_concrete().context("Symbolic input shape")?;
+                let mut input = Tensor::zero_dt(fact.datum_type, shape)?;
+                if let Some(chunk) = chunks.get(ix) {
+                    let len = chunk.shape()[axis];
+                    input.assign_slice(0..len, chunk, 0..len, axis)?;
+                }
+                Ok(input.into_tvalue())
+            })
+            .collect()
+    }
+
+    /// Drop output frames before the delay and, if the stream length is known, after the end.
+    fn collect(
+        &mut self,
+        outputs: TVec<TValue>,
+        lens: Option<&[usize]>,
+    ) -> TractResult<TVec<Tensor>> {
+        outputs
+            .into_iter()
+            .enumerate()
+            .map(|(ix, output)| {
+                let stream = if let Some(stream) = &self.outputs[ix] {
+                    stream
+                } else {
+                    return Ok(output.into_tensor());
+                };
+                let pulse = output.shape()[stream.axis];
+                let start = self.emitted[ix];
+                self.emitted[ix] += pulse;
+                let end = lens.map(|l| stream.delay + l[ix]).unwrap_or(usize::MAX);
+                let from = stream.delay.clamp(start, start + pulse) - start;
+                let to = end.clamp(start, start + pulse) - start;
+                output.slice(stream.axis, from, to.max(from))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+    use tract_core::ops::nn::DataFormat;
+
+    // two stacked centered convolutions: the pulsed model has a delay, and needs
+    // the end of the stream to produce the right padded tail
+    fn convs() -> TractResult<(TypedModel, Symbol)> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let mut wire = model.add_source("source", f32::fact(dims!(1, s)))?;
+        for (ix, kernel) in [[1f32, 2., 3.], [-1., 0., 2.]].iter().enumerate() {
+            wire = model.wire_node(
+                format!("conv.{}", ix),
+                ConvUnary {
+                    pool_spec: PoolSpec {
+                        data_format: DataFormat::CHW,
+                        dilations: None,
+                        strides: None,
+                        kernel_shape: tvec![3],
+                        padding: PaddingSpec::Explicit(tvec![1], tvec![1], false),
+                        output_channel_override: Some(1),
+                    },
+                    kernel_fmt: KernelFormat::OIHW,
+                    kernel: rctensor3(&[[*kernel]]),
+                    group: 1,
+                    bias: None,
+                    q_params: None,
+                },
+                &[wire],
+            )?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        Ok((model, s))
+    }
+
+    fn check(pulse: usize, len: usize) -> TractResult<()> {
+        let (model, s) = convs()?;
+        let input = Tensor::from_shape(&[1, len], &(0..len).map(|i| i as f32).collect::<Vec<_>>())?;
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone().into()))?;
+
+        let pulsed = PulsedModel::new(&model, s, &pulse.to_dim())?;
+        let mut stream = PulsedStream::new(&pulsed)?;
+        let mut outputs = vec![];
+        let mut pos = 0;
+        while pos + pulse <= len {
+            outputs.push(stream.push(tvec!(input.slice(1, pos, pos + pulse)?.into()))?.remove(0));
+            pos += pulse;
+        }
+        outputs.push(stream.push_last(tvec!(input.slice(1, pos, len)?.into()))?.remove(0));
+        assert_eq!(Tensor::stack_tensors(1, &outputs)?, *expected[0]);
+        assert!(stream.push_last(tvec!(input.slice(1, 0, 0)?.into())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn flush_partial_pulse() -> TractResult<()> {
+        check(4, 10)
+    }
+
+    #[test]
+    fn flush_full_pulses() -> TractResult<()> {
+        check(4, 12)
+    }
+
+    #[test]
+    fn flush_short_stream() -> TractResult<()> {
+        check(4, 3)
+    }
+}
@@ -1,4 +1,5 @@
 use tract_hir::internal::*;
+use tract_hir::ops::activations::Clip;
 use tract_hir::tract_core::ops::scan::ScanInfo;
 
 use crate::model::ParsingContext;
@@ -6,12 +7,9 @@ use crate::tfpb::tensorflow::NodeDef;
 
 pub fn block_lstm(_ctx: &ParsingContext, node: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
     let forget_bias = node.get_attr_opt_float("forget_bias")?.unwrap_or(1.0);
-    let cell_clip = node.get_attr_opt_float("cell_clip")?.unwrap_or(3.0);
+    let cell_clip = node.get_attr_opt_float("cell_clip")?;
     let t = node.get_attr_datum_type("T")?;
     let use_peephole = node.get_attr_opt_bool("use_peephole")?.unwrap_or(false);
-    if use_peephole {
-        unimplemented!("Block LSTM peeplholes");
-    }
     Ok(expand(BlockLSTM::new(forget_bias, cell_clip, t, use_peephole)))
 }
 
@@ -20,8 +18,9 @@ pub fn block_lstm(_ctx: &ParsingContext, node: &NodeDef) -> TractResult<Box<dyn
 pub struct BlockLSTM {
     #[educe(Hash(method = "hash_f32"))]
     forget_bias: f32,
-    #[educe(Hash(method = "hash_f32"))]
-    cell_clip: f32,
+    /// Bound of the cell state, only applied when the node has a positive cell_clip attribute.
+    #[educe(Hash(method = "hash_opt_f32"))]
+    cell_clip: Option<f32>,
     t: DatumType,
     use_peephole: bool,
 }
@@ -127,6 +126,25 @@ impl Expansion for BlockLSTM {
             .push(scan::InputMapping::State { initializer: scan::StateInitializer::FromInput(3) });
         wire!(h_prev = AxisOp::Rm(0), h_source);
 
+        // peepholes: body inputs 3, 4 and 5 ([1, cell_size], broadcast over batch)
+        let peepholes = if self.use_peephole {
+            let mut peepholes = tvec!();
+            for (ix, name) in [(5, "wci"), (6, "wcf"), (7, "wco")] {
+                let w = model.wire_node(
+                    format!("{}.{}-axis", prefix, name),
+                    AxisOp::Add(0),
+                    &[inputs[ix]],
+                )?[0];
+                input_mapping.push(scan::InputMapping::Full { slot: outer_inputs.len() });
+                outer_inputs.push(w);
+                let fact = model.outlet_fact(w)?.clone();
+                peepholes.push(body.add_source(format!("{}_source", name), fact)?);
+            }
+            Some((peepholes[0], peepholes[1], peepholes[2]))
+        } else {
+            None
+        };
+
         wire!(xh = array::TypedConcat::new(1), x, h_prev);
 
         let w = body.add_const(format!("{}-w", prefix), w)?;
@@ -135,22 +153,46 @@ impl Expansion for BlockLSTM {
         wire!(i_ci_f_o = math::add(), b, i_ci_f_o_1);
 
         wire!(i_1 = array::Slice::new(1, 0, cell_size), i_ci_f_o);
+        let mut i_1 = i_1;
+        if let Some((wci, _, _)) = peepholes {
+            wire!(wci_cs_prev = math::mul(), wci, cs_prev);
+            wire!(i_peep = math::add(), i_1, wci_cs_prev);
+            i_1 = i_peep;
+        }
         wire!(i = nn::sigmoid(), i_1);
 
         wire!(f_1 = array::Slice::new(1, 2 * cell_size, 3 * cell_size), i_ci_f_o);
         let bias = body.add_const(format!("{}-bias", prefix), rctensor2(&[[self.forget_bias]]))?;
         wire!(f_2 = math::add(), f_1, bias);
+        let mut f_2 = f_2;
+        if let Some((_, wcf, _)) = peepholes {
+            wire!(wcf_cs_prev = math::mul(), wcf, cs_prev);
+            wire!(f_peep = math::add(), f_2, wcf_cs_prev);
+            f_2 = f_peep;
+        }
         wire!(f = nn::sigmoid(), f_2);
 
         wire!(ci_1 = array::Slice::new(1, cell_size, 2 * cell_size), i_ci_f_o);
         wire!(ci = math::tanh(), ci_1);
 
-        wire!(o_1 = array::Slice::new(1, 3 * cell_size, 4 * cell_size), i_ci_f_o);
-        wire!(o = nn::sigmoid(), o_1);
-
         wire!(ci_i = math::mul(), ci, i);
         wire!(cs_1 = math::mul(), cs_prev, f);
-        wire!(cs = math::add(), cs_1, ci_i);
+        wire!(cs_2 = math::add(), cs_1, ci_i);
+        let cs = if let Some(cell_clip) = self.cell_clip.filter(|clip| *clip > 0.0) {
+            let clip = Clip::new(Some(-cell_clip), Some(cell_clip));
+            clip.wire(&format!("{}-cs", prefix), &mut body, &[cs_2])?[0]
+        } else {
+            cs_2
+        };
+
+        wire!(o_1 = array::Slice::new(1, 3 * cell_size, 4 * cell_size), i_ci_f_o);
+        let mut o_1 = o_1;
+        if let Some((_, _, wco)) = peepholes {
+            wire!(wco_cs = math::mul(), wco, cs);
+            wire!(o_peep = math::add(), o_1, wco_cs);
+            o_1 = o_peep;
+        }
+        wire!(o = nn::sigmoid(), o_1);
 
         wire!(co = math::tanh(), cs);
         wire!(h = math::mul(), co, o);
@@ -239,3 +281,42 @@ Ok(var_2_op.initializer.clone())
 }
 }
 */
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // zero weights and forget bias: each step halves the cell state
+    fn cell_state(cell_clip: Option<f32>) -> TractResult<Tensor> {
+        let mut model = InferenceModel::default();
+        let seq_len = model.add_const("seq_len", tensor0(1i64))?;
+        let x = model.add_source("x", f32::fact([1, 1, 1]).into())?;
+        let cs = model.add_const("cs", tensor2(&[[10f32]]))?;
+        let h = model.add_const("h", tensor2(&[[0f32]]))?;
+        let w = model.add_const("w", Tensor::zero::<f32>(&[2, 4])?)?;
+        let peephole = model.add_const("peephole", tensor1(&[0f32]))?;
+        let b = model.add_const("b", Tensor::zero::<f32>(&[4])?)?;
+        let op = BlockLSTM::new(0.0, cell_clip, f32::datum_type(), false);
+        let lstm = model.wire_node(
+            "lstm",
+            expand(op),
+            &[seq_len, x, cs, h, w, peephole, peephole, peephole, b],
+        )?;
+        model.set_output_outlets(&lstm[1..2])?;
+        let mut outputs = model.into_runnable()?.run(tvec!(tensor3(&[[[0f32]]]).into()))?;
+        Ok(outputs.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn cell_state_is_not_clipped_without_cell_clip() -> TractResult<()> {
+        assert_eq!(cell_state(None)?, tensor3(&[[[5f32]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn cell_state_is_clipped_with_cell_clip() -> TractResult<()> {
+        assert_eq!(cell_state(Some(3.0))?, tensor3(&[[[3f32]]]));
+        assert_eq!(cell_state(Some(-1.0))?, tensor3(&[[[5f32]]]));
+        Ok(())
+    }
+}
@@ -20,6 +20,7 @@ impl TryFrom<DataType> for DatumType {
             DataType::DtInt32 => Ok(DatumType::I32),
             DataType::DtInt64 => Ok(DatumType::I64),
             DataType::DtHalf => Ok(DatumType::F16),
+            DataType::DtBfloat16 => Ok(DatumType::BF16),
             DataType::DtFloat => Ok(DatumType::F32),
             DataType::DtDouble => Ok(DatumType::F64),
             DataType::DtString => Ok(DatumType::Blob),
@@ -59,6 +60,7 @@ impl TryFrom<DatumType> for DataType {
             DatumType::I32 => Ok(DataType::DtInt32),
             DatumType::I64 => Ok(DataType::DtInt64),
             DatumType::F16 => Ok(DataType::DtHalf),
+            DatumType::BF16 => Ok(DataType::DtBfloat16),
             DatumType::F32 => Ok(DataType::DtFloat),
             DatumType::F64 => Ok(DataType::DtDouble),
             DatumType::Blob => Ok(DataType::DtString),
@@ -0,0 +1,16 @@
+#![allow(clippy::len_zero)]
+pub mod model;
+mod ops;
+pub mod schema;
+
+pub use model::Tflite;
+pub use model::TfliteProtoModel;
+
+pub use tract_core;
+pub use tract_core::prelude;
+
+pub fn tflite() -> Tflite {
+    let mut tflite = Tflite::default();
+    ops::register_all_ops(&mut tflite.op_register);
+    tflite
+}
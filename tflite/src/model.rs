@@ -0,0 +1,410 @@
+use std::fmt;
+
+use tract_core::internal::*;
+use tract_core::ops::cast::cast;
+
+use crate::schema;
+use crate::schema::tensor_type;
+
+/// A verified TFLite flatbuffer.
+#[derive(Clone)]
+pub struct TfliteProtoModel {
+    buf: Vec<u8>,
+}
+
+impl TfliteProtoModel {
+    pub fn new(buf: Vec<u8>) -> TractResult<TfliteProtoModel> {
+        if buf.len() >= 8
+            && !flatbuffers::buffer_has_identifier(&buf, schema::FILE_IDENTIFIER, false)
+        {
+            bail!("Not a TFLite model (expected {} file identifier)", schema::FILE_IDENTIFIER)
+        }
+        schema::root(&buf).context("Invalid TFLite flatbuffer")?;
+        Ok(TfliteProtoModel { buf })
+    }
+
+    pub fn root(&self) -> schema::Model<'_> {
+        // Safety: the buffer has been verified in new()
+        unsafe { flatbuffers::root_unchecked::<schema::Model>(&self.buf) }
+    }
+}
+
+impl fmt::Debug for TfliteProtoModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let root = self.root();
+        write!(
+            f,
+            "TfliteProtoModel(version: {}, {} bytes, description: {:?})",
+            root.version(),
+            self.buf.len(),
+            root.description()
+        )
+    }
+}
+
+pub type PerAxisQuantization = (usize, Vec<i64>, Vec<f32>);
+
+pub type OpBuilder = fn(&mut DeserOp) -> TractResult<TVec<OutletId>>;
+
+#[derive(Clone, Default)]
+pub struct TfliteOpRegister(pub HashMap<i32, OpBuilder>);
+
+impl TfliteOpRegister {
+    pub fn insert(&mut self, code: i32, builder: OpBuilder) {
+        self.0.insert(code, builder);
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Tflite {
+    pub op_register: TfliteOpRegister,
+}
+
+impl Framework<TfliteProtoModel, TypedModel> for Tflite {
+    fn proto_model_for_read(&self, r: &mut dyn std::io::Read) -> TractResult<TfliteProtoModel> {
+        let mut buf = vec![];
+        r.read_to_end(&mut buf)?;
+        TfliteProtoModel::new(buf)
+    }
+
+    fn model_for_proto_model_with_symbols(
+        &self,
+        proto: &TfliteProtoModel,
+        symbols: &SymbolTable,
+    ) -> TractResult<TypedModel> {
+        let root = proto.root();
+        let subgraphs = root.subgraphs().context("Model has no subgraph")?;
+        ensure!(subgraphs.len() == 1, "Only models with one subgraph are supported");
+        let subgraph = subgraphs.get(0);
+        let mut target = TypedModel { symbol_table: symbols.clone(), ..TypedModel::default() };
+        let mut values = HashMap::new();
+        let ctx = TensorContext { root, subgraph };
+
+        for input in subgraph.inputs().iter().flat_map(|v| v.iter()) {
+            let fact = ctx.fact(input)?;
+            let outlet = target.add_source(ctx.name(input)?, fact)?;
+            values.insert(input, outlet);
+        }
+
+        let opcodes = root.operator_codes().context("Model has no operator codes")?;
+        for (ix, flat) in subgraph.operators().iter().flat_map(|v| v.iter()).enumerate() {
+            ensure!(
+                (flat.opcode_index() as usize) < opcodes.len(),
+                "Invalid opcode index for operator #{}",
+                ix
+            );
+            let opcode = opcodes.get(flat.opcode_index() as usize);
+            let outputs: TVec<i32> = flat.outputs().iter().flat_map(|v| v.iter()).collect();
+            let prefix = if let Some(&first) = outputs.first() {
+                ctx.name(first)?
+            } else {
+                format!("op_{}", ix)
+            };
+            let builder = if let Some(builder) = self.op_register.0.get(&opcode.code()) {
+                builder
+            } else if let Some(custom) = opcode.custom_code() {
+                bail!("Unsupported TFLite custom operator {:?} (node {})", custom, prefix)
+            } else {
+                bail!("Unsupported TFLite builtin operator #{} (node {})", opcode.code(), prefix)
+            };
+            let mut op = DeserOp {
+                ctx: &ctx,
+                target: &mut target,
+                values: &mut values,
+                prefix: prefix.clone(),
+                flat,
+                outputs: outputs.clone(),
+            };
+            let wires =
+                (builder)(&mut op).with_context(|| format!("Translating node {}", prefix))?;
+            ensure!(
+                wires.len() == outputs.len(),
+                "Node {} produced {} outputs, expected {}",
+                prefix,
+                wires.len(),
+                outputs.len()
+            );
+            for (wire, &tensor) in wires.iter().zip(outputs.iter()) {
+                let expected = ctx.fact(tensor)?;
+                let got = target.outlet_fact(*wire)?;
+                ensure!(
+                    got.datum_type == expected.datum_type,
+                    "Node {}: expected {:?} output, got {:?}",
+                    prefix,
+                    expected.datum_type,
+                    got.datum_type
+                );
+                target.set_outlet_label(*wire, ctx.name(tensor)?)?;
+                values.insert(tensor, *wire);
+            }
+        }
+
+        let mut outputs = tvec!();
+        for output in subgraph.outputs().iter().flat_map(|v| v.iter()) {
+            let outlet = if let Some(outlet) = values.get(&output) {
+                *outlet
+            } else {
+                bail!("Output tensor {} is not computed by the graph", ctx.name(output)?)
+            };
+            outputs.push(outlet);
+        }
+        target.set_output_outlets(&outputs)?;
+        Ok(target)
+    }
+}
+
+/// Access to the tensors declared in the subgraph being translated.
+#[derive(Clone, Copy)]
+pub struct TensorContext<'m> {
+    pub root: schema::Model<'m>,
+    pub subgraph: schema::SubGraph<'m>,
+}
+
+impl<'m> TensorContext<'m> {
+    pub fn tensor(&self, ix: i32) -> TractResult<schema::Tensor<'m>> {
+        let tensors = self.subgraph.tensors().context("Subgraph has no tensors")?;
+        if ix < 0 || ix as usize >= tensors.len() {
+            bail!("Invalid tensor index {}", ix)
+        }
+        Ok(tensors.get(ix as usize))
+    }
+
+    pub fn name(&self, ix: i32) -> TractResult<String> {
+        Ok(self
+            .tensor(ix)?
+            .name()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("tensor_{}", ix)))
+    }
+
+    pub fn shape(&self, ix: i32) -> TractResult<TVec<usize>> {
+        self.tensor(ix)?
+            .shape()
+            .iter()
+            .flat_map(|v| v.iter())
+            .map(|d| {
+                ensure!(d >= 0, "Negative dimension in tensor {}", ix);
+                Ok(d as usize)
+            })
+            .collect()
+    }
+
+    /// Per-axis quantization parameters, as (axis, zero points, scales).
+    pub fn per_axis_quantization(&self, ix: i32) -> TractResult<Option<PerAxisQuantization>> {
+        let q = if let Some(q) = self.tensor(ix)?.quantization() { q } else { return Ok(None) };
+        let scales: Vec<f32> = q.scale().iter().flat_map(|v| v.iter()).collect();
+        if scales.len() <= 1 {
+            return Ok(None);
+        }
+        let mut zero_points: Vec<i64> = q.zero_point().iter().flat_map(|v| v.iter()).collect();
+        if zero_points.is_empty() {
+            zero_points = vec![0; scales.len()];
+        }
+        ensure!(zero_points.len() == scales.len(), "Inconsistent quantization for tensor {}", ix);
+        Ok(Some((q.quantized_dimension() as usize, zero_points, scales)))
+    }
+
+    /// Datum type of a tensor. Per-tensor quantized 8-bit tensors map to QI8 or QU8.
+    pub fn datum_type(&self, ix: i32) -> TractResult<DatumType> {
+        let tensor = self.tensor(ix)?;
+        let dt = match tensor.type_() {
+            tensor_type::FLOAT32 => DatumType::F32,
+            tensor_type::FLOAT16 => DatumType::F16,
+            tensor_type::FLOAT64 => DatumType::F64,
+            tensor_type::INT8 => DatumType::I8,
+            tensor_type::UINT8 => DatumType::U8,
+            tensor_type::INT16 => DatumType::I16,
+            tensor_type::INT32 => DatumType::I32,
+            tensor_type::INT64 => DatumType::I64,
+            tensor_type::BOOL => DatumType::Bool,
+            other => bail!("Unsupported tensor type {} for tensor {}", other, ix),
+        };
+        if dt != DatumType::I8 && dt != DatumType::U8 {
+            return Ok(dt);
+        }
+        let q = if let Some(q) = tensor.quantization() { q } else { return Ok(dt) };
+        let scales = if let Some(scales) = q.scale() { scales } else { return Ok(dt) };
+        if scales.len() != 1 {
+            return Ok(dt);
+        }
+        let zero_point = q.zero_point().filter(|zp| zp.len() > 0).map(|zp| zp.get(0)).unwrap_or(0);
+        let qp = QParams::ZpScale { zero_point: zero_point as i32, scale: scales.get(0) };
+        Ok(if dt == DatumType::I8 { DatumType::QI8(qp) } else { DatumType::QU8(qp) })
+    }
+
+    pub fn fact(&self, ix: i32) -> TractResult<TypedFact> {
+        Ok(self.datum_type(ix)?.fact(self.shape(ix)?))
+    }
+
+    /// Constant value of a tensor, if it is backed by a non empty buffer.
+    ///
+    /// Per-axis quantized tensors can not be represented by a quantized datum type, so they keep
+    /// their raw integer type: see `per_axis_quantization` for their parameters.
+    pub fn konst(&self, ix: i32) -> TractResult<Option<Tensor>> {
+        let tensor = self.tensor(ix)?;
+        let buffers = self.root.buffers().context("Model has no buffers")?;
+        let buffer_ix = tensor.buffer() as usize;
+        // buffer 0 is the conventional empty buffer
+        if buffer_ix == 0 || buffer_ix >= buffers.len() {
+            return Ok(None);
+        }
+        let buffer = buffers.get(buffer_ix);
+        ensure!(buffer.offset() <= 1, "External buffers are not supported (tensor {})", ix);
+        let data = if let Some(data) = buffer.data().filter(|d| d.len() > 0) {
+            data
+        } else {
+            return Ok(None);
+        };
+        let dt = self.datum_type(ix)?;
+        let shape = self.shape(ix)?;
+        ensure!(
+            dt.is_copy() && data.len() == shape.iter().product::<usize>() * dt.size_of(),
+            "Inconsistent buffer size for tensor {}",
+            ix
+        );
+        if let Some((axis, _, scales)) = self.per_axis_quantization(ix)? {
+            ensure!(
+                axis < shape.len() && shape[axis] == scales.len(),
+                "Inconsistent per-axis quantization for tensor {}",
+                ix
+            );
+        }
+        Ok(Some(unsafe { Tensor::from_raw_dt(dt, &shape, data.bytes())? }))
+    }
+
+    /// Constant value of a tensor, with per-axis quantized tensors dequantized to f32.
+    pub fn dequantized_konst(&self, ix: i32) -> TractResult<Option<Tensor>> {
+        let konst = if let Some(konst) = self.konst(ix)? { konst } else { return Ok(None) };
+        if let Some((axis, zero_points, scales)) = self.per_axis_quantization(ix)? {
+            Ok(Some(dequantize_per_axis(&konst, axis, &zero_points, &scales)?))
+        } else {
+            Ok(Some(konst))
+        }
+    }
+}
+
+/// Dequantize a tensor to f32 with one zero point and scale per index along `axis`.
+pub fn dequantize_per_axis(
+    tensor: &Tensor,
+    axis: usize,
+    zero_points: &[i64],
+    scales: &[f32],
+) -> TractResult<Tensor> {
+    let mut float = tensor.cast_to::<f32>()?.into_owned();
+    float
+        .to_array_view_mut::<f32>()?
+        .axis_iter_mut(tract_ndarray::Axis(axis))
+        .enumerate()
+        .for_each(|(c, mut slice)| slice.mapv_inplace(|x| (x - zero_points[c] as f32) * scales[c]));
+    Ok(float)
+}
+
+/// Translation state for one TFLite operator.
+pub struct DeserOp<'op, 'm> {
+    pub ctx: &'op TensorContext<'m>,
+    pub target: &'op mut TypedModel,
+    pub values: &'op mut HashMap<i32, OutletId>,
+    pub prefix: String,
+    pub flat: schema::Operator<'m>,
+    pub outputs: TVec<i32>,
+}
+
+impl<'op, 'm> DeserOp<'op, 'm> {
+    /// Tensor indices of the inputs. Omitted optional inputs are -1.
+    pub fn inputs(&self) -> TVec<i32> {
+        self.flat.inputs().iter().flat_map(|v| v.iter()).collect()
+    }
+
+    fn input_index(&self, ix: usize) -> TractResult<i32> {
+        let inputs = self.inputs();
+        ensure!(ix < inputs.len(), "Node {} has no input #{}", self.prefix, ix);
+        Ok(inputs[ix])
+    }
+
+    pub fn has_input(&self, ix: usize) -> bool {
+        self.inputs().get(ix).map(|&t| t >= 0).unwrap_or(false)
+    }
+
+    pub fn input_fact(&self, ix: usize) -> TractResult<TypedFact> {
+        self.ctx.fact(self.input_index(ix)?)
+    }
+
+    pub fn output_fact(&self, ix: usize) -> TractResult<TypedFact> {
+        ensure!(ix < self.outputs.len(), "Node {} has no output #{}", self.prefix, ix);
+        self.ctx.fact(self.outputs[ix])
+    }
+
+    /// Constant value of an input, if it is backed by a buffer.
+    pub fn input_konst(&self, ix: usize) -> TractResult<Option<Tensor>> {
+        self.ctx.konst(self.input_index(ix)?)
+    }
+
+    pub fn input_konst_required(&self, ix: usize) -> TractResult<Tensor> {
+        self.input_konst(ix)?
+            .with_context(|| format!("Node {}: input #{} must be a constant", self.prefix, ix))
+    }
+
+    /// Wire for an input, turning constants into Const nodes on first use. Per-axis quantized
+    /// constants are dequantized.
+    pub fn input_wire(&mut self, ix: usize) -> TractResult<OutletId> {
+        let tensor = self.input_index(ix)?;
+        if let Some(outlet) = self.values.get(&tensor) {
+            return Ok(*outlet);
+        }
+        let konst = self.ctx.dequantized_konst(tensor)?.with_context(|| {
+            format!("Node {}: input #{} is neither computed nor constant", self.prefix, ix)
+        })?;
+        let outlet = self.target.add_const(self.ctx.name(tensor)?, konst)?;
+        self.values.insert(tensor, outlet);
+        Ok(outlet)
+    }
+
+    pub fn wire(
+        &mut self,
+        name: impl Into<String>,
+        op: impl Into<Box<dyn TypedOp>>,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        self.target.wire_node(name, op, inputs)
+    }
+
+    pub fn options<T: schema::BuiltinOptions<'m>>(&self) -> TractResult<T> {
+        self.flat
+            .builtin_options::<T>()
+            .with_context(|| format!("Node {}: missing or unexpected builtin options", self.prefix))
+    }
+
+    /// Name for the node computing output `ix` before requantization.
+    pub fn float_name(&self, ix: usize) -> TractResult<String> {
+        if self.output_fact(ix)?.datum_type.is_quantized() {
+            Ok(format!("{}.float", self.prefix))
+        } else {
+            Ok(self.prefix.clone())
+        }
+    }
+
+    /// Dequantize a wire to f32 if it is quantized.
+    pub fn dequantize(&mut self, wire: OutletId, name: &str) -> TractResult<OutletId> {
+        if !self.target.outlet_fact(wire)?.datum_type.is_quantized() {
+            return Ok(wire);
+        }
+        Ok(self.wire(name, cast(f32::datum_type()), &[wire])?[0])
+    }
+
+    /// Input `ix`, dequantized to f32 if it is quantized.
+    pub fn dequantized_input(&mut self, ix: usize) -> TractResult<OutletId> {
+        let wire = self.input_wire(ix)?;
+        let name = format!("{}.dequant_{}", self.prefix, ix);
+        self.dequantize(wire, &name)
+    }
+
+    /// Cast an f32 wire to the datum type of output `ix`, quantizing it if needed.
+    pub fn requantize(&mut self, wire: OutletId, ix: usize) -> TractResult<OutletId> {
+        let dt = self.output_fact(ix)?.datum_type;
+        if self.target.outlet_fact(wire)?.datum_type == dt {
+            return Ok(wire);
+        }
+        let name = self.prefix.clone();
+        Ok(self.wire(name, cast(dt), &[wire])?[0])
+    }
+}
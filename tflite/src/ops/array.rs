@@ -0,0 +1,81 @@
+use crate::model::{DeserOp, TfliteOpRegister};
+use crate::ops::wire_fused_activation;
+use crate::schema::{activation, builtin, ConcatenationOptions};
+use tract_core::internal::*;
+use tract_core::ops::array::{Pad, PadMode, TypedConcat};
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    reg.insert(builtin::CONCATENATION, concatenation);
+    reg.insert(builtin::EXPAND_DIMS, reshape);
+    reg.insert(builtin::PAD, pad);
+    reg.insert(builtin::RESHAPE, reshape);
+    reg.insert(builtin::SQUEEZE, reshape);
+    reg.insert(builtin::TRANSPOSE, transpose);
+}
+
+/// RESHAPE, SQUEEZE and EXPAND_DIMS all go to the declared output shape.
+fn reshape(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let input = op.input_wire(0)?;
+    let from = op.target.outlet_fact(input)?.shape.to_tvec();
+    let to: TVec<TDim> = op.output_fact(0)?.shape.to_tvec();
+    let name = op.prefix.clone();
+    op.wire(name, AxisOp::Reshape(0, from, to), &[input])
+}
+
+fn concatenation(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = op.options::<ConcatenationOptions>()?;
+    let output_fact = op.output_fact(0)?;
+    let rank = output_fact.rank() as i32;
+    let axis = options.axis();
+    ensure!(-rank <= axis && axis < rank, "Invalid concatenation axis {}", axis);
+    let axis = ((axis + rank) % rank) as usize;
+    let mut inputs = tvec!();
+    for ix in 0..op.inputs().len() {
+        let wire = op.input_wire(ix)?;
+        inputs.push(wire);
+    }
+    let same_types = inputs
+        .iter()
+        .map(|i| Ok(op.target.outlet_fact(*i)?.datum_type))
+        .collect::<TractResult<TVec<_>>>()?
+        .iter()
+        .all(|dt| *dt == output_fact.datum_type);
+    if same_types && options.fused_activation_function() == activation::NONE {
+        let name = op.prefix.clone();
+        return op.wire(name, TypedConcat { axis }, &inputs);
+    }
+    for (ix, input) in inputs.iter_mut().enumerate() {
+        *input = op.dequantize(*input, &format!("{}.dequant_{}", op.prefix, ix))?;
+    }
+    let name = op.float_name(0)?;
+    let wire = op.wire(name, TypedConcat { axis }, &inputs)?[0];
+    let wire = wire_fused_activation(op, wire, options.fused_activation_function())?;
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
+
+fn pad(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let pads = op.input_konst_required(1)?.cast_to::<i64>()?.into_owned();
+    let pads = pads.as_slice::<i64>()?;
+    ensure!(pads.iter().all(|p| *p >= 0), "Negative paddings are not supported");
+    let pads = pads.chunks(2).map(|p| (p[0] as usize, p[1] as usize)).collect();
+    // padding dequantized values with zero is padding with the zero point
+    let input = op.dequantized_input(0)?;
+    let dt = op.target.outlet_fact(input)?.datum_type;
+    let mode = PadMode::Constant(Tensor::zero_scalar_dt(dt)?.into_arc_tensor());
+    let name = op.float_name(0)?;
+    let wire = op.wire(name, Pad { pads, mode }, &[input])?[0];
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
+
+fn transpose(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let perm = op.input_konst_required(1)?.cast_to::<i64>()?.into_owned();
+    let perm: TVec<usize> = perm.as_slice::<i64>()?.iter().map(|&p| p as usize).collect();
+    let mut sorted = perm.clone();
+    sorted.sort();
+    ensure!(sorted.iter().enumerate().all(|(ix, &p)| ix == p), "Invalid permutation {:?}", perm);
+    let mut wire = op.input_wire(0)?;
+    for (ix, axis_op) in perm_to_ops(&perm).into_iter().enumerate() {
+        wire = op.wire(format!("{}.{}", op.prefix, ix), axis_op, &[wire])?[0];
+    }
+    Ok(tvec!(wire))
+}
@@ -0,0 +1,48 @@
+use crate::model::{DeserOp, TfliteOpRegister};
+use crate::ops::{wire_clip, wire_fused_activation, wire_unary_float};
+use crate::schema::{activation, builtin, AddOptions, DivOptions, MulOptions, SubOptions};
+use tract_core::internal::*;
+use tract_core::ops::binary::{wire_with_rank_broadcast, TypedBinOp};
+use tract_core::ops::math;
+use tract_core::ops::nn::sigmoid;
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    reg.insert(builtin::ADD, |op| {
+        let act = op.options::<AddOptions>()?.fused_activation_function();
+        binary(op, math::add(), act)
+    });
+    reg.insert(builtin::SUB, |op| {
+        let act = op.options::<SubOptions>()?.fused_activation_function();
+        binary(op, math::sub(), act)
+    });
+    reg.insert(builtin::MUL, |op| {
+        let act = op.options::<MulOptions>()?.fused_activation_function();
+        binary(op, math::mul(), act)
+    });
+    reg.insert(builtin::DIV, |op| {
+        let act = op.options::<DivOptions>()?.fused_activation_function();
+        binary(op, math::div(), act)
+    });
+    reg.insert(builtin::MAXIMUM, |op| binary(op, math::max(), activation::NONE));
+    reg.insert(builtin::MINIMUM, |op| binary(op, math::min(), activation::NONE));
+    reg.insert(builtin::LOGISTIC, |op| wire_unary_float(op, sigmoid()));
+    reg.insert(builtin::TANH, |op| wire_unary_float(op, math::tanh()));
+    reg.insert(builtin::RELU, |op| clip(op, Some(0.0), None));
+    reg.insert(builtin::RELU6, |op| clip(op, Some(0.0), Some(6.0)));
+}
+
+/// Binary operators work on dequantized inputs, with numpy-style rank broadcasting.
+fn binary(op: &mut DeserOp, bin: TypedBinOp, act: i8) -> TractResult<TVec<OutletId>> {
+    let a = op.dequantized_input(0)?;
+    let b = op.dequantized_input(1)?;
+    let name = op.float_name(0)?;
+    let wire = wire_with_rank_broadcast(&name, op.target, bin, &[a, b])?[0];
+    let wire = wire_fused_activation(op, wire, act)?;
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
+
+fn clip(op: &mut DeserOp, low: Option<f32>, high: Option<f32>) -> TractResult<TVec<OutletId>> {
+    let input = op.dequantized_input(0)?;
+    let wire = wire_clip(op, input, low, high)?;
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
@@ -0,0 +1,90 @@
+use crate::model::{DeserOp, TfliteOpRegister};
+use crate::schema::{activation, builtin};
+use tract_core::internal::*;
+use tract_core::ops::binary::wire_with_rank_broadcast;
+use tract_core::ops::cast::cast;
+use tract_core::ops::math;
+
+mod array;
+mod math_ops;
+mod nn;
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    array::register_all_ops(reg);
+    math_ops::register_all_ops(reg);
+    nn::register_all_ops(reg);
+    reg.insert(builtin::QUANTIZE, quantize);
+    reg.insert(builtin::DEQUANTIZE, quantize);
+}
+
+/// QUANTIZE and DEQUANTIZE are both casts between the declared tensor types.
+fn quantize(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let input = op.input_wire(0)?;
+    let dt = op.output_fact(0)?.datum_type;
+    let name = op.prefix.clone();
+    op.wire(name, cast(dt), &[input])
+}
+
+/// Apply a fused activation function on a float wire.
+pub(crate) fn wire_fused_activation(
+    op: &mut DeserOp,
+    wire: OutletId,
+    activation: i8,
+) -> TractResult<OutletId> {
+    let (low, high) = match activation {
+        activation::NONE => return Ok(wire),
+        activation::RELU => (Some(0.0), None),
+        activation::RELU_N1_TO_1 => (Some(-1.0), Some(1.0)),
+        activation::RELU6 => (Some(0.0), Some(6.0)),
+        activation::TANH => {
+            let name = format!("{}.tanh", op.prefix);
+            return Ok(op.wire(name, math::tanh(), &[wire])?[0]);
+        }
+        other => bail!("Unsupported fused activation function {}", other),
+    };
+    wire_clip(op, wire, low, high)
+}
+
+pub(crate) fn wire_clip(
+    op: &mut DeserOp,
+    mut wire: OutletId,
+    low: Option<f32>,
+    high: Option<f32>,
+) -> TractResult<OutletId> {
+    let dt = op.target.outlet_fact(wire)?.datum_type;
+    if let Some(low) = low {
+        let low = op
+            .target
+            .add_const(format!("{}.low", op.prefix), tensor0(low).cast_to_dt(dt)?.into_owned())?;
+        wire = wire_with_rank_broadcast(
+            &format!("{}.clip_low", op.prefix),
+            op.target,
+            math::max(),
+            &[wire, low],
+        )?[0];
+    }
+    if let Some(high) = high {
+        let high = op
+            .target
+            .add_const(format!("{}.high", op.prefix), tensor0(high).cast_to_dt(dt)?.into_owned())?;
+        wire = wire_with_rank_broadcast(
+            &format!("{}.clip_high", op.prefix),
+            op.target,
+            math::min(),
+            &[wire, high],
+        )?[0];
+    }
+    Ok(wire)
+}
+
+/// Wire a single input, single output float operator between dequantization and requantization
+/// of its input and output.
+pub(crate) fn wire_unary_float(
+    op: &mut DeserOp,
+    mini: impl Into<Box<dyn TypedOp>>,
+) -> TractResult<TVec<OutletId>> {
+    let input = op.dequantized_input(0)?;
+    let name = op.float_name(0)?;
+    let wire = op.wire(name, mini, &[input])?[0];
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
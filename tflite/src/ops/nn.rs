@@ -0,0 +1,338 @@
+use crate::model::{DeserOp, TfliteOpRegister};
+use crate::ops::wire_fused_activation;
+use crate::schema::{
+    activation, builtin, padding, Conv2DOptions, DepthwiseConv2DOptions, FullyConnectedOptions,
+    Pool2DOptions, ReducerOptions, SoftmaxOptions,
+};
+use tract_core::internal::*;
+use tract_core::ops::cast::cast;
+use tract_core::ops::cnn::{ConvUnary, KernelFormat, MaxPool, PaddingSpec, PoolSpec, SumPool};
+use tract_core::ops::math;
+use tract_core::ops::matmul::mir_quant::QParamKind;
+use tract_core::ops::matmul::MatMulQParams;
+use tract_core::ops::nn::{DataFormat, Reduce, Reducer, Softmax};
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    reg.insert(builtin::AVERAGE_POOL_2D, average_pool_2d);
+    reg.insert(builtin::CONV_2D, conv_2d);
+    reg.insert(builtin::DEPTHWISE_CONV_2D, depthwise_conv_2d);
+    reg.insert(builtin::FULLY_CONNECTED, fully_connected);
+    reg.insert(builtin::MAX_POOL_2D, max_pool_2d);
+    reg.insert(builtin::MEAN, mean);
+    reg.insert(builtin::SOFTMAX, softmax);
+}
+
+fn padding(padding: i8) -> TractResult<PaddingSpec> {
+    match padding {
+        padding::SAME => Ok(PaddingSpec::SameUpper),
+        padding::VALID => Ok(PaddingSpec::Valid),
+        other => bail!("Unsupported padding {}", other),
+    }
+}
+
+fn conv_2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = op.options::<Conv2DOptions>()?;
+    // OHWI -> HWIO
+    let kernel = op.input_konst_required(1)?.permute_axes(&[1, 2, 3, 0])?;
+    let pool_spec = PoolSpec {
+        data_format: DataFormat::NHWC,
+        kernel_shape: kernel.shape()[0..2].into(),
+        padding: padding(options.padding())?,
+        dilations: Some(tvec!(
+            options.dilation_h_factor() as usize,
+            options.dilation_w_factor() as usize
+        )),
+        strides: Some(tvec!(options.stride_h() as usize, options.stride_w() as usize)),
+        output_channel_override: None,
+    };
+    let input = op.input_wire(0)?;
+    let output = wire_conv(op, input, pool_spec, kernel, 1)?;
+    wire_activation_and_requant(op, output, options.fused_activation_function())
+}
+
+fn depthwise_conv_2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = op.options::<DepthwiseConv2DOptions>()?;
+    let group = op.input_fact(0)?.shape[3].to_usize()?;
+    // 1HWO -> HW1O
+    let kernel = op.input_konst_required(1)?.permute_axes(&[1, 2, 0, 3])?;
+    let pool_spec = PoolSpec {
+        data_format: DataFormat::NHWC,
+        kernel_shape: kernel.shape()[0..2].into(),
+        padding: padding(options.padding())?,
+        dilations: Some(tvec!(
+            options.dilation_h_factor() as usize,
+            options.dilation_w_factor() as usize
+        )),
+        strides: Some(tvec!(options.stride_h() as usize, options.stride_w() as usize)),
+        output_channel_override: None,
+    };
+    let input = op.input_wire(0)?;
+    let output = wire_conv(op, input, pool_spec, kernel, group)?;
+    wire_activation_and_requant(op, output, options.fused_activation_function())
+}
+
+/// FULLY_CONNECTED is translated to a 1x1 convolution over a [N, 1, K] view of its input.
+fn fully_connected(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = op.options::<FullyConnectedOptions>()?;
+    ensure!(options.weights_format() == 0, "Only default weights format is supported");
+    let weights = op.input_konst_required(1)?;
+    ensure!(weights.rank() == 2, "Fully connected weights must be of rank 2");
+    let k = weights.shape()[1];
+    // OK -> HWIO
+    let mut kernel = weights.permute_axes(&[1, 0])?;
+    kernel.insert_axis(0)?;
+    let input = op.input_wire(0)?;
+    let input_shape = op.target.outlet_fact(input)?.shape.to_tvec();
+    let volume = input_shape.iter().product::<TDim>();
+    let n = volume.clone() / k;
+    ensure!(n.clone() * k == volume, "Fully connected input is not a multiple of {}", k);
+    let input = op.wire(
+        format!("{}.input", op.prefix),
+        AxisOp::Reshape(0, input_shape, tvec!(n, 1.to_dim(), k.to_dim())),
+        &[input],
+    )?[0];
+    let pool_spec = PoolSpec {
+        data_format: DataFormat::NHWC,
+        kernel_shape: tvec!(1),
+        padding: PaddingSpec::Valid,
+        dilations: None,
+        strides: None,
+        output_channel_override: None,
+    };
+    let output = wire_conv(op, input, pool_spec, kernel, 1)?;
+    let from = op.target.outlet_fact(output)?.shape.to_tvec();
+    let to = op.output_fact(0)?.shape.to_tvec();
+    let output =
+        op.wire(format!("{}.output", op.prefix), AxisOp::Reshape(0, from, to), &[output])?[0];
+    wire_activation_and_requant(op, output, options.fused_activation_function())
+}
+
+/// Wire a NHWC convolution with a HWIO kernel.
+///
+/// Per-tensor quantized inputs, weights and outputs run as an integer convolution, with the
+/// bias in the i32 accumulator. Per-axis quantized weights with quantized inputs and outputs
+/// also run as an integer convolution, but to an i32 accumulator which is then rescaled per
+/// output channel. Otherwise the convolution runs in f32 on dequantized values, and the output
+/// still has to be requantized.
+fn wire_conv(
+    op: &mut DeserOp,
+    input: OutletId,
+    pool_spec: PoolSpec,
+    kernel: Tensor,
+    group: usize,
+) -> TractResult<OutletId> {
+    let output_channels = kernel.shape()[kernel.rank() - 1];
+    let pool_spec = PoolSpec { output_channel_override: Some(output_channels), ..pool_spec };
+    let input_dt = op.target.outlet_fact(input)?.datum_type;
+    let output_dt = op.output_fact(0)?.datum_type;
+    let bias = if op.has_input(2) { Some(op.input_konst_required(2)?) } else { None };
+    let per_axis = op.ctx.per_axis_quantization(op.inputs()[1])?;
+    // per-axis quantization is always along the output channels, the last axis of HWIO
+    let kernel = if let Some((_, zero_points, scales)) = per_axis {
+        ensure!(scales.len() == output_channels, "Weights must be quantized per output channel");
+        if input_dt.is_quantized() && output_dt.is_quantized() {
+            ensure!(
+                kernel.datum_type() == i8::datum_type() && zero_points.iter().all(|z| *z == 0),
+                "Per-axis quantized weights must be symmetric i8"
+            );
+            return wire_per_axis_conv(op, input, pool_spec, kernel, group, bias, &scales);
+        }
+        let axis = kernel.rank() - 1;
+        crate::model::dequantize_per_axis(&kernel, axis, &zero_points, &scales)?
+    } else {
+        kernel
+    };
+    if input_dt.is_quantized() && kernel.datum_type().is_quantized() && output_dt.is_quantized() {
+        let bias = bias.map(|b| b.cast_to::<i32>().map(|b| b.into_owned().into_arc_tensor()));
+        let conv = ConvUnary {
+            pool_spec,
+            kernel_fmt: KernelFormat::HWIO,
+            kernel: kernel.into_arc_tensor(),
+            group,
+            bias: bias.transpose()?,
+            q_params: Some((output_dt, MatMulQParams::all_from_qtype())),
+        };
+        let name = format!("{}.conv", op.prefix);
+        return Ok(op.wire(name, conv, &[input])?[0]);
+    }
+    let bias = if let Some(bias) = bias {
+        Some(float_bias(op, bias, input_dt)?.into_arc_tensor())
+    } else {
+        None
+    };
+    let input = op.dequantize(input, &format!("{}.dequant", op.prefix))?;
+    let conv = ConvUnary {
+        pool_spec,
+        kernel_fmt: KernelFormat::HWIO,
+        kernel: kernel.cast_to::<f32>()?.into_owned().into_arc_tensor(),
+        group,
+        bias,
+        q_params: None,
+    };
+    let name = format!("{}.conv", op.prefix);
+    Ok(op.wire(name, conv, &[input])?[0])
+}
+
+/// Integer convolution of a quantized input by per-axis quantized i8 weights.
+///
+/// The convolution accumulates (x - x_zero_point) * w and the i32 bias. The accumulator is then
+/// turned to f32 by an explicit per channel multiplication by input_scale * weight_scale.
+fn wire_per_axis_conv(
+    op: &mut DeserOp,
+    input: OutletId,
+    pool_spec: PoolSpec,
+    kernel: Tensor,
+    group: usize,
+    bias: Option<Tensor>,
+    scales: &[f32],
+) -> TractResult<OutletId> {
+    let input_scale = op.target.outlet_fact(input)?.datum_type.zp_scale().1;
+    let bias = bias.map(|b| b.cast_to::<i32>().map(|b| b.into_owned().into_arc_tensor()));
+    // the input scale comes from its type, and cancels with the output one
+    let q_params = MatMulQParams {
+        a0: QParamKind::Attr(rctensor0(0i8)),
+        a_scale: QParamKind::Attr(rctensor0(1f32)),
+        b0: QParamKind::FromQType,
+        b_scale: QParamKind::FromQType,
+        c0: QParamKind::Attr(rctensor0(0i32)),
+        c_scale: QParamKind::Attr(rctensor0(input_scale)),
+    };
+    let conv = ConvUnary {
+        pool_spec,
+        kernel_fmt: KernelFormat::HWIO,
+        kernel: kernel.into_arc_tensor(),
+        group,
+        bias: bias.transpose()?,
+        q_params: Some((i32::datum_type(), q_params)),
+    };
+    let wire = op.wire(format!("{}.conv", op.prefix), conv, &[input])?[0];
+    let wire = op.wire(format!("{}.cast", op.prefix), cast(f32::datum_type()), &[wire])?[0];
+    let rank = op.target.outlet_fact(wire)?.rank();
+    let mut shape = tvec!(1; rank);
+    shape[rank - 1] = scales.len();
+    let scales: Vec<f32> = scales.iter().map(|s| s * input_scale).collect();
+    let scales = tensor1(&scales).into_shape(&shape)?;
+    let scales = op.target.add_const(format!("{}.scales", op.prefix), scales)?;
+    Ok(op.wire(format!("{}.rescale", op.prefix), math::mul(), &[wire, scales])?[0])
+}
+
+/// Quantized biases are i32 values with a scale of input_scale * weight_scale.
+fn float_bias(op: &DeserOp, bias: Tensor, input_dt: DatumType) -> TractResult<Tensor> {
+    if bias.datum_type().is_float() {
+        return Ok(bias.cast_to::<f32>()?.into_owned());
+    }
+    ensure!(input_dt.is_quantized(), "Integer bias requires a quantized input");
+    let input_scale = input_dt.zp_scale().1;
+    let weights = op.inputs()[1];
+    let weight_scales: Vec<f32> =
+        if let Some((_, _, scales)) = op.ctx.per_axis_quantization(weights)? {
+            scales
+        } else {
+            let scale = op.ctx.datum_type(weights)?.zp_scale().1;
+            vec![scale; bias.len()]
+        };
+    ensure!(weight_scales.len() == bias.len(), "Inconsistent bias and weights quantization");
+    let bias = bias.cast_to::<i32>()?;
+    let values: Vec<f32> = bias
+        .as_slice::<i32>()?
+        .iter()
+        .zip(weight_scales.iter())
+        .map(|(&b, &s)| b as f32 * input_scale * s)
+        .collect();
+    Ok(tensor1(&values))
+}
+
+fn wire_activation_and_requant(
+    op: &mut DeserOp,
+    wire: OutletId,
+    activation: i8,
+) -> TractResult<TVec<OutletId>> {
+    let mut wire = wire;
+    if activation != activation::NONE {
+        let name = format!("{}.dequant_output", op.prefix);
+        wire = op.dequantize(wire, &name)?;
+        wire = wire_fused_activation(op, wire, activation)?;
+    }
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
+
+fn pool_spec(op: &DeserOp, options: &Pool2DOptions) -> TractResult<PoolSpec> {
+    Ok(PoolSpec {
+        data_format: DataFormat::NHWC,
+        kernel_shape: tvec!(options.filter_height() as usize, options.filter_width() as usize),
+        padding: padding(options.padding())?,
+        dilations: None,
+        strides: Some(tvec!(options.stride_h() as usize, options.stride_w() as usize)),
+        output_channel_override: Some(op.input_fact(0)?.shape[3].to_usize()?),
+    })
+}
+
+fn average_pool_2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = op.options::<Pool2DOptions>()?;
+    let pool_spec = pool_spec(op, &options)?;
+    let input = op.dequantized_input(0)?;
+    let pool = SumPool { pool_spec, count_include_pad: false, normalize: true };
+    let wire = op.wire(format!("{}.pool", op.prefix), pool, &[input])?[0];
+    wire_activation_and_requant(op, wire, options.fused_activation_function())
+}
+
+fn max_pool_2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = op.options::<Pool2DOptions>()?;
+    let pool_spec = pool_spec(op, &options)?;
+    let input = op.dequantized_input(0)?;
+    let pool = MaxPool { pool_spec, with_index_outputs: None };
+    let wire = op.wire(format!("{}.pool", op.prefix), pool, &[input])?[0];
+    wire_activation_and_requant(op, wire, options.fused_activation_function())
+}
+
+fn softmax(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let beta = op.options::<SoftmaxOptions>()?.beta();
+    let mut input = op.dequantized_input(0)?;
+    let rank = op.target.outlet_fact(input)?.rank();
+    if beta != 1.0 {
+        let beta = op
+            .target
+            .add_const(format!("{}.beta", op.prefix), tensor0(beta).broadcast_into_rank(rank)?)?;
+        input = op.wire(format!("{}.scaled", op.prefix), math::mul(), &[input, beta])?[0];
+    }
+    let softmax = Softmax { axes: tvec!(rank - 1), output_dt: f32::datum_type() };
+    let name = op.float_name(0)?;
+    let wire = op.wire(name, softmax, &[input])?[0];
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
+
+fn mean(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let keep_dims = op.options::<ReducerOptions>()?.keep_dims();
+    let input = op.dequantized_input(0)?;
+    let fact = op.target.outlet_fact(input)?.clone();
+    let rank = fact.rank() as i64;
+    let axes = op.input_konst_required(1)?.cast_to::<i64>()?.into_owned();
+    let mut axes: TVec<usize> = axes
+        .as_slice::<i64>()?
+        .iter()
+        .map(|&a| {
+            ensure!(-rank <= a && a < rank, "Invalid reduction axis {}", a);
+            Ok(((a + rank) % rank) as usize)
+        })
+        .collect::<TractResult<_>>()?;
+    axes.sort();
+    axes.dedup();
+    let sum =
+        op.wire(format!("{}.sum", op.prefix), Reduce::new(axes.clone(), Reducer::Sum), &[input])?
+            [0];
+    let count = axes.iter().map(|&a| fact.shape[a].to_usize()).product::<TractResult<usize>>()?;
+    let count = op.target.add_const(
+        format!("{}.count", op.prefix),
+        tensor0(count as f32).broadcast_into_rank(fact.rank())?,
+    )?;
+    let name = if keep_dims { op.float_name(0)? } else { format!("{}.mean", op.prefix) };
+    let mut wire = op.wire(name, math::div(), &[sum, count])?[0];
+    if !keep_dims {
+        for (ix, &axis) in axes.iter().enumerate().rev() {
+            let name =
+                if ix == 0 { op.float_name(0)? } else { format!("{}.rm_{}", op.prefix, axis) };
+            wire = op.wire(name, AxisOp::Rm(axis), &[wire])?[0];
+        }
+    }
+    Ok(tvec!(op.requantize(wire, 0)?))
+}
@@ -0,0 +1,348 @@
+//! Read-only accessors for the subset of the TFLite flatbuffer schema tract uses.
+//!
+//! Field indices and enum values follow tensorflow/lite/schema/schema.fbs. Buffers are checked
+//! by the flatbuffers verifier before any accessor is used, so field reads do not need further
+//! bound checks.
+#![allow(clippy::derive_partial_eq_without_eq)]
+
+use flatbuffers::{
+    ForwardsUOffset, InvalidFlatbuffer, Table, VOffsetT, Vector, Verifiable, Verifier,
+};
+
+pub const FILE_IDENTIFIER: &str = "TFL3";
+
+pub type Result<T> = std::result::Result<T, InvalidFlatbuffer>;
+
+const fn slot(ix: VOffsetT) -> VOffsetT {
+    4 + 2 * ix
+}
+
+macro_rules! table {
+    ($name: ident { $($field: ident: $ty: ty = $ix: expr),* $(,)? }) => {
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct $name<'a>(Table<'a>);
+
+        impl<'a> flatbuffers::Follow<'a> for $name<'a> {
+            type Inner = $name<'a>;
+            #[inline]
+            unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+                $name(Table::new(buf, loc))
+            }
+        }
+
+        impl Verifiable for $name<'_> {
+            fn run_verifier(v: &mut Verifier, pos: usize) -> Result<()> {
+                v.visit_table(pos)?
+                    $(.visit_field::<$ty>(stringify!($field), slot($ix), false)?)*
+                    .finish();
+                Ok(())
+            }
+        }
+    };
+}
+
+macro_rules! scalar {
+    ($field: ident: $ty: ty = $ix: expr, $default: expr) => {
+        pub fn $field(&self) -> $ty {
+            // Safety: the buffer has been verified
+            unsafe { self.0.get::<$ty>(slot($ix), Some($default)).unwrap() }
+        }
+    };
+}
+
+macro_rules! offset {
+    ($field: ident: $ty: ty = $ix: expr) => {
+        pub fn $field(&self) -> Option<<$ty as flatbuffers::Follow<'a>>::Inner> {
+            // Safety: the buffer has been verified
+            unsafe { self.0.get::<ForwardsUOffset<$ty>>(slot($ix), None) }
+        }
+    };
+}
+
+table!(Model {
+    version: u32 = 0,
+    operator_codes: ForwardsUOffset<Vector<ForwardsUOffset<OperatorCode>>> = 1,
+    subgraphs: ForwardsUOffset<Vector<ForwardsUOffset<SubGraph>>> = 2,
+    description: ForwardsUOffset<&str> = 3,
+    buffers: ForwardsUOffset<Vector<ForwardsUOffset<Buffer>>> = 4,
+});
+
+impl<'a> Model<'a> {
+    scalar!(version: u32 = 0, 0);
+    offset!(operator_codes: Vector<'a, ForwardsUOffset<OperatorCode<'a>>> = 1);
+    offset!(subgraphs: Vector<'a, ForwardsUOffset<SubGraph<'a>>> = 2);
+    offset!(description: &'a str = 3);
+    offset!(buffers: Vector<'a, ForwardsUOffset<Buffer<'a>>> = 4);
+}
+
+table!(OperatorCode {
+    deprecated_builtin_code: i8 = 0,
+    custom_code: ForwardsUOffset<&str> = 1,
+    version: i32 = 2,
+    builtin_code: i32 = 3,
+});
+
+impl<'a> OperatorCode<'a> {
+    scalar!(deprecated_builtin_code: i8 = 0, 0);
+    offset!(custom_code: &'a str = 1);
+    scalar!(version: i32 = 2, 1);
+    scalar!(builtin_code: i32 = 3, 0);
+
+    /// Operators below 127 may only be recorded in the deprecated byte field.
+    pub fn code(&self) -> i32 {
+        self.builtin_code().max(self.deprecated_builtin_code() as i32)
+    }
+}
+
+table!(SubGraph {
+    tensors: ForwardsUOffset<Vector<ForwardsUOffset<Tensor>>> = 0,
+    inputs: ForwardsUOffset<Vector<i32>> = 1,
+    outputs: ForwardsUOffset<Vector<i32>> = 2,
+    operators: ForwardsUOffset<Vector<ForwardsUOffset<Operator>>> = 3,
+    name: ForwardsUOffset<&str> = 4,
+});
+
+impl<'a> SubGraph<'a> {
+    offset!(tensors: Vector<'a, ForwardsUOffset<Tensor<'a>>> = 0);
+    offset!(inputs: Vector<'a, i32> = 1);
+    offset!(outputs: Vector<'a, i32> = 2);
+    offset!(operators: Vector<'a, ForwardsUOffset<Operator<'a>>> = 3);
+    offset!(name: &'a str = 4);
+}
+
+pub mod tensor_type {
+    pub const FLOAT32: i8 = 0;
+    pub const FLOAT16: i8 = 1;
+    pub const INT32: i8 = 2;
+    pub const UINT8: i8 = 3;
+    pub const INT64: i8 = 4;
+    pub const STRING: i8 = 5;
+    pub const BOOL: i8 = 6;
+    pub const INT16: i8 = 7;
+    pub const COMPLEX64: i8 = 8;
+    pub const INT8: i8 = 9;
+    pub const FLOAT64: i8 = 10;
+}
+
+table!(Tensor {
+    shape: ForwardsUOffset<Vector<i32>> = 0,
+    type_: i8 = 1,
+    buffer: u32 = 2,
+    name: ForwardsUOffset<&str> = 3,
+    quantization: ForwardsUOffset<QuantizationParameters> = 4,
+    is_variable: bool = 5,
+    shape_signature: ForwardsUOffset<Vector<i32>> = 7,
+});
+
+impl<'a> Tensor<'a> {
+    offset!(shape: Vector<'a, i32> = 0);
+    scalar!(type_: i8 = 1, tensor_type::FLOAT32);
+    scalar!(buffer: u32 = 2, 0);
+    offset!(name: &'a str = 3);
+    offset!(quantization: QuantizationParameters<'a> = 4);
+    scalar!(is_variable: bool = 5, false);
+    offset!(shape_signature: Vector<'a, i32> = 7);
+}
+
+table!(QuantizationParameters {
+    min: ForwardsUOffset<Vector<f32>> = 0,
+    max: ForwardsUOffset<Vector<f32>> = 1,
+    scale: ForwardsUOffset<Vector<f32>> = 2,
+    zero_point: ForwardsUOffset<Vector<i64>> = 3,
+    quantized_dimension: i32 = 6,
+});
+
+impl<'a> QuantizationParameters<'a> {
+    offset!(min: Vector<'a, f32> = 0);
+    offset!(max: Vector<'a, f32> = 1);
+    offset!(scale: Vector<'a, f32> = 2);
+    offset!(zero_point: Vector<'a, i64> = 3);
+    scalar!(quantized_dimension: i32 = 6, 0);
+}
+
+table!(Buffer { data: ForwardsUOffset<Vector<u8>> = 0, offset: u64 = 1, size: u64 = 2 });
+
+impl<'a> Buffer<'a> {
+    offset!(data: Vector<'a, u8> = 0);
+    scalar!(offset: u64 = 1, 0);
+    scalar!(size: u64 = 2, 0);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Operator<'a>(Table<'a>);
+
+impl<'a> flatbuffers::Follow<'a> for Operator<'a> {
+    type Inner = Operator<'a>;
+    #[inline]
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Operator(Table::new(buf, loc))
+    }
+}
+
+impl Verifiable for Operator<'_> {
+    fn run_verifier(v: &mut Verifier, pos: usize) -> Result<()> {
+        v.visit_table(pos)?
+            .visit_field::<u32>("opcode_index", slot(0), false)?
+            .visit_field::<ForwardsUOffset<Vector<i32>>>("inputs", slot(1), false)?
+            .visit_field::<ForwardsUOffset<Vector<i32>>>("outputs", slot(2), false)?
+            .visit_union::<u8, _>(
+                "builtin_options_type",
+                slot(3),
+                "builtin_options",
+                slot(4),
+                false,
+                verify_builtin_options,
+            )?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("custom_options", slot(5), false)?
+            .finish();
+        Ok(())
+    }
+}
+
+impl<'a> Operator<'a> {
+    scalar!(opcode_index: u32 = 0, 0);
+    offset!(inputs: Vector<'a, i32> = 1);
+    offset!(outputs: Vector<'a, i32> = 2);
+    scalar!(builtin_options_type: u8 = 3, 0);
+    offset!(custom_options: Vector<'a, u8> = 5);
+
+    /// Builtin options, if they are present and of the expected type.
+    pub fn builtin_options<T: BuiltinOptions<'a>>(&self) -> Option<T> {
+        if self.builtin_options_type() != T::TYPE {
+            return None;
+        }
+        // Safety: the buffer has been verified, including the union variant
+        unsafe { self.0.get::<ForwardsUOffset<T>>(slot(4), None) }
+    }
+}
+
+/// Variants of the BuiltinOptions union.
+pub trait BuiltinOptions<'a>: flatbuffers::Follow<'a, Inner = Self> + Verifiable + 'a {
+    const TYPE: u8;
+}
+
+macro_rules! options {
+    ($($name: ident = $type: literal { $($field: ident: $ty: ty = $ix: expr, $default: expr),* $(,)? })*) => {
+        $(
+            table!($name { $($field: $ty = $ix),* });
+
+            impl<'a> BuiltinOptions<'a> for $name<'a> {
+                const TYPE: u8 = $type;
+            }
+
+            impl<'a> $name<'a> {
+                $(scalar!($field: $ty = $ix, $default);)*
+            }
+        )*
+
+        fn verify_builtin_options(key: u8, v: &mut Verifier, pos: usize) -> Result<()> {
+            match key {
+                $($type => v.verify_union_variant::<ForwardsUOffset<$name>>(stringify!($name), pos),)*
+                _ => Ok(()),
+            }
+        }
+    };
+}
+
+options! {
+    Conv2DOptions = 1 {
+        padding: i8 = 0, padding::SAME,
+        stride_w: i32 = 1, 0,
+        stride_h: i32 = 2, 0,
+        fused_activation_function: i8 = 3, activation::NONE,
+        dilation_w_factor: i32 = 4, 1,
+        dilation_h_factor: i32 = 5, 1,
+    }
+    DepthwiseConv2DOptions = 2 {
+        padding: i8 = 0, padding::SAME,
+        stride_w: i32 = 1, 0,
+        stride_h: i32 = 2, 0,
+        depth_multiplier: i32 = 3, 0,
+        fused_activation_function: i8 = 4, activation::NONE,
+        dilation_w_factor: i32 = 5, 1,
+        dilation_h_factor: i32 = 6, 1,
+    }
+    Pool2DOptions = 5 {
+        padding: i8 = 0, padding::SAME,
+        stride_w: i32 = 1, 0,
+        stride_h: i32 = 2, 0,
+        filter_width: i32 = 3, 0,
+        filter_height: i32 = 4, 0,
+        fused_activation_function: i8 = 5, activation::NONE,
+    }
+    FullyConnectedOptions = 8 {
+        fused_activation_function: i8 = 0, activation::NONE,
+        weights_format: i8 = 1, 0,
+        keep_num_dims: bool = 2, false,
+    }
+    SoftmaxOptions = 9 {
+        beta: f32 = 0, 0.0,
+    }
+    ConcatenationOptions = 10 {
+        axis: i32 = 0, 0,
+        fused_activation_function: i8 = 1, activation::NONE,
+    }
+    AddOptions = 11 {
+        fused_activation_function: i8 = 0, activation::NONE,
+    }
+    MulOptions = 21 {
+        fused_activation_function: i8 = 0, activation::NONE,
+    }
+    ReducerOptions = 27 {
+        keep_dims: bool = 0, false,
+    }
+    SubOptions = 28 {
+        fused_activation_function: i8 = 0, activation::NONE,
+    }
+    DivOptions = 29 {
+        fused_activation_function: i8 = 0, activation::NONE,
+    }
+}
+
+pub mod padding {
+    pub const SAME: i8 = 0;
+    pub const VALID: i8 = 1;
+}
+
+pub mod activation {
+    pub const NONE: i8 = 0;
+    pub const RELU: i8 = 1;
+    pub const RELU_N1_TO_1: i8 = 2;
+    pub const RELU6: i8 = 3;
+    pub const TANH: i8 = 4;
+    pub const SIGN_BIT: i8 = 5;
+}
+
+/// Codes of the BuiltinOperator enum.
+pub mod builtin {
+    pub const ADD: i32 = 0;
+    pub const AVERAGE_POOL_2D: i32 = 1;
+    pub const CONCATENATION: i32 = 2;
+    pub const CONV_2D: i32 = 3;
+    pub const DEPTHWISE_CONV_2D: i32 = 4;
+    pub const DEQUANTIZE: i32 = 6;
+    pub const FULLY_CONNECTED: i32 = 9;
+    pub const LOGISTIC: i32 = 14;
+    pub const MAX_POOL_2D: i32 = 17;
+    pub const MUL: i32 = 18;
+    pub const RELU: i32 = 19;
+    pub const RELU6: i32 = 21;
+    pub const RESHAPE: i32 = 22;
+    pub const SOFTMAX: i32 = 25;
+    pub const TANH: i32 = 28;
+    pub const PAD: i32 = 34;
+    pub const TRANSPOSE: i32 = 39;
+    pub const MEAN: i32 = 40;
+    pub const SUB: i32 = 41;
+    pub const DIV: i32 = 42;
+    pub const SQUEEZE: i32 = 43;
+    pub const MAXIMUM: i32 = 55;
+    pub const MINIMUM: i32 = 57;
+    pub const EXPAND_DIMS: i32 = 70;
+    pub const QUANTIZE: i32 = 114;
+}
+
+/// Verify a buffer and access its root Model.
+pub fn root(buf: &[u8]) -> Result<Model<'_>> {
+    flatbuffers::root::<Model>(buf)
+}
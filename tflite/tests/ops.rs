@@ -0,0 +1,387 @@
+use flatbuffers::{FlatBufferBuilder, WIPOffset};
+use tract_tflite::prelude::*;
+use tract_tflite::schema::{builtin, tensor_type};
+use tract_tflite::tract_core::ops::cnn::ConvUnary;
+
+const fn slot(ix: u16) -> u16 {
+    4 + 2 * ix
+}
+
+enum Opt {
+    I8(i8),
+    I32(i32),
+    F32(f32),
+    Bool(bool),
+}
+
+struct TensorDef {
+    name: String,
+    shape: Vec<i32>,
+    type_: i8,
+    buffer: u32,
+    scale: Vec<f32>,
+    zero_point: Vec<i64>,
+    quantized_dimension: i32,
+}
+
+struct OpDef {
+    code: i32,
+    inputs: Vec<i32>,
+    outputs: Vec<i32>,
+    options_type: u8,
+    options: Vec<(u16, Opt)>,
+}
+
+/// Minimal TFLite flatbuffer writer.
+#[derive(Default)]
+struct ModelBuilder {
+    tensors: Vec<TensorDef>,
+    buffers: Vec<Vec<u8>>,
+    operators: Vec<OpDef>,
+    inputs: Vec<i32>,
+    outputs: Vec<i32>,
+}
+
+impl ModelBuilder {
+    fn tensor(&mut self, name: &str, shape: &[i32], type_: i8) -> i32 {
+        self.tensors.push(TensorDef {
+            name: name.to_string(),
+            shape: shape.to_vec(),
+            type_,
+            buffer: 0,
+            scale: vec![],
+            zero_point: vec![],
+            quantized_dimension: 0,
+        });
+        self.tensors.len() as i32 - 1
+    }
+
+    fn quantized(&mut self, name: &str, shape: &[i32], scale: &[f32], zp: &[i64]) -> i32 {
+        let t = self.tensor(name, shape, tensor_type::INT8);
+        self.tensors[t as usize].scale = scale.to_vec();
+        self.tensors[t as usize].zero_point = zp.to_vec();
+        t
+    }
+
+    fn data(&mut self, tensor: i32, data: Vec<u8>) -> i32 {
+        if self.buffers.is_empty() {
+            self.buffers.push(vec![]);
+        }
+        self.buffers.push(data);
+        self.tensors[tensor as usize].buffer = self.buffers.len() as u32 - 1;
+        tensor
+    }
+
+    fn konst_f32(&mut self, name: &str, shape: &[i32], values: &[f32]) -> i32 {
+        let t = self.tensor(name, shape, tensor_type::FLOAT32);
+        self.data(t, values.iter().flat_map(|v| v.to_le_bytes()).collect())
+    }
+
+    fn konst_i32(&mut self, name: &str, shape: &[i32], values: &[i32]) -> i32 {
+        let t = self.tensor(name, shape, tensor_type::INT32);
+        self.data(t, values.iter().flat_map(|v| v.to_le_bytes()).collect())
+    }
+
+    fn op(
+        &mut self,
+        code: i32,
+        inputs: &[i32],
+        outputs: &[i32],
+        options_type: u8,
+        options: Vec<(u16, Opt)>,
+    ) {
+        self.operators.push(OpDef {
+            code,
+            inputs: inputs.to_vec(),
+            outputs: outputs.to_vec(),
+            options_type,
+            options,
+        })
+    }
+
+    fn build(&self) -> Vec<u8> {
+        let mut fbb = FlatBufferBuilder::new();
+        let mut buffers = vec![];
+        for data in self
+            .buffers
+            .iter()
+            .chain(if self.buffers.is_empty() { Some(vec![]) } else { None }.iter())
+        {
+            let data = fbb.create_vector(data);
+            let start = fbb.start_table();
+            fbb.push_slot_always(slot(0), data);
+            buffers.push(fbb.end_table(start));
+        }
+        let mut tensors = vec![];
+        for t in &self.tensors {
+            let shape = fbb.create_vector(&t.shape);
+            let name = fbb.create_string(&t.name);
+            let quant = if !t.scale.is_empty() {
+                let scale = fbb.create_vector(&t.scale);
+                let zp = fbb.create_vector(&t.zero_point);
+                let start = fbb.start_table();
+                fbb.push_slot_always(slot(2), scale);
+                fbb.push_slot_always(slot(3), zp);
+                fbb.push_slot(slot(6), t.quantized_dimension, 0);
+                Some(fbb.end_table(start))
+            } else {
+                None
+            };
+            let start = fbb.start_table();
+            fbb.push_slot_always(slot(0), shape);
+            fbb.push_slot_always(slot(1), t.type_);
+            fbb.push_slot(slot(2), t.buffer, 0);
+            fbb.push_slot_always(slot(3), name);
+            if let Some(quant) = quant {
+                fbb.push_slot_always(slot(4), quant);
+            }
+            tensors.push(fbb.end_table(start));
+        }
+        let mut codes: Vec<i32> = self.operators.iter().map(|op| op.code).collect();
+        codes.sort();
+        codes.dedup();
+        let mut operators = vec![];
+        for op in &self.operators {
+            let inputs = fbb.create_vector(&op.inputs);
+            let outputs = fbb.create_vector(&op.outputs);
+            let start = fbb.start_table();
+            for (ix, value) in &op.options {
+                match value {
+                    Opt::I8(v) => fbb.push_slot_always(slot(*ix), *v),
+                    Opt::I32(v) => fbb.push_slot_always(slot(*ix), *v),
+                    Opt::F32(v) => fbb.push_slot_always(slot(*ix), *v),
+                    Opt::Bool(v) => fbb.push_slot_always(slot(*ix), *v),
+                }
+            }
+            let options = fbb.end_table(start);
+            let start = fbb.start_table();
+            let opcode_index = codes.iter().position(|c| *c == op.code).unwrap() as u32;
+            fbb.push_slot_always(slot(0), opcode_index);
+            fbb.push_slot_always(slot(1), inputs);
+            fbb.push_slot_always(slot(2), outputs);
+            fbb.push_slot_always(slot(3), op.options_type);
+            fbb.push_slot_always(slot(4), options);
+            operators.push(fbb.end_table(start));
+        }
+        let mut opcodes = vec![];
+        for code in &codes {
+            let start = fbb.start_table();
+            fbb.push_slot_always(slot(0), (*code).min(127) as i8);
+            fbb.push_slot_always(slot(3), *code);
+            opcodes.push(fbb.end_table(start));
+        }
+        let tensors = fbb.create_vector(&tensors);
+        let inputs = fbb.create_vector(&self.inputs);
+        let outputs = fbb.create_vector(&self.outputs);
+        let operators = fbb.create_vector(&operators);
+        let start = fbb.start_table();
+        fbb.push_slot_always(slot(0), tensors);
+        fbb.push_slot_always(slot(1), inputs);
+        fbb.push_slot_always(slot(2), outputs);
+        fbb.push_slot_always(slot(3), operators);
+        let subgraph = fbb.end_table(start);
+        let subgraphs = fbb.create_vector(&[subgraph]);
+        let opcodes = fbb.create_vector(&opcodes);
+        let buffers = fbb.create_vector(&buffers);
+        let start = fbb.start_table();
+        fbb.push_slot_always(slot(0), 3u32);
+        fbb.push_slot_always(slot(1), opcodes);
+        fbb.push_slot_always(slot(2), subgraphs);
+        fbb.push_slot_always(slot(4), buffers);
+        let root: WIPOffset<()> = WIPOffset::new(fbb.end_table(start).value());
+        fbb.finish(root, Some("TFL3"));
+        fbb.finished_data().to_vec()
+    }
+
+    fn run(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        let model = tract_tflite::tflite().model_for_read(&mut &*self.build())?;
+        let runnable = model.into_optimized()?.into_runnable()?;
+        let outputs = runnable.run(inputs.into_iter().map(|t| t.into_tvalue()).collect())?;
+        Ok(outputs.into_iter().map(|t| t.into_arc_tensor()).collect())
+    }
+}
+
+/// NHWC, OHWI, SAME padding, stride 1.
+fn reference_conv(
+    input: &[f32],
+    (h, w, c): (usize, usize, usize),
+    kernel: &[f32],
+    (o, kh, kw): (usize, usize, usize),
+    bias: &[f32],
+) -> Vec<f32> {
+    let mut output = vec![0f32; h * w * o];
+    for y in 0..h {
+        for x in 0..w {
+            for oc in 0..o {
+                let mut sum = bias[oc];
+                for ky in 0..kh {
+                    for kx in 0..kw {
+                        let iy = y as isize + ky as isize - (kh as isize - 1) / 2;
+                        let ix = x as isize + kx as isize - (kw as isize - 1) / 2;
+                        if iy < 0 || ix < 0 || iy >= h as isize || ix >= w as isize {
+                            continue;
+                        }
+                        for ic in 0..c {
+                            sum += input[(iy as usize * w + ix as usize) * c + ic]
+                                * kernel[((oc * kh + ky) * kw + kx) * c + ic];
+                        }
+                    }
+                }
+                output[(y * w + x) * o + oc] = sum;
+            }
+        }
+    }
+    output
+}
+
+#[test]
+fn reject_garbage() {
+    let garbage = b"\xff\x00\x00\x00TFL3\x00\x00\x00\x00\xff\xff\xff\xff".to_vec();
+    assert!(tract_tflite::tflite().proto_model_for_read(&mut &*garbage).is_err());
+    assert!(tract_tflite::tflite().proto_model_for_read(&mut &b"not a model at all"[..]).is_err());
+}
+
+#[test]
+fn float_fully_connected_add_relu() -> TractResult<()> {
+    let mut b = ModelBuilder::default();
+    let x = b.tensor("x", &[1, 3], tensor_type::FLOAT32);
+    let w = b.konst_f32("w", &[2, 3], &[1., 2., 3., -1., -2., -3.]);
+    let bias = b.konst_f32("b", &[2], &[0.5, 0.5]);
+    let y = b.tensor("y", &[1, 2], tensor_type::FLOAT32);
+    let c = b.konst_f32("c", &[2], &[-10., 20.]);
+    let z = b.tensor("z", &[1, 2], tensor_type::FLOAT32);
+    b.op(builtin::FULLY_CONNECTED, &[x, w, bias], &[y], 8, vec![]);
+    b.op(builtin::ADD, &[y, c], &[z], 11, vec![(0, Opt::I8(1))]);
+    b.inputs = vec![x];
+    b.outputs = vec![z];
+    let outputs = b.run(tvec!(tensor2(&[[1f32, 1., 1.]])))?;
+    // y = [6.5, -5.5], y + c = [-3.5, 14.5]
+    outputs[0].close_enough(&tensor2(&[[0f32, 14.5]]), false)
+}
+
+#[test]
+fn float_conv_pool_mean() -> TractResult<()> {
+    let mut b = ModelBuilder::default();
+    let x = b.tensor("x", &[1, 4, 4, 1], tensor_type::FLOAT32);
+    let k = b.konst_f32("k", &[1, 3, 3, 1], &[1.; 9]);
+    let conv = b.tensor("conv", &[1, 4, 4, 1], tensor_type::FLOAT32);
+    let pool = b.tensor("pool", &[1, 2, 2, 1], tensor_type::FLOAT32);
+    let axes = b.konst_i32("axes", &[2], &[1, 2]);
+    let mean = b.tensor("mean", &[1, 1], tensor_type::FLOAT32);
+    b.op(builtin::CONV_2D, &[x, k, -1], &[conv], 1, vec![(1, Opt::I32(1)), (2, Opt::I32(1))]);
+    b.op(
+        builtin::MAX_POOL_2D,
+        &[conv],
+        &[pool],
+        5,
+        vec![
+            (0, Opt::I8(1)),
+            (1, Opt::I32(2)),
+            (2, Opt::I32(2)),
+            (3, Opt::I32(2)),
+            (4, Opt::I32(2)),
+        ],
+    );
+    b.op(builtin::MEAN, &[pool, axes], &[mean], 27, vec![(0, Opt::Bool(false))]);
+    b.inputs = vec![x];
+    b.outputs = vec![mean, pool];
+    let input: Vec<f32> = (0..16).map(|i| i as f32).collect();
+    let outputs = b.run(tvec!(tensor1(&input).into_shape(&[1, 4, 4, 1])?))?;
+    let conv = reference_conv(&input, (4, 4, 1), &[1.; 9], (1, 3, 3), &[0.]);
+    let pooled: Vec<f32> = [(0, 0), (0, 2), (2, 0), (2, 2)]
+        .iter()
+        .map(|&(y, x)| {
+            [(0, 0), (0, 1), (1, 0), (1, 1)]
+                .iter()
+                .map(|(dy, dx)| conv[(y + dy) * 4 + x + dx])
+                .fold(f32::MIN, f32::max)
+        })
+        .collect();
+    outputs[1].close_enough(&tensor1(&pooled).into_shape(&[1, 2, 2, 1])?, false)?;
+    let mean = pooled.iter().sum::<f32>() / 4.;
+    outputs[0].close_enough(&tensor2(&[[mean]]), false)
+}
+
+#[test]
+fn float_softmax_with_beta() -> TractResult<()> {
+    let mut b = ModelBuilder::default();
+    let x = b.tensor("x", &[1, 3], tensor_type::FLOAT32);
+    let y = b.tensor("y", &[1, 3], tensor_type::FLOAT32);
+    b.op(builtin::SOFTMAX, &[x], &[y], 9, vec![(0, Opt::F32(2.0))]);
+    b.inputs = vec![x];
+    b.outputs = vec![y];
+    let outputs = b.run(tvec!(tensor2(&[[0f32, 0.5, 1.0]])))?;
+    let exps = [0f32.exp(), 1f32.exp(), 2f32.exp()];
+    let sum: f32 = exps.iter().sum();
+    outputs[0].close_enough(&tensor2(&[[exps[0] / sum, exps[1] / sum, exps[2] / sum]]), true)
+}
+
+fn quantized_conv(per_axis: bool) -> TractResult<()> {
+    let (h, w, c, o) = (4, 4, 2, 3);
+    let input: Vec<f32> = (0..h * w * c).map(|i| ((i * 7) % 11) as f32 * 0.1 - 0.5).collect();
+    let kernel_q: Vec<i8> = (0..o * 3 * 3 * c).map(|i| ((i * 5) % 13) as i8 - 6).collect();
+    let kernel_scales = if per_axis { vec![0.01, 0.02, 0.03] } else { vec![0.02] };
+    let bias_q = [100i32, -200, 300];
+    let (x_scale, y_scale, y_zp) = (0.05f32, 0.1f32, -10i64);
+
+    let mut b = ModelBuilder::default();
+    let x = b.tensor("x", &[1, h as i32, w as i32, c as i32], tensor_type::FLOAT32);
+    let xq = b.quantized("xq", &[1, h as i32, w as i32, c as i32], &[x_scale], &[0]);
+    let zps = vec![0; kernel_scales.len()];
+    let k = b.quantized("k", &[o as i32, 3, 3, c as i32], &kernel_scales, &zps);
+    b.data(k, kernel_q.iter().map(|v| *v as u8).collect());
+    let bias_scales: Vec<f32> = kernel_scales.iter().map(|s| s * x_scale).collect();
+    let bias = b.konst_i32("bias", &[o as i32], &bias_q);
+    b.tensors[bias as usize].scale = bias_scales;
+    b.tensors[bias as usize].zero_point = zps;
+    let yq = b.quantized("yq", &[1, h as i32, w as i32, o as i32], &[y_scale], &[y_zp]);
+    let y = b.tensor("y", &[1, h as i32, w as i32, o as i32], tensor_type::FLOAT32);
+    b.op(builtin::QUANTIZE, &[x], &[xq], 0, vec![]);
+    b.op(
+        builtin::CONV_2D,
+        &[xq, k, bias],
+        &[yq],
+        1,
+        vec![(1, Opt::I32(1)), (2, Opt::I32(1)), (3, Opt::I8(1))],
+    );
+    b.op(builtin::DEQUANTIZE, &[yq], &[y], 0, vec![]);
+    b.inputs = vec![x];
+    b.outputs = vec![y];
+
+    // weights stay i8 after loading, per-axis quantized or not
+    let model = tract_tflite::tflite().model_for_read(&mut &*b.build())?;
+    let conv = model.nodes().iter().find_map(|n| n.op_as::<ConvUnary>()).unwrap();
+    assert_eq!(conv.kernel.datum_type().unquantized(), i8::datum_type());
+    assert!(conv.q_params.is_some());
+
+    let outputs = b.run(tvec!(tensor1(&input).into_shape(&[1, h, w, c])?))?;
+
+    let input_dq: Vec<f32> = input.iter().map(|x| (x / x_scale).round() * x_scale).collect();
+    let per_channel = |oc: usize| kernel_scales[if per_axis { oc } else { 0 }];
+    let kernel_dq: Vec<f32> =
+        kernel_q.iter().enumerate().map(|(i, &k)| k as f32 * per_channel(i / (9 * c))).collect();
+    let bias_dq: Vec<f32> =
+        bias_q.iter().enumerate().map(|(oc, &b)| b as f32 * x_scale * per_channel(oc)).collect();
+    let expected: Vec<f32> = reference_conv(&input_dq, (h, w, c), &kernel_dq, (o, 3, 3), &bias_dq)
+        .into_iter()
+        .map(|y| {
+            let q = ((y / y_scale).round() + y_zp as f32).clamp(-128., 127.);
+            (q - y_zp as f32).max(0.) * y_scale
+        })
+        .collect();
+    let got = outputs[0].as_slice::<f32>()?;
+    for (g, e) in got.iter().zip(expected.iter()) {
+        assert!((g - e).abs() <= y_scale * 1.01, "got {:?} expected {:?}", got, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn quantized_conv_per_tensor() -> TractResult<()> {
+    quantized_conv(false)
+}
+
+#[test]
+fn quantized_conv_per_axis() -> TractResult<()> {
+    quantized_conv(true)
+}